@@ -0,0 +1,75 @@
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::path::PathBuf;
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use osx_dock_dodger_rs::bundle::{hide_dock_icon, is_dock_icon_hidden, restore_dock_icon};
+use osx_dock_dodger_rs::state::{self, ManagedApp};
+
+use common::TestEnv;
+
+const BUNDLES: usize = 1_000;
+
+fn build_bundles(env: &TestEnv) -> Vec<PathBuf> {
+    (0..BUNDLES)
+        .map(|index| env.bundle(&format!("App {:04}", index)).build())
+        .collect()
+}
+
+fn managed(apps: &[PathBuf]) -> Vec<ManagedApp> {
+    apps.iter()
+        .map(|path| {
+            let mut app = ManagedApp::new(path.clone());
+            app.record_checksum();
+            app
+        })
+        .collect()
+}
+
+fn batch_operations(c: &mut Criterion) {
+    let env = TestEnv::new();
+    let apps = build_bundles(&env);
+    let mut group = c.benchmark_group("batch_1000");
+    group.sample_size(10);
+
+    // 每轮开始前把状态切换回来，保证测量的都是真正需要写入的情况。
+    group.bench_function("hide", |b| {
+        b.iter_batched(
+            || apps.iter().for_each(|app| restore_dock_icon(app).unwrap()),
+            |_| apps.iter().for_each(|app| hide_dock_icon(app).unwrap()),
+            BatchSize::PerIteration,
+        )
+    });
+    group.bench_function("restore", |b| {
+        b.iter_batched(
+            || apps.iter().for_each(|app| hide_dock_icon(app).unwrap()),
+            |_| apps.iter().for_each(|app| restore_dock_icon(app).unwrap()),
+            BatchSize::PerIteration,
+        )
+    });
+
+    let records = managed(&apps);
+    group.bench_function("verify", |b| {
+        b.iter(|| {
+            records
+                .iter()
+                .filter(|app| {
+                    app.checksum_matches() == Some(true)
+                        && app.external_change().is_none()
+                        && is_dock_icon_hidden(&app.path).is_ok()
+                })
+                .count()
+        })
+    });
+    group.bench_function("save_and_load", |b| {
+        b.iter(|| {
+            state::save_apps(&records).unwrap();
+            state::load_apps().unwrap().len()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, batch_operations);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+// 启用 minimal-ui 时把界面页面用 brotli 预压缩到 OUT_DIR，由 gui::assets 内嵌，
+// 运行时通过自定义协议原样交给 WebView 并声明 Content-Encoding: br。
+fn main() {
+    println!("cargo:rerun-if-changed=src/gui");
+    #[cfg(feature = "minimal-ui")]
+    compress_pages();
+}
+
+#[cfg(feature = "minimal-ui")]
+fn compress_pages() {
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    let source = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap()).join("src/gui");
+    let out = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    for page in ["index.html", "quick.html", "drop.html"] {
+        let html = std::fs::read(source.join(page)).unwrap();
+        let mut compressed = Vec::new();
+        {
+            // 质量 11、窗口 22 是 brotli 的最高压缩率，只在构建时付出一次
+            let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
+            writer.write_all(&html).unwrap();
+        }
+        std::fs::write(out.join(format!("{}.br", page)), compressed).unwrap();
+    }
+}
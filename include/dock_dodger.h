@@ -0,0 +1,64 @@
+#ifndef DOCK_DODGER_H
+#define DOCK_DODGER_H
+
+/* 此文件由 cbindgen 生成，请勿手动修改 */
+
+#include <stdint.h>
+
+// 成功。
+#define DODGER_OK 0
+
+// 参数为空指针或不是合法的 UTF-8。
+#define DODGER_ERR_INVALID_ARGUMENT -1
+
+// 操作失败，详见 `dodger_last_error`。
+#define DODGER_ERR_FAILED -2
+
+// Info.plist 疑似由其他用户或工具修改，未传入 force 时跳过。
+#define DODGER_ERR_EXTERNAL_CHANGE -3
+
+// 内部错误（Rust 代码发生 panic）。
+#define DODGER_ERR_PANIC -4
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// 隐藏 `path` 指向的 .app 的 Dock 图标，并加入管理列表。
+//
+// # Safety
+//
+// `path` 必须是以 NUL 结尾的有效字符串指针。
+int32_t dodger_hide(const char *path);
+
+// 恢复 `path` 的 Dock 图标并移出管理列表。`force` 非 0 时同时处理疑似由其他工具管理的应用。
+//
+// # Safety
+//
+// `path` 必须是以 NUL 结尾的有效字符串指针。
+int32_t dodger_restore(const char *path, int32_t force);
+
+// 以 JSON 数组写出管理列表（每项包含 path、tags、hidden、session），
+// 结果通过 `out_json` 返回，使用后需调用 `dodger_string_free` 释放。
+//
+// # Safety
+//
+// `out_json` 必须是有效的可写指针。
+int32_t dodger_list(char **out_json);
+
+// 释放 `dodger_list` 返回的字符串；传入空指针时不做任何事。
+//
+// # Safety
+//
+// `s` 必须是本库返回且尚未释放的指针。
+void dodger_string_free(char *s);
+
+// 返回当前线程上一次调用失败的原因，没有错误时返回空指针。
+// 字符串由本库持有，在同一线程下一次调用前有效，无需释放。
+const char *dodger_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* DOCK_DODGER_H */
@@ -0,0 +1,506 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use plist::{Dictionary, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::state::write_atomic;
+
+const LABEL_PREFIX: &str = "com.github.fmnisme.dock-dodger";
+
+// launchd 启动任务时设置该环境变量，命令结束后据此记录运行时间和退出码。
+pub const AGENT_JOB_ENV: &str = "DOCKDODGER_AGENT_JOB";
+
+pub const DEFAULT_VERIFY_INTERVAL_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentJob {
+    // 注销或关机时执行 session restore
+    Session,
+    // 定期执行 verify --reapply --notify，重新隐藏因应用更新而恢复了 Dock 图标的应用并发送通知
+    Verify,
+    // 定期执行 verify --notify，只检查，发现隐藏失效时发送系统通知
+    Notify,
+}
+
+impl AgentJob {
+    pub const ALL: [AgentJob; 3] = [AgentJob::Session, AgentJob::Verify, AgentJob::Notify];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AgentJob::Session => "session",
+            AgentJob::Verify => "verify",
+            AgentJob::Notify => "notify",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|job| job.name() == name)
+    }
+
+    pub fn label(self) -> String {
+        format!("{}.{}", LABEL_PREFIX, self.name())
+    }
+
+    pub fn plist_path(self) -> PathBuf {
+        launch_agents_dir().join(format!("{}.plist", self.label()))
+    }
+}
+
+fn launch_agents_dir() -> PathBuf {
+    config::home_dir().join("Library/LaunchAgents")
+}
+
+fn agents_dir() -> PathBuf {
+    config::data_dir().join("agents")
+}
+
+fn last_run_path(job: AgentJob) -> PathBuf {
+    agents_dir().join(format!("{}.json", job.name()))
+}
+
+pub fn log_path(job: AgentJob) -> PathBuf {
+    agents_dir().join(format!("{}.log", job.name()))
+}
+
+// launchd 在注销或关机时向代理发送 SIGTERM，由 shell 的 trap 调用 session restore；
+// 可执行文件路径作为 $0 传入，避免转义问题。
+const SESSION_SCRIPT: &str =
+    r#"trap '"$0" session restore; exit 0' TERM; while :; do sleep 86400 & wait $!; done"#;
+
+// 代理与当前进程使用同一个数据目录和配置文件。
+fn job_environment() -> Dictionary {
+    let mut env = Dictionary::new();
+    for key in [config::DATA_DIR_ENV, config::CONFIG_ENV] {
+        if let Some(value) = std::env::var_os(key).filter(|value| !value.is_empty()) {
+            env.insert(key.into(), value.to_string_lossy().into_owned().into());
+        }
+    }
+    env
+}
+
+fn agent_plist(job: AgentJob, exe: &Path, interval_secs: u64) -> Value {
+    let mut dict = Dictionary::new();
+    dict.insert("Label".into(), job.label().into());
+    let exe = exe.display().to_string();
+    let arguments: Vec<Value> = match job {
+        AgentJob::Session => vec![
+            "/bin/sh".into(),
+            "-c".into(),
+            SESSION_SCRIPT.into(),
+            exe.into(),
+        ],
+        AgentJob::Verify => vec![
+            exe.into(),
+            "verify".into(),
+            "--reapply".into(),
+            "--notify".into(),
+        ],
+        AgentJob::Notify => vec![exe.into(), "verify".into(), "--notify".into()],
+    };
+    dict.insert("ProgramArguments".into(), Value::Array(arguments));
+    let mut env = job_environment();
+    env.insert(AGENT_JOB_ENV.into(), job.name().into());
+    dict.insert("EnvironmentVariables".into(), Value::Dictionary(env));
+    dict.insert("RunAtLoad".into(), true.into());
+    match job {
+        AgentJob::Session => {
+            dict.insert("ExitTimeOut".into(), 30.into());
+        }
+        AgentJob::Verify | AgentJob::Notify => {
+            dict.insert("StartInterval".into(), interval_secs.into());
+        }
+    }
+    let log = log_path(job).display().to_string();
+    dict.insert("StandardOutPath".into(), log.clone().into());
+    dict.insert("StandardErrorPath".into(), log.into());
+    Value::Dictionary(dict)
+}
+
+fn launchctl(args: &[&str]) -> io::Result<bool> {
+    Command::new("launchctl")
+        .args(args)
+        .status()
+        .map(|status| status.success())
+}
+
+// 写入 plist 并（重新）加载；已加载的旧版本先卸载。
+fn load_plist(path: &Path, contents: &Value) -> Result<(), Box<dyn std::error::Error>> {
+    config::ensure_writable()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::create_dir_all(agents_dir())?;
+    if path.exists() {
+        let _ = launchctl(&["unload", &path.display().to_string()]);
+    }
+    plist::to_file_xml(path, contents)?;
+    if !launchctl(&["load", "-w", &path.display().to_string()])? {
+        return Err(format!("launchctl load 失败：{}", path.display()).into());
+    }
+    Ok(())
+}
+
+fn unload_plist(path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+    config::ensure_writable()?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    let _ = launchctl(&["unload", "-w", &path.display().to_string()]);
+    fs::remove_file(path)?;
+    Ok(true)
+}
+
+pub fn install_agent(
+    job: AgentJob,
+    exe: &Path,
+    interval_secs: u64,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = job.plist_path();
+    load_plist(&path, &agent_plist(job, exe, interval_secs.max(60)))?;
+    Ok(path)
+}
+
+pub fn uninstall_agent(job: AgentJob) -> Result<bool, Box<dyn std::error::Error>> {
+    unload_plist(&job.plist_path())
+}
+
+// 按计划启用导出的配置文件（.dockdodger），例如工作日 9:00 启用“工作”配置：
+// 到时由 launchd 运行 dock-dodger import <配置文件>，隐藏其中尚未隐藏的应用。
+// 计划只保存在生成的 plist 中，列出时从中读回。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfileSchedule {
+    pub name: String,
+    pub profile: PathBuf,
+    // 1 为周一，7 为周日，与 launchd 的 Weekday 一致；为空表示每天
+    pub weekdays: Vec<u8>,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+const PROFILE_LABEL: &str = "profile";
+
+const WEEKDAY_NAMES: [(&str, &str); 7] = [
+    ("mon", "周一"),
+    ("tue", "周二"),
+    ("wed", "周三"),
+    ("thu", "周四"),
+    ("fri", "周五"),
+    ("sat", "周六"),
+    ("sun", "周日"),
+];
+
+// daily、weekdays、weekends，或逗号分隔的 mon…sun / 1…7。
+pub fn parse_weekdays(text: &str) -> Result<Vec<u8>, String> {
+    match text.trim().to_lowercase().as_str() {
+        "daily" => Ok(Vec::new()),
+        "weekdays" => Ok((1..=5).collect()),
+        "weekends" => Ok(vec![6, 7]),
+        list => {
+            let mut days = Vec::new();
+            for day in list.split(',').map(str::trim) {
+                let day = WEEKDAY_NAMES
+                    .iter()
+                    .position(|(name, _)| *name == day)
+                    .map(|index| index as u8 + 1)
+                    .or_else(|| day.parse().ok().filter(|day| (1..=7).contains(day)))
+                    .ok_or_else(|| {
+                        format!(
+                            "无法识别的日期：{}（可选 daily、weekdays、weekends，或 mon,tue,… 的组合）",
+                            day
+                        )
+                    })?;
+                if !days.contains(&day) {
+                    days.push(day);
+                }
+            }
+            days.sort_unstable();
+            Ok(days)
+        }
+    }
+}
+
+// 24 小时制的 HH:MM。
+pub fn parse_time(text: &str) -> Result<(u8, u8), String> {
+    let invalid = || format!("无法识别的时间：{}（格式为 HH:MM）", text);
+    let (hour, minute) = text.trim().split_once(':').ok_or_else(invalid)?;
+    let hour = hour
+        .parse()
+        .ok()
+        .filter(|hour| *hour < 24)
+        .ok_or_else(invalid)?;
+    let minute = minute
+        .parse()
+        .ok()
+        .filter(|minute| *minute < 60)
+        .ok_or_else(invalid)?;
+    Ok((hour, minute))
+}
+
+impl ProfileSchedule {
+    // 名称用于 launchd 的 Label 和文件名，只允许字母、数字、- 与 _。
+    pub fn new(
+        name: &str,
+        profile: PathBuf,
+        weekdays: Vec<u8>,
+        (hour, minute): (u8, u8),
+    ) -> Result<Self, String> {
+        check_schedule_name(name)?;
+        Ok(ProfileSchedule {
+            name: name.to_string(),
+            profile,
+            weekdays,
+            hour,
+            minute,
+        })
+    }
+
+    pub fn label(&self) -> String {
+        profile_label(&self.name)
+    }
+
+    pub fn plist_path(&self) -> PathBuf {
+        launch_agents_dir().join(format!("{}.plist", self.label()))
+    }
+
+    pub fn log_path(&self) -> PathBuf {
+        agents_dir().join(format!("{}-{}.log", PROFILE_LABEL, self.name))
+    }
+
+    // 例如“工作日 09:00”“周一、周三 18:30”。
+    pub fn describe(&self) -> String {
+        let days = match self.weekdays.as_slice() {
+            [] => "每天".to_string(),
+            [1, 2, 3, 4, 5] => "工作日".to_string(),
+            [6, 7] => "周末".to_string(),
+            days => days
+                .iter()
+                .filter_map(|day| WEEKDAY_NAMES.get(usize::from(*day).wrapping_sub(1)))
+                .map(|(_, text)| *text)
+                .collect::<Vec<_>>()
+                .join("、"),
+        };
+        format!("{} {:02}:{:02}", days, self.hour, self.minute)
+    }
+
+    // 每个星期几对应 StartCalendarInterval 中的一项；每天时只有时间。
+    pub fn to_plist(&self, exe: &Path) -> Value {
+        let mut dict = Dictionary::new();
+        dict.insert("Label".into(), self.label().into());
+        dict.insert(
+            "ProgramArguments".into(),
+            Value::Array(vec![
+                exe.display().to_string().into(),
+                "import".into(),
+                "--scheduled".into(),
+                self.profile.display().to_string().into(),
+            ]),
+        );
+        dict.insert(
+            "EnvironmentVariables".into(),
+            Value::Dictionary(job_environment()),
+        );
+        let at = |weekday: Option<u8>| {
+            let mut entry = Dictionary::new();
+            if let Some(weekday) = weekday {
+                entry.insert("Weekday".into(), u64::from(weekday).into());
+            }
+            entry.insert("Hour".into(), u64::from(self.hour).into());
+            entry.insert("Minute".into(), u64::from(self.minute).into());
+            Value::Dictionary(entry)
+        };
+        let intervals = if self.weekdays.is_empty() {
+            vec![at(None)]
+        } else {
+            self.weekdays.iter().map(|day| at(Some(*day))).collect()
+        };
+        dict.insert("StartCalendarInterval".into(), Value::Array(intervals));
+        let log = self.log_path().display().to_string();
+        dict.insert("StandardOutPath".into(), log.clone().into());
+        dict.insert("StandardErrorPath".into(), log.into());
+        Value::Dictionary(dict)
+    }
+
+    pub fn from_plist(value: &Value) -> Option<Self> {
+        let dict = value.as_dictionary()?;
+        let name = dict
+            .get("Label")?
+            .as_string()?
+            .strip_prefix(&profile_label(""))?
+            .to_string();
+        let profile = dict
+            .get("ProgramArguments")?
+            .as_array()?
+            .last()?
+            .as_string()
+            .map(PathBuf::from)?;
+        let intervals = dict.get("StartCalendarInterval")?.as_array()?;
+        let field = |entry: &Value, key: &str| {
+            entry
+                .as_dictionary()?
+                .get(key)?
+                .as_unsigned_integer()
+                .and_then(|value| u8::try_from(value).ok())
+        };
+        let first = intervals.first()?;
+        let mut weekdays: Vec<u8> = intervals
+            .iter()
+            .filter_map(|entry| field(entry, "Weekday"))
+            .map(|day| if day == 0 { 7 } else { day })
+            .collect();
+        weekdays.sort_unstable();
+        weekdays.dedup();
+        Some(ProfileSchedule {
+            name,
+            profile,
+            weekdays,
+            hour: field(first, "Hour")?,
+            minute: field(first, "Minute").unwrap_or(0),
+        })
+    }
+}
+
+fn profile_label(name: &str) -> String {
+    format!("{}.{}.{}", LABEL_PREFIX, PROFILE_LABEL, name)
+}
+
+// 同名计划已存在时替换。配置文件需要是绝对路径，launchd 运行时的工作目录不确定。
+pub fn install_profile_schedule(
+    schedule: &ProfileSchedule,
+    exe: &Path,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if !schedule.profile.is_absolute() || !schedule.profile.is_file() {
+        return Err(format!("找不到配置文件：{}", schedule.profile.display()).into());
+    }
+    let path = schedule.plist_path();
+    load_plist(&path, &schedule.to_plist(exe))?;
+    Ok(path)
+}
+
+// 名称会拼进 LaunchAgents 下的文件名，添加和删除时都要检查，避免 ../ 之类的名称指向其它文件。
+fn check_schedule_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+    {
+        return Err(format!("计划名称只能包含字母、数字、- 与 _：{}", name));
+    }
+    Ok(())
+}
+
+pub fn uninstall_profile_schedule(name: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    check_schedule_name(name)?;
+    unload_plist(&launch_agents_dir().join(format!("{}.plist", profile_label(name))))
+}
+
+pub fn profile_schedules() -> Vec<ProfileSchedule> {
+    let prefix = profile_label("");
+    let mut schedules: Vec<ProfileSchedule> = fs::read_dir(launch_agents_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .filter_map(|entry| Value::from_file(entry.path()).ok())
+        .filter_map(|value| ProfileSchedule::from_plist(&value))
+        .collect();
+    schedules.sort_by(|a, b| a.name.cmp(&b.name));
+    schedules
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastRun {
+    pub finished_at: u64,
+    pub exit_status: i32,
+}
+
+// 只在由 launchd 启动（设置了 AGENT_JOB_ENV）时记录，手动运行命令不影响 status 的结果。
+pub fn record_run(exit_status: i32) {
+    let Some(job) = std::env::var(AGENT_JOB_ENV)
+        .ok()
+        .and_then(|name| AgentJob::parse(&name))
+    else {
+        return;
+    };
+    let finished_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let run = LastRun {
+        finished_at,
+        exit_status,
+    };
+    let result = fs::create_dir_all(agents_dir()).and_then(|_| {
+        write_atomic(
+            &last_run_path(job),
+            &serde_json::to_vec_pretty(&run).unwrap_or_default(),
+        )
+    });
+    if let Err(err) = result {
+        eprintln!("[Agent] 记录运行结果失败：{}", err);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentStatus {
+    pub job: AgentJob,
+    pub label: String,
+    pub plist: PathBuf,
+    pub installed: bool,
+    pub loaded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<LastRun>,
+}
+
+// `launchctl list <label>` 成功即表示已加载，输出中带有 "PID" = 123; 这样的行。
+fn launchctl_list(label: &str) -> Option<Option<u32>> {
+    let output = Command::new("launchctl")
+        .args(["list", label])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let pid = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            line.trim()
+                .strip_prefix("\"PID\" = ")?
+                .trim_end_matches(';')
+                .parse()
+                .ok()
+        });
+    Some(pid)
+}
+
+pub fn agent_status(job: AgentJob) -> AgentStatus {
+    let plist = job.plist_path();
+    let installed = plist.exists();
+    let interval_secs = plist::Value::from_file(&plist).ok().and_then(|value| {
+        value
+            .as_dictionary()?
+            .get("StartInterval")?
+            .as_unsigned_integer()
+    });
+    let loaded = launchctl_list(&job.label());
+    let last_run = fs::read(last_run_path(job))
+        .ok()
+        .and_then(|contents| serde_json::from_slice(&contents).ok());
+    AgentStatus {
+        job,
+        label: job.label(),
+        plist,
+        installed,
+        loaded: loaded.is_some(),
+        pid: loaded.flatten(),
+        interval_secs,
+        last_run,
+    }
+}
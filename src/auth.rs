@@ -0,0 +1,63 @@
+use std::process::Command;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::config;
+
+// 验证通过后这段时间内不再重复询问，与 sudo 的做法一致。
+const GRACE_PERIOD: Duration = Duration::from_secs(5 * 60);
+
+// 用户取消或验证失败时错误信息的开头。
+pub const AUTH_ERROR: &str = "身份验证未通过";
+
+static LAST_CONFIRMED: Mutex<Option<Instant>> = Mutex::new(None);
+
+// JXA 调用 LocalAuthentication；策略 2（LAPolicyDeviceOwnerAuthentication）在没有 Touch ID
+// 时退回登录密码。回调在其他线程执行，这里转动 run loop 等它返回。
+const SCRIPT: &str = r#"
+ObjC.import("LocalAuthentication");
+function run(argv) {
+  var context = $.LAContext.alloc.init;
+  var done = false;
+  var ok = false;
+  context.evaluatePolicyLocalizedReasonReply(2, argv[0], function (success) {
+    ok = success;
+    done = true;
+  });
+  while (!done) {
+    $.NSRunLoop.currentRunLoop.runUntilDate($.NSDate.dateWithTimeIntervalSinceNow(0.1));
+  }
+  return ok ? "ok" : "denied";
+}
+"#;
+
+// 配置项 confirm_destructive 开启、且不在上次验证后的宽限期内时需要验证。
+pub fn required() -> bool {
+    if !config::load_config()
+        .unwrap_or_default()
+        .confirm_destructive
+    {
+        return false;
+    }
+    let last = LAST_CONFIRMED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    !last.is_some_and(|at| at.elapsed() < GRACE_PERIOD)
+}
+
+// 需要时弹出 Touch ID / 登录密码对话框，reason 显示在对话框中。会阻塞到用户作出选择。
+pub fn confirm(reason: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if !required() {
+        return Ok(());
+    }
+    let output = Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", SCRIPT, reason])
+        .output()?;
+    if !output.status.success() || String::from_utf8_lossy(&output.stdout).trim() != "ok" {
+        return Err(format!("{}：{}", AUTH_ERROR, reason).into());
+    }
+    *LAST_CONFIRMED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some(Instant::now());
+    Ok(())
+}
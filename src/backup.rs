@@ -0,0 +1,296 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bundle::{fnv1a, write_info_plist};
+use crate::config;
+use crate::crypto;
+use crate::state::write_atomic;
+
+const META_FILE: &str = "meta.json";
+const OBJECTS_DIR: &str = "objects";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupEntry {
+    pub app: PathBuf,
+    // 内容相同的备份共用 objects/ 下的同一个文件
+    pub file: PathBuf,
+    pub created_at: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupRef {
+    created_at: u64,
+    object: String,
+}
+
+// 每个应用目录只保存引用，按时间从新到旧排列。
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupMeta {
+    path: PathBuf,
+    // 旧版本没有这个字段，备份以 <时间戳>.plist 直接存放在应用目录中
+    #[serde(default)]
+    backups: Vec<BackupRef>,
+}
+
+pub fn backups_dir() -> PathBuf {
+    config::data_dir().join("backups")
+}
+
+fn objects_dir() -> PathBuf {
+    backups_dir().join(OBJECTS_DIR)
+}
+
+fn object_file(object: &str) -> PathBuf {
+    objects_dir().join(format!("{}.plist", object))
+}
+
+fn bundle_backup_dir(app: &Path) -> PathBuf {
+    let name = app
+        .file_stem()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let key = fnv1a(app.to_string_lossy().as_bytes());
+    backups_dir().join(format!("{}-{:016x}", name, key))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+// 按内容哈希存放，已有相同内容时直接复用；哈希相同而内容不同时（极少见）加序号区分。
+fn store_object(contents: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    fs::create_dir_all(objects_dir())?;
+    let hash = format!("{:016x}", fnv1a(contents));
+    let mut object = hash.clone();
+    let mut index = 0;
+    loop {
+        let file = object_file(&object);
+        match fs::read(&file) {
+            Ok(existing) if crypto::open(&existing)? == contents => return Ok(object),
+            Ok(_) => {
+                index += 1;
+                object = format!("{}-{}", hash, index);
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                write_atomic(&file, &crypto::seal(contents)?)?;
+                return Ok(object);
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+fn legacy_timestamp(file: &Path) -> Option<u64> {
+    file.extension()
+        .filter(|ext| *ext == "plist")
+        .and_then(|_| file.file_stem())
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.parse().ok())
+}
+
+fn save_meta(dir: &Path, meta: &BackupMeta) -> Result<(), Box<dyn std::error::Error>> {
+    write_atomic(
+        &dir.join(META_FILE),
+        &crypto::seal(&serde_json::to_vec_pretty(meta)?)?,
+    )?;
+    Ok(())
+}
+
+// 开关 encrypt_data 后，按新设置重写引用记录和它引用的内容文件。
+fn reseal_meta(dir: &Path, meta: &BackupMeta) -> Result<(), Box<dyn std::error::Error>> {
+    for backup in &meta.backups {
+        let file = object_file(&backup.object);
+        if let Ok(data) = fs::read(&file)
+            && let Some(resealed) = crypto::reseal(&data)?
+        {
+            write_atomic(&file, &resealed)?;
+        }
+    }
+    save_meta(dir, meta)
+}
+
+// 旧版本留下的 <时间戳>.plist 在第一次读取时移入内容存储。
+fn load_meta(dir: &Path) -> Result<BackupMeta, Box<dyn std::error::Error>> {
+    let raw = fs::read(dir.join(META_FILE))?;
+    let mut meta: BackupMeta = serde_json::from_slice(&crypto::open(&raw)?)?;
+    let legacy: Vec<(u64, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|item| item.ok())
+        .filter_map(|item| legacy_timestamp(&item.path()).map(|at| (at, item.path())))
+        .collect();
+    if legacy.is_empty() {
+        if crypto::is_sealed(&raw) != crypto::enabled() {
+            reseal_meta(dir, &meta)?;
+        }
+        return Ok(meta);
+    }
+    for (created_at, file) in &legacy {
+        meta.backups.push(BackupRef {
+            created_at: *created_at,
+            object: store_object(&fs::read(file)?)?,
+        });
+    }
+    meta.backups
+        .sort_by_key(|backup| Reverse(backup.created_at));
+    save_meta(dir, &meta)?;
+    for (_, file) in legacy {
+        fs::remove_file(file)?;
+    }
+    Ok(meta)
+}
+
+pub fn backup_plist(app: &Path) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let retention = config::load_config().unwrap_or_default().backup_retention;
+    if retention == 0 {
+        return Ok(None);
+    }
+    let dir = bundle_backup_dir(app);
+    fs::create_dir_all(&dir)?;
+    // 只有还没有 meta.json 时才从空列表开始；读不出或解不开时必须报错，否则保存后垃圾回收会删掉已有的备份。
+    let mut meta = match load_meta(&dir) {
+        Ok(meta) => meta,
+        Err(err)
+            if err
+                .downcast_ref::<io::Error>()
+                .is_some_and(|err| err.kind() == io::ErrorKind::NotFound) =>
+        {
+            BackupMeta {
+                path: app.to_path_buf(),
+                backups: Vec::new(),
+            }
+        }
+        Err(err) => return Err(err),
+    };
+
+    let mut created_at = now_millis();
+    while meta
+        .backups
+        .iter()
+        .any(|backup| backup.created_at == created_at)
+    {
+        created_at += 1;
+    }
+    let object = store_object(&fs::read(app.join("Contents/Info.plist"))?)?;
+    meta.backups.insert(
+        0,
+        BackupRef {
+            created_at,
+            object: object.clone(),
+        },
+    );
+    meta.backups.truncate(retention);
+    save_meta(&dir, &meta)?;
+    collect_garbage()?;
+    Ok(Some(object_file(&object)))
+}
+
+fn entries_in(dir: &Path) -> Result<Vec<BackupEntry>, Box<dyn std::error::Error>> {
+    let meta = load_meta(dir)?;
+    Ok(meta
+        .backups
+        .iter()
+        .map(|backup| {
+            let file = object_file(&backup.object);
+            BackupEntry {
+                app: meta.path.clone(),
+                size: fs::metadata(&file)
+                    .map(|meta| meta.len())
+                    .unwrap_or_default(),
+                file,
+                created_at: backup.created_at,
+            }
+        })
+        .collect())
+}
+
+fn app_dirs() -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let root = backups_dir();
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_dir(&root)?
+        .filter_map(|item| item.ok())
+        .map(|item| item.path())
+        .filter(|path| path.is_dir() && path.file_name() != Some(OBJECTS_DIR.as_ref()))
+        .collect())
+}
+
+// 删除不再被任何应用引用的内容文件。
+fn collect_garbage() -> Result<usize, Box<dyn std::error::Error>> {
+    let mut referenced = HashSet::new();
+    for dir in app_dirs()? {
+        if !dir.join(META_FILE).exists() {
+            continue;
+        }
+        // 读不了引用记录时无法确定哪些内容仍在使用，这次不做清理
+        let Ok(meta) = load_meta(&dir) else {
+            return Ok(0);
+        };
+        referenced.extend(meta.backups.into_iter().map(|backup| backup.object));
+    }
+    let Ok(items) = fs::read_dir(objects_dir()) else {
+        return Ok(0);
+    };
+    let mut removed = 0;
+    for item in items.filter_map(|item| item.ok()) {
+        let file = item.path();
+        let in_use = file
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| referenced.contains(stem));
+        if !in_use && file.extension().is_some_and(|ext| ext == "plist") {
+            fs::remove_file(&file)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+pub fn list_backups() -> Result<Vec<BackupEntry>, Box<dyn std::error::Error>> {
+    let mut entries = Vec::new();
+    for dir in app_dirs()? {
+        if let Ok(mut found) = entries_in(&dir) {
+            entries.append(&mut found);
+        }
+    }
+    entries.sort_by(|a, b| a.app.cmp(&b.app).then(b.created_at.cmp(&a.created_at)));
+    Ok(entries)
+}
+
+// 返回删除的备份条数；内容文件只在没有任何应用引用时才删除。
+pub fn prune_backups(retention: usize) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut removed = 0;
+    for dir in app_dirs()? {
+        let mut meta = load_meta(&dir)?;
+        if meta.backups.len() > retention {
+            removed += meta.backups.len() - retention;
+            meta.backups.truncate(retention);
+            save_meta(&dir, &meta)?;
+        }
+    }
+    collect_garbage()?;
+    Ok(removed)
+}
+
+pub fn restore_backup(app: &Path, created_at: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = bundle_backup_dir(app);
+    let meta = load_meta(&dir).map_err(|_| format!("没有 {} 的备份", app.display()))?;
+    let backup = meta
+        .backups
+        .iter()
+        .find(|backup| backup.created_at == created_at)
+        .ok_or_else(|| format!("找不到 {} 在 {} 的备份", app.display(), created_at))?;
+    let contents = crypto::open(&fs::read(object_file(&backup.object))?)?;
+    backup_plist(app)?;
+    write_info_plist(app, &contents)?;
+    Ok(())
+}
@@ -0,0 +1,468 @@
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
+
+use plist::Value;
+use serde::Serialize;
+
+use crate::config;
+use crate::events::{self, Event};
+use crate::explain;
+use crate::helper;
+use crate::history::{self, HistoryAction};
+use crate::plist_cache;
+use crate::shutdown;
+use crate::store::{BundleStore, FsStore};
+use crate::translocation::{self, Translocations};
+use crate::write_queue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Privilege {
+    User,
+    Admin,
+}
+
+// 以追加方式打开不会改动文件内容，只用来判断当前用户能否写入 Info.plist。
+pub fn required_privilege(app: &Path) -> Privilege {
+    match OpenOptions::new()
+        .append(true)
+        .open(app.join("Contents/Info.plist"))
+    {
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => Privilege::Admin,
+        _ => Privilege::User,
+    }
+}
+
+// 应用包缺少 Info.plist 时错误信息的开头，也用于归类错误。
+pub const MISSING_PLIST_ERROR: &str = "应用包中没有 Contents/Info.plist";
+
+// 修改前检查：应用包还在、只是缺少 Info.plist 时给出明确的错误，而不是笼统的“文件不存在”。
+pub fn preflight(app: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if app.is_dir() && !app.join("Contents/Info.plist").exists() {
+        return Err(format!("{}：{}", MISSING_PLIST_ERROR, app.display()).into());
+    }
+    Ok(())
+}
+
+// 结构不像应用包时错误信息的开头，也用于归类错误。
+pub const INVALID_BUNDLE_ERROR: &str = "不像是有效的应用包";
+
+// 隐藏前检查应用包的结构：Info.plist 能解析，Contents/MacOS 下有 CFBundleExecutable 指定
+// （未指定时与包同名）的可执行文件，避免往恰好以 .app 结尾的普通目录里写入 Info.plist。
+pub fn validate_bundle(app: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    preflight(app)?;
+    let plist = plist_cache::read(app)?;
+    let executable = plist
+        .as_dictionary()
+        .and_then(|dict| dict.get("CFBundleExecutable"))
+        .and_then(Value::as_string)
+        .map(str::to_string)
+        .or_else(|| {
+            app.file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+    let path = app.join("Contents/MacOS").join(&executable);
+    let is_executable = fs::metadata(&path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.mode() & 0o111 != 0);
+    if executable.is_empty() || !is_executable {
+        return Err(format!(
+            "{}：{} 中没有可执行文件 Contents/MacOS/{}",
+            INVALID_BUNDLE_ERROR,
+            app.display(),
+            executable
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// 只包装了一个可执行文件的简易 .app 可以补上只含基本字段和 LSUIElement 的 Info.plist。
+// 没有 Contents/MacOS 下的可执行文件时不算应用包，拒绝创建。
+pub fn create_minimal_info_plist(app: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    config::ensure_writable()?;
+    if app.join("Contents/Info.plist").exists() {
+        return Err("Info.plist 已存在".into());
+    }
+    let executables: Vec<String> = fs::read_dir(app.join("Contents/MacOS"))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    if executables.is_empty() {
+        return Err("Contents/MacOS 中没有可执行文件，无法创建 Info.plist".into());
+    }
+    let name = app
+        .file_stem()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    // 只有一个可执行文件时直接指定；多个时按系统的默认规则使用与包同名的那个。
+    let executable = match executables.as_slice() {
+        [executable] => executable.clone(),
+        _ => name.clone(),
+    };
+    let mut dict = plist::Dictionary::new();
+    dict.insert("CFBundleName".into(), Value::String(name));
+    dict.insert("CFBundleExecutable".into(), Value::String(executable));
+    dict.insert("CFBundlePackageType".into(), Value::String("APPL".into()));
+    dict.insert("LSUIElement".into(), Value::String("1".into()));
+    let mut contents = Vec::new();
+    plist::to_writer_xml(&mut contents, &Value::Dictionary(dict))?;
+    write_info_plist(app, &contents)?;
+    eprintln!("[Bundle] 已创建最小的 Info.plist：{}", app.display());
+    Ok(())
+}
+
+pub fn write_info_plist(app: &Path, contents: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    config::ensure_writable()?;
+    let _deferred = shutdown::defer();
+    let plist_path = app.join("Contents/Info.plist");
+    let result = write_queue::run(|| match required_privilege(app) {
+        Privilege::User => Ok(fs::write(&plist_path, contents)?),
+        Privilege::Admin => helper::write_file_privileged(&plist_path, contents),
+    });
+    plist_cache::invalidate(app);
+    result
+}
+
+// 只改动 LSUIElement 并保持原来的编码（二进制或 XML），返回新内容与原来的值。
+// 顶层不是字典时原样返回。
+pub fn replace_ui_element(
+    contents: &[u8],
+    value: Option<Value>,
+) -> Result<(Vec<u8>, Option<Value>), Box<dyn std::error::Error>> {
+    let mut plist = Value::from_reader(io::Cursor::new(contents))?;
+    let Value::Dictionary(ref mut dict) = plist else {
+        return Ok((contents.to_vec(), None));
+    };
+    let previous = match value {
+        Some(value) => dict.insert("LSUIElement".into(), value),
+        None => dict.remove("LSUIElement"),
+    };
+    let mut updated = Vec::new();
+    if contents.starts_with(b"bplist") {
+        plist::to_writer_binary(&mut updated, &plist)?;
+    } else {
+        plist::to_writer_xml(&mut updated, &plist)?;
+    }
+    Ok((updated, previous))
+}
+
+fn set_ui_element(
+    store: &dyn BundleStore,
+    app: &Path,
+    value: Option<Value>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let contents = store.read_info_plist(app)?;
+    let (updated, _) = replace_ui_element(&contents, value)?;
+    if updated != contents {
+        store.write_info_plist(app, &updated)?;
+    }
+    Ok(())
+}
+
+pub fn hide_dock_icon_in(
+    store: &dyn BundleStore,
+    app: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    set_ui_element(store, app, Some(Value::String("1".into())))
+}
+
+fn record_history<T>(
+    action: HistoryAction,
+    app: &Path,
+    operation: impl FnOnce() -> Result<T, Box<dyn std::error::Error>>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    events::emit(Event::OperationStarted {
+        action,
+        path: app.to_path_buf(),
+    });
+    let before = ui_element(app).ok();
+    let result = operation();
+    let error = result.as_ref().err().map(|err| err.to_string());
+    // 只在 LSUIElement 确实变化时生成等价命令。
+    let after = if error.is_none() {
+        ui_element(app).ok()
+    } else {
+        None
+    };
+    let commands = match (before, after) {
+        (Some(before), Some(after)) if before != after => {
+            explain::ui_element_commands(app, after.as_ref())
+        }
+        _ => Vec::new(),
+    };
+    explain::print(&commands);
+    events::emit(match &error {
+        None => Event::OperationSucceeded {
+            action,
+            path: app.to_path_buf(),
+        },
+        Some(error) => Event::OperationFailed {
+            action,
+            path: app.to_path_buf(),
+            error: error.clone(),
+        },
+    });
+    history::record_with_commands(action, app, error, commands);
+    result
+}
+
+pub fn hide_dock_icon(app: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    record_history(HistoryAction::Hide, app, || {
+        validate_bundle(app)?;
+        hide_dock_icon_in(&FsStore, app)
+    })
+}
+
+pub fn restore_dock_icon_in(
+    store: &dyn BundleStore,
+    app: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    set_ui_element(store, app, Some(Value::String("0".into())))
+}
+
+pub fn restore_dock_icon(app: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    record_history(HistoryAction::Restore, app, || {
+        preflight(app)?;
+        restore_dock_icon_in(&FsStore, app)
+    })
+}
+
+fn is_hiding_value(value: &Value) -> bool {
+    match value {
+        Value::String(value) => value == "1" || value.eq_ignore_ascii_case("true"),
+        Value::Boolean(value) => *value,
+        Value::Integer(value) => value.as_signed() == Some(1),
+        _ => false,
+    }
+}
+
+// 把 LSUIElement 还原成隐藏前的值（原来没有就删掉）；原来的值本身就会隐藏图标时仍写入 "0"。
+pub fn restore_original_ui_element_in(
+    store: &dyn BundleStore,
+    app: &Path,
+    original: Option<&Value>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match original {
+        Some(value) if is_hiding_value(value) => restore_dock_icon_in(store, app),
+        original => set_ui_element(store, app, original.cloned()),
+    }
+}
+
+pub fn restore_original_ui_element(
+    app: &Path,
+    original: Option<&Value>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    record_history(HistoryAction::Restore, app, || {
+        preflight(app)?;
+        restore_original_ui_element_in(&FsStore, app, original)
+    })
+}
+
+pub fn ui_element_in(
+    store: &dyn BundleStore,
+    app: &Path,
+) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+    let plist = Value::from_reader(io::Cursor::new(store.read_info_plist(app)?))?;
+    Ok(ui_element_of(&plist))
+}
+
+fn ui_element_of(plist: &Value) -> Option<Value> {
+    plist
+        .as_dictionary()
+        .and_then(|dict| dict.get("LSUIElement"))
+        .cloned()
+}
+
+// 直接读磁盘时经过 plist_cache，校验大量应用时不必每次重新解析。
+pub fn ui_element(app: &Path) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+    Ok(ui_element_of(&plist_cache::read(app)?))
+}
+
+pub fn is_dock_icon_hidden_in(
+    store: &dyn BundleStore,
+    app: &Path,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    Ok(ui_element_in(store, app)?.is_some_and(|value| is_hiding_value(&value)))
+}
+
+pub fn is_dock_icon_hidden(app: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+    Ok(ui_element(app)?.is_some_and(|value| is_hiding_value(&value)))
+}
+
+pub fn bundle_identifier(app: &Path) -> Option<String> {
+    plist_cache::string(app, "CFBundleIdentifier")
+}
+
+// ps 的 comm 列是可执行文件的完整路径，取 .app/Contents/MacOS/ 之前的部分即为所属的应用。
+// 从转移后的临时位置运行的应用换成原始位置，以便与管理列表中的路径比较。
+pub fn running_bundles() -> HashSet<PathBuf> {
+    let Ok(output) = Command::new("ps").args(["-axww", "-o", "comm="]).output() else {
+        return HashSet::new();
+    };
+    let bundles: Vec<PathBuf> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().split_once(".app/Contents/MacOS/"))
+        .map(|(bundle, _)| PathBuf::from(format!("{}.app", bundle)))
+        .collect();
+    let translocations = bundles
+        .iter()
+        .any(|bundle| translocation::is_translocated(bundle))
+        .then(Translocations::read);
+    bundles
+        .into_iter()
+        .map(|bundle| match &translocations {
+            Some(translocations) if translocation::is_translocated(&bundle) => {
+                translocations.original(&bundle).unwrap_or(bundle)
+            }
+            _ => bundle,
+        })
+        .collect()
+}
+
+// 包内 Contents/Library/LoginItems 下的登录项辅助程序会单独启动，同样可能显示 Dock 图标。
+pub fn login_item_helpers(app: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(app.join("Contents/Library/LoginItems")) else {
+        return Vec::new();
+    };
+    let mut helpers: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_app_bundle(path))
+        .collect();
+    helpers.sort();
+    helpers
+}
+
+// 尚未隐藏 Dock 图标的登录项辅助程序；大多数辅助程序本身已设置 LSUIElement，无需处理。
+pub fn visible_login_item_helpers(app: &Path) -> Vec<PathBuf> {
+    login_item_helpers(app)
+        .into_iter()
+        .filter(|helper| matches!(is_dock_icon_hidden(helper), Ok(false)))
+        .collect()
+}
+
+pub fn bundle_name(app: &Path) -> Option<String> {
+    let plist = plist_cache::read(app).ok()?;
+    let dict = plist.as_dictionary()?;
+    ["CFBundleDisplayName", "CFBundleName"]
+        .iter()
+        .find_map(|key| dict.get(key).and_then(Value::as_string))
+        .map(str::to_string)
+}
+
+pub fn ui_element_value(app: &Path) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let plist = plist_cache::read(app)?;
+    let value = plist
+        .as_dictionary()
+        .and_then(|dict| dict.get("LSUIElement"))
+        .map(|value| match value {
+            Value::String(value) => value.clone(),
+            Value::Boolean(value) => value.to_string(),
+            Value::Integer(value) => value
+                .as_signed()
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            other => format!("{:?}", other),
+        });
+    Ok(value)
+}
+
+pub fn bundle_short_version(app: &Path) -> Option<String> {
+    plist_cache::string(app, "CFBundleShortVersionString")
+}
+
+pub fn bundle_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| bundle_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or_default()
+}
+
+// 用 sips 把 .icns 转成 PNG，供界面显示图标。
+pub fn icon_png(app: &Path, size: u32) -> Option<Vec<u8>> {
+    let plist = plist_cache::read(app).ok()?;
+    let name = plist
+        .as_dictionary()?
+        .get("CFBundleIconFile")?
+        .as_string()?
+        .to_string();
+    let mut icon = app.join("Contents/Resources").join(&name);
+    if icon.extension().is_none() {
+        icon.set_extension("icns");
+    }
+    let out = std::env::temp_dir().join(format!(
+        "dock-dodger-icon-{}-{:016x}.png",
+        std::process::id(),
+        fnv1a(app.to_string_lossy().as_bytes())
+    ));
+    let status = Command::new("sips")
+        .args(["-s", "format", "png", "-Z", &size.to_string()])
+        .arg(&icon)
+        .arg("--out")
+        .arg(&out)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .ok()?;
+    let png = status.success().then(|| fs::read(&out).ok()).flatten();
+    let _ = fs::remove_file(&out);
+    png
+}
+
+pub fn bundle_version(app: &Path) -> Option<String> {
+    let plist = plist_cache::read(app).ok()?;
+    let dict = plist.as_dictionary()?;
+    ["CFBundleVersion", "CFBundleShortVersionString"]
+        .iter()
+        .find_map(|key| dict.get(key).and_then(Value::as_string))
+        .map(str::to_string)
+}
+
+pub fn plist_owner_and_mtime(app: &Path) -> io::Result<(u32, u64)> {
+    let metadata = fs::metadata(app.join("Contents/Info.plist"))?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    Ok((metadata.uid(), modified))
+}
+
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn plist_checksum(app: &Path) -> std::io::Result<String> {
+    let data = fs::read(app.join("Contents/Info.plist"))?;
+    Ok(format!("{:016x}", fnv1a(&data)))
+}
+
+pub fn is_app_bundle(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.eq_ignore_ascii_case("app"))
+        .unwrap_or(false)
+}
@@ -0,0 +1,2860 @@
+mod rpc;
+mod watch;
+
+use std::fs;
+use std::io::Write;
+use std::io::{self, BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use osx_dock_dodger_rs::agent::{self, AgentJob};
+use osx_dock_dodger_rs::auth;
+use osx_dock_dodger_rs::backup;
+use osx_dock_dodger_rs::bundle::{
+    MISSING_PLIST_ERROR, bundle_identifier, create_minimal_info_plist, hide_dock_icon,
+    is_app_bundle, is_dock_icon_hidden, preflight, running_bundles, ui_element, validate_bundle,
+    visible_login_item_helpers,
+};
+use osx_dock_dodger_rs::config;
+use osx_dock_dodger_rs::dock;
+use osx_dock_dodger_rs::error::ErrorKind;
+use osx_dock_dodger_rs::events::{self, Event};
+use osx_dock_dodger_rs::explain;
+use osx_dock_dodger_rs::history::{self, ExportFormat, HistoryAction, HistoryFilter};
+use osx_dock_dodger_rs::i18n;
+use osx_dock_dodger_rs::import::{self, MergeStrategy};
+use osx_dock_dodger_rs::installed;
+use osx_dock_dodger_rs::notify;
+use osx_dock_dodger_rs::pause::{self, PauseResult};
+use osx_dock_dodger_rs::relaunch;
+use osx_dock_dodger_rs::report;
+use osx_dock_dodger_rs::rollback::{self, RollbackStep};
+use osx_dock_dodger_rs::scan::{DEFAULT_SCAN_DEPTH, ScanScope};
+use osx_dock_dodger_rs::session;
+use osx_dock_dodger_rs::shutdown;
+use osx_dock_dodger_rs::signing;
+use osx_dock_dodger_rs::snapshot::{self, SnapshotPlan};
+use osx_dock_dodger_rs::state::{self, ColorLabel, ManagedApp, RelaunchOptions};
+use osx_dock_dodger_rs::translocation;
+use osx_dock_dodger_rs::volume;
+use osx_dock_dodger_rs::webhook;
+use serde::Serialize;
+
+// 退出前最多等待这么久，让排队的 Webhook 请求发送完。
+const WEBHOOK_WAIT: Duration = Duration::from_secs(15);
+
+const USAGE: &str = "用法：
+  dock-dodger hide [--from-file <文件|->] [--stdin] [--bundle-id <ID>]... [--with-helpers] [--create-plist] [--json] [<路径>...]
+  dock-dodger hide-all [--depth <层数>] [--exclude <模式>]... [--with-helpers] [--dry-run] [--yes] [--json] <目录>
+  dock-dodger import [--dry-run] [--json] [--strategy <策略>] <文件|->
+  dock-dodger adopt [--dry-run] [--json]
+  dock-dodger backups [list] [--json]
+  dock-dodger backups prune [--keep <数量>]
+  dock-dodger backups restore <应用路径> <备份时间戳>
+  dock-dodger pause [--force] [--json]
+  dock-dodger resume [--force] [--json]
+  dock-dodger restore [--bundle-id <ID>]... [--force] [--json] [<路径>...]
+  dock-dodger verify [--reapply|--fix] [--notify] [--bundle-id <ID>]... [--json]
+  dock-dodger status [--bundle-id <ID>]... [--json]
+  dock-dodger watch [--interval <秒>] [--events-ndjson]
+  dock-dodger --events-ndjson
+  dock-dodger label <路径> <颜色|none>
+  dock-dodger relaunch <路径> [--background] [--hidden] [--off] [-- <参数>...]
+  dock-dodger history export [--format csv|json] [--since <日期>] [--until <日期>] [--app <应用>]... [--output <文件>]
+  dock-dodger history search [<文字>] [--since <日期>] [--until <日期>] [--app <应用>]... [--json]
+  dock-dodger rollback <时间> [--dry-run] [--yes] [--force] [--json]
+  dock-dodger snapshot take <名称>
+  dock-dodger snapshot restore <名称> [--dry-run] [--yes] [--force] [--json]
+  dock-dodger snapshot list [--json]
+  dock-dodger snapshot delete <名称>
+  dock-dodger report [--anonymize] [--output <文件>]
+  dock-dodger session restore
+  dock-dodger agent install [session|verify|notify|--all] [--interval <秒>]
+  dock-dodger agent uninstall [session|verify|notify|--all]
+  dock-dodger agent status [--json]
+  dock-dodger schedule add <名称> <配置文件> --at <HH:MM> [--days <日期>]
+  dock-dodger schedule remove <名称>
+  dock-dodger schedule list [--json]
+  dock-dodger tui
+  dock-dodger rpc
+
+命令：
+  hide    隐藏一个或多个 .app 的 Dock 图标
+          --from-file <文件>  从文件读取路径（每行一个，- 表示标准输入）
+          --stdin             从标准输入读取路径
+          --bundle-id <ID>    按 Bundle ID 指定应用（如 com.example.App），通过 Launch Services
+                              找到本机的安装位置，找不到时扫描配置的目录；可重复
+          --with-helpers      一并隐藏包内 Contents/Library/LoginItems 下的登录项辅助程序，
+                              之后与主应用一起恢复；对已管理的应用也可使用
+          --create-plist      应用包缺少 Info.plist 时（只包装了可执行文件的简易 .app），
+                              先创建只含基本字段和 LSUIElement 的最小 Info.plist
+          --json              以 JSON 输出汇总报告
+  hide-all 扫描目录中的所有 .app，列出后确认并全部隐藏（与图形界面拖入文件夹相同）
+          --depth <层数>      向下查找的层数，默认 2
+          --exclude <模式>    额外排除的路径或通配符，可重复；配置中的 scan_exclude 始终生效
+          --with-helpers      同 hide
+          --dry-run           只预览，不做修改
+          --yes               不询问直接隐藏（非交互环境中必须指定）
+          --json              以 JSON 输出汇总报告
+  import  从纯文本路径列表或其他工具导出的 JSON 导入并隐藏应用
+          --dry-run           只预览解析结果，不做修改
+          --json              以 JSON 输出汇总报告
+          --strategy <策略>   与已有记录冲突时的处理方式：
+                              skip（默认，保留已有记录）
+                              overwrite（重新隐藏并以导入的标签覆盖）
+                              interactive（逐个询问）
+          --scheduled         由 schedule 添加的计划使用，不再进行 confirm_destructive 验证
+  adopt   扫描配置的目录（默认 /Applications 与 ~/Applications）中已隐藏 Dock 图标的应用并加入管理列表
+          --dry-run           只列出扫描结果，不写入列表
+          --json              以 JSON 输出结果
+  backups 查看、清理或恢复修改前自动保存的 Info.plist 备份
+          list                列出所有备份（默认）
+          prune               按保留数量清理旧备份，默认使用配置中的 backup_retention
+          restore             用指定备份覆盖对应应用的 Info.plist
+  pause   暂时恢复所有已管理应用的 Dock 图标，列表保持不变
+  resume  重新隐藏暂停前管理的所有应用
+          --force             同时处理疑似由其他用户或工具管理的应用（默认跳过）
+  restore 恢复已管理应用的 Dock 图标并移出管理列表
+          --bundle-id <ID>    按隐藏时记录的 Bundle ID 指定应用，不区分大小写，可重复
+          --force             同时处理疑似由其他用户或工具管理的应用（默认跳过）
+          --json              以 JSON 输出结果
+  verify  检查已管理应用的 Dock 图标是否仍处于隐藏状态
+          --reapply, --fix    重新隐藏因应用更新等原因恢复了图标的应用
+          --notify            发现新的隐藏失效时发送系统通知（同一次失效只通知一次）
+          --bundle-id <ID>    只检查指定 Bundle ID 的应用，可重复
+          --json              以 JSON 输出结果
+          退出码：0 全部一致，3 有隐藏失效，1 读取或修改失败，2 参数错误
+  status  重新读取每个已管理应用的 Info.plist，列出 Bundle ID、应有与实际的图标状态、
+          是否正在运行以及上次操作的时间；有不一致时退出码为 1
+          --bundle-id <ID>    只列出指定 Bundle ID 的应用，可重复
+          --json              以 JSON 输出结果
+  watch   在前台持续监视，已管理应用的图标恢复、应用被删除，以及扫描目录中安装或删除应用时逐行输出
+          --interval <秒>     检查已管理应用的间隔，默认使用配置中的 watch_interval_secs
+          --events-ndjson     每个事件输出一行 JSON（NDJSON）
+  label   为已管理的应用设置颜色标记，图形界面中可按颜色筛选
+          颜色                red、orange、yellow、green、blue、purple、gray，none 表示清除
+  relaunch 设置重新隐藏后如何重新启动正在运行的应用（隐藏只在应用下次启动时生效）
+          --background        在后台启动，不切到前台（open -g）
+          --hidden            启动后隐藏窗口（open -j）
+          --off               不再自动重新启动
+          -- <参数>...        启动时传给应用的参数
+  history export      导出隐藏、恢复与隐藏失效的操作历史
+          --format <格式>     csv 或 json，默认按 --output 的扩展名，否则为 csv
+          --since <日期>      只导出该日期（YYYY-MM-DD，UTC）及之后的记录
+          --until <日期>      只导出该日期（含当天）及之前的记录
+          --app <应用>        只导出指定应用，可写完整路径或名称的一部分，可重复
+          --output <文件>     写入文件，默认输出到标准输出
+  history search      按应用名、路径或错误信息搜索操作历史，最旧的在前
+          <文字>              不区分大小写；只按日期筛选时可省略
+          --since/--until/--app  与 history export 相同
+          --json              输出 JSON 数组
+  rollback 按操作历史把各应用的隐藏状态回滚到某个时间点，先列出需要执行的操作
+          <时间>              YYYY-MM-DD 或 “YYYY-MM-DD HH:MM”，本机时区
+          --dry-run           只列出，不执行
+          --yes               不询问确认
+          --force             由其他用户或工具改过的应用也照常处理
+          --json              以 JSON 输出计划与结果
+          任何一步失败都会撤销已执行的步骤，列表保持不变
+  snapshot 保存或恢复整个管理列表（应用、隐藏方式与隐藏前的原始值）
+          take                以指定名称保存当前列表，同名快照会被覆盖
+          restore             隐藏快照中现在显示着的应用，恢复不在快照中的应用，
+                              列表换回快照中的条目；选项与 rollback 相同，同样作为一批执行
+          list                列出已保存的快照
+          delete              删除快照
+  report  生成用于提交问题的诊断包（zip），包含系统与版本信息、应用列表、配置、
+          最近的操作历史、代理日志以及最近一次失败的操作；用户主目录会替换为 ~
+          --anonymize         同时把应用路径替换为编号
+          --output <文件>     默认保存到“下载”文件夹
+  session restore     恢复所有设为“仅本次运行”的应用并移出列表
+  agent   管理 ~/Library/LaunchAgents 下的 launchd 代理（不指定时为 session）：
+          session             注销或关机时执行 session restore
+          verify              定期执行 verify --reapply --notify，--interval 指定间隔（默认 3600 秒）
+          notify              定期执行 verify --notify，只检查并通知，不做修改；间隔同上
+          install             生成并加载代理
+          uninstall           卸载并删除代理
+          status              查看各代理是否已安装、已加载以及上次运行的时间和退出码
+  schedule 按计划启用导出的配置文件（.dockdodger）：到时由 launchd 运行 import，
+          隐藏其中尚未隐藏的应用
+          add                 生成并加载计划，同名计划会被替换；名称只能包含字母、数字、- 与 _
+          --at <HH:MM>        启用的时间，24 小时制
+          --days <日期>       daily（默认）、weekdays、weekends，或 mon,tue,… 的组合
+          remove              卸载并删除计划
+          list                列出已有的计划
+  tui     在终端中浏览、搜索已管理的应用，并隐藏、恢复或校验（需启用 tui 功能，默认启用）
+  rpc     在标准输入/输出上提供逐行 JSON-RPC 2.0 接口，供编辑器插件或脚本调用：
+          list、hide、restore、subscribe/unsubscribe（推送 event 通知），
+          health（运行时长、监视线程、待发送的请求与最近的错误）
+
+不带参数运行时启动图形界面（需在构建时启用 gui 功能，默认启用）；加 --events-ndjson 时
+同时在标准输出逐行输出事件：operation_started/operation_succeeded/operation_failed、
+drift_detected、app_installed、app_removed、config_reloaded，日志改写到标准错误。
+
+任何命令（包括图形界面）加 --read-only 时进入只读（审计）模式：照常校验和报告状态、历史与
+隐藏失效，但拒绝修改 Info.plist、应用列表、备份、历史与配置；也可在配置中设置 read_only。
+
+任何命令加 --explain 时，每次修改 Info.plist 后在标准错误输出等价的 plutil 命令，便于审计
+或手动重复；这些命令也会记入操作历史，history search --explain 会把它们列在结果后面。";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum HideStatus {
+    Hidden,
+    WouldHide,
+    Updated,
+    WouldUpdate,
+    AlreadyManaged,
+    Conflict,
+    Unresolved,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+struct HideResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    path: String,
+    status: HideStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    // 本次一并隐藏的登录项辅助程序
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    helpers: Vec<String>,
+    // 仍会显示 Dock 图标、未处理的登录项辅助程序
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    visible_helpers: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct HideReport {
+    dry_run: bool,
+    total: usize,
+    hidden: usize,
+    updated: usize,
+    skipped: usize,
+    failed: usize,
+    results: Vec<HideResult>,
+    // 收到退出信号后停止，剩下未处理的目标
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    interrupted: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unprocessed: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum RestoreStatus {
+    Restored,
+    NotManaged,
+    External,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+struct RestoreResult {
+    path: PathBuf,
+    status: RestoreStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+struct HideTarget {
+    source: Option<String>,
+    path: Option<PathBuf>,
+    tags: Vec<String>,
+}
+
+#[derive(Default)]
+struct ConflictPrompt {
+    remembered: Option<bool>,
+}
+
+impl ConflictPrompt {
+    fn ask(&mut self, path: &Path) -> bool {
+        if let Some(answer) = self.remembered {
+            return answer;
+        }
+        let tty = match fs::File::open("/dev/tty") {
+            Ok(tty) => tty,
+            Err(err) => {
+                eprintln!(
+                    "无法打开终端进行询问，跳过：{}，错误：{}",
+                    path.display(),
+                    err
+                );
+                return false;
+            }
+        };
+        let mut reader = io::BufReader::new(tty);
+        loop {
+            eprint!(
+                "{} 已在管理列表中：[s] 跳过 [o] 覆盖 [S] 全部跳过 [O] 全部覆盖 > ",
+                path.display()
+            );
+            let _ = io::stderr().flush();
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return false;
+            }
+            match line.trim() {
+                "s" => return false,
+                "o" => return true,
+                "S" => {
+                    self.remembered = Some(false);
+                    return false;
+                }
+                "O" => {
+                    self.remembered = Some(true);
+                    return true;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let Some(command) = args.first() else {
+        println!("{}", USAGE);
+        return 2;
+    };
+    let code = match command.as_str() {
+        "hide" => hide(&args[1..]),
+        "hide-all" => hide_all(&args[1..]),
+        "restore" => restore(&args[1..]),
+        "import" => import(&args[1..]),
+        "adopt" => adopt(&args[1..]),
+        "backups" => backups(&args[1..]),
+        "pause" => pause_or_resume(&args[1..], true),
+        "resume" => pause_or_resume(&args[1..], false),
+        "verify" => verify(&args[1..]),
+        "status" => status(&args[1..]),
+        "label" => label(&args[1..]),
+        "relaunch" => relaunch_command(&args[1..]),
+        "history" => history_command(&args[1..]),
+        "rollback" => rollback_command(&args[1..]),
+        "snapshot" => snapshot_command(&args[1..]),
+        "report" => report_command(&args[1..]),
+        "session" => session_command(&args[1..]),
+        "agent" => agent_command(&args[1..]),
+        "schedule" => schedule_command(&args[1..]),
+        "watch" => watch::run(&args[1..]),
+        "rpc" => rpc::run(&args[1..]),
+        #[cfg(feature = "tui")]
+        "tui" => crate::tui::run(&args[1..]),
+        #[cfg(not(feature = "tui"))]
+        "tui" => {
+            eprintln!("此版本构建时未启用终端界面（tui 功能）");
+            2
+        }
+        "help" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            0
+        }
+        other => {
+            eprintln!("未知命令：{}\n\n{}", other, USAGE);
+            2
+        }
+    };
+    agent::record_run(code);
+    webhook::wait(WEBHOOK_WAIT);
+    code
+}
+
+// 各命令开始修改 Info.plist 或应用列表前调用，返回值释放前持有数据目录的锁（正在运行的图形界面
+// 等锁释放后合并改动再保存），并把退出信号推迟到批量操作停下、列表保存之后。确认提示和身份验证
+// 要放在这之前，等待输入时 Ctrl-C 才能直接退出。
+fn begin_writes() -> (state::StateLock, shutdown::Deferred) {
+    (state::lock_state(), shutdown::defer())
+}
+
+fn read_path_lines(reader: impl BufRead, paths: &mut Vec<String>) -> io::Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        paths.push(line.to_string());
+    }
+    Ok(())
+}
+
+fn hide(args: &[String]) -> i32 {
+    let mut paths = Vec::new();
+    let mut bundle_ids = Vec::new();
+    let mut from_files = Vec::new();
+    let mut use_stdin = false;
+    let mut with_helpers = false;
+    let mut create_plist = false;
+    let mut json = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--from-file" => match iter.next() {
+                Some(file) => from_files.push(file.clone()),
+                None => {
+                    eprintln!("--from-file 需要一个文件参数");
+                    return 2;
+                }
+            },
+            "--stdin" | "-" => use_stdin = true,
+            "--bundle-id" => match iter.next() {
+                Some(bundle_id) => bundle_ids.push(bundle_id.clone()),
+                None => {
+                    eprintln!("--bundle-id 需要一个 Bundle ID 参数");
+                    return 2;
+                }
+            },
+            "--with-helpers" => with_helpers = true,
+            "--create-plist" => create_plist = true,
+            "--json" => json = true,
+            other if other.starts_with("--") => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+            _ => paths.push(arg.clone()),
+        }
+    }
+
+    for file in &from_files {
+        let result = if file == "-" {
+            use_stdin = false;
+            read_path_lines(io::stdin().lock(), &mut paths)
+        } else {
+            fs::File::open(file).and_then(|f| read_path_lines(io::BufReader::new(f), &mut paths))
+        };
+        if let Err(err) = result {
+            eprintln!("读取路径列表失败：{}，错误：{}", file, err);
+            return 2;
+        }
+    }
+
+    let read_stdin = use_stdin
+        || (paths.is_empty()
+            && from_files.is_empty()
+            && bundle_ids.is_empty()
+            && !io::stdin().is_terminal());
+    if read_stdin && let Err(err) = read_path_lines(io::stdin().lock(), &mut paths) {
+        eprintln!("读取标准输入失败：{}", err);
+        return 2;
+    }
+
+    if paths.is_empty() && bundle_ids.is_empty() {
+        eprintln!("没有需要处理的路径\n\n{}", USAGE);
+        return 2;
+    }
+
+    let scope = ScanScope::load();
+    let mut targets = Vec::new();
+    for raw in paths {
+        match config::normalize_input_path(&raw) {
+            Ok(path) => targets.push(HideTarget {
+                source: None,
+                path: Some(path),
+                tags: Vec::new(),
+            }),
+            Err(err) => {
+                eprintln!("{}", err);
+                return 2;
+            }
+        }
+    }
+    for bundle_id in bundle_ids {
+        let path = installed::resolve_bundle_id(&bundle_id, &scope).ok();
+        targets.push(HideTarget {
+            source: Some(bundle_id),
+            path,
+            tags: Vec::new(),
+        });
+    }
+    match hide_targets(
+        targets,
+        false,
+        MergeStrategy::Skip,
+        with_helpers,
+        create_plist,
+    ) {
+        Ok(report) => finish_report(&report, json),
+        Err(code) => code,
+    }
+}
+
+// 按 --bundle-id 选出已管理的应用（同一 Bundle ID 有多份时全部选中），
+// 某个 Bundle ID 没有对应的已管理应用时返回错误。
+fn select_by_bundle_id(apps: &[ManagedApp], bundle_ids: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut paths = Vec::new();
+    for bundle_id in bundle_ids {
+        let matched: Vec<&ManagedApp> = apps
+            .iter()
+            .filter(|app| app.matches_bundle_id(bundle_id))
+            .collect();
+        if matched.is_empty() {
+            return Err(format!("没有 Bundle ID 为 {} 的已管理应用", bundle_id));
+        }
+        for app in matched {
+            if !paths.contains(&app.path) {
+                paths.push(app.path.clone());
+            }
+        }
+    }
+    Ok(paths)
+}
+
+// 恢复列表中的应用并移出列表，返回逐个的结果；调用方负责保存列表。
+fn restore_paths(
+    apps: &mut Vec<ManagedApp>,
+    paths: Vec<PathBuf>,
+    force: bool,
+    mut on_restored: impl FnMut(&Path),
+) -> Vec<RestoreResult> {
+    let _dock = dock::batch();
+    let mut results = Vec::new();
+    let mut session_changed = false;
+    for path in paths {
+        let Some(index) = apps.iter().position(|app| app.path == path) else {
+            results.push(RestoreResult {
+                path,
+                status: RestoreStatus::NotManaged,
+                error: None,
+            });
+            continue;
+        };
+        if !force && let Some(reason) = apps[index].external_change() {
+            results.push(RestoreResult {
+                path,
+                status: RestoreStatus::External,
+                error: Some(reason),
+            });
+            continue;
+        }
+        match apps[index].restore_dock_icon() {
+            Ok(_) => {
+                session_changed |= apps.remove(index).session;
+                on_restored(&path);
+                results.push(RestoreResult {
+                    path,
+                    status: RestoreStatus::Restored,
+                    error: None,
+                });
+            }
+            Err(err) => results.push(RestoreResult {
+                path,
+                status: RestoreStatus::Failed,
+                error: Some(err.to_string()),
+            }),
+        }
+    }
+    if session_changed && let Err(err) = session::write_journal(apps) {
+        eprintln!("[Session] 更新会话日志失败：{}", err);
+    }
+    results
+}
+
+fn restore(args: &[String]) -> i32 {
+    let mut paths = Vec::new();
+    let mut bundle_ids = Vec::new();
+    let mut force = false;
+    let mut json = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--bundle-id" => match iter.next() {
+                Some(bundle_id) => bundle_ids.push(bundle_id.clone()),
+                None => {
+                    eprintln!("--bundle-id 需要一个 Bundle ID 参数");
+                    return 2;
+                }
+            },
+            "--force" => force = true,
+            "--json" => json = true,
+            other if other.starts_with("--") => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+            other => match config::normalize_input_path(other) {
+                Ok(path) => paths.push(path),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return 2;
+                }
+            },
+        }
+    }
+    if paths.is_empty() && bundle_ids.is_empty() {
+        eprintln!("没有需要处理的路径\n\n{}", USAGE);
+        return 2;
+    }
+    let _writes = begin_writes();
+    let mut apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return 1;
+        }
+    };
+    match select_by_bundle_id(&apps, &bundle_ids) {
+        Ok(selected) => {
+            for path in selected {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    }
+
+    let results = restore_paths(&mut apps, paths, force, |_| {});
+    if results
+        .iter()
+        .any(|result| matches!(result.status, RestoreStatus::Restored))
+        && let Err(err) = state::save_apps(&apps)
+    {
+        eprintln!("保存应用列表失败：{}", err);
+        return 1;
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results).unwrap());
+    } else {
+        for result in &results {
+            let path = result.path.display();
+            let error = result.error.as_deref().unwrap_or_default();
+            match result.status {
+                RestoreStatus::Restored => println!("[Restore] 已恢复：{}", path),
+                RestoreStatus::NotManaged => println!("[Restore] 不在管理列表中：{}", path),
+                RestoreStatus::External => {
+                    println!("[Restore] 跳过：{}，{}（确认后可加 --force）", path, error)
+                }
+                RestoreStatus::Failed => println!("[Restore] 恢复失败：{}，错误：{}", path, error),
+            }
+        }
+    }
+    if results
+        .iter()
+        .all(|result| matches!(result.status, RestoreStatus::Restored))
+    {
+        0
+    } else {
+        1
+    }
+}
+
+fn confirm_on_tty(question: &str) -> bool {
+    let Ok(tty) = fs::File::open("/dev/tty") else {
+        return false;
+    };
+    eprint!("{} [y/N] > ", question);
+    let _ = io::stderr().flush();
+    let mut line = String::new();
+    if io::BufReader::new(tty).read_line(&mut line).unwrap_or(0) == 0 {
+        return false;
+    }
+    matches!(line.trim(), "y" | "Y" | "yes")
+}
+
+fn hide_all(args: &[String]) -> i32 {
+    let mut dir = None;
+    let mut depth = DEFAULT_SCAN_DEPTH;
+    let mut exclude = Vec::new();
+    let mut dry_run = false;
+    let mut yes = false;
+    let mut with_helpers = false;
+    let mut json = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--depth" => match iter.next().and_then(|value| value.parse().ok()) {
+                Some(value) if value > 0 => depth = value,
+                _ => {
+                    eprintln!("--depth 需要一个正整数");
+                    return 2;
+                }
+            },
+            "--exclude" => match iter.next() {
+                Some(pattern) => exclude.push(pattern.clone()),
+                None => {
+                    eprintln!("--exclude 需要一个参数");
+                    return 2;
+                }
+            },
+            "--dry-run" => dry_run = true,
+            "--yes" | "-y" => yes = true,
+            "--with-helpers" => with_helpers = true,
+            "--json" => json = true,
+            _ if dir.is_none() => match config::normalize_input_path(arg) {
+                Ok(path) => dir = Some(path),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return 2;
+                }
+            },
+            other => {
+                eprintln!("多余的参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+    let Some(dir) = dir else {
+        eprintln!("hide-all 需要一个目录参数\n\n{}", USAGE);
+        return 2;
+    };
+    if !dir.is_dir() {
+        eprintln!("不是目录：{}", dir.display());
+        return 2;
+    }
+
+    let mut scope = ScanScope::folder(&dir, depth);
+    scope.exclude.extend(exclude.iter().map(|pattern| {
+        config::expand_home(Path::new(pattern))
+            .display()
+            .to_string()
+    }));
+    let mut found = scope.find_app_bundles();
+    found.sort();
+    if found.is_empty() {
+        eprintln!("{} 中没有找到 .app", dir.display());
+        return 0;
+    }
+
+    // 先列出将要处理的应用，确认后再修改；--dry-run 的报告本身就是预览。
+    if !dry_run && !yes {
+        for path in &found {
+            eprintln!("  {}", path.display());
+        }
+        if !confirm_on_tty(&format!(
+            "将隐藏以上 {} 个应用的 Dock 图标，继续？",
+            found.len()
+        )) {
+            eprintln!("已取消；非交互环境中请加 --yes");
+            return 1;
+        }
+    }
+
+    let targets = found
+        .into_iter()
+        .map(|path| HideTarget {
+            source: None,
+            path: Some(path),
+            tags: Vec::new(),
+        })
+        .collect();
+    match hide_targets(targets, dry_run, MergeStrategy::Skip, with_helpers, false) {
+        Ok(report) => finish_report(&report, json),
+        Err(code) => code,
+    }
+}
+
+fn import(args: &[String]) -> i32 {
+    let mut file = None;
+    let mut dry_run = false;
+    let mut json = false;
+    let mut scheduled = false;
+    let mut strategy = MergeStrategy::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--json" => json = true,
+            // 计划任务在添加时已验证过，到时由 launchd 无人值守地运行。
+            "--scheduled" => scheduled = true,
+            "--strategy" => match iter.next().map(|value| value.parse()) {
+                Some(Ok(value)) => strategy = value,
+                Some(Err(err)) => {
+                    eprintln!("{}", err);
+                    return 2;
+                }
+                None => {
+                    eprintln!("--strategy 需要一个参数");
+                    return 2;
+                }
+            },
+            _ if file.is_none() => file = Some(arg.clone()),
+            other => {
+                eprintln!("多余的参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+    let Some(file) = file else {
+        eprintln!("import 需要一个文件参数\n\n{}", USAGE);
+        return 2;
+    };
+
+    let data = if file == "-" {
+        io::read_to_string(io::stdin())
+    } else {
+        fs::read_to_string(&file)
+    };
+    let data = match data {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("读取导入文件失败：{}，错误：{}", file, err);
+            return 2;
+        }
+    };
+    let entries = match import::parse_import(&data) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("解析导入文件失败：{}，错误：{}", file, err);
+            return 2;
+        }
+    };
+    if !dry_run
+        && !scheduled
+        && import::is_profile(&data)
+        && let Err(err) = auth::confirm("启用配置文件会按其中的列表隐藏应用")
+    {
+        eprintln!("{}", err);
+        return 1;
+    }
+
+    let scope = ScanScope::load();
+    let targets = entries
+        .iter()
+        .map(|entry| HideTarget {
+            source: Some(entry.source.clone()),
+            path: import::resolve_entry(entry, &scope),
+            tags: entry.tags.clone(),
+        })
+        .collect();
+    match hide_targets(targets, dry_run, strategy, false, false) {
+        Ok(report) => finish_report(&report, json),
+        Err(code) => code,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AdoptReport {
+    dry_run: bool,
+    adopted: Vec<String>,
+}
+
+fn adopt(args: &[String]) -> i32 {
+    let mut dry_run = false;
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--json" => json = true,
+            other => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+
+    let _writes = begin_writes();
+    let mut apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return 1;
+        }
+    };
+    let mut report = AdoptReport {
+        dry_run,
+        adopted: Vec::new(),
+    };
+    for path in ScanScope::load().find_hidden_apps() {
+        if apps.iter().any(|app| app.path == path) {
+            continue;
+        }
+        report.adopted.push(path.display().to_string());
+        let mut app = ManagedApp::new(path);
+        app.record_checksum();
+        apps.push(app);
+    }
+
+    if !dry_run
+        && !report.adopted.is_empty()
+        && let Err(err) = state::save_apps(&apps)
+    {
+        eprintln!("保存应用列表失败：{}", err);
+        return 1;
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        let action = if dry_run { "将加入" } else { "已加入" };
+        for path in &report.adopted {
+            println!("[Adopt] {}：{}", action, path);
+        }
+        println!("共发现 {} 个未记录的已隐藏应用", report.adopted.len());
+    }
+    0
+}
+
+fn backups(args: &[String]) -> i32 {
+    let (command, rest) = match args.first().map(String::as_str) {
+        Some("list" | "prune" | "restore") => (args[0].as_str(), &args[1..]),
+        _ => ("list", args),
+    };
+    match command {
+        "prune" => {
+            let keep = match rest {
+                [] => config::load_config().unwrap_or_default().backup_retention,
+                [flag, value] if flag == "--keep" => match value.parse() {
+                    Ok(keep) => keep,
+                    Err(_) => {
+                        eprintln!("--keep 需要一个非负整数");
+                        return 2;
+                    }
+                },
+                _ => {
+                    eprintln!("用法：dock-dodger backups prune [--keep <数量>]");
+                    return 2;
+                }
+            };
+            let _writes = begin_writes();
+            match backup::prune_backups(keep) {
+                Ok(removed) => {
+                    println!(
+                        "[Backup] 已清理 {} 个旧备份，每个应用保留最近 {} 个",
+                        removed, keep
+                    );
+                    0
+                }
+                Err(err) => {
+                    eprintln!("清理备份失败：{}", err);
+                    1
+                }
+            }
+        }
+        "restore" => {
+            let [app, created_at] = rest else {
+                eprintln!("用法：dock-dodger backups restore <应用路径> <备份时间戳>");
+                return 2;
+            };
+            let Ok(created_at) = created_at.parse() else {
+                eprintln!("备份时间戳需要是 backups 列出的毫秒数");
+                return 2;
+            };
+            let app = match config::normalize_input_path(app) {
+                Ok(app) => app,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return 2;
+                }
+            };
+            let _writes = begin_writes();
+            match backup::restore_backup(&app, created_at) {
+                Ok(()) => {
+                    println!("[Backup] 已用备份恢复：{}", app.display());
+                    0
+                }
+                Err(err) => {
+                    eprintln!("恢复备份失败：{}", err);
+                    1
+                }
+            }
+        }
+        _ => {
+            let json = rest.iter().any(|arg| arg == "--json");
+            let entries = match backup::list_backups() {
+                Ok(entries) => entries,
+                Err(err) => {
+                    eprintln!("读取备份失败：{}", err);
+                    return 1;
+                }
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+            } else {
+                for entry in &entries {
+                    println!(
+                        "{}\t{}\t{} 字节\t{}",
+                        entry.app.display(),
+                        entry.created_at,
+                        entry.size,
+                        entry.file.display()
+                    );
+                }
+                println!("共 {} 个备份", entries.len());
+            }
+            0
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PauseReport {
+    paused: bool,
+    results: Vec<PauseResult>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    interrupted: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unprocessed: Vec<PathBuf>,
+}
+
+fn pause_or_resume(args: &[String], pausing: bool) -> i32 {
+    let mut json = false;
+    let mut force = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--force" => force = true,
+            other => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+
+    if pause::is_paused() == pausing {
+        let message = if pausing {
+            "当前已处于暂停状态"
+        } else {
+            "当前未暂停"
+        };
+        eprintln!("{}", message);
+        return 0;
+    }
+
+    if pausing && let Err(err) = auth::confirm("暂停隐藏会恢复所有应用的 Dock 图标") {
+        eprintln!("{}", err);
+        return 1;
+    }
+    let _writes = begin_writes();
+    let mut apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return 1;
+        }
+    };
+    let results = if pausing {
+        pause::pause(&mut apps, force)
+    } else {
+        pause::resume(&mut apps, force)
+    };
+    let results = match results {
+        Ok(results) => results,
+        Err(err) => {
+            eprintln!("切换暂停状态失败：{}", err);
+            return 1;
+        }
+    };
+    if let Err(err) = state::save_apps(&apps) {
+        eprintln!("保存应用列表失败：{}", err);
+        return 1;
+    }
+
+    let failed = results
+        .iter()
+        .filter(|result| result.error.is_some())
+        .count();
+    // 中途收到退出信号时 results 只覆盖列表前面的应用。
+    let unprocessed: Vec<PathBuf> = apps[results.len()..]
+        .iter()
+        .map(|app| app.path.clone())
+        .collect();
+    let interrupted = !unprocessed.is_empty();
+    if json {
+        let report = PauseReport {
+            paused: pausing,
+            results,
+            interrupted,
+            unprocessed,
+        };
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        let (tag, action) = if pausing {
+            ("[Pause]", "已恢复显示")
+        } else {
+            ("[Resume]", "已重新隐藏")
+        };
+        for result in &results {
+            match &result.error {
+                None => println!("{} {}：{}", tag, action, result.path.display()),
+                Some(err) => println!("{} 失败：{}，错误：{}", tag, result.path.display(), err),
+            }
+        }
+        println!("共 {} 个，失败 {}", results.len(), failed);
+        if interrupted {
+            println!("{} 已中断，还有 {} 个未处理", tag, unprocessed.len());
+        }
+    }
+    if failed > 0 || interrupted { 1 } else { 0 }
+}
+
+#[derive(Default, Serialize)]
+struct BatchOutcome {
+    executed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    undo_errors: Vec<PauseResult>,
+}
+
+#[derive(Serialize)]
+struct RollbackReport<'a> {
+    to: u64,
+    steps: &'a [RollbackStep],
+    #[serde(flatten)]
+    outcome: BatchOutcome,
+}
+
+// 先列出需要反向执行的操作，确认后作为一批执行：任何一步失败都会撤销已执行的步骤，列表不变。
+fn rollback_command(args: &[String]) -> i32 {
+    let mut time = Vec::new();
+    let mut dry_run = false;
+    let mut yes = false;
+    let mut force = false;
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--yes" | "-y" => yes = true,
+            "--force" => force = true,
+            "--json" => json = true,
+            other if other.starts_with("--") => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+            other => time.push(other),
+        }
+    }
+    let time = time.join(" ");
+    let Some(to) = history::parse_local_time(&time) else {
+        eprintln!(
+            "需要 YYYY-MM-DD 或 \"YYYY-MM-DD HH:MM\"（本机时区）格式的时间：{}\n\n{}",
+            time, USAGE
+        );
+        return 2;
+    };
+    let catalog = i18n::current();
+    let entries = match history::load_history() {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("读取操作历史失败：{}", err);
+            return 1;
+        }
+    };
+    let steps = rollback::plan(&entries, to, |path| is_dock_icon_hidden(path).ok());
+    let mut report = RollbackReport {
+        to,
+        steps: &steps,
+        outcome: BatchOutcome::default(),
+    };
+    if !json {
+        if steps.is_empty() {
+            println!("与 {} 时的状态相同，不需要回滚", catalog.date_time(to));
+            return 0;
+        }
+        println!("回滚到 {} 需要执行：", catalog.date_time(to));
+        let rows: Vec<Vec<String>> = steps
+            .iter()
+            .map(|step| {
+                vec![
+                    step.action.label().to_string(),
+                    step.path.display().to_string(),
+                    catalog.date_time(step.changed_at),
+                ]
+            })
+            .collect();
+        print_table(&["操作", "路径", "之后首次改变"], &rows);
+    }
+    if dry_run || steps.is_empty() {
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        }
+        return 0;
+    }
+    if !yes && !confirm_on_tty(&format!("执行以上 {} 项操作？", steps.len())) {
+        eprintln!("已取消，可加 --yes 跳过确认");
+        return 1;
+    }
+    if let Err(err) = auth::confirm("回滚会批量隐藏或恢复应用") {
+        eprintln!("{}", err);
+        return 1;
+    }
+    let code;
+    (code, report.outcome) = run_batch(&steps, force, json, "Rollback", |_| {});
+    if code == 0 && !json {
+        println!("[Rollback] 已回滚 {} 项", steps.len());
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    }
+    code
+}
+
+// rollback 与 snapshot restore 共用：作为一批执行，成功时先交给 finish 调整列表再保存。
+fn run_batch(
+    steps: &[RollbackStep],
+    force: bool,
+    json: bool,
+    tag: &str,
+    finish: impl FnOnce(&mut Vec<ManagedApp>),
+) -> (i32, BatchOutcome) {
+    let mut outcome = BatchOutcome::default();
+    let _writes = begin_writes();
+    let mut apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return (1, outcome);
+        }
+    };
+    match rollback::execute(&mut apps, steps, force) {
+        Ok(()) => {
+            finish(&mut apps);
+            if let Err(err) = state::save_apps(&apps) {
+                eprintln!("保存应用列表失败：{}", err);
+                return (1, outcome);
+            }
+            outcome.executed = true;
+            (0, outcome)
+        }
+        Err(failure) => {
+            outcome.error = Some(format!(
+                "{}：{}",
+                failure.step.path.display(),
+                failure.error
+            ));
+            outcome.undo_errors = failure
+                .undo_errors
+                .into_iter()
+                .map(|(path, error)| PauseResult {
+                    path,
+                    error: Some(error),
+                })
+                .collect();
+            if !json {
+                println!(
+                    "[{}] {}失败：{}，错误：{}",
+                    tag,
+                    failure.step.action.label(),
+                    failure.step.path.display(),
+                    failure.error
+                );
+                if outcome.undo_errors.is_empty() {
+                    println!("[{}] 已撤销本次执行的全部操作，没有做任何改动", tag);
+                } else {
+                    for result in &outcome.undo_errors {
+                        println!(
+                            "[{}] 撤销失败：{}，错误：{}",
+                            tag,
+                            result.path.display(),
+                            result.error.as_deref().unwrap_or_default()
+                        );
+                    }
+                }
+            }
+            (1, outcome)
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SnapshotReport<'a> {
+    name: &'a str,
+    created_at: u64,
+    #[serde(flatten)]
+    plan: &'a SnapshotPlan,
+    #[serde(flatten)]
+    outcome: BatchOutcome,
+}
+
+fn snapshot_command(args: &[String]) -> i32 {
+    let catalog = i18n::current();
+    match args.first().map(String::as_str) {
+        Some("take") => {
+            let [_, name] = args else {
+                eprintln!("用法：dock-dodger snapshot take <名称>");
+                return 2;
+            };
+            let apps = match state::load_apps() {
+                Ok(apps) => apps,
+                Err(err) => {
+                    eprintln!("读取应用列表失败：{}", err);
+                    return 1;
+                }
+            };
+            match snapshot::take(name, &apps) {
+                Ok(snapshot) => {
+                    println!(
+                        "[Snapshot] 已保存快照 {}（{} 个应用）",
+                        snapshot.name,
+                        snapshot.apps.len()
+                    );
+                    0
+                }
+                Err(err) => {
+                    eprintln!("保存快照失败：{}", err);
+                    1
+                }
+            }
+        }
+        Some("restore") => snapshot_restore(&args[1..]),
+        Some("delete") => {
+            let [_, name] = args else {
+                eprintln!("用法：dock-dodger snapshot delete <名称>");
+                return 2;
+            };
+            match snapshot::delete(name) {
+                Ok(()) => {
+                    println!("[Snapshot] 已删除快照 {}", name);
+                    0
+                }
+                Err(err) => {
+                    eprintln!("{}", err);
+                    1
+                }
+            }
+        }
+        Some("list") | None => {
+            let json = args.iter().any(|arg| arg == "--json");
+            let infos = match snapshot::list() {
+                Ok(infos) => infos,
+                Err(err) => {
+                    eprintln!("读取快照失败：{}", err);
+                    return 1;
+                }
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&infos).unwrap());
+            } else if infos.is_empty() {
+                println!("还没有快照，可用 dock-dodger snapshot take <名称> 保存");
+            } else {
+                let rows: Vec<Vec<String>> = infos
+                    .iter()
+                    .map(|info| {
+                        vec![
+                            info.name.clone(),
+                            catalog.date_time(info.created_at),
+                            info.apps.to_string(),
+                        ]
+                    })
+                    .collect();
+                print_table(&["名称", "保存时间", "应用数"], &rows);
+            }
+            0
+        }
+        Some(other) => {
+            eprintln!("未知的 snapshot 子命令：{}\n\n{}", other, USAGE);
+            2
+        }
+    }
+}
+
+// 与 rollback 相同：先列出需要执行的操作，确认后作为一批执行，失败时撤销并保持列表不变。
+fn snapshot_restore(args: &[String]) -> i32 {
+    let mut name = None;
+    let mut dry_run = false;
+    let mut yes = false;
+    let mut force = false;
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--yes" | "-y" => yes = true,
+            "--force" => force = true,
+            "--json" => json = true,
+            other if other.starts_with("--") || name.is_some() => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+            other => name = Some(other),
+        }
+    }
+    let Some(name) = name else {
+        eprintln!(
+            "用法：dock-dodger snapshot restore <名称> [--dry-run] [--yes] [--force] [--json]"
+        );
+        return 2;
+    };
+    let snapshot = match snapshot::load(name) {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 1;
+        }
+    };
+    let apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return 1;
+        }
+    };
+    let plan = snapshot::plan(&snapshot, &apps, |path| is_dock_icon_hidden(path).ok());
+    let mut report = SnapshotReport {
+        name: &snapshot.name,
+        created_at: snapshot.created_at,
+        plan: &plan,
+        outcome: BatchOutcome::default(),
+    };
+    if !json {
+        let catalog = i18n::current();
+        for path in &plan.missing {
+            println!("[Snapshot] 读取不到 Info.plist，跳过：{}", path.display());
+        }
+        if plan.steps.is_empty() {
+            println!("隐藏状态与快照 {} 相同，只更新列表中的记录", snapshot.name);
+        } else {
+            println!(
+                "恢复快照 {}（{}）需要执行：",
+                snapshot.name,
+                catalog.date_time(snapshot.created_at)
+            );
+            let rows: Vec<Vec<String>> = plan
+                .steps
+                .iter()
+                .map(|step| {
+                    vec![
+                        step.action.label().to_string(),
+                        step.path.display().to_string(),
+                    ]
+                })
+                .collect();
+            print_table(&["操作", "路径"], &rows);
+        }
+    }
+    if dry_run {
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        }
+        return 0;
+    }
+    if !plan.steps.is_empty()
+        && !yes
+        && !confirm_on_tty(&format!("执行以上 {} 项操作？", plan.steps.len()))
+    {
+        eprintln!("已取消，可加 --yes 跳过确认");
+        return 1;
+    }
+    if let Err(err) = auth::confirm("恢复快照会按快照替换应用列表") {
+        eprintln!("{}", err);
+        return 1;
+    }
+    let code;
+    (code, report.outcome) = run_batch(&plan.steps, force, json, "Snapshot", |apps| {
+        snapshot::apply_entries(apps, &snapshot, &plan.missing)
+    });
+    if code == 0 && !json {
+        println!("[Snapshot] 已恢复快照 {}", snapshot.name);
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    }
+    code
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum VerifyStatus {
+    Hidden,
+    Drifted,
+    Reapplied,
+    External,
+    Unreadable,
+    Failed,
+    Offline,
+}
+
+#[derive(Debug, Serialize)]
+struct VerifyResult {
+    path: PathBuf,
+    status: VerifyStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+// 只有隐藏失效、没有读取或修改失败时的退出码，供脚本与监控区分“需要重新隐藏”和“出错”。
+const VERIFY_DRIFT_EXIT: i32 = 3;
+
+// 退出码：0 全部与列表一致，3 有隐藏失效或疑似由其他工具管理的应用，1 读取或修改失败（或中途退出），2 参数错误。
+fn verify(args: &[String]) -> i32 {
+    let mut json = false;
+    let mut reapply = false;
+    let mut notify = false;
+    let mut bundle_ids = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--reapply" | "--fix" => reapply = true,
+            "--notify" => notify = true,
+            "--bundle-id" => match iter.next() {
+                Some(bundle_id) => bundle_ids.push(bundle_id.clone()),
+                None => {
+                    eprintln!("--bundle-id 需要一个 Bundle ID 参数");
+                    return 2;
+                }
+            },
+            other => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+    if reapply && config::read_only() {
+        eprintln!("只读模式下不能使用 --reapply/--fix");
+        return 2;
+    }
+    // 暂停期间图标本来就是显示的，重新隐藏会破坏暂停状态。
+    if pause::is_paused() {
+        eprintln!("当前处于暂停状态，跳过校验");
+        return 0;
+    }
+    let _writes = begin_writes();
+    let mut apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return 1;
+        }
+    };
+    // 指定了 --bundle-id 时只校验对应的应用，其余记录原样保存。
+    let selected = if bundle_ids.is_empty() {
+        None
+    } else {
+        match select_by_bundle_id(&apps, &bundle_ids) {
+            Ok(selected) => Some(selected),
+            Err(err) => {
+                eprintln!("{}", err);
+                return 2;
+            }
+        }
+    };
+    let total = selected.as_ref().map_or(apps.len(), Vec::len);
+
+    let mut changed = false;
+    let mut new_drifts = Vec::new();
+    let results: Vec<VerifyResult> = apps
+        .iter_mut()
+        .filter(|app| {
+            selected
+                .as_ref()
+                .is_none_or(|selected| selected.contains(&app.path))
+        })
+        .take_while(|_| !shutdown::requested())
+        .map(|app| {
+            // 外接卷未挂载时读不到 Info.plist，既不算失效也不算错误，等卷挂载后再校验。
+            if volume::is_offline(&app.path) {
+                return VerifyResult {
+                    path: app.path.clone(),
+                    status: VerifyStatus::Offline,
+                    error: None,
+                };
+            }
+            let hidden = preflight(&app.path).and_then(|_| is_dock_icon_hidden(&app.path));
+            if matches!(hidden, Ok(false)) && history::record_drift(&app.path) {
+                events::emit(Event::DriftDetected {
+                    path: app.path.clone(),
+                });
+                new_drifts.push(app.path.clone());
+            }
+            let (status, error) = match (app.external_change(), hidden) {
+                (Some(reason), _) => (VerifyStatus::External, Some(reason)),
+                (None, Ok(true)) => (VerifyStatus::Hidden, None),
+                (None, Ok(false)) if reapply => match app.hide_dock_icon() {
+                    Ok(_) => {
+                        app.record_checksum();
+                        changed = true;
+                        if let Err(err) = relaunch::after_hide(app) {
+                            eprintln!(
+                                "[Verify] 已重新隐藏，但未重新启动：{}，错误：{}",
+                                app.path.display(),
+                                err
+                            );
+                            if ErrorKind::classify(&*err) == ErrorKind::AutomationDenied {
+                                eprintln!("{}", ErrorKind::AutomationDenied.remedy());
+                            }
+                        }
+                        (VerifyStatus::Reapplied, None)
+                    }
+                    Err(err) => (VerifyStatus::Failed, Some(err.to_string())),
+                },
+                (None, Ok(false)) => (VerifyStatus::Drifted, None),
+                (None, Err(err)) => (VerifyStatus::Unreadable, Some(err.to_string())),
+            };
+            VerifyResult {
+                path: app.path.clone(),
+                status,
+                error,
+            }
+        })
+        .collect();
+    if changed && let Err(err) = state::save_apps(&apps) {
+        eprintln!("保存应用列表失败：{}", err);
+        return 1;
+    }
+
+    let problems = results
+        .iter()
+        .filter(|result| {
+            !matches!(
+                result.status,
+                VerifyStatus::Hidden | VerifyStatus::Reapplied | VerifyStatus::Offline
+            )
+        })
+        .count();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results).unwrap());
+    } else {
+        for result in &results {
+            let path = result.path.display();
+            match (&result.status, &result.error) {
+                (VerifyStatus::Hidden, _) => {}
+                (VerifyStatus::Reapplied, _) => println!("[Verify] 已重新隐藏：{}", path),
+                (VerifyStatus::Drifted, _) => println!("[Verify] Dock 图标已恢复：{}", path),
+                (VerifyStatus::Offline, _) => {
+                    println!("[Verify] 所在的卷未挂载，跳过：{}", path)
+                }
+                (VerifyStatus::External, reason) => println!(
+                    "[Verify] 可能由其他用户或工具管理：{}（{}）",
+                    path,
+                    reason.as_deref().unwrap_or_default()
+                ),
+                (_, error) => println!(
+                    "[Verify] 失败：{}，错误：{}",
+                    path,
+                    error.as_deref().unwrap_or_default()
+                ),
+            }
+        }
+        println!("共 {} 个，需要处理 {}", results.len(), problems);
+    }
+    if notify && !new_drifts.is_empty() {
+        let reapplied = results
+            .iter()
+            .filter(|result| {
+                matches!(result.status, VerifyStatus::Reapplied)
+                    && new_drifts.contains(&result.path)
+            })
+            .count();
+        notify::notify("Dock Dodger", &drift_summary(&new_drifts, reapplied));
+    }
+    // JSON 输出保持为数组，中断的情况只在标准错误中说明。
+    let interrupted = results.len() < total;
+    if interrupted {
+        eprintln!("[Verify] 已中断，还有 {} 个未校验", total - results.len());
+    }
+    let failed = results.iter().any(|result| {
+        matches!(
+            result.status,
+            VerifyStatus::Unreadable | VerifyStatus::Failed
+        )
+    });
+    if failed || interrupted {
+        1
+    } else if problems > 0 {
+        VERIFY_DRIFT_EXIT
+    } else {
+        0
+    }
+}
+
+// 同一次失效只通知一次（history::record_drift 去重），列出前几个应用的名称。
+fn drift_summary(drifted: &[PathBuf], reapplied: usize) -> String {
+    const LISTED: usize = 3;
+    let mut names: Vec<String> = drifted
+        .iter()
+        .take(LISTED)
+        .map(|path| {
+            path.file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string())
+        })
+        .collect();
+    if drifted.len() > LISTED {
+        names.push(format!("等 {} 个应用", drifted.len()));
+    }
+    let names = names.join("、");
+    if reapplied == drifted.len() {
+        format!("{} 的 Dock 图标被更新恢复，已自动重新隐藏", names)
+    } else {
+        format!(
+            "{} 的 Dock 图标被更新恢复，可打开 Dock Dodger 或运行 dock-dodger verify --reapply 重新隐藏",
+            names
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum IconState {
+    Hidden,
+    Shown,
+    Unreadable,
+    Offline,
+}
+
+impl IconState {
+    fn label(self) -> &'static str {
+        match self {
+            IconState::Hidden => "隐藏",
+            IconState::Shown => "显示",
+            IconState::Unreadable => "无法读取",
+            IconState::Offline => "卷未挂载",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LastOperation {
+    at: u64,
+    action: HistoryAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct StatusRow {
+    path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bundle_id: Option<String>,
+    expected: IconState,
+    actual: IconState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_operation: Option<LastOperation>,
+}
+
+// 按终端显示宽度对齐：中文等非 ASCII 字符按两列计算。
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+fn pad(text: &str, width: usize) -> String {
+    format!(
+        "{}{}",
+        text,
+        " ".repeat(width.saturating_sub(display_width(text)))
+    )
+}
+
+fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| display_width(&row[column]))
+                .chain([display_width(header[column])])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let line = |cells: Vec<&str>| {
+        let last = cells.len() - 1;
+        cells
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| {
+                if column == last {
+                    cell.to_string()
+                } else {
+                    pad(cell, widths[column])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+    println!("{}", line(header.to_vec()));
+    for row in rows {
+        println!("{}", line(row.iter().map(String::as_str).collect()));
+    }
+}
+
+fn status(args: &[String]) -> i32 {
+    let mut json = false;
+    let mut bundle_ids = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--bundle-id" => match iter.next() {
+                Some(bundle_id) => bundle_ids.push(bundle_id.clone()),
+                None => {
+                    eprintln!("--bundle-id 需要一个 Bundle ID 参数");
+                    return 2;
+                }
+            },
+            other => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+    let mut apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return 1;
+        }
+    };
+    if !bundle_ids.is_empty() {
+        match select_by_bundle_id(&apps, &bundle_ids) {
+            Ok(selected) => apps.retain(|app| selected.contains(&app.path)),
+            Err(err) => {
+                eprintln!("{}", err);
+                return 2;
+            }
+        }
+    }
+    let entries = history::load_history().unwrap_or_default();
+    let running = running_bundles();
+    // 暂停期间图标应当是显示的。
+    let expected = if pause::is_paused() {
+        IconState::Shown
+    } else {
+        IconState::Hidden
+    };
+
+    let rows: Vec<StatusRow> = apps
+        .iter()
+        .map(|app| {
+            let (actual, error) = match is_dock_icon_hidden(&app.path) {
+                _ if volume::is_offline(&app.path) => (IconState::Offline, None),
+                Ok(true) => (IconState::Hidden, None),
+                Ok(false) => (IconState::Shown, None),
+                Err(err) => (IconState::Unreadable, Some(err.to_string())),
+            };
+            StatusRow {
+                path: app.path.clone(),
+                bundle_id: bundle_identifier(&app.path),
+                expected,
+                actual,
+                error,
+                running: running.contains(&app.path),
+                last_operation: entries
+                    .iter()
+                    .rev()
+                    .find(|entry| entry.path == app.path && entry.action != HistoryAction::Dock)
+                    .map(|entry| LastOperation {
+                        at: entry.at,
+                        action: entry.action,
+                        error: entry.error.clone(),
+                    }),
+            }
+        })
+        .collect();
+    // 卷未挂载的应用无法判断，不计入不一致。
+    let mismatched = rows
+        .iter()
+        .filter(|row| row.actual != row.expected && row.actual != IconState::Offline)
+        .count();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rows).unwrap());
+    } else if rows.is_empty() {
+        println!("没有已管理的应用");
+    } else {
+        let table: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                let last = match &row.last_operation {
+                    Some(operation) => format!(
+                        "{}{} {}",
+                        match operation.action {
+                            HistoryAction::Hide => "隐藏",
+                            HistoryAction::Restore => "恢复",
+                            HistoryAction::Drift => "失效",
+                            HistoryAction::Dock => "Dock 与启动台",
+                        },
+                        if operation.error.is_some() {
+                            "失败"
+                        } else {
+                            ""
+                        },
+                        format_ago(operation.at)
+                    ),
+                    None => "-".into(),
+                };
+                vec![
+                    if row.actual == IconState::Offline {
+                        "离线"
+                    } else if row.actual == row.expected {
+                        "正常"
+                    } else {
+                        "不一致"
+                    }
+                    .into(),
+                    row.expected.label().into(),
+                    row.actual.label().into(),
+                    if row.running { "是" } else { "否" }.into(),
+                    last,
+                    row.bundle_id.clone().unwrap_or_else(|| "-".into()),
+                    row.path.display().to_string(),
+                ]
+            })
+            .collect();
+        print_table(
+            &[
+                "状态",
+                "应有",
+                "实际",
+                "运行",
+                "上次操作",
+                "Bundle ID",
+                "路径",
+            ],
+            &table,
+        );
+        println!("共 {} 个，不一致 {}", rows.len(), mismatched);
+    }
+    if mismatched > 0 { 1 } else { 0 }
+}
+
+fn label(args: &[String]) -> i32 {
+    let [path, color] = args else {
+        eprintln!("用法：dock-dodger label <路径> <颜色|none>\n\n{}", USAGE);
+        return 2;
+    };
+    let label = match color.as_str() {
+        "none" => None,
+        color => match ColorLabel::parse(color) {
+            Some(label) => Some(label),
+            None => {
+                eprintln!(
+                    "未知的颜色：{}（可选 red、orange、yellow、green、blue、purple、gray、none）",
+                    color
+                );
+                return 2;
+            }
+        },
+    };
+    let path = match config::normalize_input_path(path) {
+        Ok(path) => path,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    let _writes = begin_writes();
+    let mut apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return 1;
+        }
+    };
+    let Some(app) = apps.iter_mut().find(|app| app.path == path) else {
+        eprintln!("{} 不在管理列表中", path.display());
+        return 1;
+    };
+    app.label = label;
+    if let Err(err) = state::save_apps(&apps) {
+        eprintln!("保存应用列表失败：{}", err);
+        return 1;
+    }
+    println!(
+        "[Label] {} 的颜色标记改为 {}",
+        path.display(),
+        label.map_or("无", ColorLabel::name)
+    );
+    0
+}
+
+fn relaunch_command(args: &[String]) -> i32 {
+    let usage =
+        "用法：dock-dodger relaunch <路径> [--background] [--hidden] [--off] [-- <参数>...]";
+    let mut path = None;
+    let mut options = RelaunchOptions::default();
+    let mut off = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--background" => options.background = true,
+            "--hidden" => options.hidden = true,
+            "--off" => off = true,
+            "--" => {
+                options.args = iter.by_ref().cloned().collect();
+            }
+            _ if path.is_none() => match config::normalize_input_path(arg) {
+                Ok(arg) => path = Some(arg),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return 2;
+                }
+            },
+            other => {
+                eprintln!("未知参数：{}\n\n{}", other, usage);
+                return 2;
+            }
+        }
+    }
+    let Some(path) = path else {
+        eprintln!("{}\n\n{}", usage, USAGE);
+        return 2;
+    };
+    let _writes = begin_writes();
+    let mut apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return 1;
+        }
+    };
+    let Some(app) = apps.iter_mut().find(|app| app.path == path) else {
+        eprintln!("{} 不在管理列表中", path.display());
+        return 1;
+    };
+    app.relaunch = (!off).then_some(options);
+    if let Err(err) = state::save_apps(&apps) {
+        eprintln!("保存应用列表失败：{}", err);
+        return 1;
+    }
+    if off {
+        println!("[Relaunch] {} 重新隐藏后不再自动重新启动", path.display());
+    } else {
+        println!("[Relaunch] {} 重新隐藏后将自动重新启动", path.display());
+    }
+    0
+}
+
+fn history_command(args: &[String]) -> i32 {
+    match args.first().map(String::as_str) {
+        Some("export") => history_export(&args[1..]),
+        Some("search") => history_search(&args[1..]),
+        _ => {
+            eprintln!(
+                "用法：dock-dodger history export|search [选项]\n\n{}",
+                USAGE
+            );
+            2
+        }
+    }
+}
+
+// export 与 search 共用的筛选选项；不是筛选选项时返回 Ok(false)。
+fn history_filter_option(
+    filter: &mut HistoryFilter,
+    option: &str,
+    value: &str,
+) -> Result<bool, String> {
+    match option {
+        "--since" | "--until" => {
+            let Some(date) = history::parse_date(value) else {
+                return Err(format!("{} 需要 YYYY-MM-DD 格式的日期：{}", option, value));
+            };
+            if option == "--since" {
+                filter.since = Some(date);
+            } else {
+                filter.until = Some(date + 86400);
+            }
+        }
+        "--app" => filter.apps.push(value.to_string()),
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn history_export(args: &[String]) -> i32 {
+    let mut filter = HistoryFilter::default();
+    let mut format = None;
+    let mut output: Option<PathBuf> = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let option = arg.as_str();
+        if !matches!(
+            option,
+            "--format" | "--since" | "--until" | "--app" | "--output"
+        ) {
+            eprintln!("未知参数：{}\n\n{}", arg, USAGE);
+            return 2;
+        }
+        let Some(value) = iter.next() else {
+            eprintln!("{} 需要一个参数", option);
+            return 2;
+        };
+        match history_filter_option(&mut filter, option, value) {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(message) => {
+                eprintln!("{}", message);
+                return 2;
+            }
+        }
+        match option {
+            "--format" => match ExportFormat::parse(value) {
+                Some(value) => format = Some(value),
+                None => {
+                    eprintln!("不支持的格式：{}（可选 csv、json）", value);
+                    return 2;
+                }
+            },
+            _ => output = Some(PathBuf::from(value)),
+        }
+    }
+    let format = format
+        .or_else(|| {
+            output
+                .as_ref()
+                .and_then(|path| path.extension())
+                .and_then(|ext| ExportFormat::parse(&ext.to_string_lossy()))
+        })
+        .unwrap_or(ExportFormat::Csv);
+
+    let entries = match history::query(&filter) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("读取操作历史失败：{}", err);
+            return 1;
+        }
+    };
+    let exported = history::export(&entries, &filter, format);
+    match output {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, exported) {
+                eprintln!("写入导出文件失败：{}，错误：{}", path.display(), err);
+                return 1;
+            }
+            eprintln!("[History] 已导出到 {}", path.display());
+        }
+        None => print!("{}", exported),
+    }
+    0
+}
+
+fn history_search(args: &[String]) -> i32 {
+    let mut filter = HistoryFilter::default();
+    let mut json = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let option = arg.as_str();
+        match option {
+            "--json" => json = true,
+            "--since" | "--until" | "--app" => {
+                let Some(value) = iter.next() else {
+                    eprintln!("{} 需要一个参数", option);
+                    return 2;
+                };
+                if let Err(message) = history_filter_option(&mut filter, option, value) {
+                    eprintln!("{}", message);
+                    return 2;
+                }
+            }
+            _ if option.starts_with("--") => {
+                eprintln!("未知参数：{}\n\n{}", arg, USAGE);
+                return 2;
+            }
+            _ if filter.text.is_none() => filter.text = Some(arg.clone()),
+            _ => {
+                eprintln!("多余的参数：{}\n\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+    }
+    if filter.text.is_none()
+        && filter.since.is_none()
+        && filter.until.is_none()
+        && filter.apps.is_empty()
+    {
+        eprintln!("需要搜索文字或筛选条件\n\n{}", USAGE);
+        return 2;
+    }
+
+    let entries = match history::query(&filter) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("读取操作历史失败：{}", err);
+            return 1;
+        }
+    };
+    if json {
+        print!("{}", history::export(&entries, &filter, ExportFormat::Json));
+        return 0;
+    }
+    if entries.is_empty() {
+        println!("没有匹配的操作记录");
+        return 0;
+    }
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|entry| {
+            vec![
+                history::format_timestamp(entry.at),
+                entry.action.label().to_string(),
+                entry.path.display().to_string(),
+                entry.error.clone().unwrap_or_else(|| "成功".to_string()),
+            ]
+        })
+        .collect();
+    print_table(&["时间", "操作", "路径", "结果"], &rows);
+    println!("共 {} 条", entries.len());
+    if explain::enabled() {
+        println!();
+        for entry in entries.iter().filter(|entry| !entry.commands.is_empty()) {
+            println!(
+                "# {} {} {}",
+                history::format_timestamp(entry.at),
+                entry.action.label(),
+                entry.path.display()
+            );
+            for command in &entry.commands {
+                println!("{}", command);
+            }
+        }
+    }
+    0
+}
+
+fn report_command(args: &[String]) -> i32 {
+    let mut anonymize = false;
+    let mut output = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--anonymize" => anonymize = true,
+            "--output" => match iter.next() {
+                Some(value) => output = Some(PathBuf::from(value)),
+                None => {
+                    eprintln!("--output 需要一个参数");
+                    return 2;
+                }
+            },
+            _ => {
+                eprintln!("未知参数：{}\n\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+    }
+    let output = output.unwrap_or_else(report::default_report_path);
+    match report::create_report(&output, anonymize) {
+        Ok(_) => {
+            println!("[Report] 已生成诊断包：{}", output.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("生成诊断包失败：{}", err);
+            1
+        }
+    }
+}
+
+fn session_command(args: &[String]) -> i32 {
+    if args.first().map(String::as_str) != Some("restore") || args.len() > 1 {
+        eprintln!("用法：dock-dodger session restore");
+        return 2;
+    }
+    let _writes = begin_writes();
+    let mut apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return 1;
+        }
+    };
+    let results = match session::restore_all_session_apps(&mut apps) {
+        Ok(results) => results,
+        Err(err) => {
+            eprintln!("恢复仅本次运行的应用失败：{}", err);
+            return 1;
+        }
+    };
+    if !results.is_empty()
+        && let Err(err) = state::save_apps(&apps)
+    {
+        eprintln!("保存应用列表失败：{}", err);
+        return 1;
+    }
+    let mut failed = 0;
+    for result in &results {
+        match &result.error {
+            None => println!("[Session] 已恢复：{}", result.path.display()),
+            Some(err) => {
+                failed += 1;
+                println!(
+                    "[Session] 恢复失败：{}，错误：{}",
+                    result.path.display(),
+                    err
+                );
+            }
+        }
+    }
+    println!("共 {} 个，失败 {}", results.len(), failed);
+    if shutdown::requested() {
+        eprintln!("[Session] 已中断，剩下的应用仍记录在会话日志中，可再次执行 session restore");
+        return 1;
+    }
+    if failed > 0 { 1 } else { 0 }
+}
+
+fn format_ago(secs: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    match now.saturating_sub(secs) {
+        elapsed if elapsed < 60 => format!("{} 秒前", elapsed),
+        elapsed if elapsed < 3600 => format!("{} 分钟前", elapsed / 60),
+        elapsed if elapsed < 86400 => format!("{} 小时前", elapsed / 3600),
+        elapsed => format!("{} 天前", elapsed / 86400),
+    }
+}
+
+fn agent_command(args: &[String]) -> i32 {
+    let Some((command, rest)) = args.split_first() else {
+        return agent_status(&[]);
+    };
+    if command == "status" {
+        return agent_status(rest);
+    }
+    if command != "install" && command != "uninstall" {
+        eprintln!("未知的 agent 子命令：{}\n\n{}", command, USAGE);
+        return 2;
+    }
+    let mut jobs = Vec::new();
+    let mut interval = agent::DEFAULT_VERIFY_INTERVAL_SECS;
+    let mut iter = rest.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--interval" if command == "install" => {
+                match iter.next().and_then(|value| value.parse().ok()) {
+                    Some(value) => interval = value,
+                    None => {
+                        eprintln!("--interval 需要一个秒数");
+                        return 2;
+                    }
+                }
+            }
+            "--all" => jobs.extend(AgentJob::ALL),
+            name => match AgentJob::parse(name) {
+                Some(job) => jobs.push(job),
+                None => {
+                    eprintln!(
+                        "未知的代理：{}（可选 session、verify、notify）\n\n{}",
+                        name, USAGE
+                    );
+                    return 2;
+                }
+            },
+        }
+    }
+    // 不指定时只处理 session，与旧版本的 agent install/uninstall 一致。
+    if jobs.is_empty() {
+        jobs.push(AgentJob::Session);
+    }
+    jobs.dedup();
+    if command == "uninstall"
+        && let Err(err) = auth::confirm("卸载 Dock Dodger 的后台代理")
+    {
+        eprintln!("{}", err);
+        return 1;
+    }
+
+    let mut failed = false;
+    for job in jobs {
+        if command == "install" {
+            let exe = match std::env::current_exe() {
+                Ok(exe) => exe,
+                Err(err) => {
+                    eprintln!("无法确定当前程序路径：{}", err);
+                    return 1;
+                }
+            };
+            match agent::install_agent(job, &exe, interval) {
+                Ok(path) => println!("[Agent] {} 已安装：{}", job.name(), path.display()),
+                Err(err) => {
+                    eprintln!("安装 {} 代理失败：{}", job.name(), err);
+                    failed = true;
+                }
+            }
+        } else {
+            match agent::uninstall_agent(job) {
+                Ok(true) => println!(
+                    "[Agent] {} 已卸载：{}",
+                    job.name(),
+                    job.plist_path().display()
+                ),
+                Ok(false) => println!("[Agent] {} 代理未安装", job.name()),
+                Err(err) => {
+                    eprintln!("卸载 {} 代理失败：{}", job.name(), err);
+                    failed = true;
+                }
+            }
+        }
+    }
+    if failed { 1 } else { 0 }
+}
+
+fn agent_status(args: &[String]) -> i32 {
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            other => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+    let statuses: Vec<_> = AgentJob::ALL.into_iter().map(agent::agent_status).collect();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&statuses).unwrap());
+        return 0;
+    }
+    for status in &statuses {
+        let name = status.job.name();
+        if !status.installed {
+            println!("[Agent] {}：未安装", name);
+            continue;
+        }
+        let loaded = match (status.loaded, status.pid) {
+            (true, Some(pid)) => format!("已加载，运行中（PID {}）", pid),
+            (true, None) => "已加载".to_string(),
+            (false, _) => "未加载".to_string(),
+        };
+        let interval = status
+            .interval_secs
+            .map(|secs| format!("，每 {} 秒运行", secs))
+            .unwrap_or_default();
+        let last_run = match &status.last_run {
+            Some(run) => format!(
+                "上次运行：{}，退出码 {}",
+                format_ago(run.finished_at),
+                run.exit_status
+            ),
+            None => "尚未运行".to_string(),
+        };
+        println!(
+            "[Agent] {}：已安装，{}{}，{}",
+            name, loaded, interval, last_run
+        );
+        println!("        {}", status.plist.display());
+    }
+    0
+}
+
+fn schedule_command(args: &[String]) -> i32 {
+    match args.split_first() {
+        Some((command, rest)) if command == "add" => schedule_add(rest),
+        Some((command, rest)) if command == "remove" => schedule_remove(rest),
+        Some((command, rest)) if command == "list" => schedule_list(rest),
+        None => schedule_list(&[]),
+        Some((command, _)) => {
+            eprintln!("未知的 schedule 子命令：{}\n\n{}", command, USAGE);
+            2
+        }
+    }
+}
+
+fn schedule_add(args: &[String]) -> i32 {
+    let mut positional = Vec::new();
+    let mut at = None;
+    let mut days = "daily".to_string();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--at" => match iter.next() {
+                Some(value) => at = Some(value.clone()),
+                None => {
+                    eprintln!("--at 需要一个时间（HH:MM）");
+                    return 2;
+                }
+            },
+            "--days" => match iter.next() {
+                Some(value) => days = value.clone(),
+                None => {
+                    eprintln!("--days 需要一个参数");
+                    return 2;
+                }
+            },
+            other if other.starts_with("--") => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+    let [name, profile] = positional.as_slice() else {
+        eprintln!("schedule add 需要名称和配置文件两个参数\n\n{}", USAGE);
+        return 2;
+    };
+    let Some(at) = at else {
+        eprintln!("schedule add 需要 --at 指定时间\n\n{}", USAGE);
+        return 2;
+    };
+    let schedule = agent::parse_time(&at)
+        .and_then(|time| Ok((agent::parse_weekdays(&days)?, time)))
+        .and_then(|(weekdays, time)| {
+            let profile = config::normalize_input_path(profile)?;
+            let profile = fs::canonicalize(&profile).unwrap_or(profile);
+            agent::ProfileSchedule::new(name, profile, weekdays, time)
+        });
+    let schedule = match schedule {
+        Ok(schedule) => schedule,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    if let Err(err) = auth::confirm("计划会在指定时间自动启用配置文件") {
+        eprintln!("{}", err);
+        return 1;
+    }
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(err) => {
+            eprintln!("无法确定当前程序路径：{}", err);
+            return 1;
+        }
+    };
+    match agent::install_profile_schedule(&schedule, &exe) {
+        Ok(path) => {
+            println!(
+                "[Schedule] {}：{} 启用 {}",
+                schedule.name,
+                schedule.describe(),
+                schedule.profile.display()
+            );
+            println!("           {}", path.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("添加计划 {} 失败：{}", schedule.name, err);
+            1
+        }
+    }
+}
+
+fn schedule_remove(args: &[String]) -> i32 {
+    let [name] = args else {
+        eprintln!("schedule remove 需要一个计划名称\n\n{}", USAGE);
+        return 2;
+    };
+    if let Err(err) = auth::confirm("删除按计划启用配置文件的任务") {
+        eprintln!("{}", err);
+        return 1;
+    }
+    match agent::uninstall_profile_schedule(name) {
+        Ok(true) => {
+            println!("[Schedule] 已删除计划：{}", name);
+            0
+        }
+        Ok(false) => {
+            eprintln!("没有名为 {} 的计划", name);
+            1
+        }
+        Err(err) => {
+            eprintln!("删除计划 {} 失败：{}", name, err);
+            1
+        }
+    }
+}
+
+fn schedule_list(args: &[String]) -> i32 {
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            other => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+    let schedules = agent::profile_schedules();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&schedules).unwrap());
+        return 0;
+    }
+    if schedules.is_empty() {
+        println!("[Schedule] 暂无计划");
+    }
+    for schedule in &schedules {
+        println!(
+            "[Schedule] {}：{} 启用 {}",
+            schedule.name,
+            schedule.describe(),
+            schedule.profile.display()
+        );
+    }
+    0
+}
+
+fn display_paths(paths: impl IntoIterator<Item = PathBuf>) -> Vec<String> {
+    paths
+        .into_iter()
+        .map(|path| path.display().to_string())
+        .collect()
+}
+
+// with_helpers 时一并隐藏包内的登录项辅助程序，否则只列出仍会显示图标的辅助程序供提示。
+fn handle_helpers(app: &mut ManagedApp, with_helpers: bool) -> (Vec<String>, Vec<String>) {
+    if !with_helpers {
+        return (
+            Vec::new(),
+            display_paths(visible_login_item_helpers(&app.path)),
+        );
+    }
+    let before = app.helpers.len();
+    if let Err(err) = app.hide_helpers() {
+        eprintln!(
+            "[Hide] 隐藏登录项辅助程序失败：{}，错误：{}",
+            app.path.display(),
+            err
+        );
+    }
+    let hidden = display_paths(
+        app.helpers[before..]
+            .iter()
+            .map(|helper| helper.path.clone()),
+    );
+    (hidden, display_paths(visible_login_item_helpers(&app.path)))
+}
+
+// 交互式合并时先问完所有冲突再开始修改，返回选择覆盖的路径；等待输入期间 Ctrl-C 可以直接退出。
+fn ask_conflicts(targets: &[HideTarget]) -> Result<Vec<PathBuf>, i32> {
+    let apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return Err(1);
+        }
+    };
+    let mut prompt = ConflictPrompt::default();
+    let mut asked = Vec::new();
+    let mut overwrite = Vec::new();
+    for path in targets.iter().filter_map(|target| target.path.as_ref()) {
+        let path = translocation::resolve(path).unwrap_or_else(|_| path.clone());
+        if asked.contains(&path)
+            || !is_app_bundle(&path)
+            || !apps.iter().any(|app| app.path == path)
+        {
+            continue;
+        }
+        if prompt.ask(&path) {
+            overwrite.push(path.clone());
+        }
+        asked.push(path);
+    }
+    Ok(overwrite)
+}
+
+fn hide_targets(
+    targets: Vec<HideTarget>,
+    dry_run: bool,
+    strategy: MergeStrategy,
+    with_helpers: bool,
+    create_plist: bool,
+) -> Result<HideReport, i32> {
+    let overwrite = if strategy == MergeStrategy::Interactive && !dry_run {
+        ask_conflicts(&targets)?
+    } else {
+        Vec::new()
+    };
+    let _writes = begin_writes();
+    let _dock = dock::batch();
+    let mut apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return Err(1);
+        }
+    };
+
+    let mut report = HideReport {
+        dry_run,
+        ..Default::default()
+    };
+    let mut changed = false;
+    let mut targets = targets.into_iter();
+    while let Some(target) = targets.next() {
+        if shutdown::requested() {
+            report.interrupted = true;
+            report.unprocessed = std::iter::once(target)
+                .chain(targets.by_ref())
+                .map(|target| match target.path {
+                    Some(path) => path.display().to_string(),
+                    None => target.source.unwrap_or_default(),
+                })
+                .collect();
+            break;
+        }
+        let HideTarget { source, path, tags } = target;
+        let Some(path) = path else {
+            report.failed += 1;
+            report.results.push(HideResult {
+                source,
+                path: String::new(),
+                status: HideStatus::Unresolved,
+                error: Some("无法在本机找到对应的应用".into()),
+                helpers: Vec::new(),
+                visible_helpers: Vec::new(),
+            });
+            continue;
+        };
+        // 转移后的临时路径每次启动都不同，改为处理原始位置的应用。
+        let (path, translocation_error) = match translocation::resolve(&path) {
+            Ok(resolved) => {
+                if resolved != path {
+                    eprintln!("[Hide] {}", translocation::warning(&resolved));
+                }
+                (resolved, None)
+            }
+            Err(err) => (path, Some(err.to_string())),
+        };
+        let display = path.display().to_string();
+        let mut helpers = (Vec::new(), Vec::new());
+        let (status, error) = if let Some(err) = translocation_error {
+            (HideStatus::Failed, Some(err))
+        } else if !is_app_bundle(&path) {
+            (HideStatus::Failed, Some("路径不是 .app 包".to_string()))
+        } else if let Some(index) = apps.iter().position(|app| app.path == path) {
+            match (strategy, dry_run) {
+                // 已管理的应用只补上尚未隐藏的辅助程序
+                (MergeStrategy::Skip, false)
+                    if with_helpers && !visible_login_item_helpers(&path).is_empty() =>
+                {
+                    helpers = handle_helpers(&mut apps[index], true);
+                    changed |= !helpers.0.is_empty();
+                    if helpers.0.is_empty() {
+                        (HideStatus::AlreadyManaged, None)
+                    } else {
+                        (HideStatus::Updated, None)
+                    }
+                }
+                (MergeStrategy::Skip, _) => (HideStatus::AlreadyManaged, None),
+                (MergeStrategy::Overwrite, true) => (HideStatus::WouldUpdate, None),
+                (MergeStrategy::Interactive, true) => (HideStatus::Conflict, None),
+                (_, false) if strategy == MergeStrategy::Overwrite || overwrite.contains(&path) => {
+                    match apps[index].hide_dock_icon() {
+                        Ok(_) => {
+                            apps[index].tags = tags;
+                            apps[index].record_checksum();
+                            apps[index].apply_hide_options();
+                            helpers = handle_helpers(&mut apps[index], with_helpers);
+                            changed = true;
+                            (HideStatus::Updated, None)
+                        }
+                        Err(err) => (HideStatus::Failed, Some(err.to_string())),
+                    }
+                }
+                (_, false) => (HideStatus::AlreadyManaged, None),
+            }
+        } else if dry_run && let Err(err) = validate_bundle(&path) {
+            (HideStatus::Failed, Some(err.to_string()))
+        } else if dry_run {
+            let visible = display_paths(visible_login_item_helpers(&path));
+            if with_helpers {
+                helpers.0 = visible;
+            } else {
+                helpers.1 = visible;
+            }
+            (HideStatus::WouldHide, None)
+        } else if let Err(err) = preflight(&path).or_else(|err| {
+            if create_plist {
+                create_minimal_info_plist(&path)
+            } else {
+                Err(err)
+            }
+        }) {
+            (HideStatus::Failed, Some(err.to_string()))
+        } else {
+            let signing = signing::read_signing_info(&path);
+            let original = ui_element(&path).ok().flatten();
+            match hide_dock_icon(&path) {
+                Ok(_) => {
+                    let mut app = ManagedApp::new(path);
+                    app.tags = tags;
+                    app.signing = Some(signing);
+                    app.record_hide(original);
+                    app.record_checksum();
+                    app.apply_hide_options();
+                    helpers = handle_helpers(&mut app, with_helpers);
+                    apps.push(app);
+                    changed = true;
+                    (HideStatus::Hidden, None)
+                }
+                Err(err) => (HideStatus::Failed, Some(err.to_string())),
+            }
+        };
+        match status {
+            HideStatus::Hidden | HideStatus::WouldHide => report.hidden += 1,
+            HideStatus::Updated | HideStatus::WouldUpdate => report.updated += 1,
+            HideStatus::AlreadyManaged | HideStatus::Conflict => report.skipped += 1,
+            HideStatus::Unresolved | HideStatus::Failed => report.failed += 1,
+        }
+        report.results.push(HideResult {
+            source,
+            path: display,
+            status,
+            error,
+            helpers: helpers.0,
+            visible_helpers: helpers.1,
+        });
+    }
+    report.total = report.results.len();
+
+    if changed && let Err(err) = state::save_apps(&apps) {
+        eprintln!("保存应用列表失败：{}", err);
+        return Err(1);
+    }
+    Ok(report)
+}
+
+fn finish_report(report: &HideReport, json: bool) -> i32 {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report).unwrap());
+    } else {
+        for result in &report.results {
+            let source = result.source.as_deref().unwrap_or_default();
+            match result.status {
+                HideStatus::Hidden => println!("[Hide] 已隐藏：{}", result.path),
+                HideStatus::WouldHide => println!("[Hide] 将隐藏：{}", result.path),
+                HideStatus::Updated => println!("[Hide] 已覆盖已有记录：{}", result.path),
+                HideStatus::WouldUpdate => println!("[Hide] 将覆盖已有记录：{}", result.path),
+                HideStatus::Conflict => println!("[Hide] 与已有记录冲突，需确认：{}", result.path),
+                HideStatus::AlreadyManaged => println!("[Hide] 已存在记录，跳过：{}", result.path),
+                HideStatus::Unresolved => println!("[Hide] 无法解析：{}", source),
+                HideStatus::Failed => println!(
+                    "[Hide] 隐藏失败：{}，错误：{}",
+                    result.path,
+                    result.error.as_deref().unwrap_or_default()
+                ),
+            }
+            for helper in &result.helpers {
+                if report.dry_run {
+                    println!("[Hide] 将一并隐藏登录项辅助程序：{}", helper);
+                } else {
+                    println!("[Hide] 一并隐藏了登录项辅助程序：{}", helper);
+                }
+            }
+            if let Some(error) = &result.error
+                && error.starts_with(MISSING_PLIST_ERROR)
+            {
+                println!(
+                    "[Hide] 如果 {} 是只包装了可执行文件的简易应用，可加 --create-plist 创建最小的 Info.plist",
+                    result.path
+                );
+            }
+            if !result.visible_helpers.is_empty() {
+                println!(
+                    "[Hide] {} 内还有 {} 个登录项辅助程序可能显示 Dock 图标，可运行 dock-dodger hide --with-helpers {} 一并隐藏",
+                    result.path,
+                    result.visible_helpers.len(),
+                    result.path
+                );
+            }
+        }
+        let prefix = if report.dry_run { "将" } else { "" };
+        println!(
+            "共 {} 个：{}隐藏 {}，{}覆盖 {}，跳过 {}，失败 {}",
+            report.total,
+            prefix,
+            report.hidden,
+            prefix,
+            report.updated,
+            report.skipped,
+            report.failed
+        );
+        if report.interrupted {
+            println!("[Hide] 已中断，还有 {} 个未处理", report.unprocessed.len());
+        }
+    }
+
+    if report.failed > 0 || report.interrupted {
+        1
+    } else {
+        0
+    }
+}
@@ -0,0 +1,404 @@
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use osx_dock_dodger_rs::bundle::is_dock_icon_hidden;
+use osx_dock_dodger_rs::config;
+use osx_dock_dodger_rs::import::MergeStrategy;
+use osx_dock_dodger_rs::pause;
+use osx_dock_dodger_rs::scan::ScanScope;
+use osx_dock_dodger_rs::shutdown;
+use osx_dock_dodger_rs::state::{self, ColorLabel};
+use osx_dock_dodger_rs::watcher::{DirWatcher, WatchEvent};
+use osx_dock_dodger_rs::webhook;
+use osx_dock_dodger_rs::write_queue;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use super::{HideStatus, HideTarget, RestoreStatus, hide_targets, restore_paths};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: Option<String>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    id: Option<Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PathParams {
+    path: Option<PathBuf>,
+    paths: Vec<PathBuf>,
+    tags: Vec<String>,
+    force: bool,
+    overwrite: bool,
+    with_helpers: bool,
+}
+
+impl PathParams {
+    fn all_paths(&self) -> Result<Vec<PathBuf>, RpcError> {
+        self.path
+            .iter()
+            .chain(&self.paths)
+            .map(|path| config::normalize_input_path(&path.to_string_lossy()))
+            .collect::<Result<_, _>>()
+            .map_err(|err| RpcError::new(INVALID_PARAMS, err))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ListedApp {
+    path: PathBuf,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<ColorLabel>,
+    hidden: bool,
+    session: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external: Option<String>,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+// 标准输出只用于协议消息，日志写到标准错误；多个线程共用同一个输出，每条消息占一行。
+#[derive(Clone)]
+struct Output(Arc<Mutex<io::Stdout>>);
+
+impl Output {
+    fn send(&self, message: &Value) {
+        let mut stdout = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        let _ = writeln!(stdout, "{}", message);
+        let _ = stdout.flush();
+    }
+
+    fn notify(&self, event: &str, params: Value) {
+        let mut params = params;
+        params["type"] = json!(event);
+        self.send(&json!({ "jsonrpc": "2.0", "method": "event", "params": params }));
+    }
+}
+
+fn spawn_watcher(output: Output, subscribed: Arc<AtomicBool>) -> DirWatcher {
+    let config = config::load_config().unwrap_or_default();
+    DirWatcher::spawn(
+        ScanScope::from_config(&config),
+        Duration::from_secs(config.watch_interval_secs.max(1)),
+        move |event| {
+            if !subscribed.load(Ordering::Relaxed) {
+                return;
+            }
+            match event {
+                WatchEvent::Added(path) => output.notify("app_installed", json!({ "path": path })),
+                WatchEvent::Removed(path) => output.notify("app_removed", json!({ "path": path })),
+            }
+        },
+    )
+}
+
+// 监视线程也会在收到 SIGHUP 后由重新加载线程替换，所以放在锁里共享。
+type SharedWatcher = Arc<Mutex<Option<DirWatcher>>>;
+
+#[derive(Debug, Clone, Serialize)]
+struct LastError {
+    at: u64,
+    message: String,
+}
+
+// 请求失败和重新加载配置失败都记在这里，供 health 返回。
+#[derive(Clone, Default)]
+struct ErrorLog(Arc<Mutex<Option<LastError>>>);
+
+impl ErrorLog {
+    fn record(&self, message: &str) {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        *self.0.lock().unwrap_or_else(|err| err.into_inner()) = Some(LastError {
+            at,
+            message: message.to_string(),
+        });
+    }
+
+    fn last(&self) -> Option<LastError> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+}
+
+struct Server {
+    output: Output,
+    subscribed: Arc<AtomicBool>,
+    watcher: SharedWatcher,
+    started: Instant,
+    errors: ErrorLog,
+}
+
+impl Server {
+    fn handle(&mut self, request: Request) -> Result<Value, RpcError> {
+        if request.jsonrpc.as_deref() != Some("2.0") {
+            return Err(RpcError::new(INVALID_REQUEST, "只支持 JSON-RPC 2.0"));
+        }
+        let params = || -> Result<PathParams, RpcError> {
+            match &request.params {
+                Value::Null => Ok(PathParams::default()),
+                params => serde_json::from_value(params.clone())
+                    .map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string())),
+            }
+        };
+        match request.method.as_str() {
+            "health" => Ok(self.health()),
+            "list" => self.list(),
+            "hide" => self.hide(params()?),
+            "restore" => self.restore(params()?),
+            "subscribe" => Ok(self.subscribe()),
+            "unsubscribe" => {
+                self.subscribed.store(false, Ordering::Relaxed);
+                *self.watcher.lock().unwrap_or_else(|err| err.into_inner()) = None;
+                Ok(json!(true))
+            }
+            other => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("未知方法：{}", other),
+            )),
+        }
+    }
+
+    // 供进程监控和脚本确认服务仍在正常工作：已订阅却没有运行中的监视线程，或正在退出时为 degraded。
+    fn health(&self) -> Value {
+        let subscribed = self.subscribed.load(Ordering::Relaxed);
+        let watching = self
+            .watcher
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_ref()
+            .is_some_and(DirWatcher::is_running);
+        let healthy = (watching || !subscribed) && !shutdown::requested();
+        json!({
+            "status": if healthy { "ok" } else { "degraded" },
+            "pid": std::process::id(),
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime_secs": self.started.elapsed().as_secs(),
+            "watcher": { "subscribed": subscribed, "running": watching },
+            "pending_operations": webhook::pending() + write_queue::pending(),
+            "paused": pause::is_paused(),
+            "read_only": config::read_only(),
+            "last_error": self.errors.last(),
+        })
+    }
+
+    fn list(&self) -> Result<Value, RpcError> {
+        let apps = state::load_apps()
+            .map_err(|err| RpcError::new(SERVER_ERROR, format!("读取应用列表失败：{}", err)))?;
+        let listed: Vec<ListedApp> = apps
+            .iter()
+            .map(|app| ListedApp {
+                path: app.path.clone(),
+                tags: app.tags.clone(),
+                label: app.label,
+                hidden: is_dock_icon_hidden(&app.path).unwrap_or(false),
+                session: app.session,
+                external: app.external_change(),
+            })
+            .collect();
+        Ok(json!(listed))
+    }
+
+    fn hide(&self, params: PathParams) -> Result<Value, RpcError> {
+        let paths = params.all_paths()?;
+        if paths.is_empty() {
+            return Err(RpcError::new(INVALID_PARAMS, "需要 path 或 paths 参数"));
+        }
+        let targets = paths
+            .into_iter()
+            .map(|path| HideTarget {
+                source: None,
+                path: Some(path),
+                tags: params.tags.clone(),
+            })
+            .collect();
+        let strategy = if params.overwrite {
+            MergeStrategy::Overwrite
+        } else {
+            MergeStrategy::Skip
+        };
+        let report = hide_targets(targets, false, strategy, params.with_helpers, false)
+            .map_err(|_| RpcError::new(SERVER_ERROR, "读取或保存应用列表失败"))?;
+        if self.subscribed.load(Ordering::Relaxed) {
+            for result in &report.results {
+                if matches!(result.status, HideStatus::Hidden | HideStatus::Updated) {
+                    self.output.notify("hidden", json!({ "path": result.path }));
+                }
+            }
+        }
+        Ok(json!(report))
+    }
+
+    fn restore(&self, params: PathParams) -> Result<Value, RpcError> {
+        let paths = params.all_paths()?;
+        if paths.is_empty() {
+            return Err(RpcError::new(INVALID_PARAMS, "需要 path 或 paths 参数"));
+        }
+        let mut apps = state::load_apps()
+            .map_err(|err| RpcError::new(SERVER_ERROR, format!("读取应用列表失败：{}", err)))?;
+        let results = restore_paths(&mut apps, paths, params.force, |path| {
+            if self.subscribed.load(Ordering::Relaxed) {
+                self.output.notify("restored", json!({ "path": path }));
+            }
+        });
+        if results
+            .iter()
+            .any(|result| matches!(result.status, RestoreStatus::Restored))
+        {
+            state::save_apps(&apps)
+                .map_err(|err| RpcError::new(SERVER_ERROR, format!("保存应用列表失败：{}", err)))?;
+        }
+        Ok(json!(results))
+    }
+
+    // 订阅后推送本连接中的隐藏、恢复操作，以及扫描目录中新安装或删除的应用。
+    fn subscribe(&mut self) -> Value {
+        self.subscribed.store(true, Ordering::Relaxed);
+        let mut watcher = self.watcher.lock().unwrap_or_else(|err| err.into_inner());
+        if watcher.is_none() {
+            *watcher = Some(spawn_watcher(self.output.clone(), self.subscribed.clone()));
+        }
+        json!(true)
+    }
+}
+
+// 收到 SIGHUP 或配置文件被修改时重新读取配置：已订阅的连接按新的扫描目录重启监视线程，
+// 并推送 config_reloaded。
+fn watch_reload(
+    output: Output,
+    subscribed: Arc<AtomicBool>,
+    watcher: SharedWatcher,
+    errors: ErrorLog,
+) {
+    shutdown::enable_reload();
+    thread::spawn(move || {
+        let mut stamp = config::config_stamp();
+        loop {
+            thread::sleep(Duration::from_millis(200));
+            let current = config::config_stamp();
+            if !shutdown::take_reload() && current == stamp {
+                continue;
+            }
+            stamp = current;
+            if let Err(err) = config::load_config() {
+                let message = format!("读取配置失败，继续使用原配置：{}", err);
+                eprintln!("[Config] {}", message);
+                errors.record(&message);
+                continue;
+            }
+            let mut watcher = watcher.lock().unwrap_or_else(|err| err.into_inner());
+            if watcher.is_some() {
+                *watcher = Some(spawn_watcher(output.clone(), subscribed.clone()));
+            }
+            if subscribed.load(Ordering::Relaxed) {
+                output.notify("config_reloaded", json!({}));
+            }
+            eprintln!("[Config] 已重新加载配置");
+        }
+    });
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+pub fn run(args: &[String]) -> i32 {
+    if let Some(arg) = args.first() {
+        eprintln!("未知参数：{}", arg);
+        return 2;
+    }
+    let mut server = Server {
+        output: Output(Arc::new(Mutex::new(io::stdout()))),
+        subscribed: Arc::new(AtomicBool::new(false)),
+        watcher: Arc::new(Mutex::new(None)),
+        started: Instant::now(),
+        errors: ErrorLog::default(),
+    };
+    watch_reload(
+        server.output.clone(),
+        server.subscribed.clone(),
+        server.watcher.clone(),
+        server.errors.clone(),
+    );
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                eprintln!("读取标准输入失败：{}", err);
+                return 1;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = match serde_json::from_str(&line) {
+            Ok(value) => value,
+            Err(err) => {
+                server
+                    .output
+                    .send(&error_response(Value::Null, PARSE_ERROR, &err.to_string()));
+                continue;
+            }
+        };
+        let id = value.get("id").cloned().unwrap_or(Value::Null);
+        let request: Request = match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(err) => {
+                server
+                    .output
+                    .send(&error_response(id, INVALID_REQUEST, &err.to_string()));
+                continue;
+            }
+        };
+        // 没有 id 的是通知，执行但不回复。
+        let reply = request.id.is_some();
+        let _deferred = shutdown::defer();
+        let _lock = state::lock_state();
+        let response = match server.handle(request) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => {
+                if err.code == SERVER_ERROR {
+                    server.errors.record(&err.message);
+                }
+                error_response(id, err.code, &err.message)
+            }
+        };
+        if reply {
+            server.output.send(&response);
+        }
+    }
+    0
+}
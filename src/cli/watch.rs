@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use osx_dock_dodger_rs::bundle::is_dock_icon_hidden;
+use osx_dock_dodger_rs::config;
+use osx_dock_dodger_rs::events::{self, Event};
+use osx_dock_dodger_rs::history;
+use osx_dock_dodger_rs::pause;
+use osx_dock_dodger_rs::scan::ScanScope;
+use osx_dock_dodger_rs::shutdown;
+use osx_dock_dodger_rs::state;
+use osx_dock_dodger_rs::volume;
+use osx_dock_dodger_rs::wake::WakeDetector;
+use osx_dock_dodger_rs::watcher::{DirWatcher, WatchEvent};
+
+use super::USAGE;
+
+// NDJSON 由 events 模块写出；文本模式在这里逐行输出并立即刷新，方便在管道中逐行处理。
+fn emit(event: Event, ndjson: bool) {
+    if !ndjson {
+        let text = match &event {
+            Event::DriftDetected { path } => Some(("[Drift]", "Dock 图标已恢复", path)),
+            Event::DriftResolved { path } => Some(("[Drift]", "已重新隐藏", path)),
+            Event::AppMissing { path } => Some(("[Missing]", "应用已不存在", path)),
+            Event::AppInstalled { path } => Some(("[Watch]", "发现新安装的应用", path)),
+            Event::AppRemoved { path } => Some(("[Watch]", "应用已删除", path)),
+            _ => None,
+        };
+        if let Some((tag, message, path)) = text {
+            let mut stdout = io::stdout().lock();
+            let _ = writeln!(
+                stdout,
+                "{} {} {}：{}",
+                history::format_timestamp(events::now_secs()),
+                tag,
+                message,
+                path.display()
+            );
+            let _ = stdout.flush();
+        }
+    }
+    events::emit(event);
+}
+
+fn spawn_watcher(sender: mpsc::Sender<WatchEvent>) -> DirWatcher {
+    let config = config::load_config().unwrap_or_default();
+    DirWatcher::spawn(
+        ScanScope::from_config(&config),
+        Duration::from_secs(config.watch_interval_secs.max(1)),
+        move |event| {
+            let _ = sender.send(event);
+        },
+    )
+}
+
+// 只报告状态变化：同一个应用持续漂移或缺失时只输出一次。
+#[derive(Default)]
+struct Tracker {
+    drifted: HashSet<PathBuf>,
+    missing: HashSet<PathBuf>,
+}
+
+impl Tracker {
+    fn check(&mut self, ndjson: bool) {
+        // 每轮重新读取列表，其他进程隐藏或恢复的应用也能及时反映。
+        let apps = match state::load_apps() {
+            Ok(apps) => apps,
+            Err(err) => {
+                eprintln!("[Watch] 读取应用列表失败：{}", err);
+                return;
+            }
+        };
+        let paths: HashSet<PathBuf> = apps.iter().map(|app| app.path.clone()).collect();
+        self.drifted.retain(|path| paths.contains(path));
+        self.missing.retain(|path| paths.contains(path));
+        // 暂停期间图标本来就是显示的，不算漂移。
+        if pause::is_paused() {
+            self.drifted.clear();
+            return;
+        }
+        for app in &apps {
+            let path = app.path.clone();
+            // 外接卷未挂载时既不算缺失也不算漂移，保持原有状态等卷重新挂载。
+            if volume::is_offline(&app.path) {
+                continue;
+            }
+            if !app.path.exists() {
+                if self.missing.insert(app.path.clone()) {
+                    emit(Event::AppMissing { path }, ndjson);
+                }
+                continue;
+            }
+            self.missing.remove(&app.path);
+            match is_dock_icon_hidden(&app.path) {
+                Ok(false) if self.drifted.insert(app.path.clone()) => {
+                    history::record_drift(&app.path);
+                    emit(Event::DriftDetected { path }, ndjson);
+                }
+                Ok(true) if self.drifted.remove(&app.path) => {
+                    emit(Event::DriftResolved { path }, ndjson)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let mut ndjson = false;
+    let mut interval = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            // --json 是早先的写法，与 --events-ndjson 相同
+            "--events-ndjson" | "--json" => ndjson = true,
+            "--interval" => match iter.next().and_then(|value| value.parse::<u64>().ok()) {
+                Some(value) if value > 0 => interval = Some(value),
+                _ => {
+                    eprintln!("--interval 需要一个正整数秒数");
+                    return 2;
+                }
+            },
+            other => {
+                eprintln!("未知参数：{}\n\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+
+    if ndjson && let Err(err) = events::enable() {
+        eprintln!("无法输出事件流：{}", err);
+        return 1;
+    }
+    let (sender, receiver) = mpsc::channel();
+    let mut _watcher = spawn_watcher(sender.clone());
+    let mut stamp = config::config_stamp();
+    let interval_of = |interval: Option<u64>| {
+        Duration::from_secs(interval.unwrap_or_else(|| {
+            config::load_config()
+                .unwrap_or_default()
+                .watch_interval_secs
+                .max(1)
+        }))
+    };
+    let mut check_every = interval_of(interval);
+    let mut tracker = Tracker::default();
+    tracker.check(ndjson);
+    let mut next_check = Instant::now() + check_every;
+    let mut wake = WakeDetector::new();
+    let mounted = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&mounted);
+    volume::spawn(move |mount| {
+        eprintln!("[Watch] 卷已挂载：{}", mount.display());
+        flag.store(true, Ordering::SeqCst);
+    });
+    eprintln!("[Watch] 开始监视，按 Ctrl-C 退出");
+
+    while !shutdown::requested() {
+        match receiver.recv_timeout(Duration::from_millis(200)) {
+            Ok(WatchEvent::Added(path)) => emit(Event::AppInstalled { path }, ndjson),
+            Ok(WatchEvent::Removed(path)) => emit(Event::AppRemoved { path }, ndjson),
+            Err(_) => {}
+        }
+        // 配置文件被修改时按新的扫描目录和间隔继续监视。
+        let current = config::config_stamp();
+        if current != stamp {
+            stamp = current;
+            match config::load_config() {
+                Ok(_) => {
+                    _watcher = spawn_watcher(sender.clone());
+                    check_every = interval_of(interval);
+                    eprintln!("[Config] 已重新加载配置");
+                    emit(Event::ConfigReloaded, ndjson);
+                }
+                Err(err) => eprintln!("[Config] 读取配置失败，继续使用原配置：{}", err),
+            }
+        }
+        // 唤醒后立即检查一次，不必等到下一个间隔。
+        let woke = wake.woke();
+        if woke {
+            eprintln!("[Watch] 系统已唤醒，重新检查");
+        }
+        // 外接卷重新挂载后同样立即检查其上的应用。
+        let remounted = mounted.swap(false, Ordering::SeqCst);
+        if woke || remounted || Instant::now() >= next_check {
+            tracker.check(ndjson);
+            next_check = Instant::now() + check_every;
+        }
+    }
+    0
+}
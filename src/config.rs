@@ -0,0 +1,360 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::rules::Rule;
+use crate::scan::{self, ScanRoot};
+use crate::state::write_atomic;
+use crate::state_store::StorageBackend;
+use crate::webhook::Webhook;
+
+pub const DATA_DIR_ENV: &str = "DOCKDODGER_DATA_DIR";
+pub const CONFIG_ENV: &str = "DOCKDODGER_CONFIG";
+
+// 只读模式下写入失败时的错误信息，也用于归类错误。
+pub const READ_ONLY_ERROR: &str = "只读模式下不会修改任何文件";
+
+static FORCE_READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+pub const DEFAULT_BACKUP_RETENTION: usize = 5;
+pub const DEFAULT_WATCH_INTERVAL_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+    pub backup_retention: usize,
+    #[serde(alias = "watch_dirs")]
+    pub scan_roots: Vec<ScanRoot>,
+    pub scan_exclude: Vec<String>,
+    pub watch_interval_secs: u64,
+    pub notify_new_apps: bool,
+    pub rules: Vec<Rule>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<Webhook>,
+    // 操作 → 快捷键，只记录与默认值不同的条目，见 shortcuts 模块
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub shortcuts: BTreeMap<String, String>,
+    // 界面语言，如 "en"；未设置时跟随系统，见 i18n 模块
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    // 窗口内容的缩放比例，如 1.25；未设置时跟随系统的边栏尺寸
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_scale: Option<f64>,
+    // 主窗口浮在其它窗口之上
+    pub always_on_top: bool,
+    // 主窗口在所有桌面（Space）中显示，置顶时也会浮在全屏应用之上
+    pub all_spaces: bool,
+    // 用钥匙串中的密钥加密 apps.json 与备份，见 crypto 模块
+    pub encrypt_data: bool,
+    // 暂停（恢复全部图标）和卸载代理前要求 Touch ID 或登录密码，见 auth 模块
+    pub confirm_destructive: bool,
+    // 应用列表与操作历史的存储方式，见 state_store 模块；sqlite 需要编译时启用同名功能
+    pub storage: StorageBackend,
+    // 只校验和报告，不修改 Info.plist、列表、备份或历史；命令行 --read-only 只对当次运行生效
+    pub read_only: bool,
+    // 每秒最多写入多少个 Info.plist，未设置时不限速，见 write_queue 模块
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub write_rate_limit: Option<f64>,
+    // 隐藏 Dock 图标时一并从启动台移除，恢复时放回原来的位置，见 launchpad 模块
+    pub hide_from_launchpad: bool,
+    // 隐藏时一并移除固定在 Dock 中的图标，恢复时放回原来的位置，见 dock 模块
+    pub remove_dock_tiles: bool,
+    // 上面两项改动后自动重启 Dock；批量操作只在整批结束时重启一次，见 dock 模块
+    pub auto_restart_dock: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            data_dir: None,
+            backup_retention: DEFAULT_BACKUP_RETENTION,
+            scan_roots: scan::default_scan_roots(),
+            scan_exclude: Vec::new(),
+            watch_interval_secs: DEFAULT_WATCH_INTERVAL_SECS,
+            notify_new_apps: true,
+            rules: Vec::new(),
+            webhooks: Vec::new(),
+            shortcuts: BTreeMap::new(),
+            language: None,
+            text_scale: None,
+            always_on_top: false,
+            all_spaces: false,
+            encrypt_data: false,
+            confirm_destructive: false,
+            storage: StorageBackend::Json,
+            read_only: false,
+            write_rate_limit: None,
+            hide_from_launchpad: false,
+            remove_dock_tiles: false,
+            auto_restart_dock: true,
+        }
+    }
+}
+
+pub fn home_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+pub fn expand_home(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => home_dir().join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+// 命令行参数、脚本和界面传入的路径：接受 file:// URL、~ 和相对路径，统一成绝对路径，
+// 并按字面去掉 .、.. 和末尾的 /，不解析符号链接，这样同一个应用不会因为写法不同被当成两个。
+// file:// URL 只接受本机的（不写主机或写 localhost），? 与 # 之后的部分不属于路径。
+pub fn normalize_input_path(input: &str) -> Result<PathBuf, String> {
+    let path = match input.strip_prefix("file://") {
+        Some(rest) => {
+            let rest = rest.split(['?', '#']).next().unwrap_or_default();
+            let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+            if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+                return Err(format!("不支持其他主机上的文件：{}", input));
+            }
+            PathBuf::from(percent_decode(path))
+        }
+        None => expand_home(Path::new(input)),
+    };
+    let path = if path.is_relative() {
+        std::env::current_dir()
+            .map(|dir| dir.join(&path))
+            .unwrap_or(path)
+    } else {
+        path
+    };
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    Ok(normalized)
+}
+
+// 拖入的文字中的应用路径：每行可以是 file:// URL、终端拖出的路径（空格用 \ 转义或加引号，一行可有多个），
+// 也可以是一整行未转义但存在的路径。只接受绝对路径或 ~ 开头的路径，包内的路径截到 .app 为止，
+// 不是应用包的忽略；去重后按出现顺序返回，是否为有效的应用由添加流程照常检查。
+pub fn app_paths_in_text(text: &str) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let words = if line.starts_with("file://") || is_literal_path(line) {
+            vec![line.to_string()]
+        } else {
+            shell_words(line)
+        };
+        for word in words {
+            if !(word.starts_with('/') || word.starts_with('~') || word.starts_with("file://")) {
+                continue;
+            }
+            if let Ok(path) = normalize_input_path(&word)
+                && let Some(app) = app_bundle_prefix(&path)
+                && !paths.contains(&app)
+            {
+                paths.push(app);
+            }
+        }
+    }
+    paths
+}
+
+fn is_literal_path(line: &str) -> bool {
+    (line.starts_with('/') || line.starts_with('~')) && expand_home(Path::new(line)).exists()
+}
+
+// 按空白拆分，支持 \ 转义和单双引号，与终端拖入路径时的写法一致。
+fn shell_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '\'' | '"') => quote = Some(c),
+            (Some('"') | None, '\\') => word.extend(chars.next()),
+            (None, c) if c.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            (_, c) => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn app_bundle_prefix(path: &Path) -> Option<PathBuf> {
+    let mut prefix = PathBuf::new();
+    for component in path.components() {
+        prefix.push(component);
+        if let Component::Normal(name) = component
+            && Path::new(name)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("app"))
+        {
+            return Some(prefix);
+        }
+    }
+    None
+}
+
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+pub fn default_data_dir() -> PathBuf {
+    home_dir().join("Library/Application Support/DockDodger")
+}
+
+fn env_data_dir() -> Option<PathBuf> {
+    std::env::var_os(DATA_DIR_ENV)
+        .filter(|value| !value.is_empty())
+        .map(|value| expand_home(Path::new(&value)))
+}
+
+pub fn config_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(CONFIG_ENV).filter(|value| !value.is_empty()) {
+        return expand_home(Path::new(&path));
+    }
+    env_data_dir()
+        .unwrap_or_else(default_data_dir)
+        .join("config.json")
+}
+
+pub fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
+    let path = config_path();
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let data = fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+// 命令行传入 --read-only 时调用，对当前进程生效。
+pub fn force_read_only() {
+    FORCE_READ_ONLY.store(true, Ordering::SeqCst);
+}
+
+// 按配置文件的路径和修改时间缓存；配置文件无法解析时按只读处理，避免在不确定的状态下写入。
+pub fn read_only() -> bool {
+    type Cache = (PathBuf, Option<SystemTime>, bool);
+    static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+    if FORCE_READ_ONLY.load(Ordering::SeqCst) {
+        return true;
+    }
+    let path = config_path();
+    let stamp = config_stamp();
+    let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some((cached_path, cached_stamp, read_only)) = cache.as_ref()
+        && *cached_path == path
+        && *cached_stamp == stamp
+    {
+        return *read_only;
+    }
+    let read_only = match load_config() {
+        Ok(config) => config.read_only,
+        Err(err) => {
+            eprintln!("[Config] 配置文件无法解析，按只读模式运行：{}", err);
+            true
+        }
+    };
+    *cache = Some((path, stamp, read_only));
+    read_only
+}
+
+// 所有写入都经过这里（或 state::write_atomic）检查。
+pub fn ensure_writable() -> Result<(), Box<dyn std::error::Error>> {
+    if read_only() {
+        return Err(READ_ONLY_ERROR.into());
+    }
+    Ok(())
+}
+
+// 只改写配置文件中的一个顶层字段，其余内容（包括未知字段）保持原样；value 为 None 时删除该字段。
+pub fn update_config(key: &str, value: Option<Value>) -> Result<(), Box<dyn std::error::Error>> {
+    let path = config_path();
+    let mut config: Value = match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents)?,
+        Err(_) => Value::Object(Default::default()),
+    };
+    let Some(object) = config.as_object_mut() else {
+        return Err("配置文件的顶层不是对象".into());
+    };
+    match value {
+        Some(value) => object.insert(key.to_string(), value),
+        None => object.remove(key),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(&path, &serde_json::to_vec_pretty(&config)?)?;
+    Ok(())
+}
+
+// 配置文件的修改时间，文件不存在时为 None；常驻进程据此轮询判断是否需要重新加载。
+pub fn config_stamp() -> Option<SystemTime> {
+    fs::metadata(config_path())
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = env_data_dir() {
+        return dir;
+    }
+    match load_config() {
+        Ok(config) => config
+            .data_dir
+            .map(|dir| expand_home(&dir))
+            .unwrap_or_else(default_data_dir),
+        Err(err) => {
+            eprintln!(
+                "[Config] 读取配置文件失败：{}，错误：{}",
+                config_path().display(),
+                err
+            );
+            default_data_dir()
+        }
+    }
+}
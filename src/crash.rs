@@ -0,0 +1,116 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config;
+use crate::history;
+use crate::session;
+use crate::state::{self, ManagedApp, write_atomic};
+
+const SEEN_FILE: &str = "crash-seen";
+
+// 图形界面有尚未写入磁盘的修改时在这里登记最新的列表，发生 panic 时由钩子写入。
+static UNSAVED: Mutex<Option<Vec<ManagedApp>>> = Mutex::new(None);
+
+pub fn logs_dir() -> PathBuf {
+    config::data_dir().join("logs")
+}
+
+pub fn stash_unsaved(apps: &[ManagedApp]) {
+    if let Ok(mut unsaved) = UNSAVED.lock() {
+        *unsaved = Some(apps.to_vec());
+    }
+}
+
+pub fn clear_unsaved() {
+    if let Ok(mut unsaved) = UNSAVED.lock() {
+        *unsaved = None;
+    }
+}
+
+// panic 可能发生在持有锁的时候，这里只尝试获取，拿不到就放弃。
+fn flush_unsaved() {
+    let Some(apps) = UNSAVED
+        .try_lock()
+        .ok()
+        .and_then(|mut unsaved| unsaved.take())
+    else {
+        return;
+    };
+    if let Err(err) = session::write_journal(&apps) {
+        eprintln!("[Crash] 写入会话日志失败：{}", err);
+    }
+    match state::save_apps(&apps) {
+        Ok(_) => eprintln!("[Crash] 已保存未写入的应用列表"),
+        Err(err) => eprintln!("[Crash] 保存应用列表失败：{}", err),
+    }
+}
+
+fn write_crash_report(info: &PanicHookInfo) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let report = format!(
+        "Dock Dodger {}（{} {}）\n时间：{}\n线程：{}\n参数：{:?}\n\n{}\n\n调用栈：\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        history::format_timestamp(now.as_secs()),
+        thread::current().name().unwrap_or("<unnamed>"),
+        std::env::args().skip(1).collect::<Vec<_>>(),
+        info,
+        Backtrace::force_capture()
+    );
+    let dir = logs_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("crash-{}.log", now.as_millis()));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        flush_unsaved();
+        match write_crash_report(info) {
+            Ok(path) => eprintln!("[Crash] 崩溃报告已写入 {}", path.display()),
+            Err(err) => eprintln!("[Crash] 写入崩溃报告失败：{}", err),
+        }
+    }));
+}
+
+pub fn crash_reports() -> Vec<PathBuf> {
+    let mut reports: Vec<PathBuf> = fs::read_dir(logs_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with("crash-") && name.ends_with(".log"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    reports.sort();
+    reports
+}
+
+// 返回用户还没看过的最新崩溃报告；文件名中带毫秒时间戳，按名称排序即按时间排序。
+pub fn unseen_crash_report() -> Option<PathBuf> {
+    let latest = crash_reports().pop()?;
+    let seen = fs::read_to_string(logs_dir().join(SEEN_FILE)).unwrap_or_default();
+    let name = latest.file_name()?.to_string_lossy().into_owned();
+    (name.as_str() > seen.trim()).then_some(latest)
+}
+
+pub fn mark_crash_seen(report: &Path) -> std::io::Result<()> {
+    let name = report
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    write_atomic(&logs_dir().join(SEEN_FILE), name.as_bytes())
+}
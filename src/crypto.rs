@@ -0,0 +1,161 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, PoisonError};
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+use crate::config;
+
+// 加密文件的格式：MAGIC + 24 字节随机 nonce + XChaCha20-Poly1305 密文。
+// 读取时按开头判断，明文和密文可以混在一起，开关 encrypt_data 后旧文件照常读取，下次写入时转换。
+const MAGIC: &[u8] = b"DDENC1\n";
+const NONCE_LEN: usize = 24;
+// 按路径查找记录时使用的带密钥哈希，由加密密钥派生，不直接使用加密密钥本身。
+const LOOKUP_CONTEXT: &str = "Dock Dodger 2024-06 path lookup";
+
+// 密钥以十六进制存放在登录钥匙串的通用密码中。
+const KEYCHAIN_SERVICE: &str = "Dock Dodger";
+const KEYCHAIN_ACCOUNT: &str = "data-encryption-key";
+// 没有钥匙串的环境（测试、通过 MDM 统一下发）可以直接用环境变量提供十六进制密钥。
+pub const DATA_KEY_ENV: &str = "DOCKDODGER_DATA_KEY";
+
+static KEY: Mutex<Option<Key>> = Mutex::new(None);
+
+pub fn enabled() -> bool {
+    config::load_config().unwrap_or_default().encrypt_data
+}
+
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+// 写入前调用：未开启 encrypt_data 时原样返回。
+pub fn seal(data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if !enabled() {
+        return Ok(data.to_vec());
+    }
+    let cipher = XChaCha20Poly1305::new(&key(true)?);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, data).map_err(|_| "加密数据失败")?;
+    let mut sealed = MAGIC.to_vec();
+    sealed.extend_from_slice(nonce.as_slice());
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+// 读取后调用：明文原样返回，与当前是否开启加密无关。
+pub fn open(data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let Some(body) = data.strip_prefix(MAGIC) else {
+        return Ok(data.to_vec());
+    };
+    if body.len() < NONCE_LEN {
+        return Err("加密文件已截断".into());
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    let cipher = XChaCha20Poly1305::new(&key(false)?);
+    Ok(cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "解密失败：密钥不匹配或文件已损坏")?)
+}
+
+// 按当前设置转换：开启加密后把明文转成密文，关闭后反之；已符合设置时返回 None。
+pub fn reseal(data: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+    if is_sealed(data) == enabled() {
+        return Ok(None);
+    }
+    Ok(Some(seal(&open(data)?)?))
+}
+
+// 按行存储的文本（history.jsonl）使用：密文转成十六进制，不会包含换行。
+pub fn seal_text(data: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    if !enabled() {
+        return Ok(String::from_utf8(data.to_vec())?);
+    }
+    Ok(encode_hex(&seal(data)?))
+}
+
+pub fn is_sealed_text(text: &str) -> bool {
+    text.starts_with(&encode_hex(MAGIC))
+}
+
+pub fn open_text(text: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if !is_sealed_text(text) {
+        return Ok(text.as_bytes().to_vec());
+    }
+    open(&decode_hex(text).ok_or("加密文本格式无效")?)
+}
+
+// 加密时代替明文路径存入可按路径查询的列，相同路径得到相同的值。
+pub fn lookup_key(data: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    let key = blake3::derive_key(LOOKUP_CONTEXT, key(true)?.as_slice());
+    Ok(blake3::keyed_hash(&key, data).to_hex().to_string())
+}
+
+fn key(create: bool) -> Result<Key, Box<dyn std::error::Error>> {
+    let mut cached = KEY.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(key) = *cached {
+        return Ok(key);
+    }
+    let hex = match std::env::var(DATA_KEY_ENV)
+        .ok()
+        .filter(|value| !value.is_empty())
+    {
+        Some(hex) => hex,
+        None => match read_keychain() {
+            Some(hex) => hex,
+            None if create => create_keychain_key()?,
+            None => {
+                return Err("钥匙串中没有 Dock Dodger 的加密密钥，无法读取加密的数据文件".into());
+            }
+        },
+    };
+    let bytes = decode_hex(hex.trim()).filter(|bytes| bytes.len() == 32);
+    let key = *Key::from_slice(&bytes.ok_or("加密密钥格式无效，需要 64 位十六进制")?);
+    *cached = Some(key);
+    Ok(key)
+}
+
+fn read_keychain() -> Option<String> {
+    Command::new("security")
+        .args(["find-generic-password", "-s", KEYCHAIN_SERVICE])
+        .args(["-a", KEYCHAIN_ACCOUNT, "-w"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|hex| !hex.is_empty())
+}
+
+fn create_keychain_key() -> Result<String, Box<dyn std::error::Error>> {
+    let hex = encode_hex(&XChaCha20Poly1305::generate_key(&mut OsRng));
+    // -w 放在最后且不带值时 security 从标准输入读取密码（要求输入两次），密钥不会出现在进程参数中。
+    let mut child = Command::new("security")
+        .args(["add-generic-password", "-s", KEYCHAIN_SERVICE])
+        .args(["-a", KEYCHAIN_ACCOUNT, "-w"])
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "{}\n{}", hex, hex)?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err("无法把加密密钥写入钥匙串".into());
+    }
+    eprintln!("[Crypto] 已生成加密密钥并保存到钥匙串");
+    Ok(hex)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,95 @@
+// 通过分布式通知中心广播状态变化，Hammerspoon、BetterTouchTool 等本机工具可以直接订阅，
+// 不必轮询应用列表。userInfo 中只放字符串，沙盒中的观察者收不到 userInfo 时也能凭名称刷新。
+pub const STATE_CHANGED: &str = "com.dockdodger.stateChanged";
+
+#[cfg(target_os = "macos")]
+mod cf {
+    use std::ffi::c_void;
+
+    pub type CFTypeRef = *const c_void;
+
+    pub const UTF8: u32 = 0x0800_0100;
+
+    #[repr(C)]
+    pub struct CallBacks {
+        _opaque: [u8; 0],
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        pub static kCFTypeDictionaryKeyCallBacks: CallBacks;
+        pub static kCFTypeDictionaryValueCallBacks: CallBacks;
+        pub fn CFStringCreateWithBytes(
+            alloc: CFTypeRef,
+            bytes: *const u8,
+            len: isize,
+            encoding: u32,
+            external: u8,
+        ) -> CFTypeRef;
+        pub fn CFDictionaryCreate(
+            alloc: CFTypeRef,
+            keys: *const CFTypeRef,
+            values: *const CFTypeRef,
+            count: isize,
+            key_callbacks: *const CallBacks,
+            value_callbacks: *const CallBacks,
+        ) -> CFTypeRef;
+        pub fn CFNotificationCenterGetDistributedCenter() -> CFTypeRef;
+        pub fn CFNotificationCenterPostNotification(
+            center: CFTypeRef,
+            name: CFTypeRef,
+            object: CFTypeRef,
+            user_info: CFTypeRef,
+            deliver_immediately: u8,
+        );
+        pub fn CFRelease(value: CFTypeRef);
+    }
+
+    pub fn string(text: &str) -> CFTypeRef {
+        unsafe {
+            CFStringCreateWithBytes(
+                std::ptr::null(),
+                text.as_ptr(),
+                text.len() as isize,
+                UTF8,
+                0,
+            )
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn post(name: &str, info: &[(&str, &str)]) {
+    use std::ptr;
+
+    let keys: Vec<cf::CFTypeRef> = info.iter().map(|(key, _)| cf::string(key)).collect();
+    let values: Vec<cf::CFTypeRef> = info.iter().map(|(_, value)| cf::string(value)).collect();
+    let name = cf::string(name);
+    unsafe {
+        let user_info = cf::CFDictionaryCreate(
+            ptr::null(),
+            keys.as_ptr(),
+            values.as_ptr(),
+            keys.len() as isize,
+            &raw const cf::kCFTypeDictionaryKeyCallBacks,
+            &raw const cf::kCFTypeDictionaryValueCallBacks,
+        );
+        cf::CFNotificationCenterPostNotification(
+            cf::CFNotificationCenterGetDistributedCenter(),
+            name,
+            ptr::null(),
+            user_info,
+            1,
+        );
+        if !user_info.is_null() {
+            cf::CFRelease(user_info);
+        }
+        for value in keys.iter().chain(&values) {
+            cf::CFRelease(*value);
+        }
+        cf::CFRelease(name);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn post(_name: &str, _info: &[(&str, &str)]) {}
@@ -0,0 +1,260 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use plist::Value;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::explain;
+
+// 测试或特殊环境中改为读写指定的 plist 文件，而不是通过 defaults 读写 com.apple.dock。
+pub const DOCK_PREFS_ENV: &str = "DOCKDODGER_DOCK_PREFS";
+
+const PERSISTENT_APPS: &str = "persistent-apps";
+const DOCK_DOMAIN: &str = "com.apple.dock";
+const RESTART_COMMAND: &str = "killall Dock";
+
+// 修改启动台数据库或 Dock 偏好后需要重启 Dock 才会生效；退出后由 launchd 自动重新启动。
+pub fn restart() -> Result<(), Box<dyn std::error::Error>> {
+    let status = Command::new("killall").arg("Dock").status()?;
+    if !status.success() {
+        return Err("重启 Dock 失败".into());
+    }
+    Ok(())
+}
+
+// 批量操作期间只记下需要重启，整批结束时（最外层的 Batch 释放时）重启一次，
+// 避免每处理一个应用 Dock 就闪一下。
+static BATCH_DEPTH: AtomicUsize = AtomicUsize::new(0);
+static RESTART_PENDING: AtomicBool = AtomicBool::new(false);
+
+pub struct Batch(());
+
+pub fn batch() -> Batch {
+    BATCH_DEPTH.fetch_add(1, Ordering::SeqCst);
+    Batch(())
+}
+
+impl Drop for Batch {
+    fn drop(&mut self) {
+        if BATCH_DEPTH.fetch_sub(1, Ordering::SeqCst) == 1
+            && RESTART_PENDING.swap(false, Ordering::SeqCst)
+        {
+            restart_now();
+        }
+    }
+}
+
+pub fn restart_pending() -> bool {
+    RESTART_PENDING.load(Ordering::SeqCst)
+}
+
+// 改动启动台或固定图标后调用：批量操作中推迟到整批结束，否则立即重启。
+// 配置中关闭 auto_restart_dock 时不重启，只提示改动要等 Dock 重新启动后才生效。
+pub fn request_restart() {
+    if BATCH_DEPTH.load(Ordering::SeqCst) > 0 {
+        RESTART_PENDING.store(true, Ordering::SeqCst);
+    } else {
+        restart_now();
+    }
+}
+
+fn restart_now() {
+    if !config::load_config().unwrap_or_default().auto_restart_dock {
+        eprintln!(
+            "[Dock] 已关闭自动重启 Dock，改动将在 Dock 重新启动（killall Dock）或下次登录后生效"
+        );
+        return;
+    }
+    explain::print(&[RESTART_COMMAND.into()]);
+    if let Err(err) = restart() {
+        eprintln!("[Dock] {}，启动台与 Dock 图标的改动将在下次登录后生效", err);
+    }
+}
+
+// 请求重启时会执行的命令，随改动记入操作历史；关闭 auto_restart_dock 时没有。
+pub fn restart_commands() -> Vec<String> {
+    if config::load_config().unwrap_or_default().auto_restart_dock {
+        vec![RESTART_COMMAND.into()]
+    } else {
+        Vec::new()
+    }
+}
+
+// 固定在 Dock 中的图标（com.apple.dock 的 persistent-apps 中的一项），原样记下内容与位置，
+// 恢复时插回同一位置。隐藏 LSUIElement 不会移除已经固定的图标，需要单独处理。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DockTile {
+    pub position: usize,
+    pub tile: Value,
+}
+
+// 通过 defaults export/import 读写，经过 cfprefsd，不会被缓存中的旧值覆盖。
+fn load_prefs() -> Result<Value, Box<dyn std::error::Error>> {
+    let contents = match std::env::var_os(DOCK_PREFS_ENV) {
+        Some(path) => std::fs::read(path)?,
+        None => {
+            let output = Command::new("defaults")
+                .args(["export", DOCK_DOMAIN, "-"])
+                .output()?;
+            if !output.status.success() {
+                return Err(format!(
+                    "读取 Dock 偏好失败：{}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )
+                .into());
+            }
+            output.stdout
+        }
+    };
+    Ok(Value::from_reader(io::Cursor::new(contents))?)
+}
+
+// edits 是这次改动对应的 plutil 修改（见 explain::defaults_commands），保存成功后输出。
+fn save_prefs(
+    prefs: &Value,
+    edits: &[(String, Option<Value>)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    config::ensure_writable()?;
+    let mut contents = Vec::new();
+    prefs.to_writer_xml(&mut contents)?;
+    if let Some(path) = std::env::var_os(DOCK_PREFS_ENV) {
+        std::fs::write(&path, contents)?;
+        explain::record(explain::defaults_commands(
+            DOCK_DOMAIN,
+            Some(Path::new(&path)),
+            edits,
+        ));
+        return Ok(());
+    }
+    let mut child = Command::new("defaults")
+        .args(["import", DOCK_DOMAIN, "-"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or("无法写入 defaults 的标准输入")?
+        .write_all(&contents)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "保存 Dock 偏好失败：{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    explain::record(explain::defaults_commands(DOCK_DOMAIN, None, edits));
+    Ok(())
+}
+
+fn tile_data(tile: &Value) -> Option<&plist::Dictionary> {
+    tile.as_dictionary()?.get("tile-data")?.as_dictionary()
+}
+
+fn tile_bundle_id(tile: &Value) -> Option<&str> {
+    tile_data(tile)?.get("bundle-identifier")?.as_string()
+}
+
+// file-data 中的 _CFURLString 形如 file:///Applications/Foo%20Bar.app/
+fn tile_path(tile: &Value) -> Option<PathBuf> {
+    let url = tile_data(tile)?
+        .get("file-data")?
+        .as_dictionary()?
+        .get("_CFURLString")?
+        .as_string()?;
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    Some(PathBuf::from(config::percent_decode(
+        path.trim_end_matches('/'),
+    )))
+}
+
+fn tile_matches(tile: &Value, app: &Path, bundle_id: Option<&str>) -> bool {
+    let same_id = match (tile_bundle_id(tile), bundle_id) {
+        (Some(tile_id), Some(bundle_id)) => tile_id.eq_ignore_ascii_case(bundle_id),
+        _ => false,
+    };
+    same_id || tile_path(tile).is_some_and(|path| path == app)
+}
+
+fn persistent_apps(prefs: &mut Value) -> Option<&mut Vec<Value>> {
+    prefs
+        .as_dictionary_mut()?
+        .get_mut(PERSISTENT_APPS)?
+        .as_array_mut()
+}
+
+// 从 Dock 中移除该应用固定的图标，返回移除前的内容与位置；没有固定时返回空列表。
+pub fn remove_tiles(
+    app: &Path,
+    bundle_id: Option<&str>,
+) -> Result<Vec<DockTile>, Box<dyn std::error::Error>> {
+    let mut prefs = load_prefs()?;
+    let Some(tiles) = persistent_apps(&mut prefs) else {
+        return Ok(Vec::new());
+    };
+    let mut removed = Vec::new();
+    let mut position = 0;
+    tiles.retain(|tile| {
+        let keep = !tile_matches(tile, app, bundle_id);
+        if !keep {
+            removed.push(DockTile {
+                position,
+                tile: tile.clone(),
+            });
+        }
+        position += 1;
+        keep
+    });
+    if !removed.is_empty() {
+        // 从后往前删除，前面的序号不受影响。
+        let edits: Vec<(String, Option<Value>)> = removed
+            .iter()
+            .rev()
+            .map(|tile| (format!("{}.{}", PERSISTENT_APPS, tile.position), None))
+            .collect();
+        save_prefs(&prefs, &edits)?;
+    }
+    Ok(removed)
+}
+
+// 按原来的位置插回（位置超出时放到末尾）；已经重新固定过的应用跳过。返回实际插回的数量。
+pub fn restore_tiles(
+    app: &Path,
+    bundle_id: Option<&str>,
+    removed: &[DockTile],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    if removed.is_empty() {
+        return Ok(0);
+    }
+    let mut prefs = load_prefs()?;
+    let Some(dict) = prefs.as_dictionary_mut() else {
+        return Err("Dock 偏好的格式不正确".into());
+    };
+    let mut edits = Vec::new();
+    if !dict.contains_key(PERSISTENT_APPS) {
+        dict.insert(PERSISTENT_APPS.into(), Value::Array(Vec::new()));
+        edits.push((PERSISTENT_APPS.into(), Some(Value::Array(Vec::new()))));
+    }
+    let Some(tiles) = dict.get_mut(PERSISTENT_APPS).and_then(Value::as_array_mut) else {
+        return Err("Dock 偏好的格式不正确".into());
+    };
+    if tiles.iter().any(|tile| tile_matches(tile, app, bundle_id)) {
+        return Ok(0);
+    }
+    let mut sorted: Vec<&DockTile> = removed.iter().collect();
+    sorted.sort_by_key(|tile| tile.position);
+    for tile in &sorted {
+        let position = tile.position.min(tiles.len());
+        tiles.insert(position, tile.tile.clone());
+        edits.push((
+            format!("{}.{}", PERSISTENT_APPS, position),
+            Some(tile.tile.clone()),
+        ));
+    }
+    save_prefs(&prefs, &edits)?;
+    Ok(sorted.len())
+}
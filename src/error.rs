@@ -0,0 +1,109 @@
+use std::error::Error;
+use std::io;
+
+use serde::Serialize;
+
+use crate::bundle;
+use crate::config;
+use crate::helper;
+use crate::relaunch;
+
+// 按原因对 Box<dyn Error> 归类，用于向用户解释失败原因并给出处理建议。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    PermissionDenied,
+    AuthorizationFailed,
+    NotFound,
+    MissingPlist,
+    InvalidBundle,
+    InvalidPlist,
+    AutomationDenied,
+    ReadOnly,
+    Other,
+}
+
+impl ErrorKind {
+    // 沿 source() 链查找第一个能识别的错误。
+    pub fn classify(err: &(dyn Error + 'static)) -> Self {
+        let mut current = Some(err);
+        while let Some(err) = current {
+            if err.to_string().starts_with(config::READ_ONLY_ERROR) {
+                return ErrorKind::ReadOnly;
+            }
+            if err.to_string().starts_with(bundle::MISSING_PLIST_ERROR) {
+                return ErrorKind::MissingPlist;
+            }
+            if err.to_string().starts_with(bundle::INVALID_BUNDLE_ERROR) {
+                return ErrorKind::InvalidBundle;
+            }
+            if err
+                .to_string()
+                .starts_with(relaunch::AUTOMATION_DENIED_ERROR)
+            {
+                return ErrorKind::AutomationDenied;
+            }
+            if let Some(err) = err.downcast_ref::<io::Error>() {
+                match err.kind() {
+                    io::ErrorKind::PermissionDenied => return ErrorKind::PermissionDenied,
+                    io::ErrorKind::NotFound => return ErrorKind::NotFound,
+                    _ => {}
+                }
+            }
+            if err.downcast_ref::<plist::Error>().is_some() {
+                return ErrorKind::InvalidPlist;
+            }
+            if err.to_string().starts_with(helper::PRIVILEGED_ERROR) {
+                return ErrorKind::AuthorizationFailed;
+            }
+            current = err.source();
+        }
+        ErrorKind::Other
+    }
+
+    pub fn explanation(self) -> &'static str {
+        match self {
+            ErrorKind::PermissionDenied => {
+                "系统拒绝修改该应用的 Info.plist，通常是 macOS 的“App 管理”或“完全磁盘访问权限”保护所致。"
+            }
+            ErrorKind::AuthorizationFailed => "管理员授权对话框被取消，或输入的密码不正确。",
+            ErrorKind::NotFound => {
+                "找不到该应用或其 Info.plist，应用可能已被移动、删除或正在更新。"
+            }
+            ErrorKind::MissingPlist => {
+                "应用包中没有 Info.plist，通常是只包装了脚本或可执行文件的简易 .app，或者安装不完整。"
+            }
+            ErrorKind::InvalidBundle => {
+                "该路径虽然以 .app 结尾，但 Contents/MacOS 下没有对应的可执行文件，不像是真正的应用包。"
+            }
+            ErrorKind::InvalidPlist => "Info.plist 无法解析，文件可能已损坏或被其他工具写坏。",
+            ErrorKind::AutomationDenied => {
+                "重新启动应用需要先请求它退出，但 macOS 没有授予 Dock Dodger 控制该应用的“自动化”权限。隐藏已经生效，只是没有重新启动。"
+            }
+            ErrorKind::ReadOnly => "当前处于只读（审计）模式，不会修改任何文件。",
+            ErrorKind::Other => "发生了未归类的错误。",
+        }
+    }
+
+    pub fn remedy(self) -> &'static str {
+        match self {
+            ErrorKind::PermissionDenied => {
+                "在“系统设置 › 隐私与安全性”中为 Dock Dodger 打开“App 管理”或“完全磁盘访问权限”，然后重试。"
+            }
+            ErrorKind::AuthorizationFailed => "重试并在系统对话框中输入管理员密码。",
+            ErrorKind::NotFound => "确认应用仍在原位置，然后重新扫描已隐藏的应用。",
+            ErrorKind::MissingPlist => {
+                "如果这是简易的包装应用，可以创建只包含 LSUIElement 的最小 Info.plist；否则请重新安装该应用。"
+            }
+            ErrorKind::InvalidBundle => "确认拖入的是完整安装的应用；应用已损坏时请重新安装。",
+            ErrorKind::InvalidPlist => "从备份中恢复 Info.plist，或重新安装该应用。",
+            ErrorKind::AutomationDenied => {
+                "在“系统设置 › 隐私与安全性 › 自动化”中允许 Dock Dodger 控制该应用；在此之前可手动退出并重新打开应用。"
+            }
+            ErrorKind::ReadOnly => {
+                "去掉命令行的 --read-only，或把配置中的 read_only 改为 false 后重试。"
+            }
+            ErrorKind::Other => "查看终端输出的日志，必要时在 GitHub 上提交问题。",
+        }
+    }
+}
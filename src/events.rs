@@ -0,0 +1,112 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::fd::FromRawFd;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::distributed;
+use crate::history::HistoryAction;
+use crate::webhook;
+
+unsafe extern "C" {
+    fn dup(fd: i32) -> i32;
+    fn dup2(from: i32, to: i32) -> i32;
+}
+
+// 开启后每个事件以一行 JSON（NDJSON）写到原来的标准输出，供其他工具逐行读取。
+static OUTPUT: Mutex<Option<File>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    OperationStarted {
+        action: HistoryAction,
+        path: PathBuf,
+    },
+    OperationSucceeded {
+        action: HistoryAction,
+        path: PathBuf,
+    },
+    OperationFailed {
+        action: HistoryAction,
+        path: PathBuf,
+        error: String,
+    },
+    // 已管理应用的 Dock 图标又出现了
+    DriftDetected {
+        path: PathBuf,
+    },
+    // 漂移后又恢复为隐藏（例如其他进程重新隐藏了它）
+    DriftResolved {
+        path: PathBuf,
+    },
+    // 已管理应用的包被删除或移动
+    AppMissing {
+        path: PathBuf,
+    },
+    AppInstalled {
+        path: PathBuf,
+    },
+    AppRemoved {
+        path: PathBuf,
+    },
+    ConfigReloaded,
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    at: u64,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+// 原来的标准输出只留给事件，之后 println! 输出的日志改写到标准错误，避免混进事件流。
+pub fn enable() -> io::Result<()> {
+    let _ = io::stdout().flush();
+    let fd = unsafe { dup(1) };
+    if fd < 0 || unsafe { dup2(2, 1) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut output = OUTPUT.lock().unwrap_or_else(|err| err.into_inner());
+    *output = Some(unsafe { File::from_raw_fd(fd) });
+    Ok(())
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn to_line(at: u64, event: &Event) -> String {
+    serde_json::to_string(&Line { at, event }).unwrap_or_default()
+}
+
+// 开启事件流时写到原来的标准输出（File 不带缓冲，整行一次写出），同时发送到配置的 Webhook。
+pub fn emit(event: Event) {
+    let at = now_secs();
+    let mut output = OUTPUT.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some(file) = output.as_mut() {
+        let line = to_line(at, &event) + "\n";
+        let _ = file.write_all(line.as_bytes());
+    }
+    drop(output);
+    let Ok(value) = serde_json::to_value(Line { at, event: &event }) else {
+        return;
+    };
+    // 隐藏或恢复成功后广播分布式通知，userInfo 为 action 与 path。
+    if let Event::OperationSucceeded { path, .. } = &event {
+        distributed::post(
+            distributed::STATE_CHANGED,
+            &[
+                ("action", value["action"].as_str().unwrap_or_default()),
+                ("path", &path.display().to_string()),
+            ],
+        );
+    }
+    webhook::dispatch(&value);
+}
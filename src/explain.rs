@@ -0,0 +1,137 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use plist::Value;
+
+use crate::bundle::{Privilege, required_privilege};
+
+// 每次修改 Info.plist 后生成等价的 plutil 命令，记入操作历史；
+// 加 --explain 时同时输出到标准错误，便于审计，或在别的机器上手动重复同样的修改。
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static COLLECTED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn print(commands: &[String]) {
+    if enabled() {
+        for command in commands {
+            eprintln!("[Explain] {}", command);
+        }
+    }
+}
+
+// 启动台与 Dock 偏好的改动在各自模块中调用：输出的同时交给外层的 collect，
+// 由调用方与应用一起记入操作历史。
+pub fn record(commands: Vec<String>) {
+    print(&commands);
+    COLLECTED.with_borrow_mut(|collected| {
+        if let Some(collected) = collected {
+            collected.extend(commands);
+        }
+    });
+}
+
+// 执行 operation 并返回其间 record 的命令。
+pub fn collect<T>(operation: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let outer = COLLECTED.replace(Some(Vec::new()));
+    let result = operation();
+    let commands = COLLECTED.replace(outer).unwrap_or_default();
+    (result, commands)
+}
+
+// 单引号包住参数，内部的单引号写成 '\''。
+pub fn shell_quote(text: &str) -> String {
+    if !text.is_empty()
+        && text
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "/._-+:@%=,".contains(ch))
+    {
+        return text.to_string();
+    }
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn typed_argument(value: &Value) -> String {
+    match value {
+        Value::String(text) => format!("-string {}", shell_quote(text)),
+        Value::Boolean(flag) => format!("-bool {}", if *flag { "YES" } else { "NO" }),
+        Value::Integer(number) => format!("-integer {}", number),
+        Value::Real(number) => format!("-float {}", number),
+        other => {
+            let mut xml = Vec::new();
+            let _ = other.to_writer_xml(&mut xml);
+            format!("-xml {}", shell_quote(String::from_utf8_lossy(&xml).trim()))
+        }
+    }
+}
+
+// 把 LSUIElement 设为 value（None 表示删除）的等价命令。plutil 会保持文件原来的格式，
+// 与 Dock Dodger 的写入方式一致；当前用户无法写入时加 sudo。
+pub fn ui_element_commands(app: &Path, value: Option<&Value>) -> Vec<String> {
+    let plist = shell_quote(&app.join("Contents/Info.plist").display().to_string());
+    let sudo = match required_privilege(app) {
+        Privilege::User => "",
+        Privilege::Admin => "sudo ",
+    };
+    let command = match value {
+        Some(value) => format!(
+            "{}plutil -replace LSUIElement {} {}",
+            sudo,
+            typed_argument(value),
+            plist
+        ),
+        None => format!("{}plutil -remove LSUIElement {}", sudo, plist),
+    };
+    vec![command]
+}
+
+// 在启动台数据库上执行同样 SQL 的命令。
+pub fn sqlite_commands(database: &Path, sql: &str) -> Vec<String> {
+    vec![format!(
+        "sqlite3 -bail {} {}",
+        shell_quote(&database.display().to_string()),
+        shell_quote(sql)
+    )]
+}
+
+// 按顺序修改偏好域中数组的等价命令：值为 None 时删除 keypath，否则在 keypath 处插入。
+// 与 Dock Dodger 一样经 defaults export/import 读写；指定了 plist 文件时（测试等）直接修改文件。
+pub fn defaults_commands(
+    domain: &str,
+    file: Option<&Path>,
+    edits: &[(String, Option<Value>)],
+) -> Vec<String> {
+    let target = match file {
+        Some(file) => shell_quote(&file.display().to_string()),
+        None => format!("{}.plist", domain),
+    };
+    let mut commands = Vec::new();
+    if file.is_none() {
+        commands.push(format!("defaults export {} {}", domain, target));
+    }
+    for (keypath, value) in edits {
+        commands.push(match value {
+            Some(value) => format!(
+                "plutil -insert {} {} {}",
+                shell_quote(keypath),
+                typed_argument(value),
+                target
+            ),
+            None => format!("plutil -remove {} {}", shell_quote(keypath), target),
+        });
+    }
+    if file.is_none() {
+        commands.push(format!("defaults import {} {}", domain, target));
+    }
+    commands
+}
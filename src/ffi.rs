@@ -0,0 +1,237 @@
+// 供 Swift/Objective-C 等调用的 C 接口，头文件 include/dock_dodger.h 由 cbindgen 生成。
+// 导出项的 /// 注释会写入头文件。
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use crate::bundle::{hide_dock_icon, is_app_bundle, is_dock_icon_hidden, ui_element};
+use crate::config;
+use crate::session;
+use crate::signing;
+use crate::state::{self, ManagedApp};
+use crate::translocation;
+
+/// 成功。
+pub const DODGER_OK: i32 = 0;
+/// 参数为空指针或不是合法的 UTF-8。
+pub const DODGER_ERR_INVALID_ARGUMENT: i32 = -1;
+/// 操作失败，详见 `dodger_last_error`。
+pub const DODGER_ERR_FAILED: i32 = -2;
+/// Info.plist 疑似由其他用户或工具修改，未传入 force 时跳过。
+pub const DODGER_ERR_EXTERNAL_CHANGE: i32 = -3;
+/// 内部错误（Rust 代码发生 panic）。
+pub const DODGER_ERR_PANIC: i32 = -4;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = CString::new(message.into().replace('\0', " ")).ok();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+fn guard(f: impl FnOnce() -> i32) -> i32 {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(code) => code,
+        Err(_) => {
+            set_last_error("内部错误");
+            DODGER_ERR_PANIC
+        }
+    }
+}
+
+unsafe fn path_arg(path: *const c_char) -> Option<PathBuf> {
+    if path.is_null() {
+        set_last_error("路径为空指针");
+        return None;
+    }
+    match unsafe { CStr::from_ptr(path) }.to_str() {
+        Ok(path) => config::normalize_input_path(path)
+            .map_err(set_last_error)
+            .ok(),
+        Err(_) => {
+            set_last_error("路径不是合法的 UTF-8");
+            None
+        }
+    }
+}
+
+// 与命令行 hide 相同：记录签名和原始 LSUIElement 后隐藏并加入管理列表；已在列表中时重新隐藏。
+pub(crate) fn hide_app(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let resolved = translocation::resolve(path)?;
+    let path = resolved.as_path();
+    if !is_app_bundle(path) {
+        return Err("路径不是 .app 包".into());
+    }
+    let mut apps = state::load_apps()?;
+    match apps.iter().position(|app| app.path == path) {
+        Some(index) => {
+            hide_dock_icon(path)?;
+            apps[index].record_checksum();
+        }
+        None => {
+            let signing = signing::read_signing_info(path);
+            let original = ui_element(path).ok().flatten();
+            hide_dock_icon(path)?;
+            let mut app = ManagedApp::new(path.to_path_buf());
+            app.signing = Some(signing);
+            app.record_hide(original);
+            app.record_checksum();
+            app.apply_hide_options();
+            apps.push(app);
+        }
+    }
+    state::save_apps(&apps)
+}
+
+pub(crate) enum RestoreError {
+    NotManaged,
+    External(String),
+    Failed(Box<dyn std::error::Error>),
+}
+
+impl std::fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RestoreError::NotManaged => write!(f, "应用不在管理列表中"),
+            RestoreError::External(reason) => {
+                write!(f, "可能由其他用户或工具管理（{}）", reason)
+            }
+            RestoreError::Failed(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+// 恢复 Dock 图标并移出管理列表。
+pub(crate) fn restore_app(path: &Path, force: bool) -> Result<(), RestoreError> {
+    let mut apps = state::load_apps().map_err(RestoreError::Failed)?;
+    let index = apps
+        .iter()
+        .position(|app| app.path == path)
+        .ok_or(RestoreError::NotManaged)?;
+    if !force && let Some(reason) = apps[index].external_change() {
+        return Err(RestoreError::External(reason));
+    }
+    apps[index]
+        .restore_dock_icon()
+        .map_err(RestoreError::Failed)?;
+    let app = apps.remove(index);
+    if app.session
+        && let Err(err) = session::write_journal(&apps)
+    {
+        eprintln!("[Session] 更新会话日志失败：{}", err);
+    }
+    state::save_apps(&apps).map_err(RestoreError::Failed)
+}
+
+/// 隐藏 `path` 指向的 .app 的 Dock 图标，并加入管理列表。
+///
+/// # Safety
+///
+/// `path` 必须是以 NUL 结尾的有效字符串指针。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dodger_hide(path: *const c_char) -> i32 {
+    guard(|| {
+        let Some(path) = (unsafe { path_arg(path) }) else {
+            return DODGER_ERR_INVALID_ARGUMENT;
+        };
+        match hide_app(&path) {
+            Ok(_) => DODGER_OK,
+            Err(err) => {
+                set_last_error(err.to_string());
+                DODGER_ERR_FAILED
+            }
+        }
+    })
+}
+
+/// 恢复 `path` 的 Dock 图标并移出管理列表。`force` 非 0 时同时处理疑似由其他工具管理的应用。
+///
+/// # Safety
+///
+/// `path` 必须是以 NUL 结尾的有效字符串指针。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dodger_restore(path: *const c_char, force: i32) -> i32 {
+    guard(|| {
+        let Some(path) = (unsafe { path_arg(path) }) else {
+            return DODGER_ERR_INVALID_ARGUMENT;
+        };
+        match restore_app(&path, force != 0) {
+            Ok(_) => DODGER_OK,
+            Err(err) => {
+                set_last_error(err.to_string());
+                match err {
+                    RestoreError::External(_) => DODGER_ERR_EXTERNAL_CHANGE,
+                    _ => DODGER_ERR_FAILED,
+                }
+            }
+        }
+    })
+}
+
+/// 以 JSON 数组写出管理列表（每项包含 path、tags、hidden、session），
+/// 结果通过 `out_json` 返回，使用后需调用 `dodger_string_free` 释放。
+///
+/// # Safety
+///
+/// `out_json` 必须是有效的可写指针。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dodger_list(out_json: *mut *mut c_char) -> i32 {
+    guard(|| {
+        if out_json.is_null() {
+            set_last_error("out_json 为空指针");
+            return DODGER_ERR_INVALID_ARGUMENT;
+        }
+        let apps = match state::load_apps() {
+            Ok(apps) => apps,
+            Err(err) => {
+                set_last_error(format!("读取应用列表失败：{}", err));
+                return DODGER_ERR_FAILED;
+            }
+        };
+        let listed: Vec<_> = apps
+            .iter()
+            .map(|app| {
+                serde_json::json!({
+                    "path": app.path,
+                    "tags": app.tags,
+                    "hidden": is_dock_icon_hidden(&app.path).unwrap_or(false),
+                    "session": app.session,
+                })
+            })
+            .collect();
+        let json = serde_json::Value::from(listed).to_string();
+        // serde_json 会转义字符串中的 NUL，这里不会失败。
+        let json = CString::new(json).unwrap_or_default();
+        unsafe { *out_json = json.into_raw() };
+        DODGER_OK
+    })
+}
+
+/// 释放 `dodger_list` 返回的字符串；传入空指针时不做任何事。
+///
+/// # Safety
+///
+/// `s` 必须是本库返回且尚未释放的指针。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dodger_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// 返回当前线程上一次调用失败的原因，没有错误时返回空指针。
+/// 字符串由本库持有，在同一线程下一次调用前有效，无需释放。
+#[unsafe(no_mangle)]
+pub extern "C" fn dodger_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
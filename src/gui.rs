@@ -0,0 +1,3006 @@
+mod assets;
+mod drop_target;
+mod hotkeys;
+mod quick_launch;
+mod status_item;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use osx_dock_dodger_rs::agent::{self, ProfileSchedule};
+use osx_dock_dodger_rs::auth;
+use osx_dock_dodger_rs::backup;
+use osx_dock_dodger_rs::bundle::{
+    Privilege, bundle_identifier, bundle_name, bundle_short_version, bundle_size, bundle_version,
+    create_minimal_info_plist, hide_dock_icon, is_app_bundle, is_dock_icon_hidden,
+    required_privilege, restore_dock_icon, ui_element, ui_element_value,
+    visible_login_item_helpers,
+};
+use osx_dock_dodger_rs::config;
+use osx_dock_dodger_rs::crash;
+use osx_dock_dodger_rs::dock;
+use osx_dock_dodger_rs::error::ErrorKind;
+use osx_dock_dodger_rs::events;
+use osx_dock_dodger_rs::history::{self, ExportFormat, HistoryFilter};
+use osx_dock_dodger_rs::i18n;
+use osx_dock_dodger_rs::import;
+use osx_dock_dodger_rs::installed::{self, InstalledApp};
+use osx_dock_dodger_rs::migrate;
+use osx_dock_dodger_rs::notify;
+use osx_dock_dodger_rs::pause;
+use osx_dock_dodger_rs::relaunch;
+use osx_dock_dodger_rs::report;
+use osx_dock_dodger_rs::rules::{self, RuleAction};
+use osx_dock_dodger_rs::scan::{DEFAULT_SCAN_DEPTH, ScanScope};
+use osx_dock_dodger_rs::session;
+use osx_dock_dodger_rs::shortcuts;
+use osx_dock_dodger_rs::shutdown;
+use osx_dock_dodger_rs::signing::{self, SigningInfo};
+use osx_dock_dodger_rs::state::{
+    self, Autosave, ColorLabel, HideRecord, ManagedApp, RelaunchOptions,
+};
+use osx_dock_dodger_rs::state_store;
+use osx_dock_dodger_rs::translocation;
+use osx_dock_dodger_rs::update_wave;
+use osx_dock_dodger_rs::volume;
+use osx_dock_dodger_rs::wake;
+use osx_dock_dodger_rs::watcher::{DirWatcher, WatchEvent};
+use osx_dock_dodger_rs::webhook;
+use serde::{Deserialize, Serialize};
+use tao::event::{Event, StartCause, WindowEvent};
+use tao::event_loop::{ControlFlow, EventLoopBuilder, EventLoopProxy};
+use tao::window::{Window, WindowBuilder, WindowId};
+use wry::http::Request;
+use wry::{DragDropEvent, WebView, WebViewBuilder};
+
+use drop_target::DropTarget;
+use hotkeys::GlobalHotKeys;
+use quick_launch::QuickLauncher;
+use status_item::{MenuCommand, StatusItem};
+
+const AUTOSAVE_DELAY: Duration = Duration::from_millis(500);
+const ISSUES_URL: &str = "https://github.com/fmnisme/osx-dock-dodger-rs/issues/new";
+const HISTORY_SEARCH_LIMIT: usize = 200;
+// 启动时每校验这么多个应用就把结果送到界面
+const STREAM_CHUNK: usize = 20;
+
+#[derive(Debug)]
+enum UserEvent {
+    Add(PathBuf),
+    DropText(String),
+    PasteText(String),
+    Restore(PathBuf, bool),
+    Reapply(PathBuf, bool),
+    Acknowledge(PathBuf),
+    Import(PathBuf),
+    ResolveImport(Vec<PathBuf>),
+    Rescan,
+    AppInstalled(PathBuf),
+    AppRemoved(PathBuf),
+    DismissInbox(PathBuf),
+    ListBackups,
+    RestoreBackup(PathBuf, u64),
+    TogglePause,
+    AuthFailed(String),
+    ToggleSession(PathBuf),
+    SetLabel(PathBuf, Option<ColorLabel>),
+    SetRelaunch(PathBuf, Option<RelaunchOptions>),
+    HideHelpers(PathBuf),
+    CreatePlist(PathBuf),
+    Reorder(Vec<PathBuf>),
+    PreviewBatch(Vec<PathBuf>),
+    ConfirmBatch(Vec<PathBuf>),
+    RetryFailed,
+    BatchProgress(PathBuf, Vec<String>, Box<BatchStep>),
+    BatchFinished,
+    OpenPrivacySettings,
+    OpenAutomationSettings,
+    Details(PathBuf),
+    Reveal(PathBuf),
+    CopyPath(PathBuf),
+    Launch(PathBuf),
+    HotKey(u32),
+    QuickLaunchHide,
+    ToggleDropTarget,
+    ShowWindow,
+    ListInstalled,
+    InstalledApps(Vec<InstalledApp>),
+    SearchApps(String),
+    SearchResults(String, Vec<InstalledApp>),
+    AddBundleId(String),
+    BundleIdNotFound(String),
+    #[cfg(not(feature = "minimal-ui"))]
+    Stats,
+    ExportHistory(HistoryFilter, ExportFormat),
+    SearchHistory(HistoryFilter),
+    ExportProfile(Vec<PathBuf>),
+    ListSchedules,
+    AddSchedule(ProfileSchedule),
+    RemoveSchedule(String),
+    ScheduleFailed(String),
+    CreateReport(bool),
+    ViewCrash(PathBuf),
+    CrashSeen(PathBuf),
+    ReloadConfig,
+    StateChanged,
+    Woke,
+    VolumeMounted(PathBuf),
+    SaveShortcuts(BTreeMap<String, String>),
+    SetLanguage(Option<String>),
+    SetWindowOption(String, bool),
+    Quit,
+    Refresh,
+    Loaded(Box<Startup>),
+    Verified(Vec<AppView>),
+}
+
+// 启动时在后台线程读取的内容，窗口先显示占位列表，读完后交给事件循环。
+#[derive(Debug)]
+struct Startup {
+    apps: Vec<ManagedApp>,
+    // 状态文件损坏或无法读取时的提示，以及是否提供“重新扫描”
+    notice: Option<(String, bool)>,
+    // 恢复了上次运行遗留的仅本次运行应用，列表需要保存
+    session_changed: bool,
+    // 上次运行崩溃时的报告，以及恢复成功的应用数
+    crash: Option<(PathBuf, usize)>,
+    // 从磁盘读到的列表（恢复会话之前）及读取前的时间戳，交给 Autosave 用来合并其它进程的改动
+    base: Vec<ManagedApp>,
+    stamp: Option<state_store::Stamp>,
+}
+
+#[derive(Deserialize)]
+struct IpcRequest {
+    cmd: String,
+    #[serde(default)]
+    path: String,
+    #[serde(default)]
+    paths: Vec<String>,
+    #[serde(default)]
+    query: String,
+    #[serde(default)]
+    since: String,
+    #[serde(default)]
+    until: String,
+    #[serde(default)]
+    format: String,
+    #[serde(default)]
+    days: String,
+    #[serde(default)]
+    time: String,
+    #[serde(default)]
+    shortcuts: BTreeMap<String, String>,
+    #[serde(default)]
+    created_at: u64,
+    #[serde(default)]
+    relaunch: Option<RelaunchOptions>,
+    #[serde(default)]
+    enabled: bool,
+}
+
+struct ImportConflict {
+    path: PathBuf,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AppView {
+    path: String,
+    hidden: bool,
+    modified: bool,
+    session: bool,
+    label: Option<ColorLabel>,
+    privilege: Privilege,
+    external: Option<String>,
+    // 所在的外接卷未挂载，读不到 Info.plist，不算隐藏失效
+    offline: bool,
+    // 按当前语言格式化，例如 "3 天前隐藏"
+    hidden_ago: Option<String>,
+    // 启动时的占位条目，校验结果稍后送达
+    pending: bool,
+}
+
+impl AppView {
+    fn new(app: &ManagedApp) -> Self {
+        let matches = app.checksum_matches();
+        let hidden = matches == Some(true) || is_dock_icon_hidden(&app.path).unwrap_or(false);
+        AppView {
+            path: app.path.display().to_string(),
+            hidden,
+            modified: matches == Some(false),
+            session: app.session,
+            label: app.label,
+            privilege: required_privilege(&app.path),
+            external: app.external_change(),
+            offline: volume::is_offline(&app.path),
+            hidden_ago: app.hide.as_ref().map(|hide| {
+                let catalog = i18n::current();
+                catalog.tr_args("{}隐藏", &[catalog.relative(hide.hidden_at)])
+            }),
+            pending: false,
+        }
+    }
+
+    // 只用列表中记录的内容，不读取 Info.plist。
+    fn pending(app: &ManagedApp) -> Self {
+        AppView {
+            path: app.path.display().to_string(),
+            hidden: true,
+            modified: false,
+            session: app.session,
+            label: app.label,
+            privilege: Privilege::User,
+            external: None,
+            offline: false,
+            hidden_ago: None,
+            pending: true,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AppDetails {
+    path: String,
+    name: String,
+    bundle_id: Option<String>,
+    version: Option<String>,
+    build: Option<String>,
+    size: u64,
+    size_text: String,
+    hide_method: &'static str,
+    hide: Option<HideRecord>,
+    hidden_at_text: Option<String>,
+    current_ui_element: Option<String>,
+    hidden: bool,
+    session: bool,
+    privilege: Privilege,
+    tags: Vec<String>,
+    label: Option<ColorLabel>,
+    relaunch: Option<RelaunchOptions>,
+    helpers: Vec<String>,
+    visible_helpers: Vec<String>,
+    signing: SigningInfo,
+    signing_captured: bool,
+}
+
+impl AppDetails {
+    fn new(app: &ManagedApp) -> Self {
+        let catalog = i18n::current();
+        let size = bundle_size(&app.path);
+        AppDetails {
+            path: app.path.display().to_string(),
+            name: bundle_name(&app.path).unwrap_or_else(|| app_display_name(&app.path)),
+            bundle_id: bundle_identifier(&app.path),
+            version: bundle_short_version(&app.path),
+            build: bundle_version(&app.path),
+            size,
+            size_text: catalog.size(size),
+            hide_method: "Info.plist 中的 LSUIElement",
+            hide: app.hide.clone(),
+            hidden_at_text: app.hide.as_ref().map(|hide| {
+                catalog.tr_args(
+                    "{}（{}）",
+                    &[
+                        catalog.date_time(hide.hidden_at),
+                        catalog.relative(hide.hidden_at),
+                    ],
+                )
+            }),
+            current_ui_element: ui_element_value(&app.path).ok().flatten(),
+            hidden: is_dock_icon_hidden(&app.path).unwrap_or(false),
+            session: app.session,
+            privilege: required_privilege(&app.path),
+            tags: app.tags.clone(),
+            label: app.label,
+            relaunch: app.relaunch.clone(),
+            helpers: app
+                .helpers
+                .iter()
+                .map(|helper| helper.path.display().to_string())
+                .collect(),
+            visible_helpers: visible_login_item_helpers(&app.path)
+                .iter()
+                .map(|helper| helper.display().to_string())
+                .collect(),
+            signing: app
+                .signing
+                .clone()
+                .unwrap_or_else(|| signing::read_signing_info(&app.path)),
+            signing_captured: app.signing.is_some(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BatchItem {
+    path: String,
+    // managed（已隐藏）、drifted（在列表中但图标已恢复）、unmanaged 或 hidden_elsewhere（未管理但已设置 LSUIElement）
+    state: &'static str,
+    // hide、reapply 或 none
+    action: &'static str,
+    warnings: Vec<String>,
+    selected: bool,
+}
+
+impl BatchItem {
+    fn new(path: &Path, apps: &[ManagedApp]) -> Self {
+        let managed = apps.iter().find(|app| app.path == path);
+        let hidden = is_dock_icon_hidden(path);
+        let mut warnings = Vec::new();
+        let (state, action) = match (managed, &hidden) {
+            (Some(_), Ok(true)) => ("managed", "none"),
+            (Some(_), _) => ("drifted", "reapply"),
+            (None, Ok(true)) => ("hidden_elsewhere", "hide"),
+            (None, _) => ("unmanaged", "hide"),
+        };
+        if let Err(err) = &hidden {
+            warnings.push(format!("无法读取 Info.plist：{}", err));
+        }
+        if state == "hidden_elsewhere" {
+            warnings.push("LSUIElement 已由应用自身或其他工具设置".into());
+        }
+        let external = managed.and_then(ManagedApp::external_change);
+        if let Some(reason) = &external {
+            warnings.push(format!("可能由其他用户或工具管理（{}）", reason));
+        }
+        if required_privilege(path) == Privilege::Admin {
+            warnings.push("需要管理员授权".into());
+        }
+        BatchItem {
+            path: path.display().to_string(),
+            state,
+            action,
+            selected: action != "none" && hidden.is_ok() && external.is_none(),
+            warnings,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorView {
+    path: String,
+    message: String,
+    kind: ErrorKind,
+    explanation: &'static str,
+    remedy: &'static str,
+    // 处理建议对应的 IPC 命令，无法自动处理时为空
+    action: Option<&'static str>,
+    action_label: Option<&'static str>,
+}
+
+impl ErrorView {
+    fn new(path: &Path, err: &(dyn std::error::Error + 'static), retry_cmd: &'static str) -> Self {
+        let kind = ErrorKind::classify(err);
+        let (action, action_label) = match kind {
+            ErrorKind::PermissionDenied => (Some("openPrivacySettings"), Some("打开系统设置")),
+            ErrorKind::AuthorizationFailed => (Some(retry_cmd), Some("重试")),
+            ErrorKind::NotFound => (Some("rescan"), Some("重新扫描")),
+            ErrorKind::MissingPlist => (Some("createPlist"), Some("创建 Info.plist")),
+            ErrorKind::InvalidPlist => (Some("listBackups"), Some("查看备份")),
+            ErrorKind::AutomationDenied => (Some("openAutomationSettings"), Some("打开系统设置")),
+            ErrorKind::InvalidBundle | ErrorKind::ReadOnly | ErrorKind::Other => (None, None),
+        };
+        ErrorView {
+            path: path.display().to_string(),
+            message: err.to_string(),
+            kind,
+            explanation: kind.explanation(),
+            remedy: kind.remedy(),
+            action,
+            action_label,
+        }
+    }
+}
+
+fn show_error(webview: &WebView, view: &ErrorView) {
+    let _ = webview.evaluate_script(&format!(
+        "showError({});",
+        serde_json::to_string(view).unwrap()
+    ));
+}
+
+enum Outcome {
+    Done,
+    Skipped(String),
+    Failed(ErrorView),
+}
+
+// 工作线程中隐藏一项的结果，发回事件循环后再合并进列表。
+#[derive(Debug)]
+enum BatchStep {
+    Added(ManagedApp),
+    Rehidden(ManagedApp),
+    Skipped(String),
+    Failed(ErrorView),
+}
+
+#[derive(Serialize)]
+struct ReportEntry {
+    path: String,
+    reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorView>,
+}
+
+#[derive(Default, Serialize)]
+struct BatchReport {
+    succeeded: Vec<String>,
+    skipped: Vec<ReportEntry>,
+    failed: Vec<ReportEntry>,
+    // 失败项及其标签，供“重试失败项”使用
+    #[serde(skip)]
+    retry: Vec<(PathBuf, Vec<String>)>,
+}
+
+impl BatchReport {
+    fn push(&mut self, path: PathBuf, tags: Vec<String>, outcome: Outcome) {
+        let display = path.display().to_string();
+        match outcome {
+            Outcome::Done => self.succeeded.push(display),
+            Outcome::Skipped(reason) => self.skipped.push(ReportEntry {
+                path: display,
+                reason,
+                error: None,
+            }),
+            Outcome::Failed(error) => {
+                self.failed.push(ReportEntry {
+                    path: display,
+                    reason: error.message.clone(),
+                    error: Some(error),
+                });
+                self.retry.push((path, tags));
+            }
+        }
+    }
+}
+
+// 全部成功时只显示提示条，有跳过或失败时弹出报告。
+fn finish_batch(
+    webview: &WebView,
+    report: &mut BatchReport,
+    retry: &mut Vec<(PathBuf, Vec<String>)>,
+) {
+    let report = std::mem::take(report);
+    println!(
+        "[Batch] 批量操作完成：成功 {}，跳过 {}，失败 {}",
+        report.succeeded.len(),
+        report.skipped.len(),
+        report.failed.len()
+    );
+    if !report.skipped.is_empty() || !report.failed.is_empty() {
+        let _ = webview.evaluate_script(&format!(
+            "showBatchReport({});",
+            serde_json::to_string(&report).unwrap()
+        ));
+    } else if !report.succeeded.is_empty() {
+        let _ = webview.evaluate_script(&format!(
+            "showNotice({});",
+            serde_json::to_string(&format!(
+                "已隐藏 {} 个应用的 Dock 图标",
+                report.succeeded.len()
+            ))
+            .unwrap()
+        ));
+    }
+    *retry = report.retry;
+}
+
+// 不在列表中的应用：记下签名和原始值后隐藏，并按配置处理启动台与 Dock 固定项。
+fn hide_app(path: PathBuf, tags: Vec<String>) -> BatchStep {
+    let path_display = path.display().to_string();
+    if !is_app_bundle(&path) {
+        println!("[Add] 路径不是 .app 包，忽略：{}", path_display);
+        return BatchStep::Skipped("不是 .app 包".into());
+    }
+
+    let signing = signing::read_signing_info(&path);
+    let original = ui_element(&path).ok().flatten();
+    match hide_dock_icon(&path) {
+        Ok(_) => {
+            println!("[Add] 成功隐藏 Dock 图标：{}", path_display);
+            let mut app = ManagedApp::new(path);
+            app.tags = tags;
+            app.signing = Some(signing);
+            app.record_hide(original);
+            app.record_checksum();
+            app.apply_hide_options();
+            BatchStep::Added(app)
+        }
+        Err(err) => {
+            println!("[Add] 隐藏 Dock 图标失败：{}，错误：{}", path_display, err);
+            BatchStep::Failed(ErrorView::new(&path, &*err, "add"))
+        }
+    }
+}
+
+// 已在列表中的应用重新隐藏，带标签时同时替换标签。导入时选择覆盖的与 import --strategy
+// overwrite 一致：总是以导入的标签替换，并按当前设置重新应用隐藏选项。
+fn rehide_app(mut app: ManagedApp, tags: &[String], overwrite: bool) -> BatchStep {
+    match app.hide_dock_icon() {
+        Ok(_) => {
+            println!("[Batch] 已重新隐藏 Dock 图标：{}", app.path.display());
+            if overwrite || !tags.is_empty() {
+                app.tags = tags.to_vec();
+            }
+            app.record_checksum();
+            if overwrite {
+                app.apply_hide_options();
+            }
+            BatchStep::Rehidden(app)
+        }
+        Err(err) => {
+            println!(
+                "[Batch] 重新隐藏 Dock 图标失败：{}，错误：{}",
+                app.path.display(),
+                err
+            );
+            BatchStep::Failed(ErrorView::new(&app.path, &*err, "reapply"))
+        }
+    }
+}
+
+// 每一项附上列表中已有记录的副本，没有时按新应用隐藏。
+fn batch_items(
+    items: Vec<(PathBuf, Vec<String>)>,
+    apps: &[ManagedApp],
+) -> Vec<(PathBuf, Vec<String>, Option<ManagedApp>)> {
+    items
+        .into_iter()
+        .map(|(path, tags)| {
+            let managed = apps.iter().find(|app| app.path == path).cloned();
+            (path, tags, managed)
+        })
+        .collect()
+}
+
+// 批量隐藏在工作线程中进行：写入按 write_queue 的限速等待，放在事件循环中会让窗口失去响应。
+// 每完成一项通过 BatchProgress 把结果送回事件循环，整批结束后发送 BatchFinished。
+fn spawn_batch(
+    items: Vec<(PathBuf, Vec<String>, Option<ManagedApp>)>,
+    overwrite: bool,
+    proxy: &EventLoopProxy<UserEvent>,
+) {
+    let proxy = proxy.clone();
+    thread::spawn(move || {
+        let dock_batch = dock::batch();
+        for (path, tags, managed) in items {
+            let step = match managed {
+                Some(app) => rehide_app(app, &tags, overwrite),
+                None => hide_app(path.clone(), tags.clone()),
+            };
+            if proxy
+                .send_event(UserEvent::BatchProgress(path, tags, Box::new(step)))
+                .is_err()
+            {
+                return;
+            }
+        }
+        drop(dock_batch);
+        let _ = proxy.send_event(UserEvent::BatchFinished);
+    });
+}
+
+// 验证对话框会阻塞，放到后台线程；通过后在宽限期内重新发送同一事件即可执行。
+fn confirm_then(reason: &'static str, event: UserEvent, proxy: &EventLoopProxy<UserEvent>) {
+    let proxy = proxy.clone();
+    thread::spawn(move || {
+        let event = match auth::confirm(reason) {
+            Ok(()) => event,
+            Err(err) => UserEvent::AuthFailed(err.to_string()),
+        };
+        let _ = proxy.send_event(event);
+    });
+}
+
+fn is_profile_file(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|data| import::is_profile(&data))
+}
+
+// 拖入的文件夹按扫描目录相同的深度和排除规则查找其中的应用。
+fn folder_apps(dir: &Path) -> Vec<PathBuf> {
+    ScanScope::folder(dir, DEFAULT_SCAN_DEPTH).find_app_bundles()
+}
+
+fn show_batch(webview: &WebView, paths: &[PathBuf], apps: &[ManagedApp]) {
+    let items: Vec<BatchItem> = paths
+        .iter()
+        .map(|path| BatchItem::new(path, apps))
+        .collect();
+    let _ = webview.evaluate_script(&format!(
+        "showBatch({});",
+        serde_json::to_string(&items).unwrap()
+    ));
+}
+
+#[derive(Serialize)]
+struct PickerEntry<'a> {
+    #[serde(flatten)]
+    app: &'a InstalledApp,
+    managed: bool,
+}
+
+fn picker_entries<'a>(installed: &'a [InstalledApp], apps: &[ManagedApp]) -> Vec<PickerEntry<'a>> {
+    installed
+        .iter()
+        .map(|app| PickerEntry {
+            app,
+            managed: apps.iter().any(|managed| managed.path == app.path),
+        })
+        .collect()
+}
+
+fn show_picker(webview: &WebView, installed: &[InstalledApp], apps: &[ManagedApp]) {
+    let _ = webview.evaluate_script(&format!(
+        "showPicker({});",
+        serde_json::to_string(&picker_entries(installed, apps)).unwrap()
+    ));
+}
+
+fn js_add_app(app: &ManagedApp) -> String {
+    format!(
+        "addApp({});",
+        serde_json::to_string(&AppView::new(app)).unwrap()
+    )
+}
+
+fn rebuild_list(webview: &WebView, apps: &[ManagedApp]) {
+    let mut script = String::from("document.getElementById('list').innerHTML='';");
+    for app in apps {
+        script.push_str(&js_add_app(app));
+    }
+    script.push_str("toggleEmptyState();");
+    let _ = webview.evaluate_script(&script);
+}
+
+// 启动时先用列表中的记录显示占位条目，再在后台逐批校验 Info.plist，结果通过 Verified 送回。
+fn stream_list(webview: &WebView, apps: &[ManagedApp], proxy: &EventLoopProxy<UserEvent>) {
+    let mut script = String::from("document.getElementById('list').innerHTML='';");
+    for app in apps {
+        script.push_str(&format!(
+            "addApp({});",
+            serde_json::to_string(&AppView::pending(app)).unwrap()
+        ));
+    }
+    script.push_str("toggleEmptyState();");
+    let _ = webview.evaluate_script(&script);
+    let apps = apps.to_vec();
+    let proxy = proxy.clone();
+    thread::spawn(move || {
+        for chunk in apps.chunks(STREAM_CHUNK) {
+            let views = chunk.iter().map(AppView::new).collect();
+            if proxy.send_event(UserEvent::Verified(views)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+fn load_startup() -> Startup {
+    let mut notice = None;
+    let stamp = state_store::stamp();
+    let mut apps: Vec<ManagedApp> = match state::load_state() {
+        Ok(loaded) => {
+            if let Some(recovery) = loaded.recovery {
+                println!("[State] 状态文件损坏：{}", recovery.reason);
+                let message = match &recovery.backup_path {
+                    Some(backup_path) => format!(
+                        "应用列表文件已损坏，原文件已备份到 {}，从中恢复了 {} 项。可以重新扫描以找回其它已隐藏的应用。",
+                        backup_path.display(),
+                        recovery.salvaged
+                    ),
+                    None => format!(
+                        "应用列表文件已损坏，只读模式下未改动原文件，从中恢复了 {} 项。",
+                        recovery.salvaged
+                    ),
+                };
+                notice = Some((message, true));
+            }
+            loaded.apps
+        }
+        // 较新版本写入的文件：本次运行改为只读，避免空列表被自动保存覆盖原文件。
+        Err(err)
+            if err
+                .to_string()
+                .starts_with(migrate::UNSUPPORTED_VERSION_ERROR) =>
+        {
+            println!("[State] 读取应用列表失败：{}", err);
+            config::force_read_only();
+            notice = Some((
+                format!("{}。本次以只读模式运行，不会修改应用列表或任何应用。", err),
+                false,
+            ));
+            Vec::new()
+        }
+        // 其他读取失败（如钥匙串中没有密钥、无法解密）同样不能当作空列表保存。
+        Err(err) => {
+            println!("[State] 读取应用列表失败：{}", err);
+            config::force_read_only();
+            notice = Some((
+                format!(
+                    "读取应用列表失败：{}。为避免覆盖原有记录，本次以只读模式运行；解决问题后请重新打开 Dock Dodger。",
+                    err
+                ),
+                false,
+            ));
+            Vec::new()
+        }
+    };
+    let base = apps.clone();
+    let mut session_changed = false;
+    // 只读模式（包括列表无法读取而改为只读时）不恢复上次运行遗留的应用，留到下次处理。
+    let stale = if config::read_only() {
+        Ok(Vec::new())
+    } else {
+        session::recover_stale_session(&mut apps)
+    };
+    let recovered = match stale {
+        Ok(results) if !results.is_empty() => {
+            for result in &results {
+                match &result.error {
+                    None => println!(
+                        "[Session] 已恢复上次运行遗留的应用：{}",
+                        result.path.display()
+                    ),
+                    Some(err) => println!(
+                        "[Session] 恢复上次运行遗留的应用失败：{}，错误：{}",
+                        result.path.display(),
+                        err
+                    ),
+                }
+            }
+            session_changed = true;
+            results
+                .iter()
+                .filter(|result| result.error.is_none())
+                .count()
+        }
+        Ok(_) => 0,
+        Err(err) => {
+            println!("[Session] 检查上次运行的记录失败：{}", err);
+            0
+        }
+    };
+    Startup {
+        apps,
+        notice,
+        session_changed,
+        // 上次运行崩溃时，窗口就绪后提示查看崩溃报告。
+        crash: crash::unseen_crash_report().map(|report| (report, recovered)),
+        base,
+        stamp,
+    }
+}
+
+fn show_startup_notices(
+    webview: &WebView,
+    crash_notice: &mut Option<(PathBuf, usize)>,
+    startup_notice: &mut Option<(String, bool)>,
+) {
+    if let Some((report, recovered)) = crash_notice.take() {
+        println!("[Crash] 上次运行异常退出，崩溃报告：{}", report.display());
+        let _ = webview.evaluate_script(&format!(
+            "showCrash({}, {});",
+            serde_json::to_string(&report.display().to_string()).unwrap(),
+            recovered
+        ));
+    }
+    match startup_notice.take() {
+        Some((notice, true)) => {
+            let _ = webview.evaluate_script(&format!(
+                "showNotice({}, {}, 'rescan');",
+                serde_json::to_string(&notice).unwrap(),
+                serde_json::to_string("重新扫描").unwrap()
+            ));
+        }
+        Some((notice, false)) => {
+            let _ = webview.evaluate_script(&format!(
+                "showNotice({});",
+                serde_json::to_string(&notice).unwrap()
+            ));
+        }
+        None => {}
+    }
+}
+
+fn show_inbox(webview: &WebView, inbox: &[PathBuf]) {
+    let paths: Vec<String> = inbox
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    let _ = webview.evaluate_script(&format!(
+        "showInbox({});",
+        serde_json::to_string(&paths).unwrap()
+    ));
+}
+
+// 把生效的快捷键交给界面，并设置菜单栏中暂停项的快捷键和全局快捷键；配置有误的条目不生效。
+fn apply_shortcuts(webview: &WebView, status_item: &StatusItem, hotkeys: &mut GlobalHotKeys) {
+    let config = config::load_config().unwrap_or_default();
+    let (list, errors) = shortcuts::effective(&config);
+    for err in &errors {
+        println!("[Shortcuts] 配置中的快捷键无效：{}", err);
+    }
+    if let Some(shortcut) = list
+        .iter()
+        .find(|shortcut| shortcut.action == "toggle_pause")
+    {
+        status_item.set_pause_accelerator(&shortcut.accelerator);
+    }
+    for shortcut in &list {
+        if shortcuts::GLOBAL_ACTIONS.contains(&shortcut.action) {
+            hotkeys.set(shortcut.action, &shortcut.accelerator);
+        }
+    }
+    let _ = webview.evaluate_script(&format!(
+        "applyShortcuts({});",
+        serde_json::to_string(&list).unwrap()
+    ));
+}
+
+// macOS 没有系统级的动态字体，最接近的是“外观”中的边栏尺寸（小/中/大），系统表格的行高也随之变化；
+// 配置中的 text_scale 优先。
+fn text_scale() -> f64 {
+    if let Some(scale) = config::load_config()
+        .ok()
+        .and_then(|config| config.text_scale)
+    {
+        return scale.clamp(0.75, 2.0);
+    }
+    let size = Command::new("defaults")
+        .args(["read", "-g", "NSTableViewDefaultSizeMode"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .parse::<u8>()
+                .ok()
+        });
+    match size {
+        Some(1) => 0.9,
+        Some(3) => 1.15,
+        _ => 1.0,
+    }
+}
+
+// 只在比例变化时缩放，避免每次切换窗口都重新排版。
+fn apply_text_scale(webview: &WebView, current: &mut f64) {
+    let scale = text_scale();
+    if scale == *current {
+        return;
+    }
+    match webview.zoom(scale) {
+        Ok(()) => {
+            println!("[Window] 内容缩放比例改为 {}", scale);
+            *current = scale;
+        }
+        Err(err) => println!("[Window] 设置缩放比例失败：{}", err),
+    }
+}
+
+// 窗口置顶与在所有桌面显示（tao 在 macOS 上设置 NSWindow 的层级和 collectionBehavior），
+// 并同步界面上的开关。
+fn apply_window_options(window: &Window, webview: &WebView) {
+    let config = config::load_config().unwrap_or_default();
+    window.set_always_on_top(config.always_on_top);
+    window.set_visible_on_all_workspaces(config.all_spaces);
+    let _ = webview.evaluate_script(&format!(
+        "setWindowOptions({});",
+        serde_json::json!({
+            "always_on_top": config.always_on_top,
+            "all_spaces": config.all_spaces,
+        })
+    ));
+}
+
+// 把当前语言的译文和可选语言交给界面，界面按原文替换所有文字。
+fn apply_locale(webview: &WebView) {
+    let config = config::load_config().unwrap_or_default();
+    let catalog = i18n::catalog(&i18n::active_locale(&config));
+    let _ = webview.evaluate_script(&format!(
+        "applyLocale({});",
+        serde_json::json!({
+            "locale": catalog.locale,
+            "direction": catalog.direction,
+            "language": config.language,
+            "locales": i18n::available(),
+            "strings": catalog.strings,
+        })
+    ));
+}
+
+#[derive(Serialize)]
+struct BackupView {
+    #[serde(flatten)]
+    entry: backup::BackupEntry,
+    created_text: String,
+    size_text: String,
+}
+
+#[cfg(not(feature = "minimal-ui"))]
+#[derive(Serialize)]
+struct StatsView {
+    #[serde(flatten)]
+    stats: history::Stats,
+    // 以下为按当前语言格式化好的文字，与 stats 中的字段一一对应
+    totals: BTreeMap<&'static str, String>,
+    toggle_counts: Vec<String>,
+    week_labels: Vec<String>,
+    week_titles: Vec<String>,
+}
+
+#[cfg(not(feature = "minimal-ui"))]
+impl StatsView {
+    fn new(stats: history::Stats) -> Self {
+        let catalog = i18n::current();
+        let number = |value: usize| catalog.number(value as u64);
+        let totals = BTreeMap::from([
+            ("hidden_now", number(stats.hidden_now)),
+            ("total_operations", number(stats.total_operations)),
+            ("failures", number(stats.failures)),
+            ("hides", number(stats.hides)),
+            ("restores", number(stats.restores)),
+            ("drifts", number(stats.drifts)),
+        ]);
+        StatsView {
+            totals,
+            toggle_counts: stats
+                .most_toggled
+                .iter()
+                .map(|app| catalog.tr_args("{} 次", &[number(app.count)]))
+                .collect(),
+            week_labels: stats
+                .weeks
+                .iter()
+                .map(|week| catalog.short_date(week.week_start))
+                .collect(),
+            week_titles: stats
+                .weeks
+                .iter()
+                .map(|week| {
+                    catalog.tr_args(
+                        "{} 次操作，{} 次失效",
+                        &[number(week.operations), number(week.drifts)],
+                    )
+                })
+                .collect(),
+            stats,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HistoryRowView {
+    time: String,
+    action: String,
+    name: String,
+    path: String,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct HistorySearchView {
+    entries: Vec<HistoryRowView>,
+    // 结果超过上限时的提示
+    truncated: Option<String>,
+}
+
+// 最新的在前，只显示最近的 HISTORY_SEARCH_LIMIT 条。
+fn search_history(webview: &WebView, filter: &HistoryFilter) {
+    let entries = history::query(filter).unwrap_or_else(|err| {
+        println!("[History] 搜索操作历史失败：{}", err);
+        Vec::new()
+    });
+    let catalog = i18n::current();
+    let view = HistorySearchView {
+        entries: entries
+            .iter()
+            .rev()
+            .take(HISTORY_SEARCH_LIMIT)
+            .map(|entry| HistoryRowView {
+                time: catalog.date_time(entry.at),
+                action: catalog.tr(entry.action.label()),
+                name: app_display_name(&entry.path),
+                path: entry.path.display().to_string(),
+                error: entry.error.clone(),
+            })
+            .collect(),
+        truncated: (entries.len() > HISTORY_SEARCH_LIMIT).then(|| {
+            catalog.tr_args(
+                "共 {} 条，只显示最近的 {} 条",
+                &[
+                    catalog.number(entries.len() as u64),
+                    catalog.number(HISTORY_SEARCH_LIMIT as u64),
+                ],
+            )
+        }),
+    };
+    let _ = webview.evaluate_script(&format!(
+        "showHistoryResults({});",
+        serde_json::to_string(&view).unwrap()
+    ));
+}
+
+fn show_backups(webview: &WebView) {
+    match backup::list_backups() {
+        Ok(entries) => {
+            let catalog = i18n::current();
+            let views: Vec<BackupView> = entries
+                .into_iter()
+                .map(|entry| BackupView {
+                    created_text: catalog.date_time(entry.created_at / 1000),
+                    size_text: catalog.size(entry.size),
+                    entry,
+                })
+                .collect();
+            let _ = webview.evaluate_script(&format!(
+                "showBackups({});",
+                serde_json::to_string(&views).unwrap()
+            ));
+        }
+        Err(err) => {
+            println!("[Backup] 读取备份列表失败：{}", err);
+        }
+    }
+}
+
+#[cfg(not(feature = "minimal-ui"))]
+fn show_stats(webview: &WebView, apps: &[ManagedApp]) {
+    let entries = history::load_history().unwrap_or_else(|err| {
+        println!("[History] 读取操作历史失败：{}", err);
+        Vec::new()
+    });
+    let hidden_now = apps
+        .iter()
+        .filter(|app| is_dock_icon_hidden(&app.path).unwrap_or(false))
+        .count();
+    let _ = webview.evaluate_script(&format!(
+        "showStats({});",
+        serde_json::to_string(&StatsView::new(history::stats(&entries, hidden_now))).unwrap()
+    ));
+}
+
+// 暂停期间图标本来就是显示的，不算漂移。
+fn record_drifts(apps: &[ManagedApp], paused: bool) {
+    if paused {
+        return;
+    }
+    for app in apps {
+        if app.checksum_matches() == Some(false)
+            && !is_dock_icon_hidden(&app.path).unwrap_or(true)
+            && history::record_drift(&app.path)
+        {
+            events::emit(events::Event::DriftDetected {
+                path: app.path.clone(),
+            });
+        }
+    }
+}
+
+// 导出到“下载”文件夹并在 Finder 中选中。
+fn export_history(
+    filter: &HistoryFilter,
+    format: ExportFormat,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let entries = history::load_history()?;
+    let date = history::format_timestamp(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs());
+    let path = config::home_dir().join("Downloads").join(format!(
+        "dock-dodger-history-{}.{}",
+        &date[..10],
+        format.extension()
+    ));
+    fs::write(&path, history::export(&entries, filter, format))?;
+    let _ = Command::new("open").arg("-R").arg(&path).spawn();
+    Ok(path)
+}
+
+// 没有选中任何项时导出整个列表；同样放到“下载”文件夹并在 Finder 中选中，可以从那里拖到别处。
+fn export_profile(
+    apps: &[ManagedApp],
+    selected: &[PathBuf],
+) -> Result<(PathBuf, usize), Box<dyn std::error::Error>> {
+    let chosen: Vec<&ManagedApp> = apps
+        .iter()
+        .filter(|app| selected.is_empty() || selected.contains(&app.path))
+        .collect();
+    if chosen.is_empty() {
+        return Err("没有可导出的应用".into());
+    }
+    let date = history::format_timestamp(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs());
+    let path = config::home_dir().join("Downloads").join(format!(
+        "dock-dodger-{}.{}",
+        &date[..10],
+        import::PROFILE_EXTENSION
+    ));
+    fs::write(&path, import::export_profile(&chosen))?;
+    let _ = Command::new("open").arg("-R").arg(&path).spawn();
+    Ok((path, chosen.len()))
+}
+
+#[derive(Serialize)]
+struct ScheduleView {
+    name: String,
+    description: String,
+    profile: String,
+}
+
+fn show_schedules(webview: &WebView, error: Option<&str>) {
+    let views: Vec<ScheduleView> = agent::profile_schedules()
+        .into_iter()
+        .map(|schedule| ScheduleView {
+            description: schedule.describe(),
+            profile: schedule.profile.display().to_string(),
+            name: schedule.name,
+        })
+        .collect();
+    let _ = webview.evaluate_script(&format!(
+        "showSchedules({}, {});",
+        serde_json::to_string(&views).unwrap(),
+        serde_json::to_string(&error).unwrap()
+    ));
+}
+
+// 与 dock-dodger schedule add 相同：配置文件可以写 ~ 或相对路径，保存为绝对路径。
+fn parse_schedule(data: &IpcRequest) -> Result<ProfileSchedule, String> {
+    if data.path.is_empty() {
+        return Err("请填写配置文件的路径".into());
+    }
+    let time = agent::parse_time(&data.time)?;
+    let weekdays = agent::parse_weekdays(&data.days)?;
+    let profile = config::normalize_input_path(&data.path)?;
+    let profile = fs::canonicalize(&profile).unwrap_or(profile);
+    ProfileSchedule::new(data.query.trim(), profile, weekdays, time)
+}
+
+fn announce_undoable(
+    webview: &WebView,
+    proxy: &EventLoopProxy<UserEvent>,
+    message: &str,
+    undo_cmd: &str,
+    undo_event: UserEvent,
+    path: &Path,
+) {
+    let _ = webview.evaluate_script(&format!(
+        "showNotice({}, {}, {}, {});",
+        serde_json::to_string(message).unwrap(),
+        serde_json::to_string("撤销").unwrap(),
+        serde_json::to_string(undo_cmd).unwrap(),
+        serde_json::to_string(&path.display().to_string()).unwrap()
+    ));
+    let proxy = proxy.clone();
+    notify::notify_with_action("Dock Dodger", message, "撤销", move || {
+        println!("[Notify] 用户在通知中点击了撤销");
+        let _ = proxy.send_event(undo_event);
+    });
+}
+
+// 系统或一批应用更新后汇总变化；仍然隐藏的应用更新记录的校验信息，之后不再算作变化。
+// 弹出了报告时返回 true，调用方不再单独提示隐藏失效。
+fn check_update_wave(
+    webview: &WebView,
+    proxy: &EventLoopProxy<UserEvent>,
+    apps: &mut [ManagedApp],
+    autosave: &mut Autosave,
+) -> bool {
+    let report = match update_wave::check(apps) {
+        Ok(Some(report)) => report,
+        Ok(None) => return false,
+        Err(err) => {
+            println!("[Update] 保存更新检查记录失败：{}", err);
+            return false;
+        }
+    };
+    let summary = format!(
+        "{} 个应用的隐藏失效，{} 个仍然有效，{} 个已不存在",
+        report.reverted.len(),
+        report.survived.len(),
+        report.disappeared.len()
+    );
+    let message = match (&report.previous_os, &report.os_version) {
+        (Some(previous), Some(current)) => {
+            format!("macOS 已从 {} 更新到 {}：{}", previous, current, summary)
+        }
+        _ => format!("检测到一批应用更新：{}", summary),
+    };
+    println!("[Update] {}", message);
+    for app in apps
+        .iter_mut()
+        .filter(|app| report.survived.contains(&app.path))
+    {
+        app.record_checksum();
+    }
+    if !report.survived.is_empty() {
+        autosave.mark_dirty();
+    }
+    let _ = webview.evaluate_script(&format!(
+        "showUpdateReport({}, {});",
+        serde_json::to_string(&message).unwrap(),
+        serde_json::to_string(&report).unwrap()
+    ));
+    let proxy = proxy.clone();
+    notify::notify_with_action("Dock Dodger", &message, "查看", move || {
+        let _ = proxy.send_event(UserEvent::ShowWindow);
+    });
+    true
+}
+
+// 在窗口和系统通知中提示隐藏失效，“重新应用”打开批量确认。
+fn announce_drifted(
+    webview: &WebView,
+    proxy: &EventLoopProxy<UserEvent>,
+    message: &str,
+    drifted: Vec<PathBuf>,
+) {
+    let _ = webview.evaluate_script(&format!(
+        "showNotice({});",
+        serde_json::to_string(message).unwrap()
+    ));
+    let proxy = proxy.clone();
+    notify::notify_with_action("Dock Dodger", message, "重新应用", move || {
+        let _ = proxy.send_event(UserEvent::PreviewBatch(drifted));
+    });
+}
+
+fn warn_external(webview: &WebView, path: &Path, reason: &str, force_cmd: &str) {
+    let message = format!(
+        "{} 似乎由其他用户或工具管理（{}），为避免冲突未做修改。",
+        app_display_name(path),
+        reason
+    );
+    let _ = webview.evaluate_script(&format!(
+        "showNotice({}, {}, {}, {});",
+        serde_json::to_string(&message).unwrap(),
+        serde_json::to_string("仍然覆盖").unwrap(),
+        serde_json::to_string(force_cmd).unwrap(),
+        serde_json::to_string(&path.display().to_string()).unwrap()
+    ));
+}
+
+// 退出应用时要等它响应，放到后台线程，避免阻塞界面。
+fn relaunch_in_background(app: ManagedApp) {
+    if app.relaunch.is_none() {
+        return;
+    }
+    thread::spawn(move || {
+        if let Err(err) = relaunch::after_hide(&app) {
+            println!(
+                "[Relaunch] 重新启动失败：{}，错误：{}",
+                app.path.display(),
+                err
+            );
+            // 没有自动化权限时用户多半不知道为什么图标还在，提示一下并给出设置入口。
+            if ErrorKind::classify(&*err) == ErrorKind::AutomationDenied {
+                let message = format!(
+                    "已隐藏 {}，但没有“自动化”权限，未能重新启动，重新打开后生效",
+                    app_display_name(&app.path)
+                );
+                notify::notify_with_action("Dock Dodger", &message, "打开系统设置", || {
+                    let _ = Command::new("open")
+                        .arg(relaunch::AUTOMATION_SETTINGS_URL)
+                        .spawn();
+                });
+            }
+        }
+    });
+}
+
+fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    let mut child = Command::new("pbcopy").stdin(Stdio::piped()).spawn()?;
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!(
+            "pbcopy 退出码 {}",
+            status.code().unwrap_or(-1)
+        )));
+    }
+    Ok(())
+}
+
+// Finder 中拷贝的文件以 file:// URL 的形式（每行一个）输出。
+const CLIPBOARD_FILES_SCRIPT: &str = "ObjC.import('AppKit');
+var items = $.NSPasteboard.generalPasteboard.readObjectsForClassesOptions($([$.NSURL]), $({}));
+var urls = [];
+for (var i = 0; i < items.count; i++) {
+    var url = items.objectAtIndex(i);
+    if (url.isFileURL) urls.push(url.absoluteString.js);
+}
+urls.join('\\n');";
+
+// 剪贴板中的文件引用和纯文本合在一起，交给 app_paths_in_text 解析；读不到的部分为空。
+fn read_clipboard() -> String {
+    let mut text = String::new();
+    for output in [
+        Command::new("osascript")
+            .args(["-l", "JavaScript", "-e", CLIPBOARD_FILES_SCRIPT])
+            .output(),
+        Command::new("pbpaste").output(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|output| output.status.success())
+    {
+        text.push_str(&String::from_utf8_lossy(&output.stdout));
+        text.push('\n');
+    }
+    text
+}
+
+fn add_from_text(
+    webview: &WebView,
+    text: &str,
+    empty_notice: &str,
+    proxy: &EventLoopProxy<UserEvent>,
+) {
+    let paths = config::app_paths_in_text(text);
+    if paths.is_empty() {
+        println!("[Add] {}", empty_notice);
+        let _ = webview.evaluate_script(&format!(
+            "showNotice({});",
+            serde_json::to_string(empty_notice).unwrap()
+        ));
+        return;
+    }
+    println!("[Add] 从文字中找到 {} 个应用路径", paths.len());
+    dispatch_dropped(paths, proxy);
+}
+
+// 主窗口和投放窗口共用：分出应用、导入列表和文件夹；返回是否需要在主窗口中继续操作
+// （导入或批量预览）。
+fn dispatch_dropped(paths: Vec<PathBuf>, proxy: &EventLoopProxy<UserEvent>) -> bool {
+    let mut batch: Vec<PathBuf> = Vec::new();
+    let mut folder = false;
+    let mut needs_window = false;
+    for path in paths {
+        let display = path.display().to_string();
+        if is_app_bundle(&path) {
+            println!("[DragDrop] 收到来自 Finder 的 .app：{}", display);
+            batch.push(path);
+        } else if import::is_import_list(&path) {
+            println!("[DragDrop] 收到导入列表：{}", display);
+            let _ = proxy.send_event(UserEvent::Import(path));
+            needs_window = true;
+        } else if path.is_dir() {
+            let found = folder_apps(&path);
+            println!(
+                "[DragDrop] 收到文件夹：{}，包含 {} 个应用",
+                display,
+                found.len()
+            );
+            folder = true;
+            batch.extend(found);
+        } else {
+            println!("[DragDrop] 忽略非 .app 文件：{}", display);
+        }
+    }
+    batch.sort();
+    batch.dedup();
+    // 单个应用直接隐藏；文件夹或多个应用先列出预览，确认后再执行。
+    if batch.len() == 1 && !folder {
+        let _ = proxy.send_event(UserEvent::Add(batch.remove(0)));
+    } else if !batch.is_empty() {
+        let _ = proxy.send_event(UserEvent::PreviewBatch(batch));
+        needs_window = true;
+    }
+    needs_window
+}
+
+fn is_quick_window(quick_launcher: &Option<QuickLauncher>, window_id: WindowId) -> bool {
+    quick_launcher
+        .as_ref()
+        .is_some_and(|launcher| launcher.window_id() == window_id)
+}
+
+fn app_display_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+fn announce_hidden(webview: &WebView, proxy: &EventLoopProxy<UserEvent>, path: &Path) {
+    announce_undoable(
+        webview,
+        proxy,
+        &format!("已隐藏 {} 的 Dock 图标", app_display_name(path)),
+        "restore",
+        UserEvent::Restore(path.to_path_buf(), false),
+        path,
+    );
+    offer_helpers(webview, path);
+}
+
+// 包内还有会显示 Dock 图标的登录项辅助程序时，在窗口中询问是否一并隐藏；撤销仍可通过系统通知完成。
+fn offer_helpers(webview: &WebView, path: &Path) {
+    let helpers = visible_login_item_helpers(path);
+    if helpers.is_empty() {
+        return;
+    }
+    println!(
+        "[Helpers] {} 内有 {} 个登录项辅助程序未隐藏",
+        path.display(),
+        helpers.len()
+    );
+    let message = format!(
+        "已隐藏 {} 的 Dock 图标，但它附带的 {} 个登录项辅助程序仍可能显示图标，是否一并隐藏？",
+        app_display_name(path),
+        helpers.len()
+    );
+    let _ = webview.evaluate_script(&format!(
+        "showNotice({}, {}, 'hideHelpers', {});",
+        serde_json::to_string(&message).unwrap(),
+        serde_json::to_string("一并隐藏").unwrap(),
+        serde_json::to_string(&path.display().to_string()).unwrap()
+    ));
+}
+
+fn announce_restored(webview: &WebView, proxy: &EventLoopProxy<UserEvent>, path: &Path) {
+    announce_undoable(
+        webview,
+        proxy,
+        &format!("已恢复 {} 的 Dock 图标", app_display_name(path)),
+        "add",
+        UserEvent::Add(path.to_path_buf()),
+        path,
+    );
+}
+
+fn spawn_watcher(proxy: EventLoopProxy<UserEvent>) -> DirWatcher {
+    let config = config::load_config().unwrap_or_default();
+    DirWatcher::spawn(
+        ScanScope::from_config(&config),
+        Duration::from_secs(config.watch_interval_secs.max(1)),
+        move |event| match event {
+            WatchEvent::Added(path) => {
+                println!("[Watch] 发现新安装的应用：{}", path.display());
+                events::emit(events::Event::AppInstalled { path: path.clone() });
+                let _ = proxy.send_event(UserEvent::AppInstalled(path));
+            }
+            WatchEvent::Removed(path) => {
+                events::emit(events::Event::AppRemoved { path: path.clone() });
+                let _ = proxy.send_event(UserEvent::AppRemoved(path));
+            }
+        },
+    )
+}
+
+// 返回列表是否因合并其它进程的改动而变化，需要重新显示。
+fn flush_autosave(autosave: &mut Autosave, apps: &mut Vec<ManagedApp>, force: bool) -> bool {
+    let result = if force {
+        autosave.flush(apps)
+    } else {
+        autosave.flush_if_due(apps)
+    };
+    let merged = result.unwrap_or_else(|err| {
+        println!("[State] 保存应用列表失败：{}", err);
+        false
+    });
+    // 仍有未保存的修改时交给崩溃钩子，panic 时先写入再退出。
+    if autosave.due().is_some() {
+        crash::stash_unsaved(apps);
+    } else {
+        crash::clear_unsaved();
+    }
+    merged
+}
+
+pub fn run() {
+    let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build();
+    let proxy = event_loop.create_proxy();
+    let window_config = config::load_config().unwrap_or_default();
+    let window = WindowBuilder::new()
+        .with_title("Dock Dodger")
+        .with_always_on_top(window_config.always_on_top)
+        .with_visible_on_all_workspaces(window_config.all_spaces)
+        .build(&event_loop)
+        .unwrap();
+
+    let drag_proxy = proxy.clone();
+    let ipc_proxy = proxy.clone();
+    let watch_proxy = proxy.clone();
+    let undo_proxy = proxy.clone();
+    let menu_proxy = proxy.clone();
+    let picker_proxy = proxy.clone();
+    let signal_proxy = proxy.clone();
+    let wake_proxy = proxy.clone();
+    let auth_proxy = proxy.clone();
+    let plist_proxy = proxy.clone();
+    let quick_proxy = proxy.clone();
+
+    // 界面始终推迟退出信号：收到 SIGINT/SIGTERM（例如注销）后走与关闭窗口相同的流程，
+    // 恢复仅本次运行的应用并保存列表后再退出；收到 SIGHUP 或配置文件被修改时重新加载配置。
+    // 事件循环阻塞等待事件，这里由后台线程轮询；应用列表被其它进程（计划导入、代理、rpc）改动时也在这里发现。
+    let _deferred = shutdown::defer();
+    shutdown::enable_reload();
+    thread::spawn(move || {
+        let mut stamp = config::config_stamp();
+        let mut state_stamp = state_store::stamp();
+        while !shutdown::requested() {
+            thread::sleep(Duration::from_millis(200));
+            let current = config::config_stamp();
+            if shutdown::take_reload() || current != stamp {
+                stamp = current;
+                let _ = signal_proxy.send_event(UserEvent::ReloadConfig);
+            }
+            let current = state_store::stamp();
+            if current != state_stamp {
+                state_stamp = current;
+                let _ = signal_proxy.send_event(UserEvent::StateChanged);
+            }
+        }
+        let _ = signal_proxy.send_event(UserEvent::Quit);
+    });
+    let woke_proxy = proxy.clone();
+    wake::spawn(move || {
+        let _ = woke_proxy.send_event(UserEvent::Woke);
+    });
+    let mount_proxy = proxy.clone();
+    volume::spawn(move |mount| {
+        let _ = mount_proxy.send_event(UserEvent::VolumeMounted(mount));
+    });
+
+    let main_id = window.id();
+    let webview = assets::with_icons(assets::page(WebViewBuilder::new(&window), "index.html"))
+        .with_drag_drop_handler(move |event| {
+            // 列表内部拖动排序时没有文件路径，交给网页自己处理。
+            if let DragDropEvent::Drop { paths, .. } = event
+                && !paths.is_empty()
+            {
+                dispatch_dropped(paths, &drag_proxy);
+                true
+            } else {
+                false
+            }
+        })
+        .with_ipc_handler(move |req: Request<String>| {
+            if let Ok(data) = serde_json::from_str::<IpcRequest>(req.body()) {
+                match data.cmd.as_str() {
+                    "ready" => {
+                        let _ = ipc_proxy.send_event(UserEvent::Refresh);
+                    }
+                    "add" => {
+                        println!("[IPC] 收到添加请求：{}", data.path);
+                        match config::normalize_input_path(&data.path) {
+                            Ok(path) => {
+                                let _ = ipc_proxy.send_event(UserEvent::Add(path));
+                            }
+                            Err(err) => println!("[IPC] {}", err),
+                        }
+                    }
+                    "dropText" => {
+                        let _ = ipc_proxy.send_event(UserEvent::DropText(data.query));
+                    }
+                    "paste" => {
+                        let proxy = ipc_proxy.clone();
+                        thread::spawn(move || {
+                            let _ = proxy.send_event(UserEvent::PasteText(read_clipboard()));
+                        });
+                    }
+                    "dismissInbox" => {
+                        let _ =
+                            ipc_proxy.send_event(UserEvent::DismissInbox(PathBuf::from(data.path)));
+                    }
+                    "listBackups" => {
+                        let _ = ipc_proxy.send_event(UserEvent::ListBackups);
+                    }
+                    #[cfg(not(feature = "minimal-ui"))]
+                    "getStats" => {
+                        let _ = ipc_proxy.send_event(UserEvent::Stats);
+                    }
+                    "exportHistory" => {
+                        let filter = HistoryFilter {
+                            since: history::parse_date(&data.since),
+                            until: history::parse_date(&data.until).map(|date| date + 86400),
+                            apps: Some(data.query.trim().to_string())
+                                .filter(|app| !app.is_empty())
+                                .into_iter()
+                                .collect(),
+                            text: None,
+                        };
+                        let format = ExportFormat::parse(&data.format).unwrap_or(ExportFormat::Csv);
+                        let _ = ipc_proxy.send_event(UserEvent::ExportHistory(filter, format));
+                    }
+                    "searchHistory" => {
+                        let filter = HistoryFilter {
+                            since: history::parse_date(&data.since),
+                            until: history::parse_date(&data.until).map(|date| date + 86400),
+                            text: Some(data.query.trim().to_string())
+                                .filter(|text| !text.is_empty()),
+                            ..HistoryFilter::default()
+                        };
+                        let _ = ipc_proxy.send_event(UserEvent::SearchHistory(filter));
+                    }
+                    "exportProfile" => {
+                        let selected = data.paths.into_iter().map(PathBuf::from).collect();
+                        let _ = ipc_proxy.send_event(UserEvent::ExportProfile(selected));
+                    }
+                    "listSchedules" => {
+                        let _ = ipc_proxy.send_event(UserEvent::ListSchedules);
+                    }
+                    "addSchedule" => {
+                        let _ = ipc_proxy.send_event(match parse_schedule(&data) {
+                            Ok(schedule) => UserEvent::AddSchedule(schedule),
+                            Err(err) => UserEvent::ScheduleFailed(err),
+                        });
+                    }
+                    "removeSchedule" => {
+                        let _ = ipc_proxy.send_event(UserEvent::RemoveSchedule(data.query));
+                    }
+                    "restoreBackup" => {
+                        println!(
+                            "[IPC] 收到恢复备份请求：{}（{}）",
+                            data.path, data.created_at
+                        );
+                        let _ = ipc_proxy.send_event(UserEvent::RestoreBackup(
+                            PathBuf::from(data.path),
+                            data.created_at,
+                        ));
+                    }
+                    "getDetails" => {
+                        let _ = ipc_proxy.send_event(UserEvent::Details(PathBuf::from(data.path)));
+                    }
+                    "searchApps" => {
+                        let _ = ipc_proxy.send_event(UserEvent::SearchApps(data.query));
+                    }
+                    "addBundleId" => {
+                        let _ = ipc_proxy.send_event(UserEvent::AddBundleId(data.query));
+                    }
+                    "listInstalled" => {
+                        let _ = ipc_proxy.send_event(UserEvent::ListInstalled);
+                    }
+                    "copyPath" => {
+                        let _ = ipc_proxy.send_event(UserEvent::CopyPath(PathBuf::from(data.path)));
+                    }
+                    "reveal" => {
+                        let _ = ipc_proxy.send_event(UserEvent::Reveal(PathBuf::from(data.path)));
+                    }
+                    "launch" => {
+                        let _ = ipc_proxy.send_event(UserEvent::Launch(PathBuf::from(data.path)));
+                    }
+                    "toggleSession" => {
+                        println!("[IPC] 收到切换仅本次运行请求：{}", data.path);
+                        let _ = ipc_proxy
+                            .send_event(UserEvent::ToggleSession(PathBuf::from(data.path)));
+                    }
+                    "setLabel" => {
+                        let label = ColorLabel::parse(&data.query);
+                        let _ = ipc_proxy
+                            .send_event(UserEvent::SetLabel(PathBuf::from(data.path), label));
+                    }
+                    "hideHelpers" => {
+                        let _ =
+                            ipc_proxy.send_event(UserEvent::HideHelpers(PathBuf::from(data.path)));
+                    }
+                    "setRelaunch" => {
+                        let _ = ipc_proxy.send_event(UserEvent::SetRelaunch(
+                            PathBuf::from(data.path),
+                            data.relaunch,
+                        ));
+                    }
+                    "reorder" => {
+                        let order = data.paths.into_iter().map(PathBuf::from).collect();
+                        let _ = ipc_proxy.send_event(UserEvent::Reorder(order));
+                    }
+                    "setLanguage" => {
+                        let language = Some(data.query).filter(|query| !query.is_empty());
+                        let _ = ipc_proxy.send_event(UserEvent::SetLanguage(language));
+                    }
+                    "setWindowOption" => {
+                        let _ = ipc_proxy
+                            .send_event(UserEvent::SetWindowOption(data.query, data.enabled));
+                    }
+                    "saveShortcuts" => {
+                        let _ = ipc_proxy.send_event(UserEvent::SaveShortcuts(data.shortcuts));
+                    }
+                    "togglePause" => {
+                        println!("[IPC] 收到切换暂停请求");
+                        let _ = ipc_proxy.send_event(UserEvent::TogglePause);
+                    }
+                    "rescan" => {
+                        println!("[IPC] 收到重新扫描请求");
+                        let _ = ipc_proxy.send_event(UserEvent::Rescan);
+                    }
+                    "restore" | "forceRestore" => {
+                        println!("[IPC] 收到恢复请求：{}", data.path);
+                        let force = data.cmd == "forceRestore";
+                        let _ = ipc_proxy
+                            .send_event(UserEvent::Restore(PathBuf::from(data.path), force));
+                    }
+                    "reapply" | "forceReapply" => {
+                        println!("[IPC] 收到重新应用请求：{}", data.path);
+                        let force = data.cmd == "forceReapply";
+                        let _ = ipc_proxy
+                            .send_event(UserEvent::Reapply(PathBuf::from(data.path), force));
+                    }
+                    "acknowledge" => {
+                        println!("[IPC] 收到接受外部修改请求：{}", data.path);
+                        let _ =
+                            ipc_proxy.send_event(UserEvent::Acknowledge(PathBuf::from(data.path)));
+                    }
+                    "previewBatch" => {
+                        let paths = data.paths.into_iter().map(PathBuf::from).collect();
+                        let _ = ipc_proxy.send_event(UserEvent::PreviewBatch(paths));
+                    }
+                    "confirmBatch" => {
+                        println!("[IPC] 收到批量操作确认，共 {} 项", data.paths.len());
+                        let selected = data.paths.into_iter().map(PathBuf::from).collect();
+                        let _ = ipc_proxy.send_event(UserEvent::ConfirmBatch(selected));
+                    }
+                    "viewCrash" => {
+                        let _ =
+                            ipc_proxy.send_event(UserEvent::ViewCrash(PathBuf::from(data.path)));
+                    }
+                    "crashSeen" => {
+                        let _ =
+                            ipc_proxy.send_event(UserEvent::CrashSeen(PathBuf::from(data.path)));
+                    }
+                    "createReport" => {
+                        let anonymize = data.query == "anonymize";
+                        let _ = ipc_proxy.send_event(UserEvent::CreateReport(anonymize));
+                    }
+                    "createPlist" => {
+                        let _ =
+                            ipc_proxy.send_event(UserEvent::CreatePlist(PathBuf::from(data.path)));
+                    }
+                    "openAutomationSettings" => {
+                        let _ = ipc_proxy.send_event(UserEvent::OpenAutomationSettings);
+                    }
+                    "openPrivacySettings" => {
+                        let _ = ipc_proxy.send_event(UserEvent::OpenPrivacySettings);
+                    }
+                    "retryFailed" => {
+                        println!("[IPC] 收到重试失败项请求");
+                        let _ = ipc_proxy.send_event(UserEvent::RetryFailed);
+                    }
+                    "resolveImport" => {
+                        println!("[IPC] 收到导入冲突处理结果，覆盖 {} 项", data.paths.len());
+                        let overwrite = data.paths.into_iter().map(PathBuf::from).collect();
+                        let _ = ipc_proxy.send_event(UserEvent::ResolveImport(overwrite));
+                    }
+                    _ => {}
+                }
+            }
+        })
+        .build()
+        .unwrap();
+
+    // 快速启动窗口创建失败时只是少了这个入口，不影响主窗口。
+    let launch_proxy = quick_proxy.clone();
+    let quick_launcher = match QuickLauncher::new(
+        &event_loop,
+        move |path| {
+            let _ = launch_proxy.send_event(UserEvent::Launch(path));
+        },
+        move || {
+            let _ = quick_proxy.send_event(UserEvent::QuickLaunchHide);
+        },
+    ) {
+        Ok(launcher) => Some(launcher),
+        Err(err) => {
+            println!("[QuickLaunch] 创建快速启动窗口失败：{}", err);
+            None
+        }
+    };
+    let target_proxy = proxy.clone();
+    let drop_target = match DropTarget::new(&event_loop, move |paths| {
+        if dispatch_dropped(paths, &target_proxy) {
+            let _ = target_proxy.send_event(UserEvent::ShowWindow);
+        }
+    }) {
+        Ok(target) => Some(target),
+        Err(err) => {
+            println!("[DropTarget] 创建投放窗口失败：{}", err);
+            None
+        }
+    };
+    let mut hotkeys = GlobalHotKeys::new();
+    let hotkey_proxy = proxy.clone();
+    GlobalHotKeys::install(move |id| {
+        let _ = hotkey_proxy.send_event(UserEvent::HotKey(id));
+    });
+
+    // 读取列表、恢复上次遗留的会话都放到后台线程，窗口先显示出来；读完之前收到的操作先放进 deferred。
+    let startup_proxy = proxy.clone();
+    thread::spawn(move || {
+        let _ = startup_proxy.send_event(UserEvent::Loaded(Box::new(load_startup())));
+    });
+    let stream_proxy = proxy.clone();
+    let mut loading = true;
+    let mut page_ready = false;
+    let mut deferred: Vec<UserEvent> = Vec::new();
+    let mut apps: Vec<ManagedApp> = Vec::new();
+    let mut startup_notice: Option<(String, bool)> = None;
+    let mut crash_notice: Option<(PathBuf, usize)> = None;
+
+    let mut pending_conflicts: Vec<ImportConflict> = Vec::new();
+    let mut pending_batch: Vec<PathBuf> = Vec::new();
+    // 正在进行的批量操作共用一份报告，全部结束后才显示
+    let mut batch_report = BatchReport::default();
+    let mut batches = 0;
+    let batch_proxy = proxy.clone();
+    let mut retry_batch: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    let mut inbox: Vec<PathBuf> = Vec::new();
+    let mut failures = 0;
+    let mut status_item = StatusItem::default();
+    let mut paused = pause::is_paused();
+    let mut zoom = 1.0;
+    let mut autosave = Autosave::new(AUTOSAVE_DELAY);
+
+    let mut _watcher = spawn_watcher(watch_proxy.clone());
+
+    fn handle_app_drop(
+        path: PathBuf,
+        tags: Vec<String>,
+        apps: &mut Vec<ManagedApp>,
+        autosave: &mut Autosave,
+        failures: &mut usize,
+        webview: &WebView,
+    ) -> bool {
+        // 从 Gatekeeper 转移后的临时位置拖入时改为处理原始位置的应用；此时用提示代替撤销通知，
+        // 提醒用户把应用移走，否则下次启动仍会从新的临时位置运行。
+        let translocated = translocation::is_translocated(&path);
+        let path = match translocation::resolve(&path) {
+            Ok(resolved) => resolved,
+            Err(err) => {
+                println!("[Add] {}", err);
+                *failures += 1;
+                show_error(webview, &ErrorView::new(&path, &*err, "add"));
+                return false;
+            }
+        };
+        match hide_new_app(path.clone(), tags, apps, autosave, failures, webview) {
+            Outcome::Done if translocated => {
+                let message = translocation::warning(&path);
+                println!("[Add] {}", message);
+                let _ = webview.evaluate_script(&format!(
+                    "showNotice({}, {}, 'reveal', {});",
+                    serde_json::to_string(&message).unwrap(),
+                    serde_json::to_string("在 Finder 中显示").unwrap(),
+                    serde_json::to_string(&path.display().to_string()).unwrap()
+                ));
+                false
+            }
+            Outcome::Done => true,
+            Outcome::Skipped(_) => false,
+            Outcome::Failed(error) => {
+                show_error(webview, &error);
+                false
+            }
+        }
+    }
+
+    fn hide_new_app(
+        path: PathBuf,
+        tags: Vec<String>,
+        apps: &mut Vec<ManagedApp>,
+        autosave: &mut Autosave,
+        failures: &mut usize,
+        webview: &WebView,
+    ) -> Outcome {
+        println!("[Add] 处理拖入的路径：{}", path.display());
+        if apps.iter().any(|app| app.path == path) {
+            println!("[Add] 已存在记录，忽略重复：{}", path.display());
+            return Outcome::Skipped("已在管理列表中".into());
+        }
+        apply_step(hide_app(path, tags), apps, autosave, failures, webview)
+    }
+
+    // 把一项的结果合并进列表，返回报告中记录的结果。
+    fn apply_step(
+        step: BatchStep,
+        apps: &mut Vec<ManagedApp>,
+        autosave: &mut Autosave,
+        failures: &mut usize,
+        webview: &WebView,
+    ) -> Outcome {
+        match step {
+            BatchStep::Added(app) => {
+                if apps.iter().any(|existing| existing.path == app.path) {
+                    return Outcome::Skipped("已在管理列表中".into());
+                }
+                let _ = webview.evaluate_script(&js_add_app(&app));
+                apps.push(app);
+                autosave.mark_dirty();
+                Outcome::Done
+            }
+            BatchStep::Rehidden(app) => {
+                if let Some(existing) = apps.iter_mut().find(|existing| existing.path == app.path) {
+                    *existing = app.clone();
+                    relaunch_in_background(app);
+                    autosave.mark_dirty();
+                }
+                Outcome::Done
+            }
+            BatchStep::Skipped(reason) => Outcome::Skipped(reason),
+            BatchStep::Failed(error) => {
+                *failures += 1;
+                Outcome::Failed(error)
+            }
+        }
+    }
+
+    // 返回需要隐藏的新应用；与已有记录冲突的放进 pending_conflicts，整批结束后再让用户选择。
+    fn handle_import(
+        list: &Path,
+        apps: &[ManagedApp],
+        pending_conflicts: &mut Vec<ImportConflict>,
+        report: &mut BatchReport,
+        failures: &mut usize,
+    ) -> Vec<(PathBuf, Vec<String>, Option<ManagedApp>)> {
+        let display = list.display().to_string();
+        let entries = match fs::read_to_string(list)
+            .map_err(|err| err.into())
+            .and_then(|data| import::parse_import(&data))
+        {
+            Ok(entries) => entries,
+            Err(err) => {
+                println!("[Import] 读取导入列表失败：{}，错误：{}", display, err);
+                *failures += 1;
+                return Vec::new();
+            }
+        };
+        println!("[Import] 导入列表共 {} 项：{}", entries.len(), display);
+
+        let scope = ScanScope::load();
+        pending_conflicts.clear();
+        let mut new_apps = Vec::new();
+        for entry in entries {
+            let Some(path) = import::resolve_entry(&entry, &scope) else {
+                println!("[Import] 无法在本机找到对应的应用：{}", entry.source);
+                report.push(
+                    PathBuf::from(&entry.source),
+                    entry.tags,
+                    Outcome::Skipped("无法在本机找到对应的应用".into()),
+                );
+                continue;
+            };
+            if apps.iter().any(|app| app.path == path) {
+                pending_conflicts.push(ImportConflict {
+                    path,
+                    tags: entry.tags,
+                });
+            } else {
+                new_apps.push((path, entry.tags, None));
+            }
+        }
+        new_apps
+    }
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        let refresh_status = matches!(
+            event,
+            Event::UserEvent(_)
+                | Event::NewEvents(StartCause::Init)
+                | Event::WindowEvent {
+                    event: WindowEvent::Focused(true) | WindowEvent::DroppedFile(_),
+                    ..
+                }
+        );
+        match event {
+            Event::NewEvents(StartCause::Init) => {
+                let menu_proxy = menu_proxy.clone();
+                status_item.install(paused, move |command| {
+                    let event = match command {
+                        MenuCommand::TogglePause => UserEvent::TogglePause,
+                        MenuCommand::Launch(path) => UserEvent::Launch(path),
+                        MenuCommand::ToggleDropTarget => UserEvent::ToggleDropTarget,
+                        MenuCommand::UndoHide(path) => UserEvent::Restore(path, false),
+                        MenuCommand::UndoRestore(path) => UserEvent::Add(path),
+                    };
+                    let _ = menu_proxy.send_event(event);
+                });
+            }
+            Event::UserEvent(event)
+                if loading
+                    && !matches!(
+                        event,
+                        UserEvent::Loaded(_) | UserEvent::Refresh | UserEvent::ReloadConfig
+                    ) =>
+            {
+                deferred.push(event);
+            }
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::CloseRequested,
+                ..
+            } if loading && window_id == main_id => deferred.push(UserEvent::Quit),
+            Event::WindowEvent {
+                event: WindowEvent::DroppedFile(path),
+                ..
+            } if loading => deferred.push(if import::is_import_list(&path) {
+                UserEvent::Import(path)
+            } else {
+                UserEvent::Add(path)
+            }),
+            Event::UserEvent(UserEvent::Loaded(startup)) => {
+                let startup = *startup;
+                println!("[Startup] 已读取应用列表，共 {} 项", startup.apps.len());
+                apps = startup.apps;
+                autosave.track(startup.base, startup.stamp);
+                if startup.session_changed {
+                    autosave.mark_dirty();
+                }
+                startup_notice = startup.notice;
+                crash_notice = startup.crash;
+                loading = false;
+                // 页面还没加载完时，随后的 Refresh 会显示完整列表。
+                if page_ready {
+                    let _ =
+                        webview.evaluate_script(&format!("setReadOnly({});", config::read_only()));
+                    stream_list(&webview, &apps, &stream_proxy);
+                    show_inbox(&webview, &inbox);
+                    if !paused {
+                        check_update_wave(&webview, &wake_proxy, &mut apps, &mut autosave);
+                    }
+                    show_startup_notices(&webview, &mut crash_notice, &mut startup_notice);
+                }
+                for event in deferred.drain(..) {
+                    let _ = stream_proxy.send_event(event);
+                }
+            }
+            Event::UserEvent(UserEvent::Verified(views)) => {
+                let _ = webview.evaluate_script(&format!(
+                    "updateApps({});",
+                    serde_json::to_string(&views).unwrap()
+                ));
+            }
+            // 快速启动和投放窗口关闭时只是收起。
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::CloseRequested,
+                ..
+            } if window_id != main_id => {
+                if is_quick_window(&quick_launcher, window_id)
+                    && let Some(launcher) = &quick_launcher
+                {
+                    launcher.hide();
+                }
+                if let Some(target) = drop_target
+                    .as_ref()
+                    .filter(|target| target.window_id() == window_id)
+                {
+                    target.hide();
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            }
+            | Event::UserEvent(UserEvent::Quit) => {
+                println!("[Window] 接收到关闭请求，准备退出。");
+                match session::end_session(&mut apps) {
+                    Ok(results) => {
+                        for result in &results {
+                            if let Some(err) = &result.error {
+                                println!(
+                                    "[Session] 恢复仅本次运行的应用失败：{}，错误：{}",
+                                    result.path.display(),
+                                    err
+                                );
+                            }
+                        }
+                        if !results.is_empty() {
+                            println!("[Session] 已恢复 {} 个仅本次运行隐藏的应用", results.len());
+                            autosave.mark_dirty();
+                        }
+                    }
+                    Err(err) => println!("[Session] 结束会话失败：{}", err),
+                }
+                flush_autosave(&mut autosave, &mut apps, true);
+                webhook::wait(Duration::from_secs(5));
+                *control_flow = ControlFlow::Exit;
+            }
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::Focused(focused),
+                ..
+            } if is_quick_window(&quick_launcher, window_id) => {
+                // 快速启动窗口失去焦点（点到别处）时收起。
+                if !focused && let Some(launcher) = &quick_launcher {
+                    launcher.hide();
+                }
+            }
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::Focused(true),
+                ..
+            } if window_id == main_id && !loading => {
+                failures = 0;
+                apply_text_scale(&webview, &mut zoom);
+                record_drifts(&apps, paused);
+                rebuild_list(&webview, &apps);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::DroppedFile(path),
+                ..
+            } => {
+                println!("[Window] 收到窗口层面的拖入文件：{}", path.display());
+                if import::is_import_list(&path) {
+                    let items = handle_import(
+                        &path,
+                        &apps,
+                        &mut pending_conflicts,
+                        &mut batch_report,
+                        &mut failures,
+                    );
+                    batches += 1;
+                    spawn_batch(items, false, &batch_proxy);
+                } else if handle_app_drop(
+                    path.clone(),
+                    Vec::new(),
+                    &mut apps,
+                    &mut autosave,
+                    &mut failures,
+                    &webview,
+                ) {
+                    announce_hidden(&webview, &undo_proxy, &path);
+                }
+            }
+            Event::UserEvent(UserEvent::DropText(text)) => {
+                // 从终端拖出的路径、浏览器中的 file:// 链接：解析出应用后与拖入文件走同样的流程。
+                add_from_text(
+                    &webview,
+                    &text,
+                    "拖入的文字中没有找到 .app 路径",
+                    &stream_proxy,
+                );
+            }
+            Event::UserEvent(UserEvent::PasteText(text)) => {
+                add_from_text(&webview, &text, "剪贴板中没有找到 .app 路径", &stream_proxy);
+            }
+            Event::UserEvent(UserEvent::Add(path)) => {
+                println!("[Event] 处理 Add 事件：{}", path.display());
+                if inbox.contains(&path) {
+                    inbox.retain(|item| *item != path);
+                    show_inbox(&webview, &inbox);
+                }
+                if handle_app_drop(
+                    path.clone(),
+                    Vec::new(),
+                    &mut apps,
+                    &mut autosave,
+                    &mut failures,
+                    &webview,
+                ) {
+                    announce_hidden(&webview, &undo_proxy, &path);
+                }
+            }
+            Event::UserEvent(UserEvent::Import(path))
+                if auth::required() && is_profile_file(&path) =>
+            {
+                confirm_then(
+                    "启用配置文件会按其中的列表隐藏应用",
+                    UserEvent::Import(path),
+                    &auth_proxy,
+                );
+            }
+            Event::UserEvent(UserEvent::Import(path)) => {
+                println!("[Event] 处理 Import 事件：{}", path.display());
+                let items = handle_import(
+                    &path,
+                    &apps,
+                    &mut pending_conflicts,
+                    &mut batch_report,
+                    &mut failures,
+                );
+                batches += 1;
+                spawn_batch(items, false, &batch_proxy);
+            }
+            Event::UserEvent(UserEvent::ResolveImport(overwrite)) => {
+                let mut items = Vec::new();
+                for conflict in pending_conflicts.drain(..) {
+                    if overwrite.contains(&conflict.path) {
+                        println!("[Import] 覆盖已有记录：{}", conflict.path.display());
+                        items.push((conflict.path, conflict.tags));
+                    } else {
+                        println!("[Import] 保留已有记录：{}", conflict.path.display());
+                        batch_report.push(
+                            conflict.path,
+                            conflict.tags,
+                            Outcome::Skipped("保留已有记录".into()),
+                        );
+                    }
+                }
+                batches += 1;
+                spawn_batch(batch_items(items, &apps), true, &batch_proxy);
+            }
+            Event::UserEvent(UserEvent::PreviewBatch(paths)) => {
+                println!("[Batch] 等待确认批量操作，共 {} 项", paths.len());
+                show_batch(&webview, &paths, &apps);
+                pending_batch = paths;
+            }
+            Event::UserEvent(UserEvent::ConfirmBatch(selected)) => {
+                if selected.is_empty() {
+                    println!("[Batch] 用户取消了批量操作");
+                    pending_batch.clear();
+                } else {
+                    let mut items = Vec::new();
+                    for path in pending_batch.drain(..) {
+                        // 勾选即表示已确认预览中的警告。
+                        if selected.contains(&path) {
+                            items.push((path, Vec::new()));
+                        } else {
+                            batch_report.push(path, Vec::new(), Outcome::Skipped("未勾选".into()));
+                        }
+                    }
+                    batches += 1;
+                    spawn_batch(batch_items(items, &apps), false, &batch_proxy);
+                }
+            }
+            Event::UserEvent(UserEvent::OpenPrivacySettings) => {
+                let _ = Command::new("open")
+                    .arg("x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles")
+                    .spawn();
+            }
+            Event::UserEvent(UserEvent::OpenAutomationSettings) => {
+                let _ = Command::new("open")
+                    .arg(relaunch::AUTOMATION_SETTINGS_URL)
+                    .spawn();
+            }
+            Event::UserEvent(UserEvent::RetryFailed) => {
+                let items = std::mem::take(&mut retry_batch);
+                println!("[Batch] 重试 {} 个失败项", items.len());
+                batches += 1;
+                spawn_batch(batch_items(items, &apps), false, &batch_proxy);
+            }
+            Event::UserEvent(UserEvent::BatchProgress(path, tags, step)) => {
+                let outcome = apply_step(*step, &mut apps, &mut autosave, &mut failures, &webview);
+                batch_report.push(path, tags, outcome);
+            }
+            Event::UserEvent(UserEvent::BatchFinished) => {
+                batches -= 1;
+                if batches == 0 {
+                    rebuild_list(&webview, &apps);
+                    // 导入时与已有记录冲突的项等新应用处理完后再让用户选择，选择后作为下一批继续。
+                    if pending_conflicts.is_empty() {
+                        finish_batch(&webview, &mut batch_report, &mut retry_batch);
+                    } else {
+                        let paths: Vec<String> = pending_conflicts
+                            .iter()
+                            .map(|conflict| conflict.path.display().to_string())
+                            .collect();
+                        println!("[Import] {} 项与已有记录冲突，等待用户选择", paths.len());
+                        let _ = webview.evaluate_script(&format!(
+                            "showImportConflicts({});",
+                            serde_json::to_string(&paths).unwrap()
+                        ));
+                    }
+                }
+            }
+            Event::UserEvent(UserEvent::Restore(path, force)) => {
+                let display = path.display().to_string();
+                println!("[Event] 收到 Restore 事件：{}", display);
+                let external = apps
+                    .iter()
+                    .find(|app| app.path == path)
+                    .filter(|_| !force)
+                    .and_then(ManagedApp::external_change);
+                if let Some(reason) = external {
+                    println!(
+                        "[Restore] {} 由其他用户或工具管理：{}，等待确认",
+                        display, reason
+                    );
+                    warn_external(&webview, &path, &reason, "forceRestore");
+                } else {
+                    let restored = match apps.iter().find(|app| app.path == path) {
+                        Some(app) => app.restore_dock_icon(),
+                        None => restore_dock_icon(&path),
+                    };
+                    match restored {
+                        Ok(_) => {
+                            println!("[Restore] 已恢复 Dock 图标：{}", display);
+                            let was_session = apps.iter().any(|a| a.path == path && a.session);
+                            apps.retain(|a| a.path != path);
+                            if was_session && let Err(err) = session::write_journal(&apps) {
+                                println!("[Session] 更新会话日志失败：{}", err);
+                            }
+                            autosave.mark_dirty();
+                            rebuild_list(&webview, &apps);
+                            announce_restored(&webview, &undo_proxy, &path);
+                        }
+                        Err(err) => {
+                            println!("[Restore] 恢复 Dock 图标失败：{}，错误：{}", display, err);
+                            failures += 1;
+                            show_error(&webview, &ErrorView::new(&path, &*err, "restore"));
+                        }
+                    }
+                }
+            }
+            Event::UserEvent(UserEvent::Refresh) => {
+                apply_locale(&webview);
+                apply_text_scale(&webview, &mut zoom);
+                apply_window_options(&window, &webview);
+                let _ = webview.evaluate_script(&format!("setPaused({});", paused));
+                let _ = webview.evaluate_script(&format!("setReadOnly({});", config::read_only()));
+                apply_shortcuts(&webview, &status_item, &mut hotkeys);
+                page_ready = true;
+                if loading {
+                    let _ = webview.evaluate_script("showLoading();");
+                } else {
+                    rebuild_list(&webview, &apps);
+                    show_inbox(&webview, &inbox);
+                    if !paused {
+                        check_update_wave(&webview, &wake_proxy, &mut apps, &mut autosave);
+                    }
+                    show_startup_notices(&webview, &mut crash_notice, &mut startup_notice);
+                }
+            }
+            Event::UserEvent(UserEvent::AppInstalled(path)) => {
+                let config = config::load_config().unwrap_or_default();
+                let display = path.display().to_string();
+                if apps.iter().any(|app| app.path == path) {
+                    println!("[Watch] 已在管理列表中，忽略：{}", display);
+                } else if let Some(rule) = rules::find_matching_rule(&config.rules, &path) {
+                    match rule.action {
+                        RuleAction::Hide => {
+                            println!("[Rules] {} 匹配规则 {}，自动隐藏", display, rule.describe());
+                            if handle_app_drop(
+                                path.clone(),
+                                Vec::new(),
+                                &mut apps,
+                                &mut autosave,
+                                &mut failures,
+                                &webview,
+                            ) {
+                                announce_hidden(&webview, &undo_proxy, &path);
+                            }
+                        }
+                        RuleAction::Prompt => {
+                            println!(
+                                "[Rules] {} 匹配规则 {}，等待用户确认",
+                                display,
+                                rule.describe()
+                            );
+                            let message = format!(
+                                "新安装的 {} 匹配自动隐藏规则，是否隐藏其 Dock 图标？",
+                                display
+                            );
+                            let _ = webview.evaluate_script(&format!(
+                                "showNotice({}, {}, 'add', {});",
+                                serde_json::to_string(&message).unwrap(),
+                                serde_json::to_string("隐藏").unwrap(),
+                                serde_json::to_string(&display).unwrap()
+                            ));
+                        }
+                    }
+                } else if config.notify_new_apps
+                    && !inbox.contains(&path)
+                    && !is_dock_icon_hidden(&path).unwrap_or(false)
+                {
+                    println!("[Inbox] 新应用加入待处理列表：{}", display);
+                    inbox.push(path);
+                    show_inbox(&webview, &inbox);
+                }
+            }
+            Event::UserEvent(UserEvent::AppRemoved(path)) if inbox.contains(&path) => {
+                println!("[Inbox] 应用已被移除，从待处理列表删除：{}", path.display());
+                inbox.retain(|item| *item != path);
+                show_inbox(&webview, &inbox);
+            }
+            Event::UserEvent(UserEvent::DismissInbox(path)) => {
+                println!("[Inbox] 忽略新应用：{}", path.display());
+                inbox.retain(|item| *item != path);
+                show_inbox(&webview, &inbox);
+            }
+            Event::UserEvent(UserEvent::Details(path)) => {
+                if let Some(app) = apps.iter().find(|app| app.path == path) {
+                    let _ = webview.evaluate_script(&format!(
+                        "showDetails({});",
+                        serde_json::to_string(&AppDetails::new(app)).unwrap()
+                    ));
+                }
+            }
+            Event::UserEvent(UserEvent::ListInstalled) => {
+                println!("[Picker] 正在读取已安装的应用列表");
+                let picker_proxy = picker_proxy.clone();
+                thread::spawn(move || {
+                    let apps = installed::installed_apps(&ScanScope::load());
+                    let _ = picker_proxy.send_event(UserEvent::InstalledApps(apps));
+                });
+            }
+            Event::UserEvent(UserEvent::InstalledApps(installed)) => {
+                println!("[Picker] 共找到 {} 个已安装的应用", installed.len());
+                show_picker(&webview, &installed, &apps);
+            }
+            Event::UserEvent(UserEvent::SearchApps(query)) => {
+                let picker_proxy = picker_proxy.clone();
+                thread::spawn(move || {
+                    let results = installed::spotlight_search(&query, &ScanScope::load())
+                        .unwrap_or_else(|err| {
+                            println!("[Picker] Spotlight 搜索失败：{}", err);
+                            Vec::new()
+                        });
+                    let _ = picker_proxy.send_event(UserEvent::SearchResults(query, results));
+                });
+            }
+            Event::UserEvent(UserEvent::SearchResults(query, results)) => {
+                let _ = webview.evaluate_script(&format!(
+                    "showSearchResults({}, {});",
+                    serde_json::to_string(&query).unwrap(),
+                    serde_json::to_string(&picker_entries(&results, &apps)).unwrap()
+                ));
+            }
+            Event::UserEvent(UserEvent::AddBundleId(bundle_id)) => {
+                println!("[Picker] 按 Bundle ID 查找：{}", bundle_id);
+                let picker_proxy = picker_proxy.clone();
+                thread::spawn(move || {
+                    let event = match installed::resolve_bundle_id(&bundle_id, &ScanScope::load()) {
+                        Ok(path) => UserEvent::Add(path),
+                        Err(err) => UserEvent::BundleIdNotFound(err.to_string()),
+                    };
+                    let _ = picker_proxy.send_event(event);
+                });
+            }
+            Event::UserEvent(UserEvent::BundleIdNotFound(message)) => {
+                println!("[Picker] {}", message);
+                let _ = webview.evaluate_script(&format!(
+                    "showNotice({});",
+                    serde_json::to_string(&message).unwrap()
+                ));
+            }
+            Event::UserEvent(UserEvent::Launch(path)) => {
+                println!("[Launch] 启动：{}", path.display());
+                if let Err(err) = Command::new("open").arg(&path).spawn() {
+                    println!("[Launch] 启动失败：{}，错误：{}", path.display(), err);
+                    show_error(&webview, &ErrorView::new(&path, &err, "launch"));
+                }
+            }
+            Event::UserEvent(UserEvent::HotKey(id)) => match hotkeys.action(id) {
+                Some("quick_launch") => {
+                    if let Some(launcher) = &quick_launcher {
+                        launcher.toggle(&apps);
+                    }
+                }
+                Some("drop_target") => {
+                    if let Some(target) = &drop_target {
+                        target.toggle();
+                    }
+                }
+                _ => {}
+            },
+            Event::UserEvent(UserEvent::ToggleDropTarget) => {
+                if let Some(target) = &drop_target {
+                    target.toggle();
+                }
+            }
+            Event::UserEvent(UserEvent::ShowWindow) => {
+                window.set_visible(true);
+                window.set_focus();
+            }
+            Event::UserEvent(UserEvent::QuickLaunchHide) => {
+                if let Some(launcher) = &quick_launcher {
+                    launcher.hide();
+                }
+            }
+            Event::UserEvent(UserEvent::CopyPath(path)) => {
+                // 网页中显示的路径可能被截断，以后端记录的路径为准；不在任何列表中的只在文件存在时复制。
+                let known = apps
+                    .iter()
+                    .map(|app| &app.path)
+                    .chain(&inbox)
+                    .chain(&pending_batch)
+                    .find(|known| **known == path)
+                    .cloned()
+                    .or_else(|| path.exists().then_some(path));
+                let message = match known {
+                    Some(path) => match copy_to_clipboard(&path.display().to_string()) {
+                        Ok(()) => format!("已复制路径：{}", path.display()),
+                        Err(err) => format!("复制路径失败：{}", err),
+                    },
+                    None => "路径已不存在，未复制".to_string(),
+                };
+                let _ = webview.evaluate_script(&format!(
+                    "showNotice({});",
+                    serde_json::to_string(&message).unwrap()
+                ));
+            }
+            Event::UserEvent(UserEvent::Reveal(path)) => {
+                if let Err(err) = Command::new("open").arg("-R").arg(&path).spawn() {
+                    println!(
+                        "[Reveal] 在 Finder 中显示失败：{}，错误：{}",
+                        path.display(),
+                        err
+                    );
+                }
+            }
+            Event::UserEvent(UserEvent::ToggleSession(path)) => {
+                if let Some(app) = apps.iter_mut().find(|app| app.path == path) {
+                    app.session = !app.session;
+                    let mode = if app.session {
+                        "仅本次运行"
+                    } else {
+                        "始终"
+                    };
+                    println!("[Session] {} 改为{}隐藏", path.display(), mode);
+                    if let Err(err) = session::write_journal(&apps) {
+                        println!("[Session] 更新会话日志失败：{}", err);
+                    }
+                    autosave.mark_dirty();
+                }
+                rebuild_list(&webview, &apps);
+            }
+            Event::UserEvent(UserEvent::SetLabel(path, label)) => {
+                if let Some(app) = apps.iter_mut().find(|app| app.path == path) {
+                    app.label = label;
+                    println!(
+                        "[Label] {} 的颜色标记改为 {}",
+                        path.display(),
+                        label.map_or("无", ColorLabel::name)
+                    );
+                    autosave.mark_dirty();
+                }
+                rebuild_list(&webview, &apps);
+            }
+            Event::UserEvent(UserEvent::SetRelaunch(path, options)) => {
+                if let Some(app) = apps.iter_mut().find(|app| app.path == path) {
+                    println!(
+                        "[Relaunch] {} 重新隐藏后{}自动重新启动",
+                        path.display(),
+                        if options.is_some() { "将" } else { "不再" }
+                    );
+                    app.relaunch = options;
+                    autosave.mark_dirty();
+                }
+            }
+            Event::UserEvent(UserEvent::CreatePlist(path)) => {
+                match create_minimal_info_plist(&path) {
+                    // 补上 Info.plist 后按原来的操作继续：已管理的重新隐藏，否则加入列表
+                    Ok(_) => {
+                        println!("[Plist] 已创建最小的 Info.plist：{}", path.display());
+                        let _ =
+                            plist_proxy.send_event(if apps.iter().any(|app| app.path == path) {
+                                UserEvent::Reapply(path, false)
+                            } else {
+                                UserEvent::Add(path)
+                            });
+                    }
+                    Err(err) => {
+                        println!(
+                            "[Plist] 创建 Info.plist 失败：{}，错误：{}",
+                            path.display(),
+                            err
+                        );
+                        failures += 1;
+                        show_error(&webview, &ErrorView::new(&path, &*err, "createPlist"));
+                    }
+                }
+            }
+            Event::UserEvent(UserEvent::HideHelpers(path)) => {
+                if let Some(app) = apps.iter_mut().find(|app| app.path == path) {
+                    match app.hide_helpers() {
+                        Ok(count) => {
+                            println!(
+                                "[Helpers] 一并隐藏了 {} 个登录项辅助程序：{}",
+                                count,
+                                path.display()
+                            );
+                        }
+                        Err(err) => {
+                            println!(
+                                "[Helpers] 隐藏登录项辅助程序失败：{}，错误：{}",
+                                path.display(),
+                                err
+                            );
+                            failures += 1;
+                            show_error(&webview, &ErrorView::new(&path, &*err, "hideHelpers"));
+                        }
+                    }
+                    // 失败前已处理的辅助程序也要记录下来
+                    autosave.mark_dirty();
+                }
+            }
+            Event::UserEvent(UserEvent::Reorder(order)) => {
+                // 未出现在新顺序中的应用（例如拖动期间刚加入的）保持原有相对顺序排在最后。
+                apps.sort_by_key(|app| {
+                    order
+                        .iter()
+                        .position(|path| *path == app.path)
+                        .unwrap_or(usize::MAX)
+                });
+                println!("[Order] 已更新列表顺序");
+                autosave.mark_dirty();
+            }
+            Event::UserEvent(UserEvent::TogglePause) if !paused && auth::required() => {
+                confirm_then(
+                    "暂停隐藏会恢复所有应用的 Dock 图标",
+                    UserEvent::TogglePause,
+                    &auth_proxy,
+                );
+            }
+            Event::UserEvent(UserEvent::AuthFailed(message)) => {
+                println!("[Auth] {}", message);
+                let _ = webview.evaluate_script(&format!(
+                    "showNotice({});",
+                    serde_json::to_string(&message).unwrap()
+                ));
+            }
+            Event::UserEvent(UserEvent::TogglePause) => {
+                let result = if paused {
+                    pause::resume(&mut apps, false)
+                } else {
+                    pause::pause(&mut apps, false)
+                };
+                match result {
+                    Ok(results) => {
+                        paused = !paused;
+                        let tag = if paused { "[Pause]" } else { "[Resume]" };
+                        for result in &results {
+                            if let Some(err) = &result.error {
+                                println!(
+                                    "{} 处理失败：{}，错误：{}",
+                                    tag,
+                                    result.path.display(),
+                                    err
+                                );
+                                failures += 1;
+                            }
+                        }
+                        println!("{} 已处理 {} 个应用", tag, results.len());
+                        autosave.mark_dirty();
+                    }
+                    Err(err) => {
+                        println!("[Pause] 切换暂停状态失败：{}", err);
+                        failures += 1;
+                    }
+                }
+                let _ = webview.evaluate_script(&format!("setPaused({});", paused));
+                rebuild_list(&webview, &apps);
+            }
+            Event::UserEvent(UserEvent::ListBackups) => {
+                show_backups(&webview);
+            }
+            #[cfg(not(feature = "minimal-ui"))]
+            Event::UserEvent(UserEvent::Stats) => {
+                show_stats(&webview, &apps);
+            }
+            Event::UserEvent(UserEvent::SearchHistory(filter)) => {
+                search_history(&webview, &filter);
+            }
+            Event::UserEvent(UserEvent::ExportHistory(filter, format)) => {
+                let message = match export_history(&filter, format) {
+                    Ok(path) => {
+                        println!("[History] 已导出操作历史：{}", path.display());
+                        format!("操作历史已导出到 {}", path.display())
+                    }
+                    Err(err) => {
+                        println!("[History] 导出操作历史失败：{}", err);
+                        failures += 1;
+                        format!("导出操作历史失败：{}", err)
+                    }
+                };
+                let _ = webview.evaluate_script(&format!(
+                    "showNotice({});",
+                    serde_json::to_string(&message).unwrap()
+                ));
+            }
+            Event::UserEvent(UserEvent::ExportProfile(selected)) => {
+                let message = match export_profile(&apps, &selected) {
+                    Ok((path, count)) => {
+                        println!("[Profile] 已导出 {} 个应用：{}", count, path.display());
+                        format!("已导出 {} 个应用到 {}", count, path.display())
+                    }
+                    Err(err) => {
+                        println!("[Profile] 导出配置文件失败：{}", err);
+                        format!("导出配置文件失败：{}", err)
+                    }
+                };
+                let _ = webview.evaluate_script(&format!(
+                    "showNotice({});",
+                    serde_json::to_string(&message).unwrap()
+                ));
+            }
+            Event::UserEvent(UserEvent::ListSchedules) => {
+                show_schedules(&webview, None);
+            }
+            Event::UserEvent(UserEvent::AddSchedule(schedule)) if auth::required() => {
+                confirm_then(
+                    "计划会在指定时间自动启用配置文件",
+                    UserEvent::AddSchedule(schedule),
+                    &auth_proxy,
+                );
+            }
+            Event::UserEvent(UserEvent::AddSchedule(schedule)) => {
+                let error = std::env::current_exe()
+                    .map_err(|err| err.into())
+                    .and_then(|exe| agent::install_profile_schedule(&schedule, &exe));
+                let error = match error {
+                    Ok(path) => {
+                        println!(
+                            "[Schedule] 已添加计划 {}：{}，{}",
+                            schedule.name,
+                            schedule.describe(),
+                            path.display()
+                        );
+                        None
+                    }
+                    Err(err) => {
+                        println!("[Schedule] 添加计划失败：{}", err);
+                        Some(format!("添加计划失败：{}", err))
+                    }
+                };
+                show_schedules(&webview, error.as_deref());
+            }
+            Event::UserEvent(UserEvent::RemoveSchedule(name)) if auth::required() => {
+                confirm_then(
+                    "删除按计划启用配置文件的任务",
+                    UserEvent::RemoveSchedule(name),
+                    &auth_proxy,
+                );
+            }
+            Event::UserEvent(UserEvent::RemoveSchedule(name)) => {
+                let error = match agent::uninstall_profile_schedule(&name) {
+                    Ok(_) => {
+                        println!("[Schedule] 已删除计划：{}", name);
+                        None
+                    }
+                    Err(err) => {
+                        println!("[Schedule] 删除计划失败：{}", err);
+                        Some(format!("删除计划失败：{}", err))
+                    }
+                };
+                show_schedules(&webview, error.as_deref());
+            }
+            Event::UserEvent(UserEvent::ScheduleFailed(err)) => {
+                show_schedules(&webview, Some(&err));
+            }
+            Event::UserEvent(UserEvent::ViewCrash(report)) => {
+                let _ = Command::new("open").arg(&report).spawn();
+            }
+            Event::UserEvent(UserEvent::CrashSeen(report)) => {
+                if let Err(err) = crash::mark_crash_seen(&report) {
+                    println!("[Crash] 记录崩溃报告状态失败：{}", err);
+                }
+            }
+            Event::UserEvent(UserEvent::CreateReport(anonymize)) => {
+                // 先写入未保存的修改，诊断包中的应用列表与界面一致。
+                flush_autosave(&mut autosave, &mut apps, true);
+                let output = report::default_report_path();
+                let message = match report::create_report(&output, anonymize) {
+                    Ok(_) => {
+                        println!("[Report] 已生成诊断包：{}", output.display());
+                        let _ = Command::new("open").arg("-R").arg(&output).spawn();
+                        let _ = Command::new("open").arg(ISSUES_URL).spawn();
+                        format!(
+                            "诊断包已保存到 {}，请在打开的 GitHub 页面中附上该文件",
+                            output.display()
+                        )
+                    }
+                    Err(err) => {
+                        println!("[Report] 生成诊断包失败：{}", err);
+                        failures += 1;
+                        format!("生成诊断包失败：{}", err)
+                    }
+                };
+                let _ = webview.evaluate_script(&format!(
+                    "showNotice({});",
+                    serde_json::to_string(&message).unwrap()
+                ));
+            }
+            Event::UserEvent(UserEvent::RestoreBackup(path, created_at)) => {
+                match backup::restore_backup(&path, created_at) {
+                    Ok(()) => {
+                        println!("[Backup] 已用备份恢复：{}", path.display());
+                        if let Some(app) = apps.iter_mut().find(|app| app.path == path) {
+                            app.clear_checksum();
+                            autosave.mark_dirty();
+                        }
+                    }
+                    Err(err) => {
+                        println!("[Backup] 恢复备份失败：{}，错误：{}", path.display(), err);
+                        failures += 1;
+                    }
+                }
+                show_backups(&webview);
+                rebuild_list(&webview, &apps);
+            }
+            Event::UserEvent(UserEvent::ReloadConfig) => {
+                // 扫描目录和间隔可能变了，重新启动监视线程；规则等在用到时才读取配置。
+                let message = match config::load_config() {
+                    Ok(_) => {
+                        _watcher = spawn_watcher(watch_proxy.clone());
+                        events::emit(events::Event::ConfigReloaded);
+                        "已重新加载配置".to_string()
+                    }
+                    Err(err) => format!("读取配置失败，继续使用原配置：{}", err),
+                };
+                println!("[Config] {}", message);
+                apply_locale(&webview);
+                apply_text_scale(&webview, &mut zoom);
+                apply_window_options(&window, &webview);
+                apply_shortcuts(&webview, &status_item, &mut hotkeys);
+                let _ = webview.evaluate_script(&format!("setReadOnly({});", config::read_only()));
+                let _ = webview.evaluate_script(&format!(
+                    "showNotice({});",
+                    serde_json::to_string(&message).unwrap()
+                ));
+            }
+            Event::UserEvent(UserEvent::StateChanged) if !loading => {
+                // 自己保存时时间戳已经记下，reload 不会重复读取。
+                match autosave.reload(&apps) {
+                    Ok(Some(merged)) => {
+                        println!("[State] 应用列表已被其它进程修改，已重新读取");
+                        apps = merged;
+                        rebuild_list(&webview, &apps);
+                    }
+                    Ok(None) => {}
+                    Err(err) => println!("[State] 重新读取应用列表失败：{}", err),
+                }
+            }
+            Event::UserEvent(UserEvent::Woke) if !paused => {
+                // 应用更新常在夜间安装，唤醒后检查一遍，只在发现隐藏失效时提示。
+                println!("[Wake] 系统已唤醒，检查已管理的应用");
+                let drifted: Vec<PathBuf> = apps
+                    .iter()
+                    .filter(|app| {
+                        app.path.exists() && !is_dock_icon_hidden(&app.path).unwrap_or(true)
+                    })
+                    .map(|app| app.path.clone())
+                    .collect();
+                record_drifts(&apps, paused);
+                let wave = check_update_wave(&webview, &wake_proxy, &mut apps, &mut autosave);
+                rebuild_list(&webview, &apps);
+                if !wave && !drifted.is_empty() {
+                    let message = format!("唤醒后发现 {} 个应用的隐藏已失效", drifted.len());
+                    println!("[Wake] {}", message);
+                    announce_drifted(&webview, &wake_proxy, &message, drifted);
+                }
+            }
+            Event::UserEvent(UserEvent::VolumeMounted(mount))
+                if apps
+                    .iter()
+                    .any(|app| volume::volume_of(&app.path).as_ref() == Some(&mount)) =>
+            {
+                // 卸载期间列表显示为离线；卷重新挂载后校验其上的应用，期间可能在别的 Mac 上被更新过。
+                let on_volume: Vec<&ManagedApp> = apps
+                    .iter()
+                    .filter(|app| volume::volume_of(&app.path).as_ref() == Some(&mount))
+                    .collect();
+                println!(
+                    "[Volume] {} 已挂载，校验其上的 {} 个应用",
+                    mount.display(),
+                    on_volume.len()
+                );
+                let drifted: Vec<PathBuf> = on_volume
+                    .iter()
+                    .filter(|app| {
+                        app.path.exists() && !is_dock_icon_hidden(&app.path).unwrap_or(true)
+                    })
+                    .map(|app| app.path.clone())
+                    .collect();
+                record_drifts(&apps, paused);
+                rebuild_list(&webview, &apps);
+                if !paused && !drifted.is_empty() {
+                    let message = format!(
+                        "{} 重新挂载后发现 {} 个应用的隐藏已失效",
+                        app_display_name(&mount),
+                        drifted.len()
+                    );
+                    println!("[Volume] {}", message);
+                    announce_drifted(&webview, &wake_proxy, &message, drifted);
+                }
+            }
+            Event::UserEvent(UserEvent::SetLanguage(language)) => {
+                match i18n::save_language(language.as_deref()) {
+                    Ok(()) => println!(
+                        "[I18n] 界面语言改为 {}",
+                        language.as_deref().unwrap_or("跟随系统")
+                    ),
+                    Err(err) => println!("[I18n] 保存界面语言失败：{}", err),
+                }
+                apply_locale(&webview);
+            }
+            Event::UserEvent(UserEvent::SetWindowOption(key, enabled))
+                if key == "always_on_top" || key == "all_spaces" =>
+            {
+                match config::update_config(&key, Some(enabled.into())) {
+                    Ok(()) => println!("[Window] {} 改为 {}", key, enabled),
+                    Err(err) => println!("[Window] 保存窗口选项失败：{}", err),
+                }
+                apply_window_options(&window, &webview);
+            }
+            Event::UserEvent(UserEvent::SaveShortcuts(map)) => match shortcuts::save(&map) {
+                Ok(()) => {
+                    println!("[Shortcuts] 已保存快捷键");
+                    apply_shortcuts(&webview, &status_item, &mut hotkeys);
+                    let _ = webview.evaluate_script("closeModal('shortcuts-dialog');");
+                }
+                Err(err) => {
+                    println!("[Shortcuts] 保存快捷键失败：{}", err);
+                    let _ = webview.evaluate_script(&format!(
+                        "showShortcutErrors({});",
+                        serde_json::to_string(&err.to_string().lines().collect::<Vec<_>>())
+                            .unwrap()
+                    ));
+                }
+            },
+            Event::UserEvent(UserEvent::Rescan) => {
+                let mut adopted = 0;
+                for path in ScanScope::load().find_hidden_apps() {
+                    if apps.iter().any(|app| app.path == path) {
+                        continue;
+                    }
+                    println!("[Rescan] 发现已隐藏的应用：{}", path.display());
+                    let mut app = ManagedApp::new(path);
+                    app.record_checksum();
+                    apps.push(app);
+                    adopted += 1;
+                }
+                println!("[Rescan] 扫描完成，新加入 {} 项", adopted);
+                if adopted > 0 {
+                    autosave.mark_dirty();
+                }
+                rebuild_list(&webview, &apps);
+            }
+            Event::UserEvent(UserEvent::Acknowledge(path)) => {
+                if let Some(app) = apps.iter_mut().find(|app| app.path == path) {
+                    println!("[Checksum] 接受外部修改并更新校验值：{}", path.display());
+                    app.record_checksum();
+                    autosave.mark_dirty();
+                }
+                rebuild_list(&webview, &apps);
+            }
+            Event::UserEvent(UserEvent::Reapply(path, force)) => {
+                let display = path.display().to_string();
+                println!("[Event] 收到 Reapply 事件：{}", display);
+                let external = apps
+                    .iter()
+                    .find(|app| app.path == path)
+                    .filter(|_| !force)
+                    .and_then(ManagedApp::external_change);
+                if let Some(reason) = external {
+                    println!(
+                        "[Reapply] {} 由其他用户或工具管理：{}，等待确认",
+                        display, reason
+                    );
+                    warn_external(&webview, &path, &reason, "forceReapply");
+                } else {
+                    match apps.iter().position(|app| app.path == path) {
+                        None => {
+                            println!("[Reapply] 未找到对应记录，忽略：{}", display);
+                        }
+                        Some(index) => match apps[index].hide_dock_icon() {
+                            Ok(_) => {
+                                println!("[Reapply] 已重新隐藏 Dock 图标：{}", display);
+                                apps[index].record_checksum();
+                                relaunch_in_background(apps[index].clone());
+                                autosave.mark_dirty();
+                                rebuild_list(&webview, &apps);
+                            }
+                            Err(err) => {
+                                println!(
+                                    "[Reapply] 重新隐藏 Dock 图标失败：{}，错误：{}",
+                                    display, err
+                                );
+                                failures += 1;
+                                show_error(&webview, &ErrorView::new(&path, &*err, "reapply"));
+                            }
+                        },
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if refresh_status {
+            status_item.update(&apps, failures, paused);
+        }
+
+        if *control_flow != ControlFlow::Exit {
+            if flush_autosave(&mut autosave, &mut apps, false) {
+                println!("[State] 已合并其它进程对应用列表的修改");
+                rebuild_list(&webview, &apps);
+            }
+            if let Some(due) = autosave.due() {
+                *control_flow = ControlFlow::WaitUntil(due);
+            }
+        }
+    });
+}
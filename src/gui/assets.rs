@@ -0,0 +1,118 @@
+use std::borrow::Cow;
+#[cfg(not(feature = "minimal-ui"))]
+use std::path::Path;
+#[cfg(not(feature = "minimal-ui"))]
+use std::thread;
+
+#[cfg(not(feature = "minimal-ui"))]
+use osx_dock_dodger_rs::bundle::{icon_png, is_app_bundle};
+#[cfg(not(feature = "minimal-ui"))]
+use osx_dock_dodger_rs::config;
+#[cfg(not(feature = "minimal-ui"))]
+use wry::RequestAsyncResponder;
+use wry::WebViewBuilder;
+use wry::http::{Request, Response};
+
+#[cfg(not(feature = "minimal-ui"))]
+const ICON_SIZE: u32 = 128;
+
+// 内嵌的界面页面。默认直接内嵌原文交给 with_html；
+// 启用 minimal-ui 时在构建时用 brotli 预压缩（见 build.rs），通过 dockdodger:// 协议带 Content-Encoding 提供。
+#[cfg(not(feature = "minimal-ui"))]
+const PAGES: [(&str, &str); 3] = [
+    ("index.html", include_str!("index.html")),
+    ("quick.html", include_str!("quick.html")),
+    ("drop.html", include_str!("drop.html")),
+];
+
+#[cfg(feature = "minimal-ui")]
+const PAGES: [(&str, &[u8]); 3] = [
+    (
+        "index.html",
+        include_bytes!(concat!(env!("OUT_DIR"), "/index.html.br")),
+    ),
+    (
+        "quick.html",
+        include_bytes!(concat!(env!("OUT_DIR"), "/quick.html.br")),
+    ),
+    (
+        "drop.html",
+        include_bytes!(concat!(env!("OUT_DIR"), "/drop.html.br")),
+    ),
+];
+
+#[cfg(feature = "minimal-ui")]
+const SCHEME: &str = "dockdodger";
+
+#[cfg(not(feature = "minimal-ui"))]
+pub fn page<'a>(builder: WebViewBuilder<'a>, name: &str) -> WebViewBuilder<'a> {
+    let html = PAGES
+        .iter()
+        .find(|(page, _)| *page == name)
+        .map_or("", |(_, html)| *html);
+    builder.with_html(html)
+}
+
+// 页面脚本通过 window.MINIMAL_UI 隐藏图标与统计。
+#[cfg(feature = "minimal-ui")]
+pub fn page<'a>(builder: WebViewBuilder<'a>, name: &str) -> WebViewBuilder<'a> {
+    builder
+        .with_initialization_script("window.MINIMAL_UI = true;")
+        .with_custom_protocol(SCHEME.into(), serve_page)
+        .with_url(format!("{}://localhost/{}", SCHEME, name))
+}
+
+#[cfg(feature = "minimal-ui")]
+fn serve_page(request: Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> {
+    let name = request.uri().path().trim_start_matches('/');
+    match PAGES.iter().find(|(page, _)| *page == name) {
+        Some((_, compressed)) => Response::builder()
+            .header("Content-Type", "text/html; charset=utf-8")
+            .header("Content-Encoding", "br")
+            .body(Cow::Borrowed(*compressed))
+            .unwrap(),
+        None => not_found(),
+    }
+}
+
+// appicon://localhost/?path=<应用路径> 返回应用图标的 PNG；minimal-ui 不提供图标。
+#[cfg(not(feature = "minimal-ui"))]
+pub fn with_icons(builder: WebViewBuilder<'_>) -> WebViewBuilder<'_> {
+    builder.with_asynchronous_custom_protocol("appicon".into(), serve_icon)
+}
+
+#[cfg(feature = "minimal-ui")]
+pub fn with_icons(builder: WebViewBuilder<'_>) -> WebViewBuilder<'_> {
+    builder
+}
+
+// 转换图标要调用 sips，放到后台线程，不阻塞窗口绘制。
+#[cfg(not(feature = "minimal-ui"))]
+fn serve_icon(request: Request<Vec<u8>>, responder: RequestAsyncResponder) {
+    thread::spawn(move || responder.respond(icon_response(&request)));
+}
+
+#[cfg(not(feature = "minimal-ui"))]
+fn icon_response(request: &Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> {
+    let png = request
+        .uri()
+        .query()
+        .and_then(|query| query.strip_prefix("path="))
+        .map(config::percent_decode)
+        .filter(|path| is_app_bundle(Path::new(path)))
+        .and_then(|path| icon_png(Path::new(&path), ICON_SIZE));
+    match png {
+        Some(png) => Response::builder()
+            .header("Content-Type", "image/png")
+            .body(Cow::Owned(png))
+            .unwrap(),
+        None => not_found(),
+    }
+}
+
+fn not_found() -> Response<Cow<'static, [u8]>> {
+    Response::builder()
+        .status(404)
+        .body(Cow::Borrowed(&[][..]))
+        .unwrap()
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use osx_dock_dodger_rs::config;
+use tao::dpi::LogicalSize;
+use tao::event_loop::EventLoopWindowTarget;
+use tao::window::{Window, WindowBuilder, WindowId};
+use wry::http::Request;
+use wry::{DragDropEvent, WebView, WebViewBuilder};
+
+// 悬浮的投放窗口：始终置顶、在所有桌面可见，把应用拖到上面即可隐藏，不必调出主窗口。
+// 关闭按钮只是收起，可以通过全局快捷键或菜单栏再次打开。
+pub struct DropTarget {
+    window: Window,
+    _webview: WebView,
+}
+
+impl DropTarget {
+    pub fn new<T: 'static, F>(
+        target: &EventLoopWindowTarget<T>,
+        on_drop: F,
+    ) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        F: Fn(Vec<PathBuf>) + 'static,
+    {
+        let window = WindowBuilder::new()
+            .with_title("拖到这里隐藏")
+            .with_inner_size(LogicalSize::new(180.0, 150.0))
+            .with_resizable(false)
+            .with_always_on_top(true)
+            .with_visible_on_all_workspaces(true)
+            .with_visible(false)
+            .build(target)?;
+        // 进入、离开交给网页自己高亮，只拦截真正的放下。
+        // 拖入的文字由网页转发过来，解析出应用路径后同样交给 on_drop。
+        let on_drop = Rc::new(on_drop);
+        let on_text = on_drop.clone();
+        let webview = super::assets::page(WebViewBuilder::new(&window), "drop.html")
+            .with_drag_drop_handler(move |event| {
+                if let DragDropEvent::Drop { paths, .. } = event
+                    && !paths.is_empty()
+                {
+                    on_drop(paths);
+                    true
+                } else {
+                    false
+                }
+            })
+            .with_ipc_handler(move |req: Request<String>| {
+                let paths = config::app_paths_in_text(req.body());
+                if paths.is_empty() {
+                    println!("[DropTarget] 拖入的文字中没有找到应用路径");
+                } else {
+                    on_text(paths);
+                }
+            })
+            .build()?;
+        Ok(DropTarget {
+            window,
+            _webview: webview,
+        })
+    }
+
+    pub fn window_id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    pub fn hide(&self) {
+        self.window.set_visible(false);
+    }
+
+    pub fn toggle(&self) {
+        let visible = !self.window.is_visible();
+        self.window.set_visible(visible);
+    }
+}
@@ -0,0 +1,67 @@
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+
+// 全局快捷键：其它应用处于前台时也会触发。按操作名注册，触发时再把 id 换回操作名。
+pub struct GlobalHotKeys {
+    manager: Option<GlobalHotKeyManager>,
+    registered: Vec<(&'static str, HotKey)>,
+}
+
+impl GlobalHotKeys {
+    pub fn new() -> Self {
+        let manager = GlobalHotKeyManager::new()
+            .inspect_err(|err| println!("[HotKeys] 无法注册全局快捷键：{}", err))
+            .ok();
+        GlobalHotKeys {
+            manager,
+            registered: Vec::new(),
+        }
+    }
+
+    // 回调在后台线程执行。
+    pub fn install<F>(on_pressed: F)
+    where
+        F: Fn(u32) + Send + Sync + 'static,
+    {
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.state == HotKeyState::Pressed {
+                on_pressed(event.id);
+            }
+        }));
+    }
+
+    // 空字符串表示不使用全局快捷键。
+    pub fn set(&mut self, action: &'static str, accelerator: &str) {
+        let Some(manager) = &self.manager else {
+            return;
+        };
+        if let Some(index) = self.registered.iter().position(|(name, _)| *name == action) {
+            let (_, old) = self.registered.remove(index);
+            let _ = manager.unregister(old);
+        }
+        if accelerator.is_empty() {
+            return;
+        }
+        let hotkey = match accelerator.parse::<HotKey>() {
+            Ok(hotkey) => hotkey,
+            Err(err) => {
+                println!("[HotKeys] 无法解析快捷键 {}：{}", accelerator, err);
+                return;
+            }
+        };
+        match manager.register(hotkey) {
+            Ok(()) => self.registered.push((action, hotkey)),
+            Err(err) => println!(
+                "[HotKeys] 注册全局快捷键 {} 失败（可能已被其它应用占用）：{}",
+                accelerator, err
+            ),
+        }
+    }
+
+    pub fn action(&self, id: u32) -> Option<&'static str> {
+        self.registered
+            .iter()
+            .find(|(_, hotkey)| hotkey.id() == id)
+            .map(|(action, _)| *action)
+    }
+}
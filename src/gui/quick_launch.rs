@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use osx_dock_dodger_rs::bundle::bundle_name;
+use osx_dock_dodger_rs::state::ManagedApp;
+use serde::{Deserialize, Serialize};
+use tao::dpi::LogicalSize;
+use tao::event_loop::EventLoopWindowTarget;
+use tao::window::{Window, WindowBuilder, WindowId};
+use wry::http::Request;
+use wry::{WebView, WebViewBuilder};
+
+#[derive(Serialize)]
+struct QuickEntry {
+    path: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct QuickRequest {
+    cmd: String,
+    #[serde(default)]
+    path: String,
+}
+
+// 全局快速启动：不打开主窗口，按下全局快捷键弹出一个只有搜索框的小窗口，
+// 在已隐藏的应用中搜索并启动。窗口失去焦点或按 Esc 时收起。
+pub struct QuickLauncher {
+    window: Window,
+    webview: WebView,
+}
+
+impl QuickLauncher {
+    pub fn new<T: 'static, L, D>(
+        target: &EventLoopWindowTarget<T>,
+        on_launch: L,
+        on_dismiss: D,
+    ) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        L: Fn(PathBuf) + 'static,
+        D: Fn() + 'static,
+    {
+        let window = WindowBuilder::new()
+            .with_title("快速启动")
+            .with_inner_size(LogicalSize::new(520.0, 360.0))
+            .with_decorations(false)
+            .with_resizable(false)
+            .with_always_on_top(true)
+            .with_visible(false)
+            .build(target)?;
+        let webview = super::assets::with_icons(super::assets::page(
+            WebViewBuilder::new(&window),
+            "quick.html",
+        ))
+        .with_ipc_handler(move |req: Request<String>| {
+            if let Ok(data) = serde_json::from_str::<QuickRequest>(req.body()) {
+                match data.cmd.as_str() {
+                    "launch" => on_launch(PathBuf::from(data.path)),
+                    "dismiss" => on_dismiss(),
+                    _ => {}
+                }
+            }
+        })
+        .build()?;
+        Ok(QuickLauncher { window, webview })
+    }
+
+    pub fn window_id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    // 只列出当前能启动的应用：所在磁盘未挂载的跳过。
+    pub fn show(&self, apps: &[ManagedApp]) {
+        let mut entries: Vec<QuickEntry> = apps
+            .iter()
+            .filter(|app| app.path.exists())
+            .map(|app| QuickEntry {
+                path: app.path.display().to_string(),
+                name: bundle_name(&app.path).unwrap_or_else(|| super::app_display_name(&app.path)),
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.name.to_lowercase());
+        let _ = self.webview.evaluate_script(&format!(
+            "showApps({});",
+            serde_json::to_string(&entries).unwrap()
+        ));
+        self.window.set_visible(true);
+        self.window.set_focus();
+        let _ = self.webview.focus();
+    }
+
+    pub fn hide(&self) {
+        self.window.set_visible(false);
+    }
+
+    pub fn toggle(&self, apps: &[ManagedApp]) {
+        if self.window.is_visible() {
+            self.hide();
+        } else {
+            self.show(apps);
+        }
+    }
+}
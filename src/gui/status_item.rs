@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[cfg(not(feature = "minimal-ui"))]
+use osx_dock_dodger_rs::bundle::icon_png;
+use osx_dock_dodger_rs::bundle::{bundle_name, is_dock_icon_hidden};
+use osx_dock_dodger_rs::history::{self, HistoryAction, HistoryEntry};
+use osx_dock_dodger_rs::state::ManagedApp;
+use osx_dock_dodger_rs::volume;
+use tray_icon::menu::accelerator::Accelerator;
+use tray_icon::menu::{Icon, IconMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tray_icon::{TrayIcon, TrayIconBuilder};
+
+const TOGGLE_PAUSE_ID: &str = "toggle-pause";
+const TOGGLE_DROP_TARGET_ID: &str = "toggle-drop-target";
+// “启动已隐藏的应用”子菜单中各项的 id 为此前缀加应用路径。
+const LAUNCH_PREFIX: &str = "launch:";
+// 撤销最近一次操作的菜单项：撤销隐藏即恢复，撤销恢复即重新隐藏。
+const UNDO_HIDE_PREFIX: &str = "undo-hide:";
+const UNDO_RESTORE_PREFIX: &str = "undo-restore:";
+#[cfg(not(feature = "minimal-ui"))]
+const MENU_ICON_SIZE: u32 = 32;
+const RECENT_LIMIT: usize = 5;
+// “最近操作”标题之后的位置，依次为启动子菜单、分隔线、标题。
+const RECENT_POSITION: usize = 3;
+
+pub enum MenuCommand {
+    TogglePause,
+    ToggleDropTarget,
+    Launch(PathBuf),
+    UndoHide(PathBuf),
+    UndoRestore(PathBuf),
+}
+
+// 菜单栏状态项：显示已隐藏的应用数量，有失效项或失败操作时切换为警告符号；
+// 菜单中可以直接启动已隐藏的应用，并列出最近的操作。
+#[derive(Default)]
+pub struct StatusItem {
+    tray: Option<TrayIcon>,
+    menu: Option<Menu>,
+    pause_item: Option<MenuItem>,
+    launch_menu: Option<Submenu>,
+    launch_paths: Vec<PathBuf>,
+    icons: HashMap<PathBuf, Option<Icon>>,
+    recent_items: Vec<MenuItem>,
+    // 上次读取时历史文件的大小；只追加写入，大小不变就不必重新读取。
+    history_revision: Option<u64>,
+    tooltip: String,
+}
+
+fn pause_label(paused: bool) -> &'static str {
+    if paused {
+        "继续隐藏"
+    } else {
+        "暂停隐藏"
+    }
+}
+
+impl StatusItem {
+    // macOS 要求在事件循环启动之后再创建状态项。
+    pub fn install<F>(&mut self, paused: bool, on_command: F)
+    where
+        F: Fn(MenuCommand) + Send + Sync + 'static,
+    {
+        let menu = Menu::new();
+        let launch_menu = Submenu::new("启动已隐藏的应用", false);
+        let recent_header = MenuItem::new("最近操作", false, None);
+        let drop_item = MenuItem::with_id(TOGGLE_DROP_TARGET_ID, "显示/隐藏投放窗口", true, None);
+        let pause_item = MenuItem::with_id(TOGGLE_PAUSE_ID, pause_label(paused), true, None);
+        if let Err(err) = menu.append_items(&[
+            &launch_menu,
+            &PredefinedMenuItem::separator(),
+            &recent_header,
+            &PredefinedMenuItem::separator(),
+            &drop_item,
+            &pause_item,
+        ]) {
+            println!("[StatusItem] 创建菜单失败：{}", err);
+        }
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            let id = event.id.0.as_str();
+            let command = if id == TOGGLE_PAUSE_ID {
+                MenuCommand::TogglePause
+            } else if id == TOGGLE_DROP_TARGET_ID {
+                MenuCommand::ToggleDropTarget
+            } else if let Some(path) = id.strip_prefix(LAUNCH_PREFIX) {
+                MenuCommand::Launch(PathBuf::from(path))
+            } else if let Some(path) = id.strip_prefix(UNDO_HIDE_PREFIX) {
+                MenuCommand::UndoHide(PathBuf::from(path))
+            } else if let Some(path) = id.strip_prefix(UNDO_RESTORE_PREFIX) {
+                MenuCommand::UndoRestore(PathBuf::from(path))
+            } else {
+                return;
+            };
+            on_command(command);
+        }));
+        match TrayIconBuilder::new()
+            .with_title("🛶")
+            .with_menu(Box::new(menu.clone()))
+            .build()
+        {
+            Ok(tray) => {
+                self.tray = Some(tray);
+                self.menu = Some(menu);
+                self.pause_item = Some(pause_item);
+                self.launch_menu = Some(launch_menu);
+            }
+            Err(err) => println!("[StatusItem] 创建菜单栏图标失败：{}", err),
+        }
+    }
+
+    // 空字符串表示不设置快捷键。
+    pub fn set_pause_accelerator(&self, accelerator: &str) {
+        let Some(item) = &self.pause_item else {
+            return;
+        };
+        let parsed = match accelerator.parse::<Accelerator>() {
+            Ok(parsed) => Some(parsed),
+            Err(err) if !accelerator.is_empty() => {
+                println!("[StatusItem] 无法设置快捷键 {}：{}", accelerator, err);
+                return;
+            }
+            Err(_) => None,
+        };
+        if let Err(err) = item.set_accelerator(parsed) {
+            println!("[StatusItem] 设置快捷键失败：{}", err);
+        }
+    }
+
+    pub fn update(&mut self, apps: &[ManagedApp], failures: usize, paused: bool) {
+        if self.tray.is_none() {
+            return;
+        }
+        if let Some(item) = &self.pause_item {
+            item.set_text(pause_label(paused));
+        }
+        self.update_launch_menu(apps);
+        self.update_recent();
+        let Some(tray) = &self.tray else {
+            return;
+        };
+        if paused {
+            let tooltip = format!("已暂停，{} 个应用暂时恢复了 Dock 图标", apps.len());
+            if tooltip != self.tooltip {
+                tray.set_title(Some(format!("⏸ {}", apps.len())));
+                let _ = tray.set_tooltip(Some(&tooltip));
+                self.tooltip = tooltip;
+            }
+            return;
+        }
+        let drifted = apps
+            .iter()
+            .filter(|app| !volume::is_offline(&app.path))
+            .filter(|app| match app.checksum_matches() {
+                Some(matches) => !matches,
+                None => !is_dock_icon_hidden(&app.path).unwrap_or(false),
+            })
+            .count();
+        let attention = drifted + failures;
+        let title = if attention > 0 {
+            format!("⚠️ {}", attention)
+        } else {
+            format!("🛶 {}", apps.len())
+        };
+        let mut tooltip = format!("已隐藏 {} 个应用", apps.len());
+        if drifted > 0 {
+            tooltip.push_str(&format!("，{} 个隐藏可能已失效", drifted));
+        }
+        if failures > 0 {
+            tooltip.push_str(&format!("，{} 次操作失败", failures));
+        }
+        if tooltip == self.tooltip {
+            return;
+        }
+        tray.set_title(Some(title));
+        let _ = tray.set_tooltip(Some(&tooltip));
+        self.tooltip = tooltip;
+    }
+
+    // 列表变化时才重建子菜单；所在磁盘未挂载的应用无法启动，不列出。图标按路径缓存，
+    // 避免每次重建都调用 sips。
+    fn update_launch_menu(&mut self, apps: &[ManagedApp]) {
+        let Some(submenu) = &self.launch_menu else {
+            return;
+        };
+        let paths: Vec<PathBuf> = apps
+            .iter()
+            .filter(|app| !volume::is_offline(&app.path))
+            .map(|app| app.path.clone())
+            .collect();
+        if paths == self.launch_paths {
+            return;
+        }
+        for item in submenu.items() {
+            let _ = submenu.remove(item.as_ref());
+        }
+        for path in &paths {
+            let icon = self
+                .icons
+                .entry(path.clone())
+                .or_insert_with(|| menu_icon(path))
+                .clone();
+            let name = bundle_name(path).unwrap_or_else(|| super::app_display_name(path));
+            let item = IconMenuItem::with_id(
+                format!("{}{}", LAUNCH_PREFIX, path.display()),
+                name,
+                true,
+                icon,
+                None,
+            );
+            if let Err(err) = submenu.append(&item) {
+                println!("[StatusItem] 添加启动菜单项失败：{}", err);
+            }
+        }
+        submenu.set_enabled(!paths.is_empty());
+        self.launch_paths = paths;
+    }
+
+    // 历史变化时才重新读取，替换标题之后的条目；最近一次操作成功时附带撤销项。
+    fn update_recent(&mut self) {
+        let Some(menu) = &self.menu else {
+            return;
+        };
+        let revision = history::revision();
+        if self.history_revision == Some(revision) {
+            return;
+        }
+        self.history_revision = Some(revision);
+        for item in self.recent_items.drain(..) {
+            let _ = menu.remove(&item);
+        }
+        let entries = history::recent(RECENT_LIMIT).unwrap_or_else(|err| {
+            println!("[StatusItem] 读取操作历史失败：{}", err);
+            Vec::new()
+        });
+        let mut items: Vec<MenuItem> = entries
+            .iter()
+            .map(|entry| MenuItem::new(recent_label(entry), false, None))
+            .collect();
+        if items.is_empty() {
+            items.push(MenuItem::new("暂无操作记录", false, None));
+        }
+        items.extend(entries.first().and_then(undo_item));
+        for (offset, item) in items.iter().enumerate() {
+            if let Err(err) = menu.insert(item, RECENT_POSITION + offset) {
+                println!("[StatusItem] 添加最近操作失败：{}", err);
+            }
+        }
+        self.recent_items = items;
+    }
+}
+
+fn recent_label(entry: &HistoryEntry) -> String {
+    format!(
+        "{} {}：{}",
+        entry.action.label(),
+        super::app_display_name(&entry.path),
+        if entry.error.is_some() {
+            "失败"
+        } else {
+            "成功"
+        }
+    )
+}
+
+// 失败的操作没有改动，不需要撤销。
+fn undo_item(entry: &HistoryEntry) -> Option<MenuItem> {
+    if entry.error.is_some() {
+        return None;
+    }
+    let prefix = match entry.action {
+        HistoryAction::Hide => UNDO_HIDE_PREFIX,
+        HistoryAction::Restore => UNDO_RESTORE_PREFIX,
+        HistoryAction::Drift | HistoryAction::Dock => return None,
+    };
+    Some(MenuItem::with_id(
+        format!("{}{}", prefix, entry.path.display()),
+        format!(
+            "撤销{} {}",
+            entry.action.label(),
+            super::app_display_name(&entry.path)
+        ),
+        true,
+        None,
+    ))
+}
+
+// 菜单图标需要 RGBA 像素，把 sips 转出的 PNG 解码；失败时菜单项不带图标。
+#[cfg(not(feature = "minimal-ui"))]
+fn menu_icon(app: &Path) -> Option<Icon> {
+    let png = icon_png(app, MENU_ICON_SIZE)?;
+    let mut decoder = png::Decoder::new(png.as_slice());
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).ok()?;
+    buffer.truncate(info.buffer_size());
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer
+            .chunks(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+        _ => return None,
+    };
+    Icon::from_rgba(rgba, info.width, info.height).ok()
+}
+
+// minimal-ui 不带图标相关的功能，菜单项只显示名称。
+#[cfg(feature = "minimal-ui")]
+fn menu_icon(_app: &Path) -> Option<Icon> {
+    None
+}
@@ -0,0 +1,52 @@
+use std::fs;
+use std::path::Path;
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 提权失败（包括用户取消授权）时错误信息的开头，用于归类错误。
+pub const PRIVILEGED_ERROR: &str = "提权操作失败";
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn applescript_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', r"\\").replace('"', "\\\""))
+}
+
+// 通过 osascript 弹出系统的管理员授权对话框执行命令，只用于当前用户无法写入的文件。
+fn run_privileged(command: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let script = format!(
+        "do shell script {} with administrator privileges",
+        applescript_quote(command)
+    );
+    let output = Command::new("osascript").args(["-e", &script]).output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "{}：{}",
+            PRIVILEGED_ERROR,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+pub fn write_file_privileged(
+    target: &Path,
+    contents: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    let staged = std::env::temp_dir().join(format!("dock-dodger-{}-{}", process::id(), stamp));
+    fs::write(&staged, contents)?;
+    let result = run_privileged(&format!(
+        "/bin/cp {} {}",
+        shell_quote(&staged.display().to_string()),
+        shell_quote(&target.display().to_string())
+    ));
+    let _ = fs::remove_file(&staged);
+    result
+}
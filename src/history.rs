@@ -0,0 +1,464 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::crypto;
+use crate::i18n;
+use crate::state::write_atomic;
+use crate::state_store;
+
+const WEEK_SECS: u64 = 7 * 86400;
+const TREND_WEEKS: u64 = 8;
+const TOP_APPS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryAction {
+    Hide,
+    Restore,
+    // 校验时发现已管理应用的 Dock 图标又出现了
+    Drift,
+    // 随隐藏/恢复一起改动 Dock 固定项或启动台，单独记录以附上等价命令
+    Dock,
+}
+
+impl HistoryAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            HistoryAction::Hide => "隐藏",
+            HistoryAction::Restore => "恢复",
+            HistoryAction::Drift => "失效",
+            HistoryAction::Dock => "Dock 与启动台",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub at: u64,
+    pub action: HistoryAction,
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // 与这次修改等价的 plutil 命令，见 explain
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+}
+
+pub fn history_path() -> PathBuf {
+    config::data_dir().join("history.jsonl")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+// 每行一条 JSON，只追加不改写，多个进程同时写入也不会互相覆盖。
+pub(crate) fn append_json(entry: &HistoryEntry) -> io::Result<()> {
+    if config::read_only() {
+        return Err(io::Error::other(config::READ_ONLY_ERROR));
+    }
+    let path = history_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = crypto::seal_text(&serde_json::to_vec(entry)?)
+        .map_err(|err| io::Error::other(err.to_string()))?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+// 记录失败只打印日志，不影响本身的操作。
+pub fn record(action: HistoryAction, path: &Path, error: Option<String>) {
+    record_with_commands(action, path, error, Vec::new());
+}
+
+pub fn record_with_commands(
+    action: HistoryAction,
+    path: &Path,
+    error: Option<String>,
+    commands: Vec<String>,
+) {
+    let entry = HistoryEntry {
+        at: now_secs(),
+        action,
+        path: path.to_path_buf(),
+        error,
+        commands,
+    };
+    if let Err(err) = state_store::open().and_then(|store| store.append_history(&entry))
+        && !config::read_only()
+    {
+        eprintln!("[History] 记录操作历史失败：{}", err);
+    }
+}
+
+// 同一次漂移只记录一次：该应用最近一条记录已经是 drift 时跳过；返回是否新记录了一次。
+pub fn record_drift(path: &Path) -> bool {
+    let already = state_store::open()
+        .and_then(|store| store.last_history(path))
+        .ok()
+        .flatten()
+        .is_some_and(|entry| entry.action == HistoryAction::Drift);
+    if !already {
+        record(HistoryAction::Drift, path, None);
+    }
+    !already
+}
+
+pub fn load_history() -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+    query(&HistoryFilter::default())
+}
+
+// 按时间顺序返回符合条件的记录；SQLite 存储时按时间的条件走索引。
+pub fn query(filter: &HistoryFilter) -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+    state_store::open()?.load_history(filter)
+}
+
+// 历史有变化时改变，用来判断是否需要重新读取。
+pub fn revision() -> u64 {
+    state_store::open()
+        .map(|store| store.history_revision())
+        .unwrap_or(0)
+}
+
+// 无法解析或解密的行（例如写入中断留下的半行）直接跳过；
+// 但加密的行一条都解不开时（例如钥匙串中没有密钥）报错，不能当作没有历史。
+pub(crate) fn load_json_history() -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+    let contents = match fs::read_to_string(history_path()) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut entries = Vec::new();
+    let mut opened = false;
+    let mut first_error = None;
+    for line in contents.lines() {
+        match crypto::open_text(line) {
+            Ok(data) => {
+                opened |= crypto::is_sealed_text(line);
+                if let Ok(entry) = serde_json::from_slice(&data) {
+                    entries.push(entry);
+                }
+            }
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    match first_error {
+        Some(err) if !opened => Err(err),
+        _ => Ok(entries),
+    }
+}
+
+// 切换 encrypt_data 后随状态文件一起调用，把已有的历史转成当前设置的格式。
+pub(crate) fn reseal_json_history() -> Result<(), Box<dyn std::error::Error>> {
+    let entries = load_json_history()?;
+    let path = history_path();
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut contents = String::new();
+    for entry in &entries {
+        contents.push_str(&crypto::seal_text(&serde_json::to_vec(entry)?)?);
+        contents.push('\n');
+    }
+    write_atomic(&path, contents.as_bytes())?;
+    Ok(())
+}
+
+// 最近的隐藏与恢复操作，最新的在前；漂移是检测结果而不是操作，不计入。
+pub fn recent(limit: usize) -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+    state_store::open()?.recent_history(limit)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppCount {
+    pub path: PathBuf,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WeekCount {
+    // 该周第一天 0 点的时间戳（秒）
+    pub week_start: u64,
+    pub operations: usize,
+    pub drifts: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Stats {
+    pub hidden_now: usize,
+    pub total_operations: usize,
+    pub hides: usize,
+    pub restores: usize,
+    pub failures: usize,
+    pub drifts: usize,
+    pub most_toggled: Vec<AppCount>,
+    pub weeks: Vec<WeekCount>,
+}
+
+pub fn stats(entries: &[HistoryEntry], hidden_now: usize) -> Stats {
+    let count = |action| {
+        entries
+            .iter()
+            .filter(|entry| entry.action == action)
+            .count()
+    };
+    let mut toggles: HashMap<&Path, usize> = HashMap::new();
+    for entry in entries {
+        if matches!(entry.action, HistoryAction::Hide | HistoryAction::Restore)
+            && entry.error.is_none()
+        {
+            *toggles.entry(&entry.path).or_default() += 1;
+        }
+    }
+    let mut most_toggled: Vec<AppCount> = toggles
+        .into_iter()
+        .map(|(path, count)| AppCount {
+            path: path.to_path_buf(),
+            count,
+        })
+        .collect();
+    most_toggled.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.path.cmp(&b.path)));
+    most_toggled.truncate(TOP_APPS);
+
+    // 以周一为一周的开始（1970-01-01 是周四，偏移 3 天）。
+    let week_of = |at: u64| (at + 3 * 86400) / WEEK_SECS;
+    let current = week_of(now_secs());
+    let first = current.saturating_sub(TREND_WEEKS - 1);
+    let mut weeks: Vec<WeekCount> = (first..=current)
+        .map(|week| WeekCount {
+            week_start: (week * WEEK_SECS).saturating_sub(3 * 86400),
+            ..Default::default()
+        })
+        .collect();
+    for entry in entries {
+        let week = week_of(entry.at);
+        if week < first || week > current {
+            continue;
+        }
+        let bucket = &mut weeks[(week - first) as usize];
+        match entry.action {
+            HistoryAction::Drift => bucket.drifts += 1,
+            HistoryAction::Dock => {}
+            _ => bucket.operations += 1,
+        }
+    }
+
+    Stats {
+        hidden_now,
+        total_operations: count(HistoryAction::Hide) + count(HistoryAction::Restore),
+        hides: count(HistoryAction::Hide),
+        restores: count(HistoryAction::Restore),
+        failures: entries.iter().filter(|entry| entry.error.is_some()).count(),
+        drifts: count(HistoryAction::Drift),
+        most_toggled,
+        weeks,
+    }
+}
+
+// 按 UTC 计算，避免引入日期库：days_from_civil / civil_from_days 算法。
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// 解析 YYYY-MM-DD，返回当天 0 点（UTC）的时间戳。
+pub fn parse_date(text: &str) -> Option<u64> {
+    let mut parts = text.trim().splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts
+        .next()?
+        .parse()
+        .ok()
+        .filter(|month| (1..=12).contains(month))?;
+    let day = parts
+        .next()?
+        .parse()
+        .ok()
+        .filter(|day| (1..=31).contains(day))?;
+    u64::try_from(days_from_civil(year, month, day) * 86400).ok()
+}
+
+// 本机时区的 "YYYY-MM-DD HH:MM"（也可用 T 分隔），只写日期时为当天 0 点。
+pub fn parse_local_time(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (date, time) = match text.split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time.trim())),
+        None => (text, None),
+    };
+    let mut secs = parse_date(date)?;
+    if let Some(time) = time {
+        let (hour, minute) = time.split_once(':')?;
+        let hour: u64 = hour.parse().ok().filter(|hour| *hour < 24)?;
+        let minute: u64 = minute.parse().ok().filter(|minute| *minute < 60)?;
+        secs += hour * 3600 + minute * 60;
+    }
+    u64::try_from(secs as i64 - i18n::utc_offset(secs)).ok()
+}
+
+pub fn format_timestamp(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let rem = secs % 86400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub since: Option<u64>,
+    // 不含该时间点
+    pub until: Option<u64>,
+    // 完整路径，或文件名中包含的文字（不区分大小写）
+    pub apps: Vec<String>,
+    // 全文搜索：路径（含应用名）或错误信息中包含的文字，不区分大小写
+    pub text: Option<String>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        if self.since.is_some_and(|since| entry.at < since)
+            || self.until.is_some_and(|until| entry.at >= until)
+        {
+            return false;
+        }
+        if let Some(text) = &self.text {
+            let text = text.to_lowercase();
+            let found = entry.path.to_string_lossy().to_lowercase().contains(&text)
+                || entry
+                    .error
+                    .as_ref()
+                    .is_some_and(|error| error.to_lowercase().contains(&text));
+            if !found {
+                return false;
+            }
+        }
+        if self.apps.is_empty() {
+            return true;
+        }
+        let name = entry
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        self.apps
+            .iter()
+            .any(|app| entry.path == Path::new(app) || name.contains(&app.to_lowercase()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ExportRecord<'a> {
+    time: String,
+    #[serde(flatten)]
+    entry: &'a HistoryEntry,
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn export(entries: &[HistoryEntry], filter: &HistoryFilter, format: ExportFormat) -> String {
+    let entries = entries.iter().filter(|entry| filter.matches(entry));
+    match format {
+        ExportFormat::Csv => {
+            let mut csv = String::from("time,timestamp,action,path,status,error\n");
+            for entry in entries {
+                let action = serde_json::to_value(entry.action)
+                    .ok()
+                    .and_then(|value| value.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                let status = if entry.error.is_some() {
+                    "failed"
+                } else {
+                    "ok"
+                };
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    format_timestamp(entry.at),
+                    entry.at,
+                    action,
+                    csv_field(&entry.path.display().to_string()),
+                    status,
+                    csv_field(entry.error.as_deref().unwrap_or_default())
+                ));
+            }
+            csv
+        }
+        ExportFormat::Json => {
+            let records: Vec<ExportRecord> = entries
+                .map(|entry| ExportRecord {
+                    time: format_timestamp(entry.at),
+                    entry,
+                })
+                .collect();
+            serde_json::to_string_pretty(&records).unwrap_or_default() + "\n"
+        }
+    }
+}
@@ -0,0 +1,473 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, Config};
+use crate::events::now_secs;
+
+// 界面文字以简体中文写成，译文目录以中文原文为键：{ "language": "English", "strings": { "应用": "Apps" } }。
+// 原文中的数字等可变部分写作 {}，例如 "已隐藏 {} 个应用的 Dock 图标"。
+pub const SOURCE_LOCALE: &str = "zh-Hans";
+const SOURCE_NAME: &str = "简体中文";
+
+// 编译进程序的译文，保证单独运行二进制时也能切换语言。
+const BUILTIN: [(&str, &str); 1] = [("en", include_str!("../locales/en.json"))];
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct CatalogFile {
+    #[serde(default)]
+    language: String,
+    #[serde(default)]
+    strings: BTreeMap<String, String>,
+    #[serde(default)]
+    formats: Option<Formats>,
+    // "ltr" 或 "rtl"，省略时按语言判断
+    #[serde(default)]
+    direction: Option<String>,
+}
+
+// 从右向左书写的语言（按主语言标识）。
+const RTL_LANGUAGES: [&str; 8] = ["ar", "he", "fa", "ur", "yi", "ps", "ckb", "dv"];
+
+// 数字与日期的写法。日期模板中可用 {y} {m} {mm} {d} {dd} {b}（月份名称）{H} {HH} {h} {MM} {p}（上午/下午）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Formats {
+    pub group: String,
+    pub decimal: String,
+    pub date: String,
+    pub time: String,
+    pub short_date: String,
+    pub months: Vec<String>,
+    pub am_pm: Vec<String>,
+}
+
+// 译文没有提供 formats 时使用不易误解的 ISO 写法。
+impl Default for Formats {
+    fn default() -> Self {
+        Formats {
+            group: ",".into(),
+            decimal: ".".into(),
+            date: "{y}-{mm}-{dd}".into(),
+            time: "{HH}:{MM}".into(),
+            short_date: "{mm}-{dd}".into(),
+            months: [
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+            ]
+            .map(String::from)
+            .to_vec(),
+            am_pm: vec!["AM".into(), "PM".into()],
+        }
+    }
+}
+
+impl Formats {
+    fn source() -> Self {
+        Formats {
+            date: "{y}年{m}月{d}日".into(),
+            short_date: "{m}/{d}".into(),
+            am_pm: vec!["上午".into(), "下午".into()],
+            ..Formats::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocaleInfo {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Catalog {
+    pub locale: String,
+    pub strings: BTreeMap<String, String>,
+    #[serde(skip)]
+    pub formats: Formats,
+    // 界面据此切换为从右向左的布局
+    pub direction: String,
+}
+
+#[repr(C)]
+struct Tm {
+    sec: i32,
+    min: i32,
+    hour: i32,
+    mday: i32,
+    mon: i32,
+    year: i32,
+    wday: i32,
+    yday: i32,
+    isdst: i32,
+    gmtoff: i64,
+    zone: *const u8,
+}
+
+unsafe extern "C" {
+    fn localtime_r(time: *const i64, result: *mut Tm) -> *mut Tm;
+}
+
+fn local_tm(secs: u64) -> Tm {
+    let time = secs as i64;
+    let mut tm = Tm {
+        sec: 0,
+        min: 0,
+        hour: 0,
+        mday: 1,
+        mon: 0,
+        year: 70,
+        wday: 0,
+        yday: 0,
+        isdst: 0,
+        gmtoff: 0,
+        zone: std::ptr::null(),
+    };
+    unsafe { localtime_r(&time, &mut tm) };
+    tm
+}
+
+// 按本机时区拆分为 (年, 月, 日, 时, 分)。
+fn local_time(secs: u64) -> (i32, u32, u32, u32, u32) {
+    let tm = local_tm(secs);
+    (
+        tm.year + 1900,
+        tm.mon as u32 + 1,
+        tm.mday as u32,
+        tm.hour as u32,
+        tm.min as u32,
+    )
+}
+
+impl Catalog {
+    pub fn tr(&self, source: &str) -> String {
+        self.strings
+            .get(source)
+            .cloned()
+            .unwrap_or_else(|| source.to_string())
+    }
+
+    // 依次填入模板中的 {}。填好后的整句有译文时优先使用，用来处理单复数，例如 "1 天前" → "1 day ago"。
+    pub fn tr_args(&self, template: &str, args: &[String]) -> String {
+        let fill = |template: &str| {
+            let mut parts = template.split("{}");
+            let mut text = parts.next().unwrap_or_default().to_string();
+            for (index, part) in parts.enumerate() {
+                text.push_str(args.get(index).map(String::as_str).unwrap_or_default());
+                text.push_str(part);
+            }
+            text
+        };
+        let filled = fill(template);
+        match self.strings.get(&filled) {
+            Some(translated) => translated.clone(),
+            None => fill(&self.tr(template)),
+        }
+    }
+
+    pub fn number(&self, value: u64) -> String {
+        let digits = value.to_string();
+        let mut text = String::new();
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                text.push_str(&self.formats.group);
+            }
+            text.push(digit);
+        }
+        text
+    }
+
+    // 与 Finder 一致按 1000 进位。
+    pub fn size(&self, bytes: u64) -> String {
+        const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+        if bytes < 1000 {
+            return format!("{} B", self.number(bytes));
+        }
+        let mut value = bytes as f64 / 1000.0;
+        let mut unit = 0;
+        while value >= 999.95 && unit < UNITS.len() - 1 {
+            value /= 1000.0;
+            unit += 1;
+        }
+        let text = format!("{:.1}", value);
+        let (whole, fraction) = text.split_once('.').unwrap_or((&text, "0"));
+        format!(
+            "{}{}{} {}",
+            self.number(whole.parse().unwrap_or_default()),
+            self.formats.decimal,
+            fraction,
+            UNITS[unit]
+        )
+    }
+
+    fn fill_date(&self, template: &str, secs: u64) -> String {
+        let (year, month, day, hour, minute) = local_time(secs);
+        let formats = &self.formats;
+        let month_name = formats
+            .months
+            .get(month as usize - 1)
+            .cloned()
+            .unwrap_or_else(|| month.to_string());
+        let am_pm = formats
+            .am_pm
+            .get((hour >= 12) as usize)
+            .cloned()
+            .unwrap_or_default();
+        let hour12 = if hour.is_multiple_of(12) {
+            12
+        } else {
+            hour % 12
+        };
+        [
+            ("{y}", year.to_string()),
+            ("{mm}", format!("{:02}", month)),
+            ("{m}", month.to_string()),
+            ("{dd}", format!("{:02}", day)),
+            ("{d}", day.to_string()),
+            ("{b}", month_name),
+            ("{HH}", format!("{:02}", hour)),
+            ("{H}", hour.to_string()),
+            ("{h}", hour12.to_string()),
+            ("{MM}", format!("{:02}", minute)),
+            ("{p}", am_pm),
+        ]
+        .iter()
+        .fold(template.to_string(), |text, (token, value)| {
+            text.replace(token, value)
+        })
+    }
+
+    pub fn date(&self, secs: u64) -> String {
+        self.fill_date(&self.formats.date, secs)
+    }
+
+    pub fn short_date(&self, secs: u64) -> String {
+        self.fill_date(&self.formats.short_date, secs)
+    }
+
+    pub fn date_time(&self, secs: u64) -> String {
+        format!(
+            "{} {}",
+            self.date(secs),
+            self.fill_date(&self.formats.time, secs)
+        )
+    }
+
+    // 例如 "3 天前"；译文可以为 "1 天前" 等单独给出单数写法。
+    pub fn relative(&self, secs: u64) -> String {
+        const MINUTE: u64 = 60;
+        const HOUR: u64 = 60 * MINUTE;
+        const DAY: u64 = 24 * HOUR;
+        let elapsed = now_secs().saturating_sub(secs);
+        let (template, count) = match elapsed {
+            0..MINUTE => return self.tr("刚刚"),
+            MINUTE..HOUR => ("{} 分钟前", elapsed / MINUTE),
+            HOUR..DAY => ("{} 小时前", elapsed / HOUR),
+            _ if elapsed < 30 * DAY => ("{} 天前", elapsed / DAY),
+            _ if elapsed < 365 * DAY => ("{} 个月前", elapsed / (30 * DAY)),
+            _ => ("{} 年前", elapsed / (365 * DAY)),
+        };
+        self.tr_args(template, &[self.number(count)])
+    }
+}
+
+// 该时刻本机时区与 UTC 相差的秒数，东八区为 28800。
+pub fn utc_offset(secs: u64) -> i64 {
+    local_tm(secs).gmtoff
+}
+
+// 应用包中的 Contents/Resources/locales，随安装包一起分发。
+fn bundle_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.parent()?.parent()?.join("Resources/locales"))
+}
+
+// 数据目录下的 locales，放入 <语言>.json 即可使用社区翻译，无需重新编译。
+pub fn user_dir() -> PathBuf {
+    config::data_dir().join("locales")
+}
+
+fn read_dir(dir: &Path) -> Vec<(String, CatalogFile)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut catalogs: Vec<(String, CatalogFile)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.to_string();
+            let parsed = fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|contents| {
+                    serde_json::from_str::<CatalogFile>(&contents).map_err(|err| err.to_string())
+                });
+            match parsed {
+                Ok(catalog) => Some((id, catalog)),
+                Err(err) => {
+                    eprintln!("[I18n] 读取译文失败：{}，错误：{}", path.display(), err);
+                    None
+                }
+            }
+        })
+        .collect();
+    catalogs.sort_by(|a, b| a.0.cmp(&b.0));
+    catalogs
+}
+
+// 按内置、应用包、用户目录的顺序读取，同一语言后读到的条目覆盖先前的，方便只修正个别译文。
+fn load_all() -> BTreeMap<String, CatalogFile> {
+    let mut merged: BTreeMap<String, CatalogFile> = BTreeMap::new();
+    let builtin = BUILTIN.iter().filter_map(|(id, contents)| {
+        serde_json::from_str::<CatalogFile>(contents)
+            .ok()
+            .map(|catalog| (id.to_string(), catalog))
+    });
+    let bundled = bundle_dir().map(|dir| read_dir(&dir)).unwrap_or_default();
+    for (id, catalog) in builtin.chain(bundled).chain(read_dir(&user_dir())) {
+        let entry = merged.entry(id).or_default();
+        if !catalog.language.is_empty() {
+            entry.language = catalog.language;
+        }
+        if catalog.formats.is_some() {
+            entry.formats = catalog.formats;
+        }
+        if catalog.direction.is_some() {
+            entry.direction = catalog.direction;
+        }
+        entry.strings.extend(catalog.strings);
+    }
+    merged
+}
+
+pub fn available() -> Vec<LocaleInfo> {
+    let mut locales = vec![LocaleInfo {
+        id: SOURCE_LOCALE.to_string(),
+        name: SOURCE_NAME.to_string(),
+    }];
+    // 原文语言不需要译文，用户目录中同名的文件不会重复列出。
+    locales.extend(
+        load_all()
+            .into_iter()
+            .filter(|(id, _)| id != SOURCE_LOCALE)
+            .map(|(id, catalog)| LocaleInfo {
+                name: if catalog.language.is_empty() {
+                    id.clone()
+                } else {
+                    catalog.language
+                },
+                id,
+            }),
+    );
+    locales
+}
+
+// 系统的首选语言，例如 "en-US"、"zh-Hans-CN"。
+fn system_languages() -> Vec<String> {
+    Command::new("defaults")
+        .args(["read", "-g", "AppleLanguages"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .split(['(', ')', ',', '\n'])
+                .map(|item| item.trim().trim_matches('"').to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// 先找完全相同的标识，再按主语言匹配（"en-US" → "en"，"zh-Hant-TW" 不会匹配到简体）。
+fn best_match(wanted: &str, locales: &[LocaleInfo]) -> Option<String> {
+    let wanted = wanted.replace('_', "-");
+    if let Some(locale) = locales
+        .iter()
+        .find(|locale| locale.id.eq_ignore_ascii_case(&wanted))
+    {
+        return Some(locale.id.clone());
+    }
+    if wanted.starts_with("zh") {
+        let script = if wanted.contains("Hant") || wanted.ends_with("TW") || wanted.ends_with("HK")
+        {
+            "zh-Hant"
+        } else {
+            "zh-Hans"
+        };
+        return locales
+            .iter()
+            .find(|locale| locale.id == script)
+            .map(|locale| locale.id.clone());
+    }
+    let primary = wanted.split('-').next().unwrap_or_default();
+    locales
+        .iter()
+        .find(|locale| locale.id.split('-').next() == Some(primary))
+        .map(|locale| locale.id.clone())
+}
+
+// 配置中指定的语言优先，其次按系统首选语言依次匹配，都没有时使用简体中文。
+pub fn active_locale(config: &Config) -> String {
+    let locales = available();
+    config
+        .language
+        .iter()
+        .cloned()
+        .chain(system_languages())
+        .find_map(|wanted| best_match(&wanted, &locales))
+        .unwrap_or_else(|| SOURCE_LOCALE.to_string())
+}
+
+pub fn catalog(locale: &str) -> Catalog {
+    let file = if locale == SOURCE_LOCALE {
+        CatalogFile {
+            formats: Some(Formats::source()),
+            ..CatalogFile::default()
+        }
+    } else {
+        load_all().remove(locale).unwrap_or_default()
+    };
+    let primary = locale.split(['-', '_']).next().unwrap_or_default();
+    let rtl = match file.direction.as_deref() {
+        Some(direction) => direction.eq_ignore_ascii_case("rtl"),
+        None => RTL_LANGUAGES.contains(&primary),
+    };
+    Catalog {
+        locale: locale.to_string(),
+        strings: file.strings,
+        formats: file.formats.unwrap_or_default(),
+        direction: if rtl { "rtl" } else { "ltr" }.to_string(),
+    }
+}
+
+// 当前语言的译文，按配置文件的修改时间缓存；切换语言会写入配置，因此随之更新。
+pub fn current() -> Arc<Catalog> {
+    type Cache = (Option<SystemTime>, Arc<Catalog>);
+    static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+    let stamp = config::config_stamp();
+    let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some((cached, catalog)) = cache.as_ref()
+        && *cached == stamp
+    {
+        return catalog.clone();
+    }
+    let config = config::load_config().unwrap_or_default();
+    let catalog = Arc::new(catalog(&active_locale(&config)));
+    *cache = Some((stamp, catalog.clone()));
+    catalog
+}
+
+// 选择跟随系统时传 None。
+pub fn save_language(language: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(language) = language
+        && !available().iter().any(|locale| locale.id == language)
+    {
+        return Err(format!("没有找到语言：{}", language).into());
+    }
+    config::update_config("language", language.map(|id| id.into()))
+}
@@ -0,0 +1,214 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde_json::{Value, json};
+
+use crate::bundle::{bundle_identifier, bundle_name, is_app_bundle};
+use crate::scan::ScanScope;
+use crate::state::ManagedApp;
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportEntry {
+    pub source: String,
+    pub path: Option<PathBuf>,
+    pub name: Option<String>,
+    pub bundle_id: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    #[default]
+    Skip,
+    Overwrite,
+    Interactive,
+}
+
+impl FromStr for MergeStrategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "skip" => Ok(MergeStrategy::Skip),
+            "overwrite" => Ok(MergeStrategy::Overwrite),
+            "interactive" => Ok(MergeStrategy::Interactive),
+            other => Err(format!(
+                "未知的合并策略：{}（可选 skip、overwrite、interactive）",
+                other
+            )),
+        }
+    }
+}
+
+const PATH_KEYS: &[&str] = &["path", "bundlePath", "bundle_path", "appPath", "app_path"];
+const NAME_KEYS: &[&str] = &["name", "appName", "app_name", "CFBundleName"];
+const BUNDLE_ID_KEYS: &[&str] = &[
+    "bundleId",
+    "bundle_id",
+    "bundleID",
+    "bundleIdentifier",
+    "bundle_identifier",
+    "CFBundleIdentifier",
+];
+const TAGS_KEY: &str = "tags";
+const LIST_KEYS: &[&str] = &["apps", "applications", "items", "entries"];
+// 导出的配置文件：带 apps 列表的 JSON，导入时和其它列表一样解析。
+pub const PROFILE_EXTENSION: &str = "dockdodger";
+const PROFILE_FORMAT: &str = "dock-dodger-profile";
+
+pub fn is_import_list(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| {
+            ext.eq_ignore_ascii_case("json")
+                || ext.eq_ignore_ascii_case("txt")
+                || ext.eq_ignore_ascii_case(PROFILE_EXTENSION)
+        })
+        .unwrap_or(false)
+}
+
+// 另一台电脑上应用可能装在别处，除路径外同时写入名称和 Bundle ID，导入时依次用来查找。
+pub fn export_profile(apps: &[&ManagedApp]) -> String {
+    let entries: Vec<Value> = apps
+        .iter()
+        .map(|app| {
+            let mut entry = json!({ "path": app.path });
+            if let Some(name) = bundle_name(&app.path) {
+                entry["name"] = name.into();
+            }
+            if let Some(bundle_id) = bundle_identifier(&app.path) {
+                entry["bundle_id"] = bundle_id.into();
+            }
+            if !app.tags.is_empty() {
+                entry[TAGS_KEY] = app.tags.clone().into();
+            }
+            entry
+        })
+        .collect();
+    let profile = json!({ "format": PROFILE_FORMAT, "version": 1, "apps": entries });
+    serde_json::to_string_pretty(&profile).unwrap()
+}
+
+// 只有 export_profile 导出的配置文件算作切换配置，其它列表照常导入。
+pub fn is_profile(data: &str) -> bool {
+    serde_json::from_str::<Value>(data.trim_start())
+        .is_ok_and(|value| value.get("format").and_then(Value::as_str) == Some(PROFILE_FORMAT))
+}
+
+pub fn parse_import(data: &str) -> Result<Vec<ImportEntry>, Box<dyn std::error::Error>> {
+    let trimmed = data.trim_start();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        let value: Value = serde_json::from_str(trimmed)?;
+        return Ok(parse_json(&value));
+    }
+    Ok(data
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(entry_from_text)
+        .collect())
+}
+
+fn parse_json(value: &Value) -> Vec<ImportEntry> {
+    match value {
+        Value::Array(items) => items.iter().filter_map(entry_from_json).collect(),
+        Value::Object(map) => LIST_KEYS
+            .iter()
+            .find_map(|key| map.get(*key))
+            .map(parse_json)
+            .unwrap_or_else(|| entry_from_json(value).into_iter().collect()),
+        _ => Vec::new(),
+    }
+}
+
+fn entry_from_text(text: &str) -> ImportEntry {
+    if text.contains('/') {
+        ImportEntry {
+            source: text.to_string(),
+            path: Some(PathBuf::from(text)),
+            ..Default::default()
+        }
+    } else {
+        ImportEntry {
+            source: text.to_string(),
+            name: Some(text.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+fn entry_from_json(value: &Value) -> Option<ImportEntry> {
+    match value {
+        Value::String(text) => Some(entry_from_text(text)),
+        Value::Object(map) => {
+            let field = |keys: &[&str]| {
+                keys.iter()
+                    .find_map(|key| map.get(*key).and_then(Value::as_str))
+                    .map(str::to_string)
+            };
+            let entry = ImportEntry {
+                source: value.to_string(),
+                path: field(PATH_KEYS).map(PathBuf::from),
+                name: field(NAME_KEYS),
+                bundle_id: field(BUNDLE_ID_KEYS),
+                tags: map
+                    .get(TAGS_KEY)
+                    .and_then(Value::as_array)
+                    .map(|tags| {
+                        tags.iter()
+                            .filter_map(Value::as_str)
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            };
+            if entry.path.is_none() && entry.name.is_none() && entry.bundle_id.is_none() {
+                None
+            } else {
+                Some(entry)
+            }
+        }
+        _ => None,
+    }
+}
+
+pub fn resolve_entry(entry: &ImportEntry, scope: &ScanScope) -> Option<PathBuf> {
+    if let Some(path) = &entry.path
+        && is_app_bundle(path)
+        && path.exists()
+    {
+        return Some(path.clone());
+    }
+
+    let mut names = Vec::new();
+    if let Some(name) = &entry.name {
+        names.push(name.clone());
+    }
+    if let Some(file_name) = entry
+        .path
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .and_then(|name| name.to_str())
+    {
+        names.push(file_name.to_string());
+    }
+    for name in &names {
+        let file_name = if is_app_bundle(Path::new(name)) {
+            name.clone()
+        } else {
+            format!("{}.app", name)
+        };
+        for (root, _) in &scope.roots {
+            let candidate = root.join(&file_name);
+            if candidate.exists() && !scope.is_excluded(&candidate) {
+                return Some(candidate);
+            }
+        }
+    }
+
+    let bundle_id = entry.bundle_id.as_ref()?;
+    scope
+        .find_app_bundles()
+        .into_iter()
+        .find(|path| bundle_identifier(path).as_deref() == Some(bundle_id.as_str()))
+}
@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::bundle::{bundle_identifier, bundle_name, bundle_short_version, is_app_bundle};
+use crate::scan::ScanScope;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstalledApp {
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl InstalledApp {
+    pub fn from_path(path: PathBuf) -> Self {
+        let name = bundle_name(&path).unwrap_or_else(|| {
+            path.file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        InstalledApp {
+            name,
+            bundle_id: bundle_identifier(&path),
+            version: bundle_short_version(&path),
+            path,
+        }
+    }
+}
+
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |text: &str| -> Vec<u64> {
+        text.split(|ch: char| !ch.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse().unwrap_or_default())
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
+
+// 同一个 Bundle ID 只保留一份：优先版本更高的，其次路径更短的（通常是 /Applications 下的正式安装）。
+fn prefer(candidate: &InstalledApp, existing: &InstalledApp) -> bool {
+    let version = match (&candidate.version, &existing.version) {
+        (Some(a), Some(b)) => compare_versions(a, b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => Ordering::Equal,
+    };
+    match version {
+        Ordering::Equal => candidate.path.as_os_str().len() < existing.path.as_os_str().len(),
+        other => other == Ordering::Greater,
+    }
+}
+
+pub fn dedupe_by_bundle_id(apps: Vec<InstalledApp>) -> Vec<InstalledApp> {
+    let mut result: Vec<InstalledApp> = Vec::new();
+    for app in apps {
+        let existing = result
+            .iter()
+            .position(|other| match (&app.bundle_id, &other.bundle_id) {
+                (Some(a), Some(b)) => a == b,
+                _ => app.path == other.path,
+            });
+        match existing {
+            Some(index) if prefer(&app, &result[index]) => result[index] = app,
+            Some(_) => {}
+            None => result.push(app),
+        }
+    }
+    result.sort_by_key(|app| app.name.to_lowercase());
+    result
+}
+
+fn system_profiler_paths() -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let output = Command::new("system_profiler")
+        .args(["-json", "-detailLevel", "mini", "SPApplicationsDataType"])
+        .output()?;
+    if !output.status.success() {
+        return Err("system_profiler 执行失败".into());
+    }
+    let value: Value = serde_json::from_slice(&output.stdout)?;
+    let items = value
+        .get("SPApplicationsDataType")
+        .and_then(Value::as_array)
+        .ok_or("system_profiler 输出中没有应用列表")?;
+    Ok(items
+        .iter()
+        .filter_map(|item| item.get("path").and_then(Value::as_str))
+        .map(PathBuf::from)
+        .filter(|path| is_app_bundle(path))
+        .collect())
+}
+
+// 通过 Launch Services 登记的信息（system_profiler）列出已安装的应用，能找到非标准位置的应用；
+// 失败时退回到扫描配置的目录。配置中排除的路径两种方式下都不列出。
+pub fn installed_apps(scope: &ScanScope) -> Vec<InstalledApp> {
+    let paths = system_profiler_paths().unwrap_or_else(|err| {
+        eprintln!(
+            "[Installed] 读取 Launch Services 应用列表失败，改为扫描目录：{}",
+            err
+        );
+        scope.find_app_bundles()
+    });
+    dedupe_by_bundle_id(
+        paths
+            .into_iter()
+            .filter(|path| path.join("Contents/Info.plist").is_file() && !scope.is_excluded(path))
+            .map(InstalledApp::from_path)
+            .collect(),
+    )
+}
+
+fn escape_spotlight(text: &str) -> String {
+    text.chars()
+        .flat_map(|ch| match ch {
+            '\'' | '"' | '\\' | '*' => vec!['\\', ch],
+            _ => vec![ch],
+        })
+        .collect()
+}
+
+// kMDItemKind 会随系统语言变化，这里用内容类型限定为应用，名称做不区分大小写的子串匹配。
+pub fn spotlight_search(
+    query: &str,
+    scope: &ScanScope,
+) -> Result<Vec<InstalledApp>, Box<dyn std::error::Error>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let expression = format!(
+        "kMDItemContentType == 'com.apple.application-bundle' && (kMDItemDisplayName == '*{0}*'cd || kMDItemCFBundleIdentifier == '*{0}*'cd)",
+        escape_spotlight(query)
+    );
+    let output = Command::new("mdfind").arg(&expression).output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "mdfind 执行失败：{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(dedupe_by_bundle_id(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(PathBuf::from)
+            .filter(|path| {
+                is_app_bundle(path)
+                    && path.join("Contents/Info.plist").is_file()
+                    && !scope.is_excluded(path)
+            })
+            .map(InstalledApp::from_path)
+            .collect(),
+    ))
+}
+
+// NSWorkspace 按 Bundle ID 查询 Launch Services 登记的安装位置；Bundle ID 作为参数传入，不拼进脚本。
+const LAUNCH_SERVICES_SCRIPT: &str = "ObjC.import('AppKit');
+function run(argv) {
+  const url = $.NSWorkspace.sharedWorkspace.URLForApplicationWithBundleIdentifier(argv[0]);
+  return url.isNil() ? '' : url.path.js;
+}";
+
+fn launch_services_path(bundle_id: &str) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let output = Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", LAUNCH_SERVICES_SCRIPT, bundle_id])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "osascript 执行失败：{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!path.is_empty()).then(|| PathBuf::from(path)))
+}
+
+fn matches_bundle_id(path: &Path, bundle_id: &str) -> bool {
+    bundle_identifier(path).is_some_and(|id| id.eq_ignore_ascii_case(bundle_id))
+}
+
+// 同一个应用在不同机器上的路径不同，Bundle ID 则保持一致：先问 Launch Services，
+// 查询失败或没有登记时退回到扫描配置的目录（有多份时按 dedupe_by_bundle_id 的偏好取一份）。
+// 配置中排除的路径不会作为结果。Bundle ID 不区分大小写，与 Launch Services 一致。
+pub fn resolve_bundle_id(
+    bundle_id: &str,
+    scope: &ScanScope,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let bundle_id = bundle_id.trim();
+    if bundle_id.is_empty() {
+        return Err("Bundle ID 不能为空".into());
+    }
+    match launch_services_path(bundle_id) {
+        Ok(Some(path))
+            if is_app_bundle(&path)
+                && !scope.is_excluded(&path)
+                && matches_bundle_id(&path, bundle_id) =>
+        {
+            return Ok(path);
+        }
+        Ok(_) => {}
+        Err(err) => eprintln!(
+            "[Installed] 通过 Launch Services 查找 {} 失败，改为扫描目录：{}",
+            bundle_id, err
+        ),
+    }
+    dedupe_by_bundle_id(
+        scope
+            .find_app_bundles()
+            .into_iter()
+            .filter(|path| matches_bundle_id(path, bundle_id))
+            .map(InstalledApp::from_path)
+            .collect(),
+    )
+    .into_iter()
+    .next()
+    .map(|app| app.path)
+    .ok_or_else(|| format!("找不到 Bundle ID 为 {} 的应用", bundle_id).into())
+}
@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config;
+use crate::explain;
+
+// 测试或特殊环境中改用指定的数据库文件。
+pub const LAUNCHPAD_DB_ENV: &str = "DOCKDODGER_LAUNCHPAD_DB";
+
+// 启动台的布局保存在 $(getconf DARWIN_USER_DIR)com.apple.dock.launchpad/db/db（SQLite）：
+// 每个应用在 items 中占一行（所在的页或文件夹与顺序），在 apps 中有一行 Bundle ID 等信息。
+// 隐藏时删除这两行并原样记下，恢复时插回原来的位置；改动期间按启动台自己的约定
+// 打开 ignore_items_update_triggers，避免触发器重排其余图标。改动后需要重启 Dock 才会生效，
+// 由调用方决定何时重启（见 dock 模块）。通过系统自带的 sqlite3 命令操作，不依赖 sqlite 功能。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LaunchpadEntry {
+    pub item_id: i64,
+    #[serde(default)]
+    pub uuid: Option<String>,
+    #[serde(default)]
+    pub flags: Option<i64>,
+    pub item_type: i64,
+    pub parent_id: i64,
+    pub ordering: i64,
+    #[serde(default)]
+    pub title: Option<String>,
+    pub bundle_id: String,
+    #[serde(default)]
+    pub storekey: Option<String>,
+    #[serde(default)]
+    pub category_id: Option<i64>,
+    #[serde(default)]
+    pub moddate: Option<f64>,
+    // 十六进制编码的书签数据
+    #[serde(default)]
+    pub bookmark: Option<String>,
+}
+
+pub fn database_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if let Some(path) = std::env::var_os(LAUNCHPAD_DB_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+    let output = Command::new("getconf").arg("DARWIN_USER_DIR").output()?;
+    if !output.status.success() {
+        return Err("无法取得启动台数据库的位置".into());
+    }
+    let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let path = PathBuf::from(dir).join("com.apple.dock.launchpad/db/db");
+    if !path.is_file() {
+        return Err("没有找到启动台数据库，当前系统可能不再使用启动台".into());
+    }
+    Ok(path)
+}
+
+fn sqlite(args: &[&str], sql: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let database = database_path()?;
+    let output = Command::new("sqlite3")
+        .args(args)
+        .arg(&database)
+        .arg(sql)
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "修改启动台数据库失败：{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn text_or_null(value: Option<&str>) -> String {
+    value.map_or_else(|| "NULL".into(), quote)
+}
+
+fn number_or_null(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "NULL".into(), |value| value.to_string())
+}
+
+// 写入改动，并按 --explain 输出等价的 sqlite3 命令。
+fn execute(sql: &str) -> Result<(), Box<dyn std::error::Error>> {
+    sqlite(&["-bail"], sql)?;
+    explain::record(explain::sqlite_commands(&database_path()?, sql));
+    Ok(())
+}
+
+// 没有行时 sqlite3 -json 不输出任何内容。
+fn query(sql: &str) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+    let output = sqlite(&["-json", "-bail"], sql)?;
+    if output.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_str(&output)?)
+}
+
+fn entries(bundle_id: &str) -> Result<Vec<LaunchpadEntry>, Box<dyn std::error::Error>> {
+    let rows = query(&format!(
+        "SELECT items.rowid AS item_id, items.uuid AS uuid, items.flags AS flags, \
+         items.type AS item_type, items.parent_id AS parent_id, items.ordering AS ordering, \
+         apps.title AS title, apps.bundleid AS bundle_id, apps.storekey AS storekey, \
+         apps.category_id AS category_id, apps.moddate AS moddate, \
+         hex(apps.bookmark) AS bookmark \
+         FROM apps JOIN items ON items.rowid = apps.item_id \
+         WHERE apps.bundleid = {} COLLATE NOCASE",
+        quote(bundle_id)
+    ))?;
+    rows.into_iter()
+        .map(|row| Ok(serde_json::from_value(row)?))
+        .collect()
+}
+
+fn without_triggers(statements: &[String]) -> String {
+    let mut sql = String::from(
+        "BEGIN;\nUPDATE dbinfo SET value = 1 WHERE key = 'ignore_items_update_triggers';\n",
+    );
+    for statement in statements {
+        sql.push_str(statement);
+        sql.push_str(";\n");
+    }
+    sql.push_str(
+        "UPDATE dbinfo SET value = 0 WHERE key = 'ignore_items_update_triggers';\nCOMMIT;",
+    );
+    sql
+}
+
+// 从启动台删除该 Bundle ID 的所有图标，返回删除前的记录；不在启动台中时返回空列表。
+pub fn remove(bundle_id: &str) -> Result<Vec<LaunchpadEntry>, Box<dyn std::error::Error>> {
+    config::ensure_writable()?;
+    let removed = entries(bundle_id)?;
+    if removed.is_empty() {
+        return Ok(removed);
+    }
+    let ids = removed
+        .iter()
+        .map(|entry| entry.item_id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    execute(&without_triggers(&[
+        format!("DELETE FROM apps WHERE item_id IN ({})", ids),
+        format!("DELETE FROM items WHERE rowid IN ({})", ids),
+    ]))?;
+    Ok(removed)
+}
+
+// 把记录插回原来的页与位置。启动台已经重新收录了该应用（如应用更新后）时跳过，
+// 原来的位置被占用时由启动台下次整理时处理。返回实际插回的数量。
+pub fn restore(entries_to_restore: &[LaunchpadEntry]) -> Result<usize, Box<dyn std::error::Error>> {
+    config::ensure_writable()?;
+    let mut statements = Vec::new();
+    let mut restored = 0;
+    for entry in entries_to_restore {
+        if !entries(&entry.bundle_id)?.is_empty() {
+            continue;
+        }
+        statements.push(format!(
+            "INSERT OR IGNORE INTO items (rowid, uuid, flags, type, parent_id, ordering) \
+             VALUES ({}, {}, {}, {}, {}, {})",
+            entry.item_id,
+            text_or_null(entry.uuid.as_deref()),
+            number_or_null(entry.flags),
+            entry.item_type,
+            entry.parent_id,
+            entry.ordering
+        ));
+        statements.push(format!(
+            "INSERT OR IGNORE INTO apps (item_id, title, bundleid, storekey, category_id, moddate, bookmark) \
+             VALUES ({}, {}, {}, {}, {}, {}, {})",
+            entry.item_id,
+            text_or_null(entry.title.as_deref()),
+            quote(&entry.bundle_id),
+            text_or_null(entry.storekey.as_deref()),
+            number_or_null(entry.category_id),
+            number_or_null(entry.moddate),
+            entry
+                .bookmark
+                .as_deref()
+                .filter(|hex| !hex.is_empty() && hex.chars().all(|ch| ch.is_ascii_hexdigit()))
+                .map_or_else(|| "NULL".into(), |hex| format!("X'{}'", hex))
+        ));
+        restored += 1;
+    }
+    if !statements.is_empty() {
+        execute(&without_triggers(&statements))?;
+    }
+    Ok(restored)
+}
@@ -0,0 +1,45 @@
+pub mod agent;
+pub mod auth;
+pub mod backup;
+pub mod bundle;
+pub mod config;
+pub mod crash;
+pub mod crypto;
+pub mod distributed;
+pub mod dock;
+pub mod error;
+pub mod events;
+pub mod explain;
+pub mod ffi;
+pub mod helper;
+pub mod history;
+pub mod i18n;
+pub mod import;
+pub mod installed;
+pub mod launchpad;
+pub mod migrate;
+pub mod notify;
+pub mod pause;
+pub mod plist_cache;
+#[cfg(feature = "python")]
+mod python;
+pub mod relaunch;
+pub mod report;
+pub mod rollback;
+pub mod rules;
+pub mod scan;
+pub mod session;
+pub mod shortcuts;
+pub mod shutdown;
+pub mod signing;
+pub mod snapshot;
+pub mod state;
+pub mod state_store;
+pub mod store;
+pub mod translocation;
+pub mod update_wave;
+pub mod volume;
+pub mod wake;
+pub mod watcher;
+pub mod webhook;
+pub mod write_queue;
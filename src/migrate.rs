@@ -0,0 +1,75 @@
+use serde_json::{Map, Value, json};
+
+pub const SCHEMA_VERSION: u64 = 2;
+
+// 由较新版本写入的状态文件不能读取，更不能用空列表覆盖。
+pub const UNSUPPORTED_VERSION_ERROR: &str = "状态文件版本高于当前支持的版本";
+
+type Migration = fn(Value) -> Result<Value, String>;
+
+// MIGRATIONS[n] 把版本 n 的数据升级到版本 n + 1。
+// 版本 0：早期的字符串数组 ["/Applications/Foo.app", ...]
+// 版本 1：对象数组 [{"path": "...", "tags": [...]}]
+// 版本 2：{"schema_version": 2, "apps": [...]}
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1, migrate_v1_to_v2];
+
+pub fn detect_version(value: &Value) -> Result<u64, String> {
+    match value {
+        Value::Array(items) => {
+            if items.iter().all(Value::is_string) {
+                Ok(0)
+            } else if items.iter().all(Value::is_object) {
+                Ok(1)
+            } else {
+                Err("状态文件数组中混有无法识别的条目".into())
+            }
+        }
+        Value::Object(map) => map
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| "状态文件缺少 schema_version 字段".into()),
+        _ => Err("无法识别的状态文件格式".into()),
+    }
+}
+
+pub fn migrate(mut value: Value) -> Result<(Value, bool), String> {
+    let mut version = detect_version(&value)?;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "{}（{} > {}），请升级 Dock Dodger",
+            UNSUPPORTED_VERSION_ERROR, version, SCHEMA_VERSION
+        ));
+    }
+    let migrated = version < SCHEMA_VERSION;
+    while version < SCHEMA_VERSION {
+        value = MIGRATIONS[version as usize](value)?;
+        version += 1;
+    }
+    Ok((value, migrated))
+}
+
+fn migrate_v0_to_v1(value: Value) -> Result<Value, String> {
+    let Value::Array(items) = value else {
+        return Err("版本 0 的状态文件应为字符串数组".into());
+    };
+    Ok(Value::Array(
+        items
+            .into_iter()
+            .map(|path| {
+                let mut entry = Map::new();
+                entry.insert("path".into(), path);
+                Value::Object(entry)
+            })
+            .collect(),
+    ))
+}
+
+fn migrate_v1_to_v2(value: Value) -> Result<Value, String> {
+    if !value.is_array() {
+        return Err("版本 1 的状态文件应为对象数组".into());
+    }
+    Ok(json!({
+        "schema_version": 2,
+        "apps": value,
+    }))
+}
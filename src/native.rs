@@ -0,0 +1,504 @@
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+
+use cacao::appkit::menu::Menu;
+use cacao::appkit::toolbar::{ItemIdentifier, Toolbar, ToolbarDelegate, ToolbarItem};
+use cacao::appkit::window::{Window, WindowConfig, WindowDelegate};
+use cacao::appkit::{App, AppDelegate};
+use cacao::dragdrop::{DragInfo, DragOperation};
+use cacao::filesystem::FileSelectPanel;
+use cacao::layout::{Layout, LayoutConstraint};
+use cacao::listview::{ListView, ListViewDelegate, ListViewRow};
+use cacao::notification_center::Dispatcher;
+use cacao::pasteboard::PasteboardType;
+use cacao::text::{Font, Label};
+use cacao::view::{View, ViewDelegate};
+use osx_dock_dodger_rs::auth;
+use osx_dock_dodger_rs::bundle::{bundle_name, hide_dock_icon, is_app_bundle, ui_element};
+use osx_dock_dodger_rs::config;
+use osx_dock_dodger_rs::pause;
+use osx_dock_dodger_rs::session;
+use osx_dock_dodger_rs::signing;
+use osx_dock_dodger_rs::state::{self, ManagedApp};
+use osx_dock_dodger_rs::translocation;
+
+const APP_ROW: &str = "DockDodgerAppRow";
+const ADD_ITEM: &str = "add";
+const RESTORE_ITEM: &str = "restore";
+const PAUSE_ITEM: &str = "pause";
+
+#[derive(Debug)]
+enum Message {
+    ChooseApps,
+    Add(Vec<PathBuf>),
+    RestoreSelected,
+    TogglePause,
+}
+
+fn dispatch(message: Message) {
+    App::<DockDodgerApp, Message>::dispatch_main(message);
+}
+
+fn display_name(path: &Path) -> String {
+    bundle_name(path).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string())
+    })
+}
+
+struct AppRow {
+    name: Label,
+    detail: Label,
+}
+
+impl Default for AppRow {
+    fn default() -> Self {
+        AppRow {
+            name: Label::new(),
+            detail: Label::new(),
+        }
+    }
+}
+
+impl AppRow {
+    fn configure(&self, app: &ManagedApp) {
+        self.name.set_text(display_name(&app.path));
+        let mut detail = app.path.display().to_string();
+        if app.session {
+            detail.push_str(" · 仅本次运行");
+        }
+        if let Some(reason) = app.external_change() {
+            detail.push_str(&format!(" · 可能由其他工具管理（{}）", reason));
+        }
+        self.detail.set_text(detail);
+    }
+}
+
+impl ViewDelegate for AppRow {
+    const NAME: &'static str = "DockDodgerAppRowView";
+
+    fn did_load(&mut self, view: View) {
+        self.detail.set_font(Font::system(11.));
+        view.add_subview(&self.name);
+        view.add_subview(&self.detail);
+        LayoutConstraint::activate(&[
+            self.name.top.constraint_equal_to(&view.top).offset(6.),
+            self.name
+                .leading
+                .constraint_equal_to(&view.leading)
+                .offset(12.),
+            self.name
+                .trailing
+                .constraint_equal_to(&view.trailing)
+                .offset(-12.),
+            self.detail
+                .top
+                .constraint_equal_to(&self.name.bottom)
+                .offset(2.),
+            self.detail.leading.constraint_equal_to(&self.name.leading),
+            self.detail
+                .trailing
+                .constraint_equal_to(&self.name.trailing),
+            self.detail
+                .bottom
+                .constraint_equal_to(&view.bottom)
+                .offset(-6.),
+        ]);
+    }
+}
+
+// 列表数据由这个代理持有，AppKit 回调都在主线程，用 RefCell 即可。
+#[derive(Default)]
+struct AppList {
+    apps: RefCell<Vec<ManagedApp>>,
+    selected: Cell<Option<usize>>,
+    view: Option<ListView>,
+}
+
+impl AppList {
+    fn reload(&self) {
+        if let Some(view) = &self.view {
+            view.reload();
+        }
+    }
+
+    fn save(&self) {
+        if let Err(err) = state::save_apps(&self.apps.borrow()) {
+            println!("[Native] 保存应用列表失败：{}", err);
+        }
+    }
+
+    fn add(&self, paths: Vec<PathBuf>) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut apps = self.apps.borrow_mut();
+        for path in paths {
+            let path = match translocation::resolve(&path) {
+                Ok(path) => path,
+                Err(err) => {
+                    errors.push(err.to_string());
+                    continue;
+                }
+            };
+            if !is_app_bundle(&path) || apps.iter().any(|app| app.path == path) {
+                continue;
+            }
+            let signing = signing::read_signing_info(&path);
+            let original = ui_element(&path).ok().flatten();
+            match hide_dock_icon(&path) {
+                Ok(_) => {
+                    println!("[Native] 成功隐藏 Dock 图标：{}", path.display());
+                    let mut app = ManagedApp::new(path);
+                    app.signing = Some(signing);
+                    app.record_hide(original);
+                    app.record_checksum();
+                    app.apply_hide_options();
+                    apps.push(app);
+                }
+                Err(err) => errors.push(format!("{}：{}", display_name(&path), err)),
+            }
+        }
+        drop(apps);
+        self.save();
+        self.reload();
+        errors
+    }
+
+    fn restore_selected(&self) -> Option<String> {
+        let index = self.selected.get()?;
+        let mut apps = self.apps.borrow_mut();
+        let app = apps.get(index)?;
+        let name = display_name(&app.path);
+        if let Some(reason) = app.external_change() {
+            return Some(format!(
+                "{} 可能由其他用户或工具管理（{}），请在命令行或网页界面中确认后再恢复",
+                name, reason
+            ));
+        }
+        let message = match app.restore_dock_icon() {
+            Ok(_) => {
+                let was_session = app.session;
+                apps.remove(index);
+                if was_session && let Err(err) = session::write_journal(&apps) {
+                    println!("[Session] 更新会话日志失败：{}", err);
+                }
+                format!("已恢复 {} 的 Dock 图标", name)
+            }
+            Err(err) => format!("恢复 {} 失败：{}", name, err),
+        };
+        drop(apps);
+        self.selected.set(None);
+        self.save();
+        self.reload();
+        Some(message)
+    }
+
+    fn toggle_pause(&self) -> String {
+        let pausing = !pause::is_paused();
+        if pausing && let Err(err) = auth::confirm("暂停隐藏会恢复所有应用的 Dock 图标")
+        {
+            return err.to_string();
+        }
+        let mut apps = self.apps.borrow_mut();
+        let results = if pausing {
+            pause::pause(&mut apps, false)
+        } else {
+            pause::resume(&mut apps, false)
+        };
+        drop(apps);
+        self.save();
+        self.reload();
+        match results {
+            Ok(results) => {
+                let failed = results
+                    .iter()
+                    .filter(|result| result.error.is_some())
+                    .count();
+                let action = if pausing {
+                    "已暂停隐藏"
+                } else {
+                    "已继续隐藏"
+                };
+                if failed == 0 {
+                    action.to_string()
+                } else {
+                    format!("{}，{} 个应用处理失败", action, failed)
+                }
+            }
+            Err(err) => format!("切换暂停状态失败：{}", err),
+        }
+    }
+}
+
+impl ListViewDelegate for AppList {
+    const NAME: &'static str = "DockDodgerAppList";
+
+    fn did_load(&mut self, view: ListView) {
+        view.register(APP_ROW, AppRow::default);
+        view.set_row_height(44.);
+        view.register_for_dragged_types(&[PasteboardType::FileUrl]);
+        self.view = Some(view);
+    }
+
+    fn number_of_items(&self) -> usize {
+        self.apps.borrow().len()
+    }
+
+    fn item_for(&self, row: usize) -> ListViewRow {
+        let mut cell = self
+            .view
+            .as_ref()
+            .expect("列表视图尚未加载")
+            .dequeue::<AppRow>(APP_ROW);
+        if let Some(view) = &mut cell.delegate
+            && let Some(app) = self.apps.borrow().get(row)
+        {
+            view.configure(app);
+        }
+        cell.into_row()
+    }
+
+    fn item_selected(&self, row: usize) {
+        self.selected.set(Some(row));
+    }
+
+    fn dragging_entered(&self, _info: DragInfo) -> DragOperation {
+        DragOperation::Copy
+    }
+
+    fn perform_drag_operation(&self, info: DragInfo) -> bool {
+        let paths: Vec<PathBuf> = info
+            .get_file_urls()
+            .map(|urls| urls.iter().map(|url| url.pathbuf()).collect())
+            .unwrap_or_default();
+        let accepted = paths.iter().any(|path| is_app_bundle(path));
+        if accepted {
+            dispatch(Message::Add(paths));
+        }
+        accepted
+    }
+}
+
+struct MainToolbar {
+    add: ToolbarItem,
+    restore: ToolbarItem,
+    pause: ToolbarItem,
+}
+
+impl MainToolbar {
+    fn new() -> Self {
+        let mut add = ToolbarItem::new(ADD_ITEM);
+        add.set_title("添加应用");
+        add.set_action(|| dispatch(Message::ChooseApps));
+
+        let mut restore = ToolbarItem::new(RESTORE_ITEM);
+        restore.set_title("恢复所选");
+        restore.set_action(|| dispatch(Message::RestoreSelected));
+
+        let mut pause = ToolbarItem::new(PAUSE_ITEM);
+        pause.set_title("暂停/继续隐藏");
+        pause.set_action(|| dispatch(Message::TogglePause));
+
+        MainToolbar {
+            add,
+            restore,
+            pause,
+        }
+    }
+}
+
+impl ToolbarDelegate for MainToolbar {
+    const NAME: &'static str = "DockDodgerToolbar";
+
+    fn allowed_item_identifiers(&self) -> Vec<ItemIdentifier> {
+        vec![
+            ItemIdentifier::Custom(ADD_ITEM),
+            ItemIdentifier::Custom(RESTORE_ITEM),
+            ItemIdentifier::FlexibleSpace,
+            ItemIdentifier::Custom(PAUSE_ITEM),
+        ]
+    }
+
+    fn default_item_identifiers(&self) -> Vec<ItemIdentifier> {
+        self.allowed_item_identifiers()
+    }
+
+    fn item_for(&self, identifier: &str) -> &ToolbarItem {
+        match identifier {
+            ADD_ITEM => &self.add,
+            RESTORE_ITEM => &self.restore,
+            _ => &self.pause,
+        }
+    }
+}
+
+struct MainWindow {
+    content: View,
+    status: Label,
+    list: ListView<AppList>,
+    toolbar: Toolbar<MainToolbar>,
+}
+
+impl MainWindow {
+    fn new(apps: Vec<ManagedApp>) -> Self {
+        let list = AppList {
+            apps: RefCell::new(apps),
+            ..Default::default()
+        };
+        MainWindow {
+            content: View::new(),
+            status: Label::new(),
+            list: ListView::with(list),
+            toolbar: Toolbar::new("DockDodgerMainToolbar", MainToolbar::new()),
+        }
+    }
+
+    fn app_list(&self) -> &AppList {
+        self.list.delegate.as_ref().expect("列表代理尚未创建")
+    }
+
+    fn set_status(&self, text: &str) {
+        println!("[Native] {}", text);
+        self.status.set_text(text);
+    }
+
+    fn on_message(&self, message: Message) {
+        match message {
+            Message::ChooseApps => {
+                let mut panel = FileSelectPanel::new();
+                panel.set_can_choose_directories(false);
+                panel.set_allows_multiple_selection(true);
+                panel.show(|urls| {
+                    dispatch(Message::Add(urls.iter().map(|url| url.pathbuf()).collect()));
+                });
+            }
+            Message::Add(paths) => {
+                let errors = self.app_list().add(paths);
+                if errors.is_empty() {
+                    self.set_status("拖入 .app 或点击“添加应用”隐藏其 Dock 图标");
+                } else {
+                    self.set_status(&format!("隐藏失败：{}", errors.join("；")));
+                }
+            }
+            Message::RestoreSelected => match self.app_list().restore_selected() {
+                Some(message) => self.set_status(&message),
+                None => self.set_status("请先在列表中选择一个应用"),
+            },
+            Message::TogglePause => {
+                let message = self.app_list().toggle_pause();
+                self.set_status(&message);
+            }
+        }
+    }
+}
+
+impl WindowDelegate for MainWindow {
+    const NAME: &'static str = "DockDodgerMainWindow";
+
+    fn did_load(&mut self, window: Window) {
+        window.set_title("Dock Dodger");
+        window.set_minimum_content_size(480., 360.);
+        window.set_toolbar(&self.toolbar);
+
+        self.status
+            .set_text("拖入 .app 或点击“添加应用”隐藏其 Dock 图标");
+        self.content.add_subview(&self.status);
+        self.content.add_subview(&self.list);
+        LayoutConstraint::activate(&[
+            self.status
+                .top
+                .constraint_equal_to(&self.content.top)
+                .offset(12.),
+            self.status
+                .leading
+                .constraint_equal_to(&self.content.leading)
+                .offset(16.),
+            self.status
+                .trailing
+                .constraint_equal_to(&self.content.trailing)
+                .offset(-16.),
+            self.list
+                .top
+                .constraint_equal_to(&self.status.bottom)
+                .offset(12.),
+            self.list.leading.constraint_equal_to(&self.content.leading),
+            self.list
+                .trailing
+                .constraint_equal_to(&self.content.trailing),
+            self.list.bottom.constraint_equal_to(&self.content.bottom),
+        ]);
+        window.set_content_view(&self.content);
+    }
+}
+
+struct DockDodgerApp {
+    window: Window<MainWindow>,
+}
+
+impl AppDelegate for DockDodgerApp {
+    fn did_finish_launching(&self) {
+        App::set_menu(Menu::standard());
+        App::activate();
+        self.window.show();
+    }
+
+    fn should_terminate_after_last_window_closed(&self) -> bool {
+        true
+    }
+
+    // 与网页界面一致：退出时恢复“仅本次运行”的应用。
+    fn will_terminate(&self) {
+        if let Some(window) = &self.window.delegate {
+            let list = window.app_list();
+            match session::end_session(&mut list.apps.borrow_mut()) {
+                Ok(results) if !results.is_empty() => {
+                    println!("[Session] 退出时恢复了 {} 个应用", results.len())
+                }
+                Ok(_) => {}
+                Err(err) => println!("[Session] 退出时恢复失败：{}", err),
+            }
+            list.save();
+        }
+    }
+}
+
+impl Dispatcher for DockDodgerApp {
+    type Message = Message;
+
+    fn on_ui_message(&self, message: Message) {
+        if let Some(window) = &self.window.delegate {
+            window.on_message(message);
+        }
+    }
+}
+
+// 原生 AppKit 界面：列表、拖放与工具栏，只提供最常用的隐藏、恢复与暂停操作。
+pub fn run() {
+    let mut apps = match state::load_state() {
+        Ok(loaded) => loaded.apps,
+        // 与图形界面一致：读取失败时改为只读，不能用空列表覆盖原有记录。
+        Err(err) => {
+            println!("[Native] 读取应用列表失败，本次以只读模式运行：{}", err);
+            config::force_read_only();
+            Vec::new()
+        }
+    };
+    let stale = if config::read_only() {
+        Ok(Vec::new())
+    } else {
+        session::recover_stale_session(&mut apps)
+    };
+    match stale {
+        Ok(results) if !results.is_empty() => {
+            println!("[Session] 已恢复上次运行遗留的 {} 个应用", results.len());
+            if let Err(err) = state::save_apps(&apps) {
+                println!("[Native] 保存应用列表失败：{}", err);
+            }
+        }
+        Ok(_) => {}
+        Err(err) => println!("[Session] 检查上次运行的记录失败：{}", err),
+    }
+
+    let window = Window::with(WindowConfig::default(), MainWindow::new(apps));
+    App::new("com.github.fmnisme.dock-dodger", DockDodgerApp { window }).run();
+}
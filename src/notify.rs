@@ -0,0 +1,49 @@
+// 发送带操作按钮的系统通知；用户点击按钮后在后台线程中回调 on_action。
+#[cfg(target_os = "macos")]
+pub fn notify_with_action<F>(title: &str, message: &str, action: &str, on_action: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    use std::thread;
+
+    use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+
+    let title = title.to_string();
+    let message = message.to_string();
+    let action = action.to_string();
+    thread::spawn(move || {
+        let response = Notification::new()
+            .title(&title)
+            .message(&message)
+            .main_button(MainButton::SingleAction(&action))
+            .send();
+        match response {
+            Ok(NotificationResponse::ActionButton(_)) => on_action(),
+            Ok(_) => {}
+            Err(err) => eprintln!("[Notify] 发送通知失败：{}", err),
+        }
+    });
+}
+
+// 只显示一条通知，不等待用户操作；用于命令行和 launchd 代理，进程随后就会退出。
+#[cfg(target_os = "macos")]
+pub fn notify(title: &str, message: &str) {
+    use mac_notification_sys::Notification;
+
+    if let Err(err) = Notification::new().title(title).message(message).send() {
+        eprintln!("[Notify] 发送通知失败：{}", err);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn notify(title: &str, message: &str) {
+    eprintln!("[Notify] {}：{}", title, message);
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn notify_with_action<F>(title: &str, message: &str, _action: &str, _on_action: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    eprintln!("[Notify] {}：{}", title, message);
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::config;
+use crate::dock;
+use crate::shutdown;
+use crate::state::{ManagedApp, write_atomic};
+
+#[derive(Debug, Serialize)]
+pub struct PauseResult {
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub fn pause_marker_path() -> PathBuf {
+    config::data_dir().join("paused")
+}
+
+pub fn is_paused() -> bool {
+    pause_marker_path().exists()
+}
+
+type BundleOp = fn(&mut ManagedApp) -> Result<(), Box<dyn std::error::Error>>;
+
+// 插回或移除固定图标需要重启 Dock，整批处理完后只重启一次。
+fn apply_all(apps: &mut [ManagedApp], force: bool, op: BundleOp) -> Vec<PauseResult> {
+    let _dock = dock::batch();
+    apps.iter_mut()
+        .take_while(|_| !shutdown::requested())
+        .map(|app| {
+            let error = match app.external_change().filter(|_| !force) {
+                Some(reason) => Some(format!("可能由其他用户或工具管理（{}），已跳过", reason)),
+                None => match op(app) {
+                    Ok(_) => {
+                        app.record_checksum();
+                        None
+                    }
+                    Err(err) => Some(err.to_string()),
+                },
+            };
+            PauseResult {
+                path: app.path.clone(),
+                error,
+            }
+        })
+        .collect()
+}
+
+// 先写入暂停标记再逐个恢复，这样中途退出后仍能通过“继续”重新隐藏全部应用。
+pub fn pause(
+    apps: &mut [ManagedApp],
+    force: bool,
+) -> Result<Vec<PauseResult>, Box<dyn std::error::Error>> {
+    write_atomic(&pause_marker_path(), b"")?;
+    Ok(apply_all(apps, force, |app| app.restore_dock_icon()))
+}
+
+pub fn resume(
+    apps: &mut [ManagedApp],
+    force: bool,
+) -> Result<Vec<PauseResult>, Box<dyn std::error::Error>> {
+    config::ensure_writable()?;
+    let results = apply_all(apps, force, ManagedApp::hide_dock_icon);
+    // 中途停止时保留暂停标记，再次执行“继续”即可处理剩下的应用。
+    if shutdown::requested() {
+        return Ok(results);
+    }
+    match fs::remove_file(pause_marker_path()) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(results),
+    }
+}
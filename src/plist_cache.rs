@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use plist::Value;
+
+// 超过这么多条时整体清空，避免扫描大目录后一直占着内存。
+const MAX_ENTRIES: usize = 4096;
+
+// 校验与界面列表会反复读取同一批 Info.plist：解析结果按路径缓存，
+// 每次使用前比较文件的修改时间与大小，不一致（或读不到）时重新解析。
+// 本进程写入 Info.plist 后，以及监视线程发现应用增删时，主动作废对应条目。
+struct Cached {
+    modified: SystemTime,
+    len: u64,
+    plist: Value,
+}
+
+static CACHE: Mutex<Option<HashMap<PathBuf, Cached>>> = Mutex::new(None);
+
+fn lock() -> MutexGuard<'static, Option<HashMap<PathBuf, Cached>>> {
+    CACHE.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn plist_path(app: &Path) -> PathBuf {
+    app.join("Contents/Info.plist")
+}
+
+pub fn read(app: &Path) -> Result<Value, Box<dyn std::error::Error>> {
+    let path = plist_path(app);
+    let metadata = fs::metadata(&path)?;
+    let modified = metadata.modified()?;
+    let len = metadata.len();
+    if let Some(cached) = lock().get_or_insert_with(HashMap::new).get(&path)
+        && cached.modified == modified
+        && cached.len == len
+    {
+        return Ok(cached.plist.clone());
+    }
+    let plist = Value::from_reader(io::Cursor::new(fs::read(&path)?))?;
+    let mut cache = lock();
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if cache.len() >= MAX_ENTRIES {
+        cache.clear();
+    }
+    cache.insert(
+        path,
+        Cached {
+            modified,
+            len,
+            plist: plist.clone(),
+        },
+    );
+    Ok(plist)
+}
+
+// 只取一个字符串字段，读不到或类型不对时为 None。
+pub fn string(app: &Path, key: &str) -> Option<String> {
+    read(app)
+        .ok()?
+        .as_dictionary()?
+        .get(key)?
+        .as_string()
+        .map(str::to_string)
+}
+
+pub fn invalidate(app: &Path) {
+    if let Some(cache) = lock().as_mut() {
+        cache.remove(&plist_path(app));
+    }
+}
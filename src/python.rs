@@ -0,0 +1,139 @@
+// Python 模块 dock_dodger，用 maturin 构建（见 pyproject.toml）。
+// 隐藏与恢复复用 C 接口中的实现，行为与命令行一致。
+
+// pyo3 0.22 的宏展开还没有适配 edition 2024：会生成未声明的 gil-refs cfg、unsafe fn 中不带
+// unsafe 块的调用以及多余的 PyErr 转换。这些都在宏生成的代码里，升级 pyo3 后删除。
+#![allow(unexpected_cfgs, unsafe_op_in_unsafe_fn, clippy::useless_conversion)]
+
+use std::path::PathBuf;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+
+use crate::bundle::is_dock_icon_hidden;
+use crate::ffi::{RestoreError, hide_app, restore_app};
+use crate::state;
+use crate::volume;
+
+create_exception!(dock_dodger, DockDodgerError, PyRuntimeError);
+create_exception!(dock_dodger, ExternalChangeError, DockDodgerError);
+
+#[pyclass(name = "App", get_all, frozen)]
+struct PyApp {
+    path: String,
+    tags: Vec<String>,
+    hidden: bool,
+    session: bool,
+}
+
+#[pymethods]
+impl PyApp {
+    fn __repr__(&self) -> String {
+        format!("App(path={:?}, hidden={})", self.path, self.hidden)
+    }
+}
+
+#[pyclass(name = "VerifyResult", get_all, frozen)]
+struct PyVerifyResult {
+    path: String,
+    // hidden、drifted（图标已恢复）、external（疑似由其他工具管理）、unreadable
+    // 或 offline（所在的外接卷未挂载）
+    status: String,
+    detail: Option<String>,
+}
+
+#[pymethods]
+impl PyVerifyResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "VerifyResult(path={:?}, status={:?})",
+            self.path, self.status
+        )
+    }
+}
+
+fn load_apps() -> PyResult<Vec<state::ManagedApp>> {
+    state::load_apps().map_err(|err| DockDodgerError::new_err(format!("读取应用列表失败：{}", err)))
+}
+
+#[pyfunction]
+fn hide(py: Python<'_>, path: PathBuf) -> PyResult<()> {
+    py.allow_threads(|| hide_app(&path).map_err(|err| err.to_string()))
+        .map_err(DockDodgerError::new_err)
+}
+
+#[pyfunction]
+#[pyo3(signature = (path, force = false))]
+fn restore(py: Python<'_>, path: PathBuf, force: bool) -> PyResult<()> {
+    py.allow_threads(|| {
+        restore_app(&path, force)
+            .map_err(|err| (matches!(err, RestoreError::External(_)), err.to_string()))
+    })
+    .map_err(|(external, message)| {
+        if external {
+            ExternalChangeError::new_err(message)
+        } else {
+            DockDodgerError::new_err(message)
+        }
+    })
+}
+
+#[pyfunction]
+fn list() -> PyResult<Vec<PyApp>> {
+    Ok(load_apps()?
+        .into_iter()
+        .map(|app| PyApp {
+            path: app.path.display().to_string(),
+            hidden: is_dock_icon_hidden(&app.path).unwrap_or(false),
+            tags: app.tags,
+            session: app.session,
+        })
+        .collect())
+}
+
+#[pyfunction]
+fn verify() -> PyResult<Vec<PyVerifyResult>> {
+    Ok(load_apps()?
+        .iter()
+        .map(|app| {
+            if volume::is_offline(&app.path) {
+                return PyVerifyResult {
+                    path: app.path.display().to_string(),
+                    status: "offline".into(),
+                    detail: None,
+                };
+            }
+            let (status, detail) = match (app.external_change(), is_dock_icon_hidden(&app.path)) {
+                (Some(reason), _) => ("external", Some(reason)),
+                (None, Ok(true)) => ("hidden", None),
+                (None, Ok(false)) => ("drifted", None),
+                (None, Err(err)) => ("unreadable", Some(err.to_string())),
+            };
+            PyVerifyResult {
+                path: app.path.display().to_string(),
+                status: status.into(),
+                detail,
+            }
+        })
+        .collect())
+}
+
+#[pymodule]
+fn dock_dodger(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add(
+        "DockDodgerError",
+        m.py().get_type_bound::<DockDodgerError>(),
+    )?;
+    m.add(
+        "ExternalChangeError",
+        m.py().get_type_bound::<ExternalChangeError>(),
+    )?;
+    m.add_class::<PyApp>()?;
+    m.add_class::<PyVerifyResult>()?;
+    m.add_function(wrap_pyfunction!(hide, m)?)?;
+    m.add_function(wrap_pyfunction!(restore, m)?)?;
+    m.add_function(wrap_pyfunction!(list, m)?)?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bundle::{bundle_identifier, running_bundles};
+use crate::state::{ManagedApp, RelaunchOptions};
+
+// 等待应用自行退出的最长时间；有未保存文档时应用可能弹窗询问，超时后放弃重新启动。
+const QUIT_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// 请求应用退出需要“自动化”（TCC）权限：第一次会弹出询问，被拒绝后 osascript 直接失败。
+pub const AUTOMATION_DENIED_ERROR: &str = "没有控制该应用的“自动化”权限";
+pub const AUTOMATION_SETTINGS_URL: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Automation";
+
+// -1743：用户拒绝了授权；-1744：需要用户同意但无法询问（例如在后台代理中运行）。
+pub fn is_automation_denied(osascript_stderr: &str) -> bool {
+    osascript_stderr.contains("-1743") || osascript_stderr.contains("-1744")
+}
+
+// LSUIElement 只在应用启动时读取。应用设置了 relaunch 且正在运行时，退出后按选项重新打开；
+// 返回是否真的重新启动了。会阻塞到应用退出，图形界面应在后台线程调用。
+// 出错时隐藏已经生效，只是没有重新启动，调用方提示即可。
+pub fn after_hide(app: &ManagedApp) -> Result<bool, Box<dyn std::error::Error>> {
+    let Some(options) = &app.relaunch else {
+        return Ok(false);
+    };
+    if !running_bundles().contains(&app.path) {
+        return Ok(false);
+    }
+    relaunch(&app.path, options)?;
+    Ok(true)
+}
+
+pub fn relaunch(app: &Path, options: &RelaunchOptions) -> Result<(), Box<dyn std::error::Error>> {
+    quit(app)?;
+    let status = open_command(app, options)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(format!("open 退出码 {}", status.code().unwrap_or(-1)).into());
+    }
+    eprintln!("[Relaunch] 已重新启动：{}", app.display());
+    Ok(())
+}
+
+fn open_command(app: &Path, options: &RelaunchOptions) -> Command {
+    let mut command = Command::new("open");
+    if options.background {
+        command.arg("-g");
+    }
+    if options.hidden {
+        command.arg("-j");
+    }
+    command.arg(app);
+    if !options.args.is_empty() {
+        command.arg("--args").args(&options.args);
+    }
+    command
+}
+
+// 通过 Apple Event 请求应用正常退出，而不是直接结束进程，以免丢失未保存的内容。
+fn quit(app: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let bundle_id = bundle_identifier(app).ok_or("无法读取 Bundle ID，不能请求应用退出")?;
+    let script = format!("quit app id \"{}\"", bundle_id.replace('"', ""));
+    let output = Command::new("osascript")
+        .args(["-e", &script])
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        if is_automation_denied(&String::from_utf8_lossy(&output.stderr)) {
+            return Err(format!(
+                "{}，未能请求 {} 退出",
+                AUTOMATION_DENIED_ERROR,
+                app.display()
+            )
+            .into());
+        }
+        return Err("应用拒绝退出".into());
+    }
+    let started = Instant::now();
+    while running_bundles().contains(app) {
+        if started.elapsed() > QUIT_TIMEOUT {
+            return Err("等待应用退出超时，未重新启动".into());
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    Ok(())
+}
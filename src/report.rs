@@ -0,0 +1,193 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::agent::{self, AgentJob};
+use crate::config;
+use crate::crash;
+use crate::crypto;
+use crate::history::{self, HistoryEntry};
+use crate::pause;
+use crate::state;
+
+const REPORT_DIR: &str = "dock-dodger-report";
+const RECENT_HISTORY: usize = 200;
+const LOG_TAIL_LINES: usize = 500;
+const CRASH_REPORTS: usize = 3;
+
+// 报告中的文本统一替换：用户主目录换成 ~；匿名化时每个应用路径换成编号。
+struct Sanitizer {
+    replacements: Vec<(String, String)>,
+}
+
+impl Sanitizer {
+    fn new(apps: &[PathBuf], anonymize: bool) -> Self {
+        // Webhook 地址里常带有令牌，无论是否匿名化都不放进报告。
+        let mut replacements: Vec<(String, String)> = config::load_config()
+            .unwrap_or_default()
+            .webhooks
+            .into_iter()
+            .map(|webhook| (webhook.url, "<webhook>".into()))
+            .collect();
+        if anonymize {
+            for (index, path) in apps.iter().enumerate() {
+                replacements.push((
+                    path.display().to_string(),
+                    format!("/匿名/应用{}.app", index + 1),
+                ));
+            }
+        }
+        let home = config::home_dir().display().to_string();
+        if home.len() > 1 {
+            replacements.push((home, "~".into()));
+        }
+        // 先替换较长的路径，避免应用路径被主目录替换打断。
+        replacements.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+        Sanitizer { replacements }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(text.to_string(), |text, (from, to)| text.replace(from, to))
+    }
+}
+
+fn os_version() -> String {
+    Command::new("sw_vers")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| format!("{}（sw_vers 不可用）", std::env::consts::OS))
+}
+
+fn system_info(last_failure: Option<&HistoryEntry>) -> String {
+    let mut info = format!(
+        "Dock Dodger {}\n架构：{}\n{}\n数据目录：{}\n配置文件：{}\n暂停隐藏：{}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::ARCH,
+        os_version(),
+        config::data_dir().display(),
+        config::config_path().display(),
+        if pause::is_paused() { "是" } else { "否" },
+    );
+    for job in AgentJob::ALL {
+        let status = agent::agent_status(job);
+        info.push_str(&format!(
+            "代理 {}：{}\n",
+            job.name(),
+            match (status.installed, status.loaded) {
+                (false, _) => "未安装",
+                (true, false) => "已安装，未加载",
+                (true, true) => "已加载",
+            }
+        ));
+    }
+    match last_failure {
+        Some(entry) => info.push_str(&format!(
+            "\n最近一次失败的操作：\n时间：{}\n操作：{:?}\n应用：{}\n错误：{}\n",
+            history::format_timestamp(entry.at),
+            entry.action,
+            entry.path.display(),
+            entry.error.as_deref().unwrap_or_default()
+        )),
+        None => info.push_str("\n没有失败的操作记录\n"),
+    }
+    info
+}
+
+fn tail_lines(text: &str, count: usize) -> &str {
+    match text.rmatch_indices('\n').nth(count) {
+        Some((index, _)) => &text[index + 1..],
+        None => text,
+    }
+}
+
+fn collect(
+    dir: &Path,
+    entries: &[HistoryEntry],
+    sanitizer: &Sanitizer,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir.join("logs"))?;
+    let last_failure = entries.iter().rev().find(|entry| entry.error.is_some());
+    fs::write(
+        dir.join("system.txt"),
+        sanitizer.apply(&system_info(last_failure)),
+    )?;
+    // 诊断包由用户主动生成，apps.json 加密时解密后再写入
+    if let Ok(raw) = fs::read(state::state_file_path())
+        && let Ok(contents) = crypto::open(&raw)
+    {
+        fs::write(
+            dir.join("apps.json"),
+            sanitizer.apply(&String::from_utf8_lossy(&contents)),
+        )?;
+    }
+    if let Ok(contents) = fs::read_to_string(config::config_path()) {
+        fs::write(dir.join("config.json"), sanitizer.apply(&contents))?;
+    }
+    let mut history = String::new();
+    for entry in &entries[entries.len().saturating_sub(RECENT_HISTORY)..] {
+        history.push_str(&serde_json::to_string(entry)?);
+        history.push('\n');
+    }
+    fs::write(dir.join("history.jsonl"), sanitizer.apply(&history))?;
+    for job in AgentJob::ALL {
+        if let Ok(contents) = fs::read_to_string(agent::log_path(job)) {
+            fs::write(
+                dir.join("logs").join(format!("agent-{}.log", job.name())),
+                sanitizer.apply(tail_lines(&contents, LOG_TAIL_LINES)),
+            )?;
+        }
+    }
+    for report in crash::crash_reports().iter().rev().take(CRASH_REPORTS) {
+        if let (Some(name), Ok(contents)) = (report.file_name(), fs::read_to_string(report)) {
+            fs::write(dir.join("logs").join(name), sanitizer.apply(&contents))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn default_report_path() -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    config::home_dir().join("Downloads").join(format!(
+        "dock-dodger-report-{}.zip",
+        &history::format_timestamp(now)[..10]
+    ))
+}
+
+// 收集诊断信息到临时目录，再用 ditto 打包为 zip。
+pub fn create_report(output: &Path, anonymize: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let apps = state::load_apps().unwrap_or_default();
+    let entries = history::load_history().unwrap_or_default();
+    let mut paths: Vec<PathBuf> = apps.iter().map(|app| app.path.clone()).collect();
+    for entry in &entries {
+        if !paths.contains(&entry.path) {
+            paths.push(entry.path.clone());
+        }
+    }
+    let sanitizer = Sanitizer::new(&paths, anonymize);
+
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    let staging =
+        std::env::temp_dir().join(format!("dock-dodger-report-{}-{}", process::id(), stamp));
+    let dir = staging.join(REPORT_DIR);
+    let result = collect(&dir, &entries, &sanitizer).and_then(|_| {
+        let status = Command::new("ditto")
+            .args(["-c", "-k", "--keepParent"])
+            .arg(&dir)
+            .arg(output)
+            .status()?;
+        if !status.success() {
+            return Err("ditto 打包失败".into());
+        }
+        Ok(())
+    });
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
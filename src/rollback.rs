@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::bundle::{self, ui_element};
+use crate::config;
+use crate::dock;
+use crate::history::{HistoryAction, HistoryEntry};
+use crate::shutdown;
+use crate::state::ManagedApp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollbackAction {
+    // 目标时间点时已隐藏，现在显示着
+    Hide,
+    // 目标时间点时显示着（或还没有被隐藏过），现在已隐藏
+    Restore,
+}
+
+impl RollbackAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            RollbackAction::Hide => "重新隐藏",
+            RollbackAction::Restore => "恢复 Dock 图标",
+        }
+    }
+
+    fn inverse(self) -> Self {
+        match self {
+            RollbackAction::Hide => RollbackAction::Restore,
+            RollbackAction::Restore => RollbackAction::Hide,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RollbackStep {
+    pub path: PathBuf,
+    pub action: RollbackAction,
+    // 目标时间点之后第一次改变该应用状态的操作时间，用于预览；恢复快照时为快照创建的时间
+    pub changed_at: u64,
+}
+
+#[derive(Debug)]
+pub struct RollbackFailure {
+    pub step: RollbackStep,
+    pub error: String,
+    // 撤销已执行步骤时失败的应用与原因；为空表示已全部回到执行前的样子
+    pub undo_errors: Vec<(PathBuf, String)>,
+}
+
+// 按操作历史推算 at 时各应用的状态：此前最后一次成功的隐藏或恢复决定是否隐藏，此前没有记录的当时未隐藏。
+// 当前状态优先用 is_hidden 读取 Info.plist，读不到（例如应用已删除）时按历史中的最后一次操作。
+pub fn plan(
+    entries: &[HistoryEntry],
+    at: u64,
+    is_hidden: impl Fn(&Path) -> Option<bool>,
+) -> Vec<RollbackStep> {
+    struct Timeline {
+        then: bool,
+        now: bool,
+        changed_at: Option<u64>,
+    }
+    let mut timelines: BTreeMap<&Path, Timeline> = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| entry.error.is_none()) {
+        let hidden = match entry.action {
+            HistoryAction::Hide => true,
+            HistoryAction::Restore => false,
+            HistoryAction::Drift | HistoryAction::Dock => continue,
+        };
+        let timeline = timelines.entry(&entry.path).or_insert(Timeline {
+            then: false,
+            now: false,
+            changed_at: None,
+        });
+        if entry.at <= at {
+            timeline.then = hidden;
+        } else if timeline.changed_at.is_none() {
+            timeline.changed_at = Some(entry.at);
+        }
+        timeline.now = hidden;
+    }
+    timelines
+        .into_iter()
+        .filter_map(|(path, timeline)| {
+            let now = is_hidden(path).unwrap_or(timeline.now);
+            (now != timeline.then).then(|| RollbackStep {
+                path: path.to_path_buf(),
+                action: if timeline.then {
+                    RollbackAction::Hide
+                } else {
+                    RollbackAction::Restore
+                },
+                changed_at: timeline.changed_at.unwrap_or(at),
+            })
+        })
+        .collect()
+}
+
+// 逐个执行；任何一步失败（或收到退出信号）时按相反顺序撤销已执行的步骤，列表也恢复原样，
+// 调用方只需在成功时保存列表。
+pub fn execute(
+    apps: &mut Vec<ManagedApp>,
+    steps: &[RollbackStep],
+    force: bool,
+) -> Result<(), Box<RollbackFailure>> {
+    let _dock = dock::batch();
+    let before = apps.clone();
+    for (index, step) in steps.iter().enumerate() {
+        let result = if shutdown::requested() {
+            Err("已中断".into())
+        } else {
+            config::ensure_writable().and_then(|_| apply(apps, &step.path, step.action, force))
+        };
+        if let Err(err) = result {
+            let mut failure = RollbackFailure {
+                step: step.clone(),
+                error: err.to_string(),
+                undo_errors: Vec::new(),
+            };
+            for done in steps[..index].iter().rev() {
+                if let Err(err) = apply(apps, &done.path, done.action.inverse(), true) {
+                    failure
+                        .undo_errors
+                        .push((done.path.clone(), err.to_string()));
+                }
+            }
+            *apps = before;
+            return Err(Box::new(failure));
+        }
+    }
+    Ok(())
+}
+
+fn apply(
+    apps: &mut Vec<ManagedApp>,
+    path: &Path,
+    action: RollbackAction,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let index = apps.iter().position(|app| app.path == path);
+    if let Some(index) = index
+        && !force
+        && let Some(reason) = apps[index].external_change()
+    {
+        return Err(format!("可能由其他用户或工具管理（{}）", reason).into());
+    }
+    match (action, index) {
+        (RollbackAction::Hide, Some(index)) => {
+            apps[index].hide_dock_icon()?;
+            apps[index].record_checksum();
+        }
+        (RollbackAction::Hide, None) => {
+            let original = ui_element(path)?;
+            bundle::hide_dock_icon(path)?;
+            let mut app = ManagedApp::new(path.to_path_buf());
+            app.record_hide(original);
+            app.record_checksum();
+            apps.push(app);
+        }
+        (RollbackAction::Restore, Some(index)) => {
+            apps[index].restore_dock_icon()?;
+            apps.remove(index);
+        }
+        (RollbackAction::Restore, None) => bundle::restore_dock_icon(path)?,
+    }
+    Ok(())
+}
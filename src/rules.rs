@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::bundle::{bundle_identifier, bundle_name};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    #[default]
+    Hide,
+    Prompt,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub action: RuleAction,
+}
+
+impl Rule {
+    pub fn describe(&self) -> String {
+        match (&self.bundle_id, &self.name) {
+            (Some(bundle_id), Some(name)) => format!("bundle_id={} name=/{}/", bundle_id, name),
+            (Some(bundle_id), None) => format!("bundle_id={}", bundle_id),
+            (None, Some(name)) => format!("name=/{}/", name),
+            (None, None) => "(空规则)".into(),
+        }
+    }
+
+    pub fn matches(&self, app: &Path) -> Result<bool, regex::Error> {
+        if self.bundle_id.is_none() && self.name.is_none() {
+            return Ok(false);
+        }
+        if let Some(pattern) = &self.bundle_id {
+            let Some(bundle_id) = bundle_identifier(app) else {
+                return Ok(false);
+            };
+            if !glob_match(pattern, &bundle_id) {
+                return Ok(false);
+            }
+        }
+        if let Some(pattern) = &self.name {
+            let regex = RegexBuilder::new(pattern).case_insensitive(true).build()?;
+            let file_name = app
+                .file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let display_name = bundle_name(app).unwrap_or_default();
+            if !regex.is_match(&file_name) && !regex.is_match(&display_name) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+pub fn find_matching_rule<'a>(rules: &'a [Rule], app: &Path) -> Option<&'a Rule> {
+    rules.iter().find(|rule| match rule.matches(app) {
+        Ok(matched) => matched,
+        Err(err) => {
+            eprintln!("[Rules] 规则 {} 的正则无效：{}", rule.describe(), err);
+            false
+        }
+    })
+}
+
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|ch| *ch == '*')
+}
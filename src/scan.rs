@@ -0,0 +1,143 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bundle::{is_app_bundle, is_dock_icon_hidden_in};
+use crate::config::{self, Config};
+use crate::rules::glob_match;
+use crate::store::{BundleStore, FsStore};
+
+pub const DEFAULT_SCAN_DEPTH: usize = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScanRoot {
+    Path(PathBuf),
+    Detailed {
+        path: PathBuf,
+        #[serde(default = "default_depth")]
+        depth: usize,
+    },
+}
+
+fn default_depth() -> usize {
+    DEFAULT_SCAN_DEPTH
+}
+
+impl ScanRoot {
+    pub fn path(&self) -> PathBuf {
+        match self {
+            ScanRoot::Path(path) | ScanRoot::Detailed { path, .. } => config::expand_home(path),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        match self {
+            ScanRoot::Path(_) => DEFAULT_SCAN_DEPTH,
+            ScanRoot::Detailed { depth, .. } => *depth,
+        }
+    }
+}
+
+pub fn default_scan_roots() -> Vec<ScanRoot> {
+    vec![
+        ScanRoot::Path(PathBuf::from("/Applications")),
+        ScanRoot::Path(PathBuf::from("~/Applications")),
+    ]
+}
+
+// 选择器、adopt 扫描、导入查找与目录监视共用同一组扫描目录和排除规则。
+#[derive(Debug, Clone)]
+pub struct ScanScope {
+    pub roots: Vec<(PathBuf, usize)>,
+    pub exclude: Vec<String>,
+}
+
+impl ScanScope {
+    pub fn from_config(config: &Config) -> Self {
+        ScanScope {
+            roots: config
+                .scan_roots
+                .iter()
+                .map(|root| (root.path(), root.depth()))
+                .collect(),
+            exclude: config
+                .scan_exclude
+                .iter()
+                .map(|pattern| {
+                    config::expand_home(Path::new(pattern))
+                        .display()
+                        .to_string()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load() -> Self {
+        ScanScope::from_config(&config::load_config().unwrap_or_default())
+    }
+
+    // 拖入的文件夹或 hide-all 指定的目录：只扫描该目录，沿用配置中的排除规则。
+    pub fn folder(dir: &Path, depth: usize) -> Self {
+        ScanScope {
+            roots: vec![(dir.to_path_buf(), depth)],
+            exclude: ScanScope::load().exclude,
+        }
+    }
+
+    pub fn root_dirs(&self) -> Vec<PathBuf> {
+        self.roots.iter().map(|(path, _)| path.clone()).collect()
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let text = path.display().to_string();
+        self.exclude.iter().any(|pattern| {
+            glob_match(pattern, &text) || path.ancestors().any(|dir| dir == Path::new(pattern))
+        })
+    }
+
+    pub fn find_app_bundles(&self) -> Vec<PathBuf> {
+        self.find_app_bundles_in(&FsStore)
+    }
+
+    pub fn find_app_bundles_in(&self, store: &dyn BundleStore) -> Vec<PathBuf> {
+        let mut found = Vec::new();
+        for (root, depth) in &self.roots {
+            self.collect_bundles(store, root, *depth, &mut found);
+        }
+        found
+    }
+
+    fn collect_bundles(
+        &self,
+        store: &dyn BundleStore,
+        dir: &Path,
+        depth: usize,
+        found: &mut Vec<PathBuf>,
+    ) {
+        let Ok(entries) = store.list_dir(dir) else {
+            return;
+        };
+        for (path, is_dir) in entries {
+            if self.is_excluded(&path) {
+                continue;
+            }
+            if is_app_bundle(&path) {
+                found.push(path);
+            } else if depth > 1 && is_dir {
+                self.collect_bundles(store, &path, depth - 1, found);
+            }
+        }
+    }
+
+    pub fn find_hidden_apps(&self) -> Vec<PathBuf> {
+        self.find_hidden_apps_in(&FsStore)
+    }
+
+    pub fn find_hidden_apps_in(&self, store: &dyn BundleStore) -> Vec<PathBuf> {
+        self.find_app_bundles_in(store)
+            .into_iter()
+            .filter(|path| is_dock_icon_hidden_in(store, path).unwrap_or(false))
+            .collect()
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::{self, Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bundle::restore_dock_icon;
+use crate::config;
+use crate::crypto;
+use crate::dock;
+use crate::pause::PauseResult;
+use crate::shutdown;
+use crate::state::{ManagedApp, write_atomic};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionJournal {
+    pid: u32,
+    apps: Vec<PathBuf>,
+}
+
+pub fn journal_path() -> PathBuf {
+    config::data_dir().join("session.json")
+}
+
+fn process_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+// 日志与 apps.json 分开、每次变化立即写入，不受自动保存延迟影响，
+// 这样即使进程崩溃，下次启动时也知道哪些应用只应在上次运行期间隐藏。
+pub fn write_journal(apps: &[ManagedApp]) -> Result<(), Box<dyn std::error::Error>> {
+    let journal = SessionJournal {
+        pid: process::id(),
+        apps: apps
+            .iter()
+            .filter(|app| app.session)
+            .map(|app| app.path.clone())
+            .collect(),
+    };
+    if journal.apps.is_empty() {
+        return remove_journal();
+    }
+    write_atomic(
+        &journal_path(),
+        &crypto::seal(&serde_json::to_vec_pretty(&journal)?)?,
+    )?;
+    Ok(())
+}
+
+fn remove_journal() -> Result<(), Box<dyn std::error::Error>> {
+    match fs::remove_file(journal_path()) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+// 收到退出信号时在当前应用处理完后停止，返回的结果只包含已处理的应用。
+fn restore_paths(apps: &mut Vec<ManagedApp>, paths: &[PathBuf]) -> Vec<PauseResult> {
+    let _dock = dock::batch();
+    let results: Vec<PauseResult> = paths
+        .iter()
+        .take_while(|_| !shutdown::requested())
+        .map(|path| {
+            let restored = match apps.iter().find(|app| &app.path == path) {
+                Some(app) => app.restore_dock_icon(),
+                None => restore_dock_icon(path),
+            };
+            PauseResult {
+                path: path.clone(),
+                error: restored.err().map(|err| err.to_string()),
+            }
+        })
+        .collect();
+    // 恢复失败的应用仍留在列表中，以后还能从记录中恢复。
+    let restored: Vec<&PathBuf> = results
+        .iter()
+        .filter(|result| result.error.is_none())
+        .map(|result| &result.path)
+        .collect();
+    apps.retain(|app| !restored.contains(&&app.path));
+    results
+}
+
+// 中途停止或有应用恢复失败时，把失败与未处理的应用留在日志中，
+// 下次启动或再次执行 session restore 时继续恢复。
+fn finish_journal(
+    paths: &[PathBuf],
+    results: &[PauseResult],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let remaining: Vec<PathBuf> = results
+        .iter()
+        .filter(|result| result.error.is_some())
+        .map(|result| result.path.clone())
+        .chain(paths[results.len()..].iter().cloned())
+        .collect();
+    if remaining.is_empty() {
+        return remove_journal();
+    }
+    let journal = SessionJournal {
+        pid: process::id(),
+        apps: remaining,
+    };
+    write_atomic(
+        &journal_path(),
+        &crypto::seal(&serde_json::to_vec_pretty(&journal)?)?,
+    )?;
+    Ok(())
+}
+
+pub fn end_session(
+    apps: &mut Vec<ManagedApp>,
+) -> Result<Vec<PauseResult>, Box<dyn std::error::Error>> {
+    let paths: Vec<PathBuf> = apps
+        .iter()
+        .filter(|app| app.session)
+        .map(|app| app.path.clone())
+        .collect();
+    let results = restore_paths(apps, &paths);
+    finish_journal(&paths, &results)?;
+    Ok(results)
+}
+
+fn restore_recorded(
+    apps: &mut Vec<ManagedApp>,
+    check_owner: bool,
+) -> Result<Vec<PauseResult>, Box<dyn std::error::Error>> {
+    let path = journal_path();
+    let mut journal = SessionJournal::default();
+    if path.exists() {
+        journal = serde_json::from_slice(&crypto::open(&fs::read(&path)?)?)?;
+        if check_owner && journal.pid != process::id() && process_alive(journal.pid) {
+            return Err(format!("另一个 Dock Dodger 进程（{}）正在运行", journal.pid).into());
+        }
+    }
+    for app in apps.iter().filter(|app| app.session) {
+        if !journal.apps.contains(&app.path) {
+            journal.apps.push(app.path.clone());
+        }
+    }
+    let results = restore_paths(apps, &journal.apps);
+    finish_journal(&journal.apps, &results)?;
+    Ok(results)
+}
+
+// 上次运行没有正常退出时，恢复日志与列表中标记为仅本次运行的应用。
+pub fn recover_stale_session(
+    apps: &mut Vec<ManagedApp>,
+) -> Result<Vec<PauseResult>, Box<dyn std::error::Error>> {
+    restore_recorded(apps, true)
+}
+
+// 注销时由 launchd 代理调用，不论图形界面是否仍在运行都恢复全部仅本次运行的应用。
+pub fn restore_all_session_apps(
+    apps: &mut Vec<ManagedApp>,
+) -> Result<Vec<PauseResult>, Box<dyn std::error::Error>> {
+    restore_recorded(apps, false)
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::config::{self, Config};
+
+// 可自定义快捷键的界面操作：(标识, 名称, 默认快捷键)。
+pub const ACTIONS: [(&str, &str, &str); 7] = [
+    ("add", "从已安装应用中选择", "CmdOrCtrl+N"),
+    ("toggle_pause", "暂停/继续隐藏", "CmdOrCtrl+Shift+P"),
+    ("backups", "查看备份", "CmdOrCtrl+B"),
+    ("launcher", "打开启动器", "CmdOrCtrl+L"),
+    ("stats", "查看统计", "CmdOrCtrl+I"),
+    ("quick_launch", "快速启动（全局）", "CmdOrCtrl+Shift+Space"),
+    (
+        "drop_target",
+        "显示/隐藏投放窗口（全局）",
+        "CmdOrCtrl+Shift+D",
+    ),
+];
+
+// 注册为全局快捷键的操作，在其它应用处于前台时也生效。
+pub const GLOBAL_ACTIONS: [&str; 2] = ["quick_launch", "drop_target"];
+
+// 系统菜单或网页编辑操作已经占用的组合。
+const RESERVED: [&str; 10] = [
+    "CmdOrCtrl+Q",
+    "CmdOrCtrl+W",
+    "CmdOrCtrl+H",
+    "CmdOrCtrl+M",
+    "CmdOrCtrl+A",
+    "CmdOrCtrl+C",
+    "CmdOrCtrl+V",
+    "CmdOrCtrl+X",
+    "CmdOrCtrl+Z",
+    "CmdOrCtrl+Shift+Z",
+];
+
+const MODIFIERS: [&str; 4] = ["CmdOrCtrl", "Ctrl", "Alt", "Shift"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Shortcut {
+    pub action: &'static str,
+    pub label: &'static str,
+    pub default: &'static str,
+    // 空字符串表示不使用快捷键
+    pub accelerator: String,
+}
+
+fn parse_key(key: &str) -> Option<String> {
+    let upper = key.to_ascii_uppercase();
+    let mut chars = upper.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() || ",.;/[]-=`'\\".contains(c) => {
+            Some(c.to_string())
+        }
+        _ if upper
+            .strip_prefix('F')
+            .and_then(|n| n.parse::<u8>().ok())
+            .is_some_and(|n| (1..=12).contains(&n)) =>
+        {
+            Some(upper)
+        }
+        _ => match upper.as_str() {
+            "SPACE" => Some("Space".into()),
+            "ENTER" | "RETURN" => Some("Enter".into()),
+            "BACKSPACE" | "DELETE" => Some("Backspace".into()),
+            _ => None,
+        },
+    }
+}
+
+// 统一成 CmdOrCtrl+Ctrl+Alt+Shift+键 的写法，便于比较冲突；没有 Cmd、Ctrl 或 Alt 的组合
+// 会和普通输入冲突，只有功能键例外。
+pub fn normalize(text: &str) -> Result<String, String> {
+    let mut modifiers = [false; 4];
+    let mut key = None;
+    for part in text.split('+').map(str::trim) {
+        let index = match part.to_ascii_lowercase().as_str() {
+            "cmdorctrl" | "commandorcontrol" | "cmd" | "command" | "super" | "meta" => Some(0),
+            "ctrl" | "control" => Some(1),
+            "alt" | "option" | "opt" => Some(2),
+            "shift" => Some(3),
+            _ => None,
+        };
+        match index {
+            Some(index) => modifiers[index] = true,
+            None if key.is_none() => {
+                key = Some(parse_key(part).ok_or_else(|| format!("无法识别的按键：{}", part))?)
+            }
+            None => return Err(format!("只能包含一个非修饰键：{}", text)),
+        }
+    }
+    let key = key.ok_or_else(|| format!("缺少按键：{}", text))?;
+    if !modifiers[..3].iter().any(|&on| on) && !key.starts_with('F') {
+        return Err(format!("需要包含 Cmd、Ctrl 或 Alt：{}", text));
+    }
+    let mut parts: Vec<&str> = MODIFIERS
+        .iter()
+        .zip(modifiers)
+        .filter(|(_, on)| *on)
+        .map(|(name, _)| *name)
+        .collect();
+    parts.push(&key);
+    Ok(parts.join("+"))
+}
+
+// 配置中没有写的操作使用默认快捷键。
+pub fn resolve(overrides: &BTreeMap<String, String>) -> Vec<Shortcut> {
+    ACTIONS
+        .iter()
+        .map(|&(action, label, default)| Shortcut {
+            action,
+            label,
+            default,
+            accelerator: overrides
+                .get(action)
+                .cloned()
+                .unwrap_or_else(|| default.to_string()),
+        })
+        .collect()
+}
+
+// 返回所有问题：未知操作、无法解析、系统保留以及两个操作使用同一组合。
+pub fn validate(overrides: &BTreeMap<String, String>) -> Vec<String> {
+    let mut errors: Vec<String> = overrides
+        .keys()
+        .filter(|action| !ACTIONS.iter().any(|(known, _, _)| known == action))
+        .map(|action| format!("未知的操作：{}", action))
+        .collect();
+    let mut used: BTreeMap<String, &str> = BTreeMap::new();
+    for shortcut in resolve(overrides) {
+        if shortcut.accelerator.is_empty() {
+            continue;
+        }
+        let normalized = match normalize(&shortcut.accelerator) {
+            Ok(normalized) => normalized,
+            Err(err) => {
+                errors.push(format!("{}：{}", shortcut.label, err));
+                continue;
+            }
+        };
+        if RESERVED.contains(&normalized.as_str()) {
+            errors.push(format!("{}：{} 已被系统占用", shortcut.label, normalized));
+        } else if let Some(other) = used.insert(normalized.clone(), shortcut.label) {
+            errors.push(format!(
+                "“{}”与“{}”都使用了 {}",
+                shortcut.label, other, normalized
+            ));
+        }
+    }
+    errors
+}
+
+// 界面实际使用的快捷键：有问题的条目不生效，其余按规范写法返回。
+pub fn effective(config: &Config) -> (Vec<Shortcut>, Vec<String>) {
+    let errors = validate(&config.shortcuts);
+    let mut seen = Vec::new();
+    let shortcuts = resolve(&config.shortcuts)
+        .into_iter()
+        .map(|mut shortcut| {
+            shortcut.accelerator = normalize(&shortcut.accelerator)
+                .ok()
+                .filter(|normalized| {
+                    !RESERVED.contains(&normalized.as_str()) && !seen.contains(normalized)
+                })
+                .inspect(|normalized| seen.push(normalized.clone()))
+                .unwrap_or_default();
+            shortcut
+        })
+        .collect();
+    (shortcuts, errors)
+}
+
+// 校验不通过时每个问题占一行返回。与默认值相同的条目不写入配置文件。
+pub fn save(shortcuts: &BTreeMap<String, String>) -> Result<(), Box<dyn std::error::Error>> {
+    let errors = validate(shortcuts);
+    if !errors.is_empty() {
+        return Err(errors.join("\n").into());
+    }
+    let overrides: BTreeMap<&String, &String> = shortcuts
+        .iter()
+        .filter(|(action, accelerator)| {
+            ACTIONS
+                .iter()
+                .all(|(known, _, default)| known != action || default != accelerator)
+        })
+        .collect();
+    let value = if overrides.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_value(overrides)?)
+    };
+    config::update_config("shortcuts", value)
+}
@@ -0,0 +1,84 @@
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+
+const SIGHUP: i32 = 1;
+const SIGINT: i32 = 2;
+const SIGTERM: i32 = 15;
+const SIG_DFL: usize = 0;
+
+unsafe extern "C" {
+    fn signal(signum: i32, handler: usize) -> usize;
+    fn raise(signum: i32) -> i32;
+    fn write(fd: i32, buf: *const u8, count: usize) -> isize;
+}
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static RELOAD: AtomicBool = AtomicBool::new(false);
+static SIGNAL: AtomicI32 = AtomicI32::new(0);
+// 正在写入 Info.plist、保存列表或执行批量操作的数量，大于 0 时推迟退出。
+static DEFERRED: AtomicUsize = AtomicUsize::new(0);
+
+const NOTICE: &[u8] = "\n正在完成当前操作后退出，再按一次 Ctrl-C 强制退出\n".as_bytes();
+
+fn terminate(signum: i32) {
+    unsafe {
+        signal(signum, SIG_DFL);
+        raise(signum);
+    }
+}
+
+// 信号处理函数里只能做异步信号安全的操作：原子变量、signal、raise 和 write。
+extern "C" fn on_signal(signum: i32) {
+    if DEFERRED.load(Ordering::SeqCst) == 0 || REQUESTED.swap(true, Ordering::SeqCst) {
+        terminate(signum);
+        return;
+    }
+    SIGNAL.store(signum, Ordering::SeqCst);
+    unsafe {
+        write(2, NOTICE.as_ptr(), NOTICE.len());
+    }
+}
+
+extern "C" fn on_reload(_signum: i32) {
+    RELOAD.store(true, Ordering::SeqCst);
+}
+
+pub fn install() {
+    for signum in [SIGHUP, SIGINT, SIGTERM] {
+        unsafe {
+            signal(signum, on_signal as extern "C" fn(i32) as usize);
+        }
+    }
+}
+
+// 常驻运行的图形界面和 rpc 服务把 SIGHUP 用作重新加载配置，其余命令收到 SIGHUP 时与 SIGTERM 相同。
+pub fn enable_reload() {
+    unsafe {
+        signal(SIGHUP, on_reload as extern "C" fn(i32) as usize);
+    }
+}
+
+pub fn take_reload() -> bool {
+    RELOAD.swap(false, Ordering::SeqCst)
+}
+
+// 收到 SIGINT/SIGTERM/SIGHUP 后，批量操作应在处理完当前一项后停止并保存。
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+// 持有期间收到的退出信号会推迟到最后一个 Deferred 释放时再按原信号退出，
+// 避免 Info.plist 或应用列表只写了一半。
+pub struct Deferred(());
+
+pub fn defer() -> Deferred {
+    DEFERRED.fetch_add(1, Ordering::SeqCst);
+    Deferred(())
+}
+
+impl Drop for Deferred {
+    fn drop(&mut self) {
+        if DEFERRED.fetch_sub(1, Ordering::SeqCst) == 1 && requested() {
+            terminate(SIGNAL.load(Ordering::SeqCst));
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::path::Path;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SigningInfo {
+    pub signed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notarized: Option<bool>,
+}
+
+fn command_stderr(program: &str, args: &[&str]) -> Option<(bool, String)> {
+    let output = Command::new(program).args(args).output().ok()?;
+    Some((
+        output.status.success(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    ))
+}
+
+fn field<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    text.lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+        .map(str::trim)
+}
+
+// 修改 Info.plist 会使签名失效，所以要在第一次隐藏之前读取。
+pub fn read_signing_info(app: &Path) -> SigningInfo {
+    let app = app.display().to_string();
+    let mut info = SigningInfo::default();
+    if let Some((signed, text)) = command_stderr("codesign", &["-dv", "--verbose=2", &app]) {
+        info.signed = signed;
+        info.authority = field(&text, "Authority").map(str::to_string);
+        info.team_id = field(&text, "TeamIdentifier")
+            .filter(|team| *team != "not set")
+            .map(str::to_string);
+    }
+    if info.signed
+        && let Some((_, text)) =
+            command_stderr("spctl", &["--assess", "--type", "execute", "-vv", &app])
+    {
+        info.notarized = field(&text, "source").map(|source| source.contains("Notarized"));
+    }
+    info
+}
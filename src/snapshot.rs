@@ -0,0 +1,182 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::config;
+use crate::crypto;
+use crate::migrate::SCHEMA_VERSION;
+use crate::rollback::{RollbackAction, RollbackStep};
+use crate::state::{self, ManagedApp, write_atomic};
+
+const MAX_NAME_CHARS: usize = 64;
+
+// 快照保存完整的列表条目（隐藏方式、原始值、标签等），格式版本随列表一起升级。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub name: String,
+    pub created_at: u64,
+    pub apps: Vec<ManagedApp>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotInfo {
+    pub name: String,
+    pub created_at: u64,
+    pub apps: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SnapshotPlan {
+    pub steps: Vec<RollbackStep>,
+    // 读不到 Info.plist（通常是应用已删除）的应用，保持原样
+    pub missing: Vec<PathBuf>,
+}
+
+pub fn snapshots_dir() -> PathBuf {
+    config::data_dir().join("snapshots")
+}
+
+fn snapshot_file(name: &str) -> PathBuf {
+    snapshots_dir().join(format!("{}.json", name))
+}
+
+fn validate_name(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let valid = !name.trim().is_empty()
+        && name.chars().count() <= MAX_NAME_CHARS
+        && !name.starts_with('.')
+        && !name.chars().any(|c| c == '/' || c == ':' || c.is_control());
+    if !valid {
+        return Err(format!(
+            "快照名称不能为空、不能以 . 开头、不能包含 / 或 :，且不超过 {} 个字符：{}",
+            MAX_NAME_CHARS, name
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// 同名快照直接覆盖。
+pub fn take(name: &str, apps: &[ManagedApp]) -> Result<Snapshot, Box<dyn std::error::Error>> {
+    validate_name(name)?;
+    let snapshot = Snapshot {
+        name: name.to_string(),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default(),
+        apps: apps.to_vec(),
+    };
+    let mut data = serde_json::to_value(&snapshot)?;
+    data["schema_version"] = json!(SCHEMA_VERSION);
+    write_atomic(
+        &snapshot_file(name),
+        &crypto::seal(&serde_json::to_vec_pretty(&data)?)?,
+    )?;
+    Ok(snapshot)
+}
+
+pub fn load(name: &str) -> Result<Snapshot, Box<dyn std::error::Error>> {
+    validate_name(name)?;
+    let file = snapshot_file(name);
+    let raw = fs::read(&file).map_err(|err| format!("读取快照 {} 失败：{}", name, err))?;
+    let value: Value = serde_json::from_slice(&crypto::open(&raw)?)?;
+    let version = value["schema_version"].as_u64().unwrap_or_default();
+    state::ensure_supported_version(version)?;
+    let apps = json!({ "schema_version": version, "apps": value["apps"] }).to_string();
+    let (apps, _) = state::parse_state(&apps)?;
+    Ok(Snapshot {
+        name: value["name"].as_str().unwrap_or(name).to_string(),
+        created_at: value["created_at"].as_u64().unwrap_or_default(),
+        apps,
+    })
+}
+
+// 最新的在前；读不出的文件跳过。
+pub fn list() -> Result<Vec<SnapshotInfo>, Box<dyn std::error::Error>> {
+    let dir = snapshots_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut infos: Vec<SnapshotInfo> = fs::read_dir(dir)?
+        .filter_map(|item| item.ok())
+        .filter_map(|item| {
+            let path = item.path();
+            (path.extension()? == "json")
+                .then(|| path.file_stem()?.to_str().map(str::to_string))
+                .flatten()
+        })
+        .filter_map(|name| load(&name).ok())
+        .map(|snapshot| SnapshotInfo {
+            name: snapshot.name,
+            created_at: snapshot.created_at,
+            apps: snapshot.apps.len(),
+        })
+        .collect();
+    infos.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(a.name.cmp(&b.name)));
+    Ok(infos)
+}
+
+pub fn delete(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    validate_name(name)?;
+    config::ensure_writable()?;
+    fs::remove_file(snapshot_file(name))
+        .map_err(|err| format!("删除快照 {} 失败：{}", name, err))?;
+    Ok(())
+}
+
+// 快照中现在显示着的应用重新隐藏，不在快照中的已管理应用恢复；步骤交给 rollback::execute 作为一批执行。
+pub fn plan(
+    snapshot: &Snapshot,
+    apps: &[ManagedApp],
+    is_hidden: impl Fn(&Path) -> Option<bool>,
+) -> SnapshotPlan {
+    let mut plan = SnapshotPlan::default();
+    let wanted: HashSet<&Path> = snapshot.apps.iter().map(|app| app.path.as_path()).collect();
+    let mut step = |path: &Path, action| {
+        plan.steps.push(RollbackStep {
+            path: path.to_path_buf(),
+            action,
+            changed_at: snapshot.created_at,
+        })
+    };
+    let mut missing = Vec::new();
+    for app in &snapshot.apps {
+        match is_hidden(&app.path) {
+            None => missing.push(app.path.clone()),
+            Some(false) => step(&app.path, RollbackAction::Hide),
+            Some(true) => {}
+        }
+    }
+    for app in apps
+        .iter()
+        .filter(|app| !wanted.contains(app.path.as_path()))
+    {
+        match is_hidden(&app.path) {
+            None => missing.push(app.path.clone()),
+            Some(_) => step(&app.path, RollbackAction::Restore),
+        }
+    }
+    plan.missing = missing;
+    plan
+}
+
+// 执行成功后调用：列表换成快照中的条目（保留当时的原始值与设置），已不存在的应用保持原来的条目。
+pub fn apply_entries(apps: &mut Vec<ManagedApp>, snapshot: &Snapshot, missing: &[PathBuf]) {
+    let mut entries: Vec<ManagedApp> = snapshot
+        .apps
+        .iter()
+        .filter(|app| !missing.contains(&app.path))
+        .cloned()
+        .map(|mut app| {
+            app.record_checksum();
+            app
+        })
+        .collect();
+    let kept: HashSet<PathBuf> = entries.iter().map(|app| app.path.clone()).collect();
+    entries.extend(apps.drain(..).filter(|app| !kept.contains(&app.path)));
+    *apps = entries;
+}
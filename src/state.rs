@@ -0,0 +1,810 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::bundle::{
+    bundle_identifier, bundle_version, hide_dock_icon, is_app_bundle, plist_checksum,
+    plist_owner_and_mtime, restore_dock_icon, restore_original_ui_element, ui_element,
+    visible_login_item_helpers,
+};
+use crate::config;
+use crate::crypto;
+use crate::dock::{self, DockTile};
+use crate::explain;
+use crate::history::{self, HistoryAction};
+use crate::launchpad::{self, LaunchpadEntry};
+use crate::migrate::{self, SCHEMA_VERSION};
+use crate::shutdown;
+use crate::signing::SigningInfo;
+use crate::state_store;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HideRecord {
+    pub hidden_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_ui_element: Option<plist::Value>,
+}
+
+// 与主应用一起隐藏的登录项辅助程序，记录原来的 LSUIElement 以便一起还原。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HelperRecord {
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_ui_element: Option<plist::Value>,
+}
+
+// 隐藏后自动重新启动应用时的选项：background 对应 open -g（不切到前台），hidden 对应 open -j
+// （启动后隐藏窗口），args 通过 --args 传给应用。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelaunchOptions {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub background: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+}
+
+// Finder 风格的颜色标记，用于在列表中区分不同用途的应用。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorLabel {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Gray,
+}
+
+impl ColorLabel {
+    pub const ALL: [ColorLabel; 7] = [
+        ColorLabel::Red,
+        ColorLabel::Orange,
+        ColorLabel::Yellow,
+        ColorLabel::Green,
+        ColorLabel::Blue,
+        ColorLabel::Purple,
+        ColorLabel::Gray,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorLabel::Red => "red",
+            ColorLabel::Orange => "orange",
+            ColorLabel::Yellow => "yellow",
+            ColorLabel::Green => "green",
+            ColorLabel::Blue => "blue",
+            ColorLabel::Purple => "purple",
+            ColorLabel::Gray => "gray",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        Self::ALL.into_iter().find(|label| label.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedApp {
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<ColorLabel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub session: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    // 隐藏时记下的 Bundle ID，供命令行按 Bundle ID 而不是路径指定应用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hide: Option<HideRecord>,
+    // 设置后，重新隐藏成功时若应用正在运行，会退出并按选项重新启动，使改动立即生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relaunch: Option<RelaunchOptions>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub helpers: Vec<HelperRecord>,
+    // 从启动台移除前的位置，恢复时放回
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub launchpad: Vec<LaunchpadEntry>,
+    // 移除前固定在 Dock 中的图标，恢复时放回
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dock_tiles: Vec<DockTile>,
+}
+
+impl ManagedApp {
+    pub fn new(path: PathBuf) -> Self {
+        ManagedApp {
+            path,
+            tags: Vec::new(),
+            label: None,
+            checksum: None,
+            session: false,
+            owner: None,
+            modified_at: None,
+            version: None,
+            bundle_id: None,
+            signing: None,
+            hide: None,
+            relaunch: None,
+            helpers: Vec::new(),
+            launchpad: Vec::new(),
+            dock_tiles: Vec::new(),
+        }
+    }
+
+    pub fn record_checksum(&mut self) {
+        self.checksum = plist_checksum(&self.path).ok();
+        let metadata = plist_owner_and_mtime(&self.path).ok();
+        self.owner = metadata.map(|(owner, _)| owner);
+        self.modified_at = metadata.map(|(_, modified_at)| modified_at);
+        self.version = bundle_version(&self.path);
+        if let Some(bundle_id) = bundle_identifier(&self.path) {
+            self.bundle_id = Some(bundle_id);
+        }
+    }
+
+    pub fn record_hide(&mut self, original_ui_element: Option<plist::Value>) {
+        let hidden_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        self.hide = Some(HideRecord {
+            hidden_at,
+            original_ui_element,
+        });
+    }
+
+    // 有隐藏记录时还原成隐藏前的值，否则（如 adopt 接管的应用）写入 "0"。
+    // 一起隐藏的登录项辅助程序同时还原；应用更新后已不存在的辅助程序跳过。
+    pub fn restore_dock_icon(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match &self.hide {
+            Some(record) => {
+                restore_original_ui_element(&self.path, record.original_ui_element.as_ref())?
+            }
+            None => restore_dock_icon(&self.path)?,
+        }
+        for helper in self.helpers.iter().filter(|helper| helper.path.exists()) {
+            restore_original_ui_element(&helper.path, helper.original_ui_element.as_ref())?;
+        }
+        let (changed, commands) =
+            explain::collect(|| -> Result<bool, Box<dyn std::error::Error>> {
+                let mut changed =
+                    !self.launchpad.is_empty() && launchpad::restore(&self.launchpad)? > 0;
+                changed |=
+                    dock::restore_tiles(&self.path, self.bundle_id.as_deref(), &self.dock_tiles)?
+                        > 0;
+                Ok(changed)
+            });
+        self.record_dock_changes(matches!(changed, Ok(true)), commands);
+        changed?;
+        Ok(())
+    }
+
+    // 启动台与固定图标的改动附上等价命令单独记一条历史（不计入隐藏/恢复的统计），
+    // 有改动时重启 Dock。
+    fn record_dock_changes(&self, changed: bool, mut commands: Vec<String>) {
+        if changed {
+            commands.extend(dock::restart_commands());
+            dock::request_restart();
+        }
+        if !commands.is_empty() {
+            history::record_with_commands(HistoryAction::Dock, &self.path, None, commands);
+        }
+    }
+
+    fn recorded_bundle_id(&self) -> Option<String> {
+        self.bundle_id
+            .clone()
+            .or_else(|| bundle_identifier(&self.path))
+    }
+
+    // 从启动台移除并记下原来的位置，返回新移除的图标数。
+    // 又找到图标说明之前记下的已经插回（暂停后）或被启动台重新收录，以本次的位置为准。
+    pub fn hide_from_launchpad(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let bundle_id = self
+            .recorded_bundle_id()
+            .ok_or("应用没有 Bundle ID，无法从启动台移除")?;
+        let removed = launchpad::remove(&bundle_id)?;
+        let count = removed.len();
+        if count > 0 {
+            self.launchpad = removed;
+        }
+        Ok(count)
+    }
+
+    // 移除固定在 Dock 中的图标并记下原来的位置，返回新移除的图标数。
+    // 又找到图标说明之前记下的已经插回（暂停后或手动重新固定），以本次的位置为准。
+    pub fn remove_dock_tiles(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let removed = dock::remove_tiles(&self.path, self.recorded_bundle_id().as_deref())?;
+        let count = removed.len();
+        if count > 0 {
+            self.dock_tiles = removed;
+        }
+        Ok(count)
+    }
+
+    // 隐藏 Dock 图标后调用：按配置一并从启动台移除（hide_from_launchpad）、
+    // 移除固定在 Dock 中的图标（remove_dock_tiles），有改动时重启一次 Dock。
+    // 失败只打印日志，不影响已经完成的隐藏。
+    pub fn apply_hide_options(&mut self) {
+        let config = config::load_config().unwrap_or_default();
+        let (changed, commands) = explain::collect(|| {
+            let mut changed = false;
+            if config.hide_from_launchpad {
+                match self.hide_from_launchpad() {
+                    Ok(count) => changed |= count > 0,
+                    Err(err) => eprintln!(
+                        "[Launchpad] 从启动台移除失败：{}，错误：{}",
+                        self.path.display(),
+                        err
+                    ),
+                }
+            }
+            if config.remove_dock_tiles {
+                match self.remove_dock_tiles() {
+                    Ok(count) => changed |= count > 0,
+                    Err(err) => eprintln!(
+                        "[Dock] 移除固定的 Dock 图标失败：{}，错误：{}",
+                        self.path.display(),
+                        err
+                    ),
+                }
+            }
+            changed
+        });
+        self.record_dock_changes(changed, commands);
+    }
+
+    // 重新隐藏主应用与一起管理的登录项辅助程序，用于重新应用和继续隐藏。
+    // 暂停或恢复时插回的启动台图标与固定图标一并重新移除。
+    pub fn hide_dock_icon(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        hide_dock_icon(&self.path)?;
+        for helper in self.helpers.iter().filter(|helper| helper.path.exists()) {
+            hide_dock_icon(&helper.path)?;
+        }
+        self.reapply_removals();
+        Ok(())
+    }
+
+    // 只重做隐藏时确实做过的移除，与当前配置无关；失败只打印日志，不影响已经完成的隐藏。
+    fn reapply_removals(&mut self) {
+        let (changed, commands) = explain::collect(|| {
+            let mut changed = false;
+            if !self.launchpad.is_empty() {
+                match self.hide_from_launchpad() {
+                    Ok(count) => changed |= count > 0,
+                    Err(err) => eprintln!(
+                        "[Launchpad] 重新从启动台移除失败：{}，错误：{}",
+                        self.path.display(),
+                        err
+                    ),
+                }
+            }
+            if !self.dock_tiles.is_empty() {
+                match self.remove_dock_tiles() {
+                    Ok(count) => changed |= count > 0,
+                    Err(err) => eprintln!(
+                        "[Dock] 重新移除固定的 Dock 图标失败：{}，错误：{}",
+                        self.path.display(),
+                        err
+                    ),
+                }
+            }
+            changed
+        });
+        self.record_dock_changes(changed, commands);
+    }
+
+    // 隐藏包内尚未隐藏的登录项辅助程序并记录下来，返回新处理的数量。
+    pub fn hide_helpers(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut hidden = 0;
+        for path in visible_login_item_helpers(&self.path) {
+            if self.helpers.iter().any(|helper| helper.path == path) {
+                continue;
+            }
+            let original_ui_element = ui_element(&path)?;
+            hide_dock_icon(&path)?;
+            self.helpers.push(HelperRecord {
+                path,
+                original_ui_element,
+            });
+            hidden += 1;
+        }
+        Ok(hidden)
+    }
+
+    pub fn clear_checksum(&mut self) {
+        self.checksum = None;
+        self.owner = None;
+        self.modified_at = None;
+        self.version = None;
+    }
+
+    // 所有者变化，或版本号没变但修改时间与内容都和记录不一致（不像是应用更新），
+    // 说明 Info.plist 由其他用户或工具改过，此时不应直接覆盖。
+    pub fn external_change(&self) -> Option<String> {
+        let (owner, modified_at) = plist_owner_and_mtime(&self.path).ok()?;
+        if let Some(recorded) = self.owner
+            && recorded != owner
+        {
+            return Some(format!(
+                "Info.plist 的所有者已从 uid {} 变为 {}",
+                recorded, owner
+            ));
+        }
+        if let Some(recorded) = self.modified_at
+            && recorded != modified_at
+            && self.version.is_some()
+            && self.version == bundle_version(&self.path)
+            && self.checksum_matches() == Some(false)
+        {
+            return Some("Info.plist 在版本未变化的情况下被其他工具修改".into());
+        }
+        None
+    }
+
+    // 旧记录没有 Bundle ID 时读取 Info.plist；与 Launch Services 一致，不区分大小写。
+    pub fn matches_bundle_id(&self, bundle_id: &str) -> bool {
+        self.bundle_id
+            .clone()
+            .or_else(|| bundle_identifier(&self.path))
+            .is_some_and(|id| id.eq_ignore_ascii_case(bundle_id.trim()))
+    }
+
+    pub fn checksum_matches(&self) -> Option<bool> {
+        let recorded = self.checksum.as_ref()?;
+        let current = plist_checksum(&self.path).ok()?;
+        Some(*recorded == current)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StateFile {
+    schema_version: u64,
+    apps: Vec<ManagedApp>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Recovery {
+    pub reason: String,
+    // 只读模式下不会移动原文件，此时为 None。
+    pub backup_path: Option<PathBuf>,
+    pub salvaged: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct LoadedState {
+    pub apps: Vec<ManagedApp>,
+    pub recovery: Option<Recovery>,
+}
+
+const LOCK_FILE: &str = "apps.lock";
+
+pub fn state_file_path() -> PathBuf {
+    config::data_dir().join("apps.json")
+}
+
+pub fn load_apps() -> Result<Vec<ManagedApp>, Box<dyn std::error::Error>> {
+    let state = load_state()?;
+    if let Some(recovery) = &state.recovery {
+        match &recovery.backup_path {
+            Some(backup_path) => eprintln!(
+                "[State] 状态文件损坏（{}），已备份到 {}，恢复了 {} 项；可运行 dock-dodger adopt 重新扫描已隐藏的应用",
+                recovery.reason,
+                backup_path.display(),
+                recovery.salvaged
+            ),
+            None => eprintln!(
+                "[State] 状态文件损坏（{}），只读模式下未改动原文件，恢复了 {} 项",
+                recovery.reason, recovery.salvaged
+            ),
+        }
+    }
+    Ok(state.apps)
+}
+
+pub fn load_state() -> Result<LoadedState, Box<dyn std::error::Error>> {
+    state_store::open()?.load_state()
+}
+
+pub fn save_apps(apps: &[ManagedApp]) -> Result<(), Box<dyn std::error::Error>> {
+    state_store::open()?.save_apps(apps)
+}
+
+pub(crate) fn load_json_state() -> Result<LoadedState, Box<dyn std::error::Error>> {
+    let path = state_file_path();
+    if !path.exists() {
+        return Ok(LoadedState::default());
+    }
+    // 解密失败（例如钥匙串中没有密钥）时直接报错，不能当作损坏的文件处理。
+    let raw = fs::read(&path)?;
+    let data = String::from_utf8(crypto::open(&raw)?)?;
+    if let Ok(value) = serde_json::from_str::<Value>(&data)
+        && let Ok(version) = migrate::detect_version(&value)
+    {
+        ensure_supported_version(version)?;
+    }
+    match parse_state(&data) {
+        Ok((apps, migrated)) => {
+            // 只读模式下只在内存中升级，不写回文件。
+            let resealed = crypto::is_sealed(&raw) != crypto::enabled();
+            if (migrated || resealed) && !config::read_only() {
+                if migrated {
+                    eprintln!("[State] 状态文件已升级到版本 {}", SCHEMA_VERSION);
+                }
+                save_json_apps(&apps)?;
+                if resealed {
+                    history::reseal_json_history()?;
+                }
+            }
+            Ok(LoadedState {
+                apps,
+                recovery: None,
+            })
+        }
+        Err(reason) => {
+            let recovery = recover_corrupt_state(&path, &data, reason)?;
+            Ok(recovery)
+        }
+    }
+}
+
+pub(crate) fn ensure_supported_version(version: u64) -> Result<(), Box<dyn std::error::Error>> {
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "{}（{} > {}），请升级 Dock Dodger",
+            migrate::UNSUPPORTED_VERSION_ERROR,
+            version,
+            SCHEMA_VERSION
+        )
+        .into());
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_state(data: &str) -> Result<(Vec<ManagedApp>, bool), String> {
+    let value = serde_json::from_str(data).map_err(|err| err.to_string())?;
+    let (value, migrated) = migrate::migrate(value)?;
+    let state: StateFile = serde_json::from_value(value).map_err(|err| err.to_string())?;
+    Ok((state.apps, migrated))
+}
+
+fn recover_corrupt_state(
+    path: &Path,
+    data: &str,
+    reason: String,
+) -> Result<LoadedState, Box<dyn std::error::Error>> {
+    let apps = salvage_entries(data);
+    // 只读模式下只在内存中恢复，原文件保持不动。
+    let backup_path = if config::read_only() {
+        None
+    } else {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let backup_path = path.with_file_name(format!("apps.json.corrupt-{}", timestamp));
+        fs::rename(path, &backup_path)?;
+        save_json_apps(&apps)?;
+        Some(backup_path)
+    };
+    Ok(LoadedState {
+        recovery: Some(Recovery {
+            reason,
+            backup_path,
+            salvaged: apps.len(),
+        }),
+        apps,
+    })
+}
+
+fn salvage_entries(data: &str) -> Vec<ManagedApp> {
+    let mut apps: Vec<ManagedApp> = Vec::new();
+    let mut push = |app: ManagedApp| {
+        if is_app_bundle(&app.path) && !apps.iter().any(|existing| existing.path == app.path) {
+            apps.push(app);
+        }
+    };
+
+    if let Ok(value) = serde_json::from_str::<Value>(data) {
+        salvage_value(&value, &mut push);
+    } else {
+        for text in quoted_strings(data) {
+            push(ManagedApp::new(PathBuf::from(text)));
+        }
+    }
+    apps
+}
+
+fn salvage_value(value: &Value, push: &mut impl FnMut(ManagedApp)) {
+    match value {
+        Value::String(path) => push(ManagedApp::new(PathBuf::from(path))),
+        Value::Array(items) => items.iter().for_each(|item| salvage_value(item, push)),
+        Value::Object(map) => {
+            if let Some(path) = map.get("path").and_then(Value::as_str) {
+                let mut app = ManagedApp::new(PathBuf::from(path));
+                if let Some(Value::Array(tags)) = map.get("tags") {
+                    app.tags = tags
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect();
+                }
+                push(app);
+            } else {
+                map.values().for_each(|item| salvage_value(item, push));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn quoted_strings(data: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut rest = data;
+    while let Some(start) = rest.find('"') {
+        let body = &rest[start + 1..];
+        let mut escaped = false;
+        let end = body.char_indices().find_map(|(index, ch)| {
+            let found = ch == '"' && !escaped;
+            escaped = ch == '\\' && !escaped;
+            found.then_some(index)
+        });
+        let Some(end) = end else {
+            break;
+        };
+        if let Ok(text) = serde_json::from_str::<String>(&rest[start..start + end + 2]) {
+            strings.push(text);
+        }
+        rest = &body[end + 1..];
+    }
+    strings
+}
+
+pub(crate) fn save_json_apps(apps: &[ManagedApp]) -> Result<(), Box<dyn std::error::Error>> {
+    let state = StateFile {
+        schema_version: SCHEMA_VERSION,
+        apps: apps.to_vec(),
+    };
+    write_atomic(
+        &state_file_path(),
+        &crypto::seal(serde_json::to_string_pretty(&state)?.as_bytes())?,
+    )?;
+    Ok(())
+}
+
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if config::read_only() {
+        return Err(std::io::Error::other(config::READ_ONLY_ERROR));
+    }
+    let _deferred = shutdown::defer();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(".{}.tmp-{}", file_name, std::process::id()));
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+// 数据目录下的建议锁：修改应用列表的进程在“读取—修改—保存”期间持有，进程退出时自动释放。
+// 只读模式下不创建锁文件；无法创建时只打印日志，不影响本身的操作。
+pub struct StateLock {
+    _file: Option<fs::File>,
+}
+
+fn lock_file() -> Option<fs::File> {
+    if config::read_only() {
+        return None;
+    }
+    let dir = config::data_dir();
+    let file = fs::create_dir_all(&dir).and_then(|_| {
+        fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join(LOCK_FILE))
+    });
+    match file {
+        Ok(file) => Some(file),
+        Err(err) => {
+            eprintln!("[State] 无法创建数据目录的锁文件：{}", err);
+            None
+        }
+    }
+}
+
+pub fn lock_state() -> StateLock {
+    let file = lock_file();
+    if let Some(file) = &file
+        && let Err(err) = file.lock()
+    {
+        eprintln!("[State] 无法锁定数据目录：{}", err);
+    }
+    StateLock { _file: file }
+}
+
+// 其它进程持有锁时返回 None。
+pub fn try_lock_state() -> Option<StateLock> {
+    let file = lock_file();
+    if let Some(file) = &file {
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(fs::TryLockError::WouldBlock) => return None,
+            Err(fs::TryLockError::Error(err)) => eprintln!("[State] 无法锁定数据目录：{}", err),
+        }
+    }
+    Some(StateLock { _file: file })
+}
+
+// 三方合并：base 是上次读取或保存时的列表，local 是本进程修改后的列表，remote 是磁盘上的最新列表。
+// 本进程增加、删除或修改过的应用以 local 为准，其余以 remote 为准；顺序跟随 remote，新增的排在最后。
+pub fn merge_apps(
+    base: &[ManagedApp],
+    local: &[ManagedApp],
+    remote: Vec<ManagedApp>,
+) -> Vec<ManagedApp> {
+    let find = |list: &[ManagedApp], path: &Path| list.iter().position(|app| app.path == path);
+    let changed = |old: &ManagedApp, new: &ManagedApp| {
+        serde_json::to_value(old).ok() != serde_json::to_value(new).ok()
+    };
+    let mut merged: Vec<ManagedApp> = remote
+        .into_iter()
+        .filter_map(
+            |app| match (find(base, &app.path), find(local, &app.path)) {
+                (Some(_), None) => None,
+                (Some(old), Some(new)) if changed(&base[old], &local[new]) => {
+                    Some(local[new].clone())
+                }
+                (None, Some(new)) => Some(local[new].clone()),
+                _ => Some(app),
+            },
+        )
+        .collect();
+    for app in local {
+        if find(base, &app.path).is_none() && find(&merged, &app.path).is_none() {
+            merged.push(app.clone());
+        }
+    }
+    merged
+}
+
+pub struct Autosave {
+    delay: Duration,
+    due: Option<Instant>,
+    // 上次读取或保存时磁盘上的列表及其时间戳，用来发现并合并其它进程（计划导入、代理、rpc）的改动
+    base: Vec<ManagedApp>,
+    stamp: Option<state_store::Stamp>,
+}
+
+impl Autosave {
+    pub fn new(delay: Duration) -> Self {
+        Autosave {
+            delay,
+            due: None,
+            base: Vec::new(),
+            stamp: None,
+        }
+    }
+
+    // 读取列表后调用，记下读到的内容和读取前的时间戳。
+    pub fn track(&mut self, base: Vec<ManagedApp>, stamp: Option<state_store::Stamp>) {
+        self.base = base;
+        self.stamp = stamp;
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.due = Some(Instant::now() + self.delay);
+    }
+
+    pub fn due(&self) -> Option<Instant> {
+        self.due
+    }
+
+    // 其它进程改动了列表时读入并与本进程的修改合并，返回合并后的列表；锁被占用时留到下次检查。
+    pub fn reload(
+        &mut self,
+        apps: &[ManagedApp],
+    ) -> Result<Option<Vec<ManagedApp>>, Box<dyn std::error::Error>> {
+        if state_store::stamp() == self.stamp {
+            return Ok(None);
+        }
+        let Some(_lock) = try_lock_state() else {
+            return Ok(None);
+        };
+        self.merge_remote(apps).map(Some)
+    }
+
+    fn merge_remote(
+        &mut self,
+        apps: &[ManagedApp],
+    ) -> Result<Vec<ManagedApp>, Box<dyn std::error::Error>> {
+        let stamp = state_store::stamp();
+        let remote = load_apps()?;
+        let merged = merge_apps(&self.base, apps, remote.clone());
+        self.track(remote, stamp);
+        Ok(merged)
+    }
+
+    // 到期时保存；其它进程正在修改列表时推迟一个周期。返回列表是否因合并其它进程的改动而变化。
+    pub fn flush_if_due(
+        &mut self,
+        apps: &mut Vec<ManagedApp>,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        match self.due {
+            Some(due) if Instant::now() >= due => match try_lock_state() {
+                Some(lock) => self.save(apps, lock),
+                None => {
+                    self.mark_dirty();
+                    Ok(false)
+                }
+            },
+            _ => Ok(false),
+        }
+    }
+
+    // 退出等场合立即保存，等待其它进程释放锁。
+    pub fn flush(
+        &mut self,
+        apps: &mut Vec<ManagedApp>,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        if self.due.is_none() {
+            return Ok(false);
+        }
+        let lock = lock_state();
+        self.save(apps, lock)
+    }
+
+    // 保存前先合并磁盘上其它进程写入的改动，避免用过时的列表覆盖它们。
+    fn save(
+        &mut self,
+        apps: &mut Vec<ManagedApp>,
+        _lock: StateLock,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        if self.due.is_none() {
+            return Ok(false);
+        }
+        let merged = state_store::stamp() != self.stamp;
+        let result = if merged {
+            self.merge_remote(apps).map(|remote| *apps = remote)
+        } else {
+            Ok(())
+        };
+        // 失败时保留未保存的标记，过一个周期再试。
+        if let Err(err) = result.and_then(|()| save_apps(apps)) {
+            self.mark_dirty();
+            return Err(err);
+        }
+        self.due = None;
+        self.track(apps.clone(), state_store::stamp());
+        Ok(merged)
+    }
+}
@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::history::{self, HistoryAction, HistoryEntry, HistoryFilter};
+use crate::state::{self, LoadedState, ManagedApp};
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+pub const SQLITE_FILE: &str = "dockdodger.sqlite3";
+pub const SQLITE_UNAVAILABLE_ERROR: &str =
+    "配置选择了 SQLite 存储，但此版本编译时没有启用 sqlite 功能";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    // apps.json + history.jsonl
+    #[default]
+    Json,
+    // 数据目录下的 dockdodger.sqlite3
+    Sqlite,
+}
+
+// 应用列表与操作历史的持久化。state 与 history 模块的读写都经过这里，按配置的 storage 选择实现。
+pub trait StateStore {
+    fn load_state(&self) -> Result<LoadedState, Box<dyn std::error::Error>>;
+
+    fn save_apps(&self, apps: &[ManagedApp]) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn append_history(&self, entry: &HistoryEntry) -> Result<(), Box<dyn std::error::Error>>;
+
+    // 按时间顺序返回，旧的在前。
+    fn load_history(
+        &self,
+        filter: &HistoryFilter,
+    ) -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>>;
+
+    // 历史有变化时改变，不要求连续。
+    fn history_revision(&self) -> u64;
+
+    fn last_history(
+        &self,
+        path: &Path,
+    ) -> Result<Option<HistoryEntry>, Box<dyn std::error::Error>> {
+        Ok(self
+            .load_history(&HistoryFilter::default())?
+            .into_iter()
+            .rev()
+            .find(|entry| entry.path == path && entry.action != HistoryAction::Dock))
+    }
+
+    // 最新的在前，只含隐藏/恢复，跳过漂移和 Dock 记录。
+    fn recent_history(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+        Ok(self
+            .load_history(&HistoryFilter::default())?
+            .into_iter()
+            .rev()
+            .filter(|entry| matches!(entry.action, HistoryAction::Hide | HistoryAction::Restore))
+            .take(limit)
+            .collect())
+    }
+}
+
+// 默认实现：整份 apps.json 原子替换，history.jsonl 只追加。
+pub struct JsonStore;
+
+impl StateStore for JsonStore {
+    fn load_state(&self) -> Result<LoadedState, Box<dyn std::error::Error>> {
+        state::load_json_state()
+    }
+
+    fn save_apps(&self, apps: &[ManagedApp]) -> Result<(), Box<dyn std::error::Error>> {
+        state::save_json_apps(apps)
+    }
+
+    fn append_history(&self, entry: &HistoryEntry) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(history::append_json(entry)?)
+    }
+
+    fn load_history(
+        &self,
+        filter: &HistoryFilter,
+    ) -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+        let mut entries = history::load_json_history()?;
+        entries.retain(|entry| filter.matches(entry));
+        Ok(entries)
+    }
+
+    fn history_revision(&self) -> u64 {
+        fs::metadata(history::history_path())
+            .map(|metadata| metadata.len())
+            .unwrap_or(0)
+    }
+}
+
+pub fn sqlite_path() -> PathBuf {
+    config::data_dir().join(SQLITE_FILE)
+}
+
+// 当前存储文件的修改时间和大小，用来发现其它进程写入了列表。
+pub type Stamp = (SystemTime, u64);
+
+pub fn stamp() -> Option<Stamp> {
+    let path = match config::load_config().unwrap_or_default().storage {
+        StorageBackend::Json => state::state_file_path(),
+        StorageBackend::Sqlite => sqlite_path(),
+    };
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+// 每次调用都按当前配置重新选择，切换 storage 后不需要重启。
+// 只读模式下 SQLite 数据库还不存在时，数据仍在 JSON 文件中，直接读取它们。
+pub fn open() -> Result<Box<dyn StateStore>, Box<dyn std::error::Error>> {
+    match config::load_config().unwrap_or_default().storage {
+        StorageBackend::Json => Ok(Box::new(JsonStore)),
+        StorageBackend::Sqlite if config::read_only() && !sqlite_path().exists() => {
+            Ok(Box::new(JsonStore))
+        }
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => Ok(Box::new(SqliteStore::open(&sqlite_path())?)),
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => Err(SQLITE_UNAVAILABLE_ERROR.into()),
+    }
+}
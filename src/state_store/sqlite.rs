@@ -0,0 +1,398 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Params, Row, params};
+use serde_json::{Value, json};
+
+use super::{JsonStore, StateStore};
+use crate::config;
+use crate::crypto;
+use crate::history::{HistoryAction, HistoryEntry, HistoryFilter};
+use crate::migrate::SCHEMA_VERSION;
+use crate::shutdown;
+use crate::state::{self, LoadedState, ManagedApp};
+
+// 每个应用一行，内容与 apps.json 中的条目相同（开启 encrypt_data 时加密）；
+// 列表的格式版本记在 user_version 中，升级时拼回完整的状态文件交给 migrate 处理。
+// 开启 encrypt_data 时历史整条加密存入 data，path 列改存 crypto::lookup_key，error 与 commands 留空。
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS apps (
+    position INTEGER PRIMARY KEY,
+    data BLOB NOT NULL
+);
+CREATE TABLE IF NOT EXISTS history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    at INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    path TEXT NOT NULL,
+    error TEXT,
+    commands TEXT,
+    data BLOB
+);
+CREATE INDEX IF NOT EXISTS history_at ON history (at);
+CREATE INDEX IF NOT EXISTS history_path ON history (path, id);
+CREATE VIRTUAL TABLE IF NOT EXISTS history_search USING fts5 (
+    path, error, content = 'history', content_rowid = 'id', tokenize = 'trigram'
+);
+CREATE TRIGGER IF NOT EXISTS history_search_insert AFTER INSERT ON history BEGIN
+    INSERT INTO history_search (rowid, path, error) VALUES (new.id, new.path, new.error);
+END;
+";
+
+// trigram 分词按三个字符建索引，中文和路径片段都能搜；更短的文字只能逐条比较。
+const MIN_SEARCH_CHARS: usize = 3;
+
+const HISTORY_COLUMNS: &str = "at, action, path, error, commands, data";
+
+// 历史很长时使用：按时间和应用的查询走索引，保存列表在一个事务中完成，中途退出不会留下半份数据。
+pub struct SqliteStore {
+    conn: Connection,
+    // 只读模式下不会补建缺少的全文索引和列，查询时按实际情况退回。
+    searchable: bool,
+    history_columns: String,
+}
+
+impl SqliteStore {
+    // 数据库第一次创建时导入已有的 apps.json 与 history.jsonl，原文件保留，切回 JSON 时仍可使用。
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            config::ensure_writable()?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        // 只读模式下以只读方式打开已有的数据库，不创建表、不补列也不重建索引。
+        if config::read_only() {
+            let mut store = SqliteStore {
+                conn: Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?,
+                searchable: false,
+                history_columns: String::new(),
+            };
+            if !store.has_table("apps")? {
+                return Err("SQLite 数据库尚未完成导入，只读模式下不会导入".into());
+            }
+            store.searchable = store.has_table("history_search")?;
+            let mut columns = vec!["at", "action", "path", "error"];
+            for column in ["commands", "data"] {
+                let present = store.has_column("history", column)?;
+                columns.push(if present { column } else { "NULL" });
+            }
+            store.history_columns = columns.join(", ");
+            return Ok(store);
+        }
+        let store = SqliteStore {
+            conn: Connection::open(path)?,
+            searchable: true,
+            history_columns: HISTORY_COLUMNS.to_string(),
+        };
+        // 建表与导入在同一个事务中完成：导入失败时整体回滚，数据库里没有表，下次打开时重新导入。
+        let fresh = !store.has_table("apps")?;
+        let indexed = fresh || store.has_table("history_search")?;
+        let tx = store.conn.unchecked_transaction()?;
+        tx.execute_batch(SCHEMA)?;
+        // 早于记录等价命令的数据库：补上 commands 列。
+        if !store.has_column("history", "commands")? {
+            tx.execute_batch("ALTER TABLE history ADD COLUMN commands TEXT;")?;
+        }
+        // 早于加密历史的数据库：补上 data 列，去掉应用列表中的明文路径。
+        if !store.has_column("history", "data")? {
+            tx.execute_batch("ALTER TABLE history ADD COLUMN data BLOB;")?;
+        }
+        if store.has_column("apps", "path")? {
+            tx.execute_batch("ALTER TABLE apps DROP COLUMN path;")?;
+        }
+        if fresh {
+            import_json(&tx)?;
+        }
+        // 没有全文索引时创建的数据库：为已有的历史补建索引。
+        if !indexed {
+            tx.execute_batch("INSERT INTO history_search (history_search) VALUES ('rebuild');")?;
+        }
+        tx.commit()?;
+        Ok(store)
+    }
+
+    fn has_table(&self, name: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    fn has_column(&self, table: &str, column: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+            params![table, column],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    // 切换 encrypt_data 后把已有的历史整体转成当前设置的格式，并重建全文索引，索引中不留明文。
+    fn reseal_history(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let stale: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM history WHERE (data IS NULL) = ?1",
+            params![crypto::enabled()],
+            |row| row.get(0),
+        )?;
+        if stale == 0 {
+            return Ok(());
+        }
+        let sql = format!("SELECT {} FROM history ORDER BY id", HISTORY_COLUMNS);
+        let entries = self.query_history(&sql, params![])?;
+        let _deferred = shutdown::defer();
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM history", params![])?;
+        for entry in &entries {
+            insert_history(&tx, entry)?;
+        }
+        tx.execute_batch("INSERT INTO history_search (history_search) VALUES ('rebuild');")?;
+        tx.commit()?;
+        eprintln!("[State] 已按加密设置转换 {} 条历史", entries.len());
+        Ok(())
+    }
+
+    fn query_history<P: Params>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+        let mut statement = self.conn.prepare(sql)?;
+        let rows = statement.query_map(params, history_row)?;
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(parse_history(row?)?);
+        }
+        Ok(entries)
+    }
+}
+
+impl StateStore for SqliteStore {
+    fn load_state(&self) -> Result<LoadedState, Box<dyn std::error::Error>> {
+        let version: i64 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        let mut statement = self
+            .conn
+            .prepare("SELECT data FROM apps ORDER BY position")?;
+        let rows = statement.query_map(params![], |row| row.get::<_, Vec<u8>>(0))?;
+        let mut apps = Vec::new();
+        let mut resealed = false;
+        for data in rows {
+            let data = data?;
+            resealed |= crypto::is_sealed(&data) != crypto::enabled();
+            apps.push(serde_json::from_slice::<Value>(&crypto::open(&data)?)?);
+        }
+        if !config::read_only() {
+            self.reseal_history()?;
+        }
+        if apps.is_empty() {
+            return Ok(LoadedState::default());
+        }
+        state::ensure_supported_version(version as u64)?;
+        let data = json!({ "schema_version": version, "apps": apps }).to_string();
+        let (apps, migrated) = state::parse_state(&data)?;
+        // 只读模式下只在内存中升级，不写回数据库。
+        if (migrated || resealed) && !config::read_only() {
+            if migrated {
+                eprintln!("[State] SQLite 存储已升级到版本 {}", SCHEMA_VERSION);
+            }
+            self.save_apps(&apps)?;
+        }
+        Ok(LoadedState {
+            apps,
+            recovery: None,
+        })
+    }
+
+    fn save_apps(&self, apps: &[ManagedApp]) -> Result<(), Box<dyn std::error::Error>> {
+        config::ensure_writable()?;
+        let _deferred = shutdown::defer();
+        let tx = self.conn.unchecked_transaction()?;
+        write_apps(&tx, apps)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn append_history(&self, entry: &HistoryEntry) -> Result<(), Box<dyn std::error::Error>> {
+        config::ensure_writable()?;
+        insert_history(&self.conn, entry)
+    }
+
+    // 时间范围和搜索文字在 SQL 中过滤，最后仍用 HistoryFilter 核对一遍，与 JSON 存储的结果一致。
+    fn load_history(
+        &self,
+        filter: &HistoryFilter,
+    ) -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+        let mut sql = format!(
+            "SELECT {} FROM history WHERE at >= ?1 AND at < ?2",
+            self.history_columns
+        );
+        let phrase = filter
+            .text
+            .as_deref()
+            .filter(|_| self.searchable && !crypto::enabled())
+            .filter(|text| text.chars().count() >= MIN_SEARCH_CHARS)
+            .map(|text| format!("\"{}\"", text.replace('"', "\"\"")));
+        if phrase.is_some() {
+            sql.push_str(
+                " AND id IN (SELECT rowid FROM history_search WHERE history_search MATCH ?3)",
+            );
+        }
+        sql.push_str(" ORDER BY id");
+        let since = filter.since.map_or(0, |since| since as i64);
+        let until = filter.until.map_or(i64::MAX, |until| until as i64);
+        let mut entries = match phrase {
+            Some(phrase) => self.query_history(&sql, params![since, until, phrase])?,
+            None => self.query_history(&sql, params![since, until])?,
+        };
+        entries.retain(|entry| filter.matches(entry));
+        Ok(entries)
+    }
+
+    fn history_revision(&self) -> u64 {
+        self.conn
+            .query_row(
+                "SELECT COALESCE(MAX(id), 0) FROM history",
+                params![],
+                |row| row.get::<_, i64>(0),
+            )
+            .map_or(0, |id| id as u64)
+    }
+
+    fn last_history(
+        &self,
+        path: &Path,
+    ) -> Result<Option<HistoryEntry>, Box<dyn std::error::Error>> {
+        let sql = format!(
+            "SELECT {} FROM history WHERE path IN (?1, ?2) AND action != 'dock' ORDER BY id DESC LIMIT 1",
+            self.history_columns
+        );
+        // 加密时同时按 lookup_key 查找，尚未转换的明文记录也能找到。
+        let plain = path.to_string_lossy();
+        let lookup = if crypto::enabled() {
+            crypto::lookup_key(plain.as_bytes())?
+        } else {
+            plain.to_string()
+        };
+        let row = self
+            .conn
+            .query_row(&sql, params![plain, lookup], history_row)
+            .optional()?;
+        row.map(parse_history).transpose()
+    }
+
+    fn recent_history(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+        let sql = format!(
+            "SELECT {} FROM history WHERE action NOT IN ('drift', 'dock') ORDER BY id DESC LIMIT ?1",
+            self.history_columns
+        );
+        self.query_history(&sql, params![limit as i64])
+    }
+}
+
+type HistoryRow = (
+    i64,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<Vec<u8>>,
+);
+
+fn history_row(row: &Row<'_>) -> rusqlite::Result<HistoryRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+    ))
+}
+
+// commands 列存 JSON 数组，没有命令时为 NULL；有 data 时以其中加密的整条记录为准。
+fn parse_history(
+    (at, action, path, error, commands, data): HistoryRow,
+) -> Result<HistoryEntry, Box<dyn std::error::Error>> {
+    if let Some(data) = data {
+        return Ok(serde_json::from_slice(&crypto::open(&data)?)?);
+    }
+    let action: HistoryAction = serde_json::from_value(Value::String(action))?;
+    let commands = match commands {
+        Some(commands) => serde_json::from_str(&commands)?,
+        None => Vec::new(),
+    };
+    Ok(HistoryEntry {
+        at: at as u64,
+        action,
+        path: PathBuf::from(path),
+        error,
+        commands,
+    })
+}
+
+fn insert_history(
+    conn: &Connection,
+    entry: &HistoryEntry,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let action = serde_json::to_value(entry.action)?;
+    let path = entry.path.to_string_lossy();
+    let (path, error, commands, data) = if crypto::enabled() {
+        let data = crypto::seal(&serde_json::to_vec(entry)?)?;
+        (crypto::lookup_key(path.as_bytes())?, None, None, Some(data))
+    } else if entry.commands.is_empty() {
+        (path.to_string(), entry.error.clone(), None, None)
+    } else {
+        let commands = serde_json::to_string(&entry.commands)?;
+        (path.to_string(), entry.error.clone(), Some(commands), None)
+    };
+    conn.execute(
+        "INSERT INTO history (at, action, path, error, commands, data) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            entry.at as i64,
+            action.as_str().unwrap_or_default(),
+            path,
+            error,
+            commands,
+            data
+        ],
+    )?;
+    Ok(())
+}
+
+fn import_json(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let apps = JsonStore.load_state()?.apps;
+    let entries = JsonStore.load_history(&HistoryFilter::default())?;
+    if apps.is_empty() && entries.is_empty() {
+        return Ok(());
+    }
+    write_apps(conn, &apps)?;
+    for entry in &entries {
+        insert_history(conn, entry)?;
+    }
+    eprintln!(
+        "[State] 已把 {} 个应用和 {} 条历史导入 SQLite 存储",
+        apps.len(),
+        entries.len()
+    );
+    Ok(())
+}
+
+fn write_apps(conn: &Connection, apps: &[ManagedApp]) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute("DELETE FROM apps", params![])?;
+    let mut statement = conn.prepare("INSERT INTO apps (position, data) VALUES (?1, ?2)")?;
+    for (position, app) in apps.iter().enumerate() {
+        statement.execute(params![
+            position as i64,
+            crypto::seal(&serde_json::to_vec(app)?)?
+        ])?;
+    }
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION as i64)?;
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::backup::backup_plist;
+use crate::bundle;
+
+// 隐藏、恢复与扫描只通过这个接口访问文件系统，测试时可换成内存中的实现。
+pub trait BundleStore {
+    fn read_info_plist(&self, app: &Path) -> io::Result<Vec<u8>>;
+
+    // 覆盖 Info.plist；真实实现会先备份原文件，需要时请求管理员权限。
+    fn write_info_plist(
+        &self,
+        app: &Path,
+        contents: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
+    // 列出目录下的直接子项，返回路径以及它是否为目录。
+    fn list_dir(&self, dir: &Path) -> io::Result<Vec<(PathBuf, bool)>>;
+}
+
+pub struct FsStore;
+
+impl BundleStore for FsStore {
+    fn read_info_plist(&self, app: &Path) -> io::Result<Vec<u8>> {
+        fs::read(app.join("Contents/Info.plist"))
+    }
+
+    fn write_info_plist(
+        &self,
+        app: &Path,
+        contents: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        backup_plist(app)?;
+        bundle::write_info_plist(app, contents)
+    }
+
+    fn list_dir(&self, dir: &Path) -> io::Result<Vec<(PathBuf, bool)>> {
+        Ok(fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| {
+                let is_dir = entry.file_type().map(|ty| ty.is_dir()).unwrap_or(false);
+                (entry.path(), is_dir)
+            })
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct MemoryFiles {
+    files: BTreeMap<PathBuf, Vec<u8>>,
+    denied: BTreeSet<PathBuf>,
+    read_only: bool,
+    writes: usize,
+}
+
+// 内存中的文件系统，可以模拟没有写权限、只读卷等错误，不会碰到磁盘。
+#[derive(Default)]
+pub struct MemoryStore {
+    inner: Mutex<MemoryFiles>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MemoryFiles> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn insert_file(&self, path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) {
+        self.lock().files.insert(path.into(), contents.into());
+    }
+
+    pub fn insert_info_plist(&self, app: &Path, contents: impl Into<Vec<u8>>) {
+        self.insert_file(app.join("Contents/Info.plist"), contents);
+    }
+
+    pub fn info_plist(&self, app: &Path) -> Option<Vec<u8>> {
+        self.lock()
+            .files
+            .get(&app.join("Contents/Info.plist"))
+            .cloned()
+    }
+
+    // 之后写入该应用的 Info.plist 时返回权限不足。
+    pub fn deny_writes(&self, app: &Path) {
+        self.lock().denied.insert(app.to_path_buf());
+    }
+
+    pub fn set_read_only(&self, read_only: bool) {
+        self.lock().read_only = read_only;
+    }
+
+    pub fn write_count(&self) -> usize {
+        self.lock().writes
+    }
+}
+
+impl BundleStore for MemoryStore {
+    fn read_info_plist(&self, app: &Path) -> io::Result<Vec<u8>> {
+        self.info_plist(app).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("找不到 {}/Contents/Info.plist", app.display()),
+            )
+        })
+    }
+
+    fn write_info_plist(
+        &self,
+        app: &Path,
+        contents: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut inner = self.lock();
+        if inner.read_only {
+            return Err(io::Error::from(io::ErrorKind::ReadOnlyFilesystem).into());
+        }
+        if inner.denied.contains(app) {
+            return Err(io::Error::from(io::ErrorKind::PermissionDenied).into());
+        }
+        inner
+            .files
+            .insert(app.join("Contents/Info.plist"), contents.to_vec());
+        inner.writes += 1;
+        Ok(())
+    }
+
+    fn list_dir(&self, dir: &Path) -> io::Result<Vec<(PathBuf, bool)>> {
+        let inner = self.lock();
+        let mut entries = BTreeMap::new();
+        for path in inner.files.keys() {
+            let Ok(rest) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let mut components = rest.components();
+            if let Some(first) = components.next() {
+                let is_dir = components.next().is_some();
+                *entries.entry(dir.join(first)).or_insert(false) |= is_dir;
+            }
+        }
+        if entries.is_empty() {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+        Ok(entries.into_iter().collect())
+    }
+}
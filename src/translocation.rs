@@ -0,0 +1,81 @@
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+// 带隔离属性、未移动过的应用首次打开时，Gatekeeper 会把它挂载到
+// /private/var/folders/…/AppTranslocation/<UUID>/d/ 下的只读位置再运行（App Translocation）。
+// 这个路径每次启动都不同，修改其中的 Info.plist 也没有意义，应当改为处理原始位置的应用。
+const MARKER: &str = "AppTranslocation";
+
+pub fn is_translocated(path: &Path) -> bool {
+    path.components()
+        .any(|component| component == Component::Normal(MARKER.as_ref()))
+}
+
+// 当前的转移挂载：mount 的每一行形如 “<原始位置> on <挂载点> (nullfs, …)”。
+pub struct Translocations {
+    mounts: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Translocations {
+    pub fn read() -> Self {
+        let output = Command::new("mount")
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .unwrap_or_default();
+        Translocations::parse(&output)
+    }
+
+    pub fn parse(output: &str) -> Self {
+        let mounts = output
+            .lines()
+            .filter_map(|line| {
+                let (source, rest) = line.split_once(" on ")?;
+                let (mount_point, options) = rest.rsplit_once(" (")?;
+                (options.starts_with("nullfs") && mount_point.contains(MARKER))
+                    .then(|| (PathBuf::from(source), PathBuf::from(mount_point)))
+            })
+            .collect();
+        Translocations { mounts }
+    }
+
+    // 挂载源可能是应用本身，也可能是它所在的目录。
+    pub fn original(&self, path: &Path) -> Option<PathBuf> {
+        let (source, mount_point) = self
+            .mounts
+            .iter()
+            .find(|(_, mount_point)| path.starts_with(mount_point))?;
+        let relative = path.strip_prefix(mount_point).ok()?;
+        let relative = relative.strip_prefix("d").unwrap_or(relative);
+        if source.extension().is_some_and(|ext| ext == "app") {
+            let inner = relative.components().skip(1).collect::<PathBuf>();
+            Some(source.join(inner))
+        } else {
+            Some(source.join(relative))
+        }
+    }
+}
+
+pub fn original_path(path: &Path) -> Option<PathBuf> {
+    Translocations::read().original(path)
+}
+
+// 未被转移的路径原样返回；被转移的换成原始位置，找不到原始位置时报错，而不是去修改临时副本。
+pub fn resolve(path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if !is_translocated(path) {
+        return Ok(path.to_path_buf());
+    }
+    original_path(path).ok_or_else(|| {
+        format!(
+            "{} 是 Gatekeeper 转移（App Translocation）后的临时副本，且无法确定原始位置；请先把应用移到“应用程序”文件夹",
+            path.display()
+        )
+        .into()
+    })
+}
+
+pub fn warning(original: &Path) -> String {
+    format!(
+        "{} 是从 Gatekeeper 转移（App Translocation）后的临时位置打开的，已改为修改原始位置的应用；把它移到“应用程序”文件夹后再打开，隐藏才会稳定生效",
+        original.display()
+    )
+}
@@ -0,0 +1,378 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use osx_dock_dodger_rs::bundle::{bundle_name, hide_dock_icon, is_dock_icon_hidden, ui_element};
+use osx_dock_dodger_rs::scan::ScanScope;
+use osx_dock_dodger_rs::session;
+use osx_dock_dodger_rs::signing;
+use osx_dock_dodger_rs::state::{self, ManagedApp};
+use osx_dock_dodger_rs::volume;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+const HELP: &str =
+    "↑↓/jk 移动  / 搜索  Tab 全部/已管理  h 隐藏  r 恢复  R 强制恢复  v 校验  q 退出";
+
+#[derive(Debug, Clone)]
+enum Status {
+    Hidden,
+    // 已管理但 Dock 图标又出现了（例如应用更新覆盖了 Info.plist）。
+    Drifted,
+    External(String),
+    Visible,
+    HiddenElsewhere,
+    Unreadable,
+    // 所在的外接卷未挂载。
+    Offline,
+}
+
+impl Status {
+    fn label(&self) -> (String, Color) {
+        match self {
+            Status::Hidden => ("已隐藏".into(), Color::Green),
+            Status::Drifted => ("图标已恢复，按 h 重新隐藏".into(), Color::Yellow),
+            Status::External(reason) => (format!("可能由其他工具管理：{}", reason), Color::Magenta),
+            Status::Visible => ("未管理".into(), Color::Gray),
+            Status::HiddenElsewhere => ("已隐藏（未管理）".into(), Color::Cyan),
+            Status::Unreadable => ("无法读取 Info.plist".into(), Color::Red),
+            Status::Offline => ("所在的卷未挂载".into(), Color::DarkGray),
+        }
+    }
+}
+
+struct Row {
+    path: PathBuf,
+    name: String,
+    managed: bool,
+    status: Status,
+}
+
+struct Tui {
+    apps: Vec<ManagedApp>,
+    rows: Vec<Row>,
+    show_all: bool,
+    query: String,
+    searching: bool,
+    list: ListState,
+    message: String,
+}
+
+fn display_name(path: &Path) -> String {
+    bundle_name(path).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string())
+    })
+}
+
+impl Tui {
+    fn new(apps: Vec<ManagedApp>) -> Self {
+        let mut tui = Tui {
+            apps,
+            rows: Vec::new(),
+            show_all: false,
+            query: String::new(),
+            searching: false,
+            list: ListState::default(),
+            message: String::new(),
+        };
+        tui.verify();
+        tui
+    }
+
+    fn status_of(&self, path: &Path) -> Status {
+        if volume::is_offline(path) {
+            return Status::Offline;
+        }
+        let hidden = match is_dock_icon_hidden(path) {
+            Ok(hidden) => hidden,
+            Err(_) => return Status::Unreadable,
+        };
+        match self.apps.iter().find(|app| app.path == path) {
+            Some(app) => match app.external_change() {
+                Some(reason) => Status::External(reason),
+                None if hidden => Status::Hidden,
+                None => Status::Drifted,
+            },
+            None if hidden => Status::HiddenElsewhere,
+            None => Status::Visible,
+        }
+    }
+
+    // 重新读取所有应用的 Info.plist，列出已管理的应用，切换到“全部”时再加上扫描目录中的其他应用。
+    fn verify(&mut self) {
+        let mut paths: Vec<PathBuf> = self.apps.iter().map(|app| app.path.clone()).collect();
+        if self.show_all {
+            for path in ScanScope::load().find_app_bundles() {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        self.rows = paths
+            .into_iter()
+            .map(|path| Row {
+                name: display_name(&path),
+                managed: self.apps.iter().any(|app| app.path == path),
+                status: self.status_of(&path),
+                path,
+            })
+            .collect();
+        self.rows.sort_by(|a, b| {
+            b.managed
+                .cmp(&a.managed)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        let drifted = self
+            .rows
+            .iter()
+            .filter(|row| row.managed && !matches!(row.status, Status::Hidden | Status::Offline))
+            .count();
+        self.message = if drifted == 0 {
+            format!("已校验 {} 个应用，全部正常", self.apps.len())
+        } else {
+            format!("已校验 {} 个应用，{} 个需要处理", self.apps.len(), drifted)
+        };
+        self.clamp_selection();
+    }
+
+    fn visible_rows(&self) -> Vec<&Row> {
+        let query = self.query.to_lowercase();
+        self.rows
+            .iter()
+            .filter(|row| {
+                query.is_empty()
+                    || row.name.to_lowercase().contains(&query)
+                    || row.path.to_string_lossy().to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    fn clamp_selection(&mut self) {
+        let count = self.visible_rows().len();
+        let selected = match self.list.selected() {
+            _ if count == 0 => None,
+            Some(index) => Some(index.min(count - 1)),
+            None => Some(0),
+        };
+        self.list.select(selected);
+    }
+
+    fn selected_path(&self) -> Option<PathBuf> {
+        let index = self.list.selected()?;
+        self.visible_rows().get(index).map(|row| row.path.clone())
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let count = self.visible_rows().len();
+        if count == 0 {
+            return;
+        }
+        let current = self.list.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, count as isize - 1);
+        self.list.select(Some(next as usize));
+    }
+
+    fn save(&mut self) {
+        if let Err(err) = state::save_apps(&self.apps) {
+            self.message = format!("保存应用列表失败：{}", err);
+        }
+    }
+
+    fn hide_selected(&mut self) {
+        let Some(path) = self.selected_path() else {
+            return;
+        };
+        let name = display_name(&path);
+        let existing = self.apps.iter().position(|app| app.path == path);
+        let signing = existing
+            .is_none()
+            .then(|| signing::read_signing_info(&path));
+        let original = ui_element(&path).ok().flatten();
+        if let Err(err) = hide_dock_icon(&path) {
+            self.message = format!("隐藏 {} 失败：{}", name, err);
+            return;
+        }
+        match existing {
+            Some(index) => self.apps[index].record_checksum(),
+            None => {
+                let mut app = ManagedApp::new(path);
+                app.signing = signing;
+                app.record_hide(original);
+                app.record_checksum();
+                app.apply_hide_options();
+                self.apps.push(app);
+            }
+        }
+        self.verify();
+        self.message = format!("已隐藏 {} 的 Dock 图标", name);
+        self.save();
+    }
+
+    fn restore_selected(&mut self, force: bool) {
+        let Some(path) = self.selected_path() else {
+            return;
+        };
+        let name = display_name(&path);
+        let Some(index) = self.apps.iter().position(|app| app.path == path) else {
+            self.message = format!("{} 不在管理列表中", name);
+            return;
+        };
+        if !force && let Some(reason) = self.apps[index].external_change() {
+            self.message = format!("{} 可能由其他工具管理（{}），按 R 强制恢复", name, reason);
+            return;
+        }
+        if let Err(err) = self.apps[index].restore_dock_icon() {
+            self.message = format!("恢复 {} 失败：{}", name, err);
+            return;
+        }
+        let app = self.apps.remove(index);
+        self.verify();
+        self.message = format!("已恢复 {} 的 Dock 图标", name);
+        if app.session
+            && let Err(err) = session::write_journal(&self.apps)
+        {
+            self.message = format!("更新会话日志失败：{}", err);
+        }
+        self.save();
+    }
+
+    // 返回 false 表示退出。
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        if self.searching {
+            match code {
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.query.clear();
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                KeyCode::Char(ch) => self.query.push(ch),
+                KeyCode::Up => self.move_selection(-1),
+                KeyCode::Down => self.move_selection(1),
+                _ => {}
+            }
+            self.clamp_selection();
+            return true;
+        }
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Tab => {
+                self.show_all = !self.show_all;
+                self.verify();
+            }
+            KeyCode::Char('h') => self.hide_selected(),
+            KeyCode::Char('r') => self.restore_selected(false),
+            KeyCode::Char('R') => self.restore_selected(true),
+            KeyCode::Char('v') => self.verify(),
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, search, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let scope = if self.show_all {
+            "全部应用"
+        } else {
+            "已管理的应用"
+        };
+        frame.render_widget(
+            Paragraph::new(format!("Dock Dodger · {}", scope))
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+            header,
+        );
+
+        let items: Vec<ListItem> = self
+            .visible_rows()
+            .into_iter()
+            .map(|row| {
+                let (label, color) = row.status.label();
+                ListItem::new(vec![
+                    Line::from(vec![
+                        Span::styled(
+                            row.name.clone(),
+                            Style::default().add_modifier(Modifier::BOLD),
+                        ),
+                        Span::raw("  "),
+                        Span::styled(label, Style::default().fg(color)),
+                    ]),
+                    Line::styled(
+                        row.path.display().to_string(),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ])
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, body, &mut self.list);
+
+        let search_line = if self.searching {
+            format!("搜索：{}▏", self.query)
+        } else if self.query.is_empty() {
+            self.message.clone()
+        } else {
+            format!("搜索：{}（/ 修改，Esc 清除）  {}", self.query, self.message)
+        };
+        frame.render_widget(Paragraph::new(search_line), search);
+        frame.render_widget(
+            Paragraph::new(HELP).style(Style::default().fg(Color::DarkGray)),
+            footer,
+        );
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && !self.handle_key(key.code)
+            {
+                return Ok(());
+            }
+        }
+    }
+}
+
+pub fn run(args: &[String]) -> i32 {
+    if let Some(arg) = args.first() {
+        eprintln!("未知参数：{}", arg);
+        return 2;
+    }
+    let apps = match state::load_apps() {
+        Ok(apps) => apps,
+        Err(err) => {
+            eprintln!("读取应用列表失败：{}", err);
+            return 1;
+        }
+    };
+    let mut tui = Tui::new(apps);
+    let mut terminal = ratatui::init();
+    let result = tui.run(&mut terminal);
+    ratatui::restore();
+    match result {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("终端界面出错：{}", err);
+            1
+        }
+    }
+}
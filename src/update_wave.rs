@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bundle::{is_dock_icon_hidden, plist_owner_and_mtime};
+use crate::config;
+use crate::state::{ManagedApp, write_atomic};
+use crate::volume;
+
+const CHECK_FILE: &str = "update_check.json";
+// 一次检查中至少这么多已管理应用的 Info.plist 被改写或被删除，才算一轮更新。
+pub const WAVE_THRESHOLD: usize = 3;
+
+// 上次检查时的系统版本，以及已经报告过的应用，避免同一批变化反复提示。
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateCheck {
+    #[serde(default)]
+    pub os_version: Option<String>,
+    #[serde(default)]
+    pub reported: Vec<PathBuf>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct UpdateReport {
+    // 系统版本有变化时为更新前的版本
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_os: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os_version: Option<String>,
+    // Info.plist 被改写且图标已恢复
+    pub reverted: Vec<PathBuf>,
+    // Info.plist 被改写但仍然隐藏
+    pub survived: Vec<PathBuf>,
+    // 应用已不存在
+    pub disappeared: Vec<PathBuf>,
+}
+
+impl UpdateReport {
+    pub fn len(&self) -> usize {
+        self.reverted.len() + self.survived.len() + self.disappeared.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.reverted
+            .iter()
+            .chain(&self.survived)
+            .chain(&self.disappeared)
+    }
+}
+
+fn check_path() -> PathBuf {
+    config::data_dir().join(CHECK_FILE)
+}
+
+pub fn current_os_version() -> Option<String> {
+    Command::new("sw_vers")
+        .arg("-productVersion")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|version| !version.is_empty())
+}
+
+// 与记录的修改时间比较；没有记录（旧版本留下的条目）的应用无法判断，跳过。
+// 外接卷没有挂载时读不到 Info.plist 不代表应用已删除，也跳过。
+pub fn changes(apps: &[ManagedApp]) -> UpdateReport {
+    let mut report = UpdateReport::default();
+    for app in apps {
+        if volume::is_offline(&app.path) {
+            continue;
+        }
+        if !app.path.exists() {
+            report.disappeared.push(app.path.clone());
+            continue;
+        }
+        let Some(recorded) = app.modified_at else {
+            continue;
+        };
+        match plist_owner_and_mtime(&app.path) {
+            Ok((_, modified_at)) if modified_at == recorded => {}
+            _ if is_dock_icon_hidden(&app.path).unwrap_or(false) => {
+                report.survived.push(app.path.clone())
+            }
+            _ => report.reverted.push(app.path.clone()),
+        }
+    }
+    report
+}
+
+// 系统版本变化，或有足够多的应用发生了变化且其中有之前没报告过的，才生成报告。
+pub fn detect(
+    report: UpdateReport,
+    os_version: Option<&str>,
+    last: &UpdateCheck,
+) -> Option<UpdateReport> {
+    let os_changed = last.os_version.is_some()
+        && os_version.is_some()
+        && last.os_version.as_deref() != os_version;
+    let unreported = report.paths().any(|path| !last.reported.contains(path));
+    let wave = report.len() >= WAVE_THRESHOLD && unreported;
+    if !(wave || os_changed && !report.is_empty()) {
+        return None;
+    }
+    Some(UpdateReport {
+        previous_os: os_changed.then(|| last.os_version.clone()).flatten(),
+        os_version: os_version.map(str::to_string),
+        ..report
+    })
+}
+
+fn load_check(path: &Path) -> UpdateCheck {
+    fs::read(path)
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+// 检查一轮并记下系统版本与当前的变化；出现更新潮时返回报告。
+// 记录按每次检查的结果覆盖：重新隐藏或移出列表的应用下次不再算作变化。
+pub fn check(apps: &[ManagedApp]) -> Result<Option<UpdateReport>, Box<dyn std::error::Error>> {
+    let path = check_path();
+    let last = load_check(&path);
+    let os_version = current_os_version();
+    let report = changes(apps);
+    let current = UpdateCheck {
+        os_version: os_version.clone().or(last.os_version.clone()),
+        reported: report.paths().cloned().collect(),
+    };
+    let found = detect(report, os_version.as_deref(), &last);
+    if !config::read_only()
+        && (current.os_version != last.os_version || current.reported != last.reported)
+    {
+        write_atomic(&path, &serde_json::to_vec_pretty(&current)?)?;
+    }
+    Ok(found)
+}
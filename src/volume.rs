@@ -0,0 +1,62 @@
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::shutdown;
+
+const VOLUMES: &str = "/Volumes";
+pub const MOUNT_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+// 位于 /Volumes/<卷名> 下的应用所在的挂载点，其他位置返回 None。
+pub fn volume_of(path: &Path) -> Option<PathBuf> {
+    let rest = path.strip_prefix(VOLUMES).ok()?;
+    match rest.components().next()? {
+        Component::Normal(name) => Some(Path::new(VOLUMES).join(name)),
+        _ => None,
+    }
+}
+
+// 卸载后挂载点通常会被删除；残留的空目录与 /Volumes 位于同一设备，同样视为未挂载。
+pub fn is_mounted(mount: &Path) -> bool {
+    match (fs::metadata(mount), fs::metadata(VOLUMES)) {
+        (Ok(mount), Ok(volumes)) => mount.dev() != volumes.dev(),
+        _ => false,
+    }
+}
+
+// 应用所在的外接卷当前没有挂载：此时读不到 Info.plist 不代表隐藏失效或应用已删除。
+pub fn is_offline(path: &Path) -> bool {
+    volume_of(path).is_some_and(|mount| !is_mounted(&mount))
+}
+
+pub fn mounted_volumes() -> HashSet<PathBuf> {
+    let Ok(entries) = fs::read_dir(VOLUMES) else {
+        return HashSet::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_mounted(path))
+        .collect()
+}
+
+// 轮询 /Volumes，有新卷挂载时调用 on_mount，收到退出信号后停止。
+pub fn spawn<F>(on_mount: F)
+where
+    F: Fn(PathBuf) + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut known = mounted_volumes();
+        while !shutdown::requested() {
+            thread::sleep(MOUNT_POLL_INTERVAL);
+            let current = mounted_volumes();
+            for mount in current.difference(&known) {
+                on_mount(mount.clone());
+            }
+            known = current;
+        }
+    });
+}
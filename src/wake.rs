@@ -0,0 +1,54 @@
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::shutdown;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+// 超过轮询间隔这么多才算睡眠过，避免把系统繁忙时的调度延迟当成唤醒。
+const THRESHOLD: Duration = Duration::from_secs(30);
+
+// 系统睡眠期间单调时钟（Instant）停止计时而系统时间照常前进，两者的差值超过阈值即说明刚刚唤醒。
+// 不需要订阅 NSWorkspace 通知；手动把时钟调快也会触发一次，只是多做一次检查。
+pub struct WakeDetector {
+    instant: Instant,
+    wall: SystemTime,
+}
+
+impl Default for WakeDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WakeDetector {
+    pub fn new() -> Self {
+        WakeDetector {
+            instant: Instant::now(),
+            wall: SystemTime::now(),
+        }
+    }
+
+    // 自上次调用以来是否经历过睡眠。
+    pub fn woke(&mut self) -> bool {
+        let monotonic = self.instant.elapsed();
+        let wall = self.wall.elapsed().unwrap_or_default();
+        *self = WakeDetector::new();
+        wall > monotonic + THRESHOLD
+    }
+}
+
+// 在后台线程中每隔 10 秒检查一次，唤醒后调用 on_wake，收到退出信号后停止。
+pub fn spawn<F>(on_wake: F)
+where
+    F: Fn() + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut detector = WakeDetector::new();
+        while !shutdown::requested() {
+            thread::sleep(POLL_INTERVAL);
+            if detector.woke() {
+                on_wake();
+            }
+        }
+    });
+}
@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::plist_cache;
+use crate::scan::ScanScope;
+
+#[derive(Debug, Clone)]
+pub enum WatchEvent {
+    Added(PathBuf),
+    Removed(PathBuf),
+}
+
+pub struct DirWatcher {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+// 轮询方式监视目录中的 .app：只有 Info.plist 已经存在的包才算安装完成，
+// 这样正在拷贝中的应用会等到下一轮再报告。
+fn snapshot(scope: &ScanScope) -> HashSet<PathBuf> {
+    scope
+        .find_app_bundles()
+        .into_iter()
+        .filter(|path| path.join("Contents/Info.plist").is_file())
+        .collect()
+}
+
+impl DirWatcher {
+    pub fn spawn<F>(scope: ScanScope, interval: Duration, on_event: F) -> Self
+    where
+        F: Fn(WatchEvent) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let handle = thread::spawn(move || {
+            let mut known = snapshot(&scope);
+            while !thread_stop.load(Ordering::Relaxed) {
+                thread::park_timeout(interval);
+                if thread_stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let current = snapshot(&scope);
+                // 重新安装到同一位置的应用不能沿用旧的解析结果。
+                for path in current.difference(&known) {
+                    plist_cache::invalidate(path);
+                    on_event(WatchEvent::Added(path.clone()));
+                }
+                for path in known.difference(&current) {
+                    plist_cache::invalidate(path);
+                    on_event(WatchEvent::Removed(path.clone()));
+                }
+                known = current;
+            }
+        });
+        DirWatcher {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    // 监视线程仍在运行（没有因 panic 等原因提前结束）。
+    pub fn is_running(&self) -> bool {
+        self.handle
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+}
+
+impl Drop for DirWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, mpsc};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config;
+
+pub const SCHEMA_VERSION: u32 = 1;
+pub const DEFAULT_RETRIES: u32 = 3;
+const TIMEOUT_SECS: u64 = 10;
+// 没有指定 events 时只发送操作结果和漂移，不发送 operation_started。
+const DEFAULT_EVENTS: [&str; 3] = ["operation_succeeded", "operation_failed", "drift_detected"];
+
+fn default_retries() -> u32 {
+    DEFAULT_RETRIES
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+}
+
+impl Webhook {
+    fn wants(&self, kind: &str) -> bool {
+        if self.events.is_empty() {
+            DEFAULT_EVENTS.contains(&kind)
+        } else {
+            self.events
+                .iter()
+                .any(|event| event == kind || event == "*")
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    schema_version: u32,
+    host: &'a str,
+    user: String,
+    version: &'static str,
+    event: &'a Value,
+}
+
+// 按配置文件的修改时间缓存，配置改动后下一个事件就会使用新的地址。
+type Cache = (Option<SystemTime>, Vec<Webhook>);
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+static QUEUE: Mutex<Option<mpsc::Sender<(Webhook, String)>>> = Mutex::new(None);
+static PENDING: AtomicUsize = AtomicUsize::new(0);
+
+fn configured() -> Vec<Webhook> {
+    let stamp = config::config_stamp();
+    let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some((cached, webhooks)) = cache.as_ref()
+        && *cached == stamp
+    {
+        return webhooks.clone();
+    }
+    let webhooks = config::load_config().unwrap_or_default().webhooks;
+    *cache = Some((stamp, webhooks.clone()));
+    webhooks
+}
+
+// 用电脑名称区分不同的机器，取不到时退回主机名。
+fn host_name() -> &'static str {
+    static HOST: OnceLock<String> = OnceLock::new();
+    HOST.get_or_init(|| {
+        [
+            ("scutil", &["--get", "ComputerName"][..]),
+            ("hostname", &[][..]),
+        ]
+        .iter()
+        .find_map(|(program, args)| {
+            Command::new(program)
+                .args(*args)
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                .filter(|name| !name.is_empty())
+        })
+        .unwrap_or_default()
+    })
+}
+
+// 由 curl 负责超时和重试：--fail 让 4xx/5xx 也算失败，--retry-all-errors 对这些响应同样重试。
+fn post(webhook: &Webhook, body: &str) -> Result<(), String> {
+    let mut child = Command::new("curl")
+        .args(["-sS", "--fail", "--retry-all-errors", "-X", "POST"])
+        .args(["--max-time", &TIMEOUT_SECS.to_string()])
+        .args(["--retry", &webhook.retries.to_string()])
+        .args([
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ])
+        .arg(&webhook.url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(body.as_bytes());
+    }
+    let output = child.wait_with_output().map_err(|err| err.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// 在后台线程逐个发送，不阻塞隐藏、恢复等操作本身。
+fn enqueue(webhook: Webhook, body: String) {
+    let mut queue = QUEUE.lock().unwrap_or_else(|err| err.into_inner());
+    let sender = queue.get_or_insert_with(|| {
+        let (sender, receiver) = mpsc::channel::<(Webhook, String)>();
+        thread::spawn(move || {
+            for (webhook, body) in receiver {
+                if let Err(err) = post(&webhook, &body) {
+                    eprintln!("[Webhook] 发送到 {} 失败：{}", webhook.url, err);
+                }
+                PENDING.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+        sender
+    });
+    PENDING.fetch_add(1, Ordering::SeqCst);
+    if sender.send((webhook, body)).is_err() {
+        PENDING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub fn dispatch(event: &Value) {
+    let webhooks = configured();
+    if webhooks.is_empty() {
+        return;
+    }
+    let kind = event["type"].as_str().unwrap_or_default();
+    let payload = Payload {
+        schema_version: SCHEMA_VERSION,
+        host: host_name(),
+        user: std::env::var("USER").unwrap_or_default(),
+        version: env!("CARGO_PKG_VERSION"),
+        event,
+    };
+    let Ok(body) = serde_json::to_string(&payload) else {
+        return;
+    };
+    for webhook in webhooks.into_iter().filter(|webhook| webhook.wants(kind)) {
+        enqueue(webhook, body.clone());
+    }
+}
+
+// 排队中和正在发送的请求数。
+pub fn pending() -> usize {
+    PENDING.load(Ordering::SeqCst)
+}
+
+// 命令行退出或关闭窗口前调用，等待排队的请求发送完，最多等待 timeout。
+pub fn wait(timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    while PENDING.load(Ordering::SeqCst) > 0 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{self, Config};
+
+// 所有 Info.plist 写入都经过这里排队：配置了 per_second 时相邻两次写入之间至少间隔 1/per_second 秒，
+// 避免大批量操作把慢速外接盘占满或引发云同步风暴。只协调同一个进程内的写入。
+// 等待发生在调用写入的线程上，图形界面的批量操作因此放在工作线程中进行。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WriteLimits {
+    pub per_second: Option<f64>,
+}
+
+impl WriteLimits {
+    pub fn from_config(config: &Config) -> Self {
+        WriteLimits {
+            per_second: config.write_rate_limit.filter(|rate| *rate > 0.0),
+        }
+    }
+}
+
+struct Queue {
+    // 正在排队（等待间隔）的写入数
+    waiting: usize,
+    // 下一次写入最早的开始时间，只在限速时使用
+    next_start: Option<Instant>,
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue {
+    waiting: 0,
+    next_start: None,
+});
+
+fn lock() -> MutexGuard<'static, Queue> {
+    QUEUE.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+pub fn pending() -> usize {
+    lock().waiting
+}
+
+// 按当前配置排队后执行 write；配置每次重新读取，修改后不需要重启。
+pub fn run<T>(write: impl FnOnce() -> T) -> T {
+    let config = config::load_config().unwrap_or_default();
+    run_with(&WriteLimits::from_config(&config), write)
+}
+
+pub fn run_with<T>(limits: &WriteLimits, write: impl FnOnce() -> T) -> T {
+    let Some(rate) = limits.per_second else {
+        return write();
+    };
+    let start = {
+        let mut queue = lock();
+        queue.waiting += 1;
+        let now = Instant::now();
+        let start = queue.next_start.map_or(now, |next| next.max(now));
+        queue.next_start = Some(start + Duration::from_secs_f64(1.0 / rate));
+        start
+    };
+    let delay = start.saturating_duration_since(Instant::now());
+    if !delay.is_zero() {
+        thread::sleep(delay);
+    }
+    lock().waiting -= 1;
+    write()
+}
@@ -0,0 +1,216 @@
+// 各个测试文件只用到其中一部分。
+#![allow(dead_code)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use osx_dock_dodger_rs::config::{CONFIG_ENV, DATA_DIR_ENV};
+use plist::{Dictionary, Value};
+use tempfile::TempDir;
+
+// 数据目录通过环境变量指定，是整个进程共享的；同一个测试文件中的用例依次持有锁，
+// 各自使用独立的临时目录。
+pub struct TestEnv {
+    root: TempDir,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    pub fn new() -> Self {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let lock = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let root = tempfile::tempdir().unwrap();
+        unsafe {
+            std::env::set_var(DATA_DIR_ENV, root.path().join("data"));
+            std::env::remove_var(CONFIG_ENV);
+        }
+        fs::create_dir_all(root.path().join("Applications")).unwrap();
+        TestEnv { root, _lock: lock }
+    }
+
+    pub fn root(&self) -> &Path {
+        self.root.path()
+    }
+
+    pub fn applications(&self) -> PathBuf {
+        self.root.path().join("Applications")
+    }
+
+    pub fn data_dir(&self) -> PathBuf {
+        self.root.path().join("data")
+    }
+
+    pub fn write_config(&self, config: &serde_json::Value) {
+        fs::create_dir_all(self.data_dir()).unwrap();
+        fs::write(
+            self.data_dir().join("config.json"),
+            serde_json::to_vec_pretty(config).unwrap(),
+        )
+        .unwrap();
+    }
+
+    pub fn bundle(&self, name: &str) -> FakeBundle {
+        FakeBundle::new(&self.applications(), name)
+    }
+}
+
+// 生成接近真实结构的 .app：Info.plist、PkgInfo、可执行的 MacOS 桩程序，
+// 以及可选的嵌套辅助应用（放在 Contents/Frameworks 下，与 Electron 等应用一致）。
+pub struct FakeBundle {
+    dir: PathBuf,
+    name: String,
+    bundle_id: Option<String>,
+    version: Option<String>,
+    ui_element: Option<Value>,
+    extra: Dictionary,
+    info: Option<Dictionary>,
+    binary: bool,
+    helpers: Vec<FakeBundle>,
+}
+
+impl FakeBundle {
+    pub fn new(dir: &Path, name: &str) -> Self {
+        FakeBundle {
+            dir: dir.to_path_buf(),
+            name: name.to_string(),
+            bundle_id: Some(format!(
+                "com.example.{}",
+                name.to_lowercase().replace(' ', "-")
+            )),
+            version: Some("1.0".into()),
+            ui_element: None,
+            extra: Dictionary::new(),
+            info: None,
+            binary: false,
+            helpers: Vec::new(),
+        }
+    }
+
+    pub fn bundle_id(mut self, bundle_id: Option<&str>) -> Self {
+        self.bundle_id = bundle_id.map(str::to_string);
+        self
+    }
+
+    pub fn version(mut self, version: Option<&str>) -> Self {
+        self.version = version.map(str::to_string);
+        self
+    }
+
+    pub fn ui_element(mut self, value: Value) -> Self {
+        self.ui_element = Some(value);
+        self
+    }
+
+    pub fn hidden(self) -> Self {
+        self.ui_element(Value::String("1".into()))
+    }
+
+    pub fn key(mut self, key: &str, value: Value) -> Self {
+        self.extra.insert(key.into(), value);
+        self
+    }
+
+    // 直接使用给定的 Info.plist 内容，忽略其他设置。
+    pub fn info(mut self, dict: Dictionary) -> Self {
+        self.info = Some(dict);
+        self
+    }
+
+    pub fn binary(mut self) -> Self {
+        self.binary = true;
+        self
+    }
+
+    pub fn helper(mut self, helper: impl FnOnce(FakeBundle) -> FakeBundle) -> Self {
+        let name = format!("{} Helper {}", self.name, self.helpers.len() + 1);
+        let frameworks = self.path().join("Contents/Frameworks");
+        self.helpers
+            .push(helper(FakeBundle::new(&frameworks, &name)));
+        self
+    }
+
+    pub fn login_item(mut self, helper: impl FnOnce(FakeBundle) -> FakeBundle) -> Self {
+        let name = format!("{} Login Item {}", self.name, self.helpers.len() + 1);
+        let login_items = self.path().join("Contents/Library/LoginItems");
+        self.helpers
+            .push(helper(FakeBundle::new(&login_items, &name)));
+        self
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(format!("{}.app", self.name))
+    }
+
+    fn info_dictionary(&self) -> Dictionary {
+        if let Some(info) = &self.info {
+            return info.clone();
+        }
+        let mut dict = Dictionary::new();
+        dict.insert("CFBundleName".into(), Value::String(self.name.clone()));
+        dict.insert(
+            "CFBundleExecutable".into(),
+            Value::String(self.name.clone()),
+        );
+        dict.insert("CFBundlePackageType".into(), Value::String("APPL".into()));
+        if let Some(bundle_id) = &self.bundle_id {
+            dict.insert(
+                "CFBundleIdentifier".into(),
+                Value::String(bundle_id.clone()),
+            );
+        }
+        if let Some(version) = &self.version {
+            dict.insert(
+                "CFBundleShortVersionString".into(),
+                Value::String(version.clone()),
+            );
+            dict.insert("CFBundleVersion".into(), Value::String(version.clone()));
+        }
+        for (key, value) in &self.extra {
+            dict.insert(key.clone(), value.clone());
+        }
+        if let Some(value) = &self.ui_element {
+            dict.insert("LSUIElement".into(), value.clone());
+        }
+        dict
+    }
+
+    pub fn build(self) -> PathBuf {
+        let app = self.path();
+        let contents = app.join("Contents");
+        fs::create_dir_all(contents.join("MacOS")).unwrap();
+        fs::create_dir_all(contents.join("Resources")).unwrap();
+
+        let executable = contents.join("MacOS").join(&self.name);
+        fs::write(&executable, "#!/bin/sh\nexit 0\n").unwrap();
+        fs::set_permissions(&executable, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(contents.join("PkgInfo"), "APPL????").unwrap();
+
+        let plist = Value::Dictionary(self.info_dictionary());
+        let plist_path = contents.join("Info.plist");
+        if self.binary {
+            plist.to_file_binary(&plist_path).unwrap();
+        } else {
+            plist.to_file_xml(&plist_path).unwrap();
+        }
+
+        for helper in self.helpers {
+            helper.build();
+        }
+        app
+    }
+}
+
+pub fn read_info_plist(app: &Path) -> Dictionary {
+    Value::from_file(app.join("Contents/Info.plist"))
+        .unwrap()
+        .into_dictionary()
+        .unwrap()
+}
+
+pub fn is_binary_plist(app: &Path) -> bool {
+    fs::read(app.join("Contents/Info.plist"))
+        .unwrap()
+        .starts_with(b"bplist")
+}
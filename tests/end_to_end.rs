@@ -0,0 +1,1627 @@
+mod common;
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use osx_dock_dodger_rs::agent::{self, ProfileSchedule};
+use osx_dock_dodger_rs::backup;
+use osx_dock_dodger_rs::bundle::{self, hide_dock_icon, is_dock_icon_hidden, ui_element};
+use osx_dock_dodger_rs::config;
+use osx_dock_dodger_rs::crypto;
+use osx_dock_dodger_rs::dock;
+use osx_dock_dodger_rs::error::ErrorKind;
+use osx_dock_dodger_rs::explain;
+use osx_dock_dodger_rs::history::{self, HistoryAction, HistoryEntry, HistoryFilter};
+use osx_dock_dodger_rs::import::{self, ImportEntry};
+use osx_dock_dodger_rs::installed;
+use osx_dock_dodger_rs::launchpad;
+use osx_dock_dodger_rs::migrate;
+use osx_dock_dodger_rs::pause;
+use osx_dock_dodger_rs::relaunch;
+use osx_dock_dodger_rs::rollback::{self, RollbackAction, RollbackStep};
+use osx_dock_dodger_rs::scan::ScanScope;
+use osx_dock_dodger_rs::session;
+use osx_dock_dodger_rs::snapshot;
+use osx_dock_dodger_rs::state::{self, Autosave, ManagedApp, RelaunchOptions};
+use osx_dock_dodger_rs::state_store;
+use osx_dock_dodger_rs::translocation::{self, Translocations};
+use osx_dock_dodger_rs::update_wave::{self, UpdateCheck};
+use osx_dock_dodger_rs::write_queue::{self, WriteLimits};
+use plist::Value;
+
+use common::{TestEnv, read_info_plist};
+
+fn hide(app: &std::path::Path) -> ManagedApp {
+    let original = ui_element(app).unwrap();
+    hide_dock_icon(app).unwrap();
+    let mut managed = ManagedApp::new(app.to_path_buf());
+    managed.record_hide(original);
+    managed.record_checksum();
+    managed
+}
+
+fn scope(env: &TestEnv, exclude: &[&str]) -> ScanScope {
+    ScanScope {
+        roots: vec![(env.applications(), 2)],
+        exclude: exclude.iter().map(|pattern| pattern.to_string()).collect(),
+    }
+}
+
+#[test]
+fn add_hide_persist_restore() {
+    let env = TestEnv::new();
+    let app = env
+        .bundle("Foo")
+        .key("LSMinimumSystemVersion", Value::String("11.0".into()))
+        .build();
+    let before = read_info_plist(&app);
+
+    let managed = hide(&app);
+    assert!(is_dock_icon_hidden(&app).unwrap());
+    assert_eq!(managed.checksum_matches(), Some(true));
+    state::save_apps(&[managed]).unwrap();
+    assert!(env.data_dir().join("apps.json").is_file());
+
+    let loaded = state::load_apps().unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].path, app);
+    assert!(loaded[0].hide.is_some());
+    assert_eq!(loaded[0].external_change(), None);
+
+    loaded[0].restore_dock_icon().unwrap();
+    assert!(!is_dock_icon_hidden(&app).unwrap());
+    assert_eq!(read_info_plist(&app), before);
+
+    let backups = backup::list_backups().unwrap();
+    assert!(backups.iter().any(|entry| entry.app == app));
+}
+
+#[test]
+fn identical_backups_share_one_file() {
+    let env = TestEnv::new();
+    let app = env.bundle("Repeat").build();
+    let original = read_info_plist(&app);
+    for _ in 0..3 {
+        hide(&app).restore_dock_icon().unwrap();
+    }
+
+    // 默认保留 5 份，内容只有原始、隐藏后和恢复后几种
+    let backups = backup::list_backups().unwrap();
+    assert_eq!(backups.len(), 5);
+    let files: HashSet<_> = backups.iter().map(|entry| &entry.file).collect();
+    assert!(files.len() < backups.len());
+    let objects = env.data_dir().join("backups/objects");
+    assert_eq!(fs::read_dir(&objects).unwrap().count(), files.len());
+
+    hide_dock_icon(&app).unwrap();
+    let restored = backups.iter().find(|entry| !is_hidden_backup(&entry.file));
+    backup::restore_backup(&app, restored.unwrap().created_at).unwrap();
+    assert_eq!(read_info_plist(&app), original);
+
+    backup::prune_backups(1).unwrap();
+    assert_eq!(backup::list_backups().unwrap().len(), 1);
+    assert_eq!(fs::read_dir(&objects).unwrap().count(), 1);
+}
+
+#[test]
+fn unreadable_backup_meta_keeps_existing_backups() {
+    let env = TestEnv::new();
+    let app = env.bundle("Kept").build();
+    backup::backup_plist(&app).unwrap();
+    let meta = fs::read_dir(env.data_dir().join("backups"))
+        .unwrap()
+        .map(|item| item.unwrap().path().join("meta.json"))
+        .find(|path| path.is_file())
+        .unwrap();
+    fs::write(&meta, b"{ not json").unwrap();
+
+    assert!(backup::backup_plist(&app).is_err());
+    let objects = env.data_dir().join("backups/objects");
+    assert_eq!(fs::read_dir(&objects).unwrap().count(), 1);
+    assert_eq!(fs::read(&meta).unwrap(), b"{ not json");
+}
+
+#[test]
+fn encrypted_state_and_backups_round_trip() {
+    let env = TestEnv::new();
+    unsafe {
+        std::env::set_var(crypto::DATA_KEY_ENV, "42".repeat(32));
+    }
+    env.write_config(&serde_json::json!({ "encrypt_data": true }));
+    let app = env.bundle("Secret").build();
+    state::save_apps(&[hide(&app)]).unwrap();
+
+    let name = app.to_string_lossy().into_owned();
+    let raw = fs::read(state::state_file_path()).unwrap();
+    assert!(crypto::is_sealed(&raw));
+    assert!(!String::from_utf8_lossy(&raw).contains(&name));
+    let backups = backup::list_backups().unwrap();
+    assert_eq!(backups[0].app, app);
+    assert!(crypto::is_sealed(&fs::read(&backups[0].file).unwrap()));
+    assert_eq!(state::load_apps().unwrap()[0].path, app);
+    history::record(HistoryAction::Hide, &app, None);
+    let lines = fs::read_to_string(history::history_path()).unwrap();
+    assert!(!lines.contains(&name));
+    assert_eq!(history::load_history().unwrap()[0].path, app);
+
+    // 关闭后读取一次即转回明文
+    env.write_config(&serde_json::json!({ "encrypt_data": false }));
+    state::load_apps().unwrap();
+    backup::list_backups().unwrap();
+    let raw = fs::read(state::state_file_path()).unwrap();
+    assert!(String::from_utf8_lossy(&raw).contains(&name));
+    let lines = fs::read_to_string(history::history_path()).unwrap();
+    assert!(lines.contains(&name));
+    assert!(!crypto::is_sealed(&fs::read(&backups[0].file).unwrap()));
+}
+
+#[test]
+fn unreadable_encrypted_state_is_an_error_not_an_empty_list() {
+    let env = TestEnv::new();
+    let raw = b"DDENC1\ntruncated".to_vec();
+    assert!(crypto::is_sealed(&raw));
+    fs::create_dir_all(env.data_dir()).unwrap();
+    fs::write(state::state_file_path(), &raw).unwrap();
+
+    assert!(state::load_state().is_err());
+    assert_eq!(fs::read(state::state_file_path()).unwrap(), raw);
+}
+
+#[test]
+fn read_only_mode_refuses_writes() {
+    let env = TestEnv::new();
+    env.write_config(&serde_json::json!({ "read_only": true }));
+    let app = env.bundle("Audit").build();
+    let before = read_info_plist(&app);
+
+    assert!(hide_dock_icon(&app).is_err());
+    assert_eq!(read_info_plist(&app), before);
+    assert!(state::save_apps(&[ManagedApp::new(app.clone())]).is_err());
+    assert!(!state::state_file_path().exists());
+    assert!(backup::list_backups().unwrap().is_empty());
+}
+
+#[test]
+fn failed_autosave_keeps_changes_pending() {
+    let env = TestEnv::new();
+    let app = env.bundle("Pending").build();
+    let mut autosave = Autosave::new(Duration::ZERO);
+    let mut apps = vec![ManagedApp::new(app.clone())];
+    autosave.mark_dirty();
+
+    env.write_config(&serde_json::json!({ "read_only": true }));
+    assert!(autosave.flush(&mut apps).is_err());
+    assert!(autosave.due().is_some());
+    assert!(!state::state_file_path().exists());
+
+    env.write_config(&serde_json::json!({}));
+    autosave.flush(&mut apps).unwrap();
+    assert!(autosave.due().is_none());
+    assert_eq!(state::load_apps().unwrap()[0].path, app);
+}
+
+#[test]
+fn read_only_loads_migrate_and_salvage_in_memory() {
+    let env = TestEnv::new();
+    env.write_config(&serde_json::json!({ "read_only": true }));
+    let app = env.bundle("Old").build();
+    let legacy = serde_json::to_string(&[&app]).unwrap();
+    fs::write(state::state_file_path(), &legacy).unwrap();
+
+    let loaded = state::load_state().unwrap();
+    assert_eq!(loaded.apps[0].path, app);
+    assert_eq!(
+        fs::read_to_string(state::state_file_path()).unwrap(),
+        legacy
+    );
+
+    let corrupt = format!("{{\"apps\": [{:?}", app);
+    fs::write(state::state_file_path(), &corrupt).unwrap();
+    let loaded = state::load_state().unwrap();
+    let recovery = loaded.recovery.unwrap();
+    assert!(recovery.backup_path.is_none());
+    assert_eq!(loaded.apps[0].path, app);
+    assert_eq!(
+        fs::read_to_string(state::state_file_path()).unwrap(),
+        corrupt
+    );
+    assert_eq!(fs::read_dir(env.data_dir()).unwrap().count(), 2);
+}
+
+#[test]
+fn autosave_merges_changes_from_other_processes() {
+    let env = TestEnv::new();
+    let (a, b, c) = (
+        env.bundle("A").build(),
+        env.bundle("B").build(),
+        env.bundle("C").build(),
+    );
+    state::save_apps(&[ManagedApp::new(a.clone()), ManagedApp::new(b.clone())]).unwrap();
+    let mut autosave = Autosave::new(Duration::ZERO);
+    let stamp = state_store::stamp();
+    let mut apps = state::load_apps().unwrap();
+    autosave.track(apps.clone(), stamp);
+
+    // 另一个进程加入 C，本进程同时删除了 A
+    let mut other = state::load_apps().unwrap();
+    other.push(ManagedApp::new(c.clone()));
+    state::save_apps(&other).unwrap();
+    apps.retain(|app| app.path != a);
+    autosave.mark_dirty();
+
+    // 其它进程持有锁时推迟保存
+    let lock = state::lock_state();
+    assert!(state::try_lock_state().is_none());
+    assert!(!autosave.flush_if_due(&mut apps).unwrap());
+    assert!(autosave.due().is_some());
+    drop(lock);
+
+    assert!(autosave.flush(&mut apps).unwrap());
+    let paths = |apps: &[ManagedApp]| apps.iter().map(|app| app.path.clone()).collect::<Vec<_>>();
+    assert_eq!(paths(&apps), vec![b.clone(), c.clone()]);
+    assert_eq!(paths(&state::load_apps().unwrap()), vec![b, c]);
+    assert!(autosave.reload(&apps).unwrap().is_none());
+}
+
+#[test]
+fn newer_state_files_are_refused_and_left_untouched() {
+    let env = TestEnv::new();
+    let contents = r#"{"schema_version": 99, "apps": [], "future": true}"#;
+    fs::create_dir_all(env.data_dir()).unwrap();
+    fs::write(state::state_file_path(), contents).unwrap();
+
+    let err = state::load_state().unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with(migrate::UNSUPPORTED_VERSION_ERROR)
+    );
+    assert_eq!(
+        fs::read_to_string(state::state_file_path()).unwrap(),
+        contents
+    );
+}
+
+fn is_hidden_backup(file: &std::path::Path) -> bool {
+    Value::from_file(file)
+        .unwrap()
+        .as_dictionary()
+        .is_some_and(|dict| dict.contains_key("LSUIElement"))
+}
+
+#[test]
+fn legacy_backups_move_into_content_store() {
+    let env = TestEnv::new();
+    let app = env.bundle("Legacy").build();
+    let dir = env.data_dir().join("backups/Legacy-0000000000000000");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("meta.json"),
+        serde_json::to_vec(&serde_json::json!({ "path": app })).unwrap(),
+    )
+    .unwrap();
+    fs::copy(app.join("Contents/Info.plist"), dir.join("1000.plist")).unwrap();
+    fs::copy(app.join("Contents/Info.plist"), dir.join("2000.plist")).unwrap();
+
+    let backups = backup::list_backups().unwrap();
+    assert_eq!(backups.len(), 2);
+    assert_eq!(backups[0].created_at, 2000);
+    assert_eq!(backups[0].file, backups[1].file);
+    assert!(!dir.join("1000.plist").exists());
+    assert!(!dir.join("2000.plist").exists());
+}
+
+#[test]
+fn binary_plist_stays_binary() {
+    let env = TestEnv::new();
+    let app = env.bundle("Binary").binary().build();
+    let managed = hide(&app);
+    assert!(common::is_binary_plist(&app));
+    managed.restore_dock_icon().unwrap();
+    assert!(common::is_binary_plist(&app));
+}
+
+#[test]
+fn adopt_scan_skips_nested_helpers() {
+    let env = TestEnv::new();
+    let hidden = env
+        .bundle("Menubar")
+        .hidden()
+        .helper(|helper| helper.hidden())
+        .build();
+    env.bundle("Visible").build();
+    fs::create_dir_all(env.applications().join("Utilities")).unwrap();
+    let nested = common::FakeBundle::new(&env.applications().join("Utilities"), "Tool")
+        .hidden()
+        .build();
+
+    let mut found = scope(&env, &[]).find_hidden_apps();
+    found.sort();
+    assert_eq!(found, vec![hidden, nested]);
+}
+
+#[test]
+fn scan_exclude_skips_directories_and_bundles() {
+    let env = TestEnv::new();
+    let kept = env.bundle("Kept").hidden().build();
+    env.bundle("Skipped").hidden().build();
+    let downloads = env.applications().join("Downloads");
+    fs::create_dir_all(&downloads).unwrap();
+    common::FakeBundle::new(&downloads, "Installer")
+        .hidden()
+        .build();
+
+    let excluded = downloads.display().to_string();
+    let found = scope(&env, &[&excluded, "*/Skipped.app"]).find_hidden_apps();
+    assert_eq!(found, vec![kept]);
+}
+
+#[test]
+fn scan_scope_follows_config() {
+    let env = TestEnv::new();
+    let deep = env.applications().join("a/b");
+    fs::create_dir_all(&deep).unwrap();
+    let app = common::FakeBundle::new(&deep, "Deep").hidden().build();
+    env.write_config(&serde_json::json!({
+        "scan_roots": [{ "path": env.applications(), "depth": 3 }],
+    }));
+    assert_eq!(ScanScope::load().find_hidden_apps(), vec![app]);
+
+    env.write_config(&serde_json::json!({ "scan_roots": [env.applications()] }));
+    assert!(ScanScope::load().find_hidden_apps().is_empty());
+}
+
+#[test]
+fn import_resolves_by_name_and_bundle_id() {
+    let env = TestEnv::new();
+    let by_name = env.bundle("Named").build();
+    let by_id = env
+        .bundle("Renamed")
+        .bundle_id(Some("org.example.id"))
+        .build();
+    let scope = scope(&env, &[]);
+
+    let entry = ImportEntry {
+        name: Some("Named".into()),
+        ..Default::default()
+    };
+    assert_eq!(import::resolve_entry(&entry, &scope), Some(by_name));
+
+    let entry = ImportEntry {
+        bundle_id: Some("org.example.id".into()),
+        ..Default::default()
+    };
+    assert_eq!(import::resolve_entry(&entry, &scope), Some(by_id));
+}
+
+#[test]
+fn profile_schedules_round_trip_through_launchd_plists() {
+    let env = TestEnv::new();
+    assert_eq!(
+        agent::parse_weekdays("weekdays").unwrap(),
+        vec![1, 2, 3, 4, 5]
+    );
+    assert_eq!(agent::parse_weekdays("fri, mon,1").unwrap(), vec![1, 5]);
+    assert!(agent::parse_weekdays("someday").is_err());
+    assert_eq!(agent::parse_time("9:05").unwrap(), (9, 5));
+    assert!(agent::parse_time("24:00").is_err());
+    assert!(ProfileSchedule::new("a b", PathBuf::from("/p"), Vec::new(), (9, 0)).is_err());
+
+    let profile = env.root().join("work.dockdodger");
+    let schedule = ProfileSchedule::new(
+        "work",
+        profile.clone(),
+        agent::parse_weekdays("weekdays").unwrap(),
+        (9, 0),
+    )
+    .unwrap();
+    assert_eq!(schedule.describe(), "工作日 09:00");
+    let plist = schedule.to_plist(Path::new("/usr/local/bin/dock-dodger"));
+    let dict = plist.as_dictionary().unwrap();
+    let arguments: Vec<&str> = dict["ProgramArguments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(Value::as_string)
+        .collect();
+    assert_eq!(
+        arguments[1..],
+        ["import", "--scheduled", profile.to_str().unwrap()]
+    );
+    assert_eq!(dict["StartCalendarInterval"].as_array().unwrap().len(), 5);
+    assert_eq!(
+        ProfileSchedule::from_plist(&plist).as_ref(),
+        Some(&schedule)
+    );
+
+    // 列出时从 LaunchAgents 下的 plist 读回，其他代理不算
+    let home = std::env::var_os("HOME");
+    unsafe { std::env::set_var("HOME", env.root()) };
+    let agents = env.root().join("Library/LaunchAgents");
+    fs::create_dir_all(&agents).unwrap();
+    plist.to_file_xml(schedule.plist_path()).unwrap();
+    let daily = ProfileSchedule::new("home", profile, Vec::new(), (18, 30)).unwrap();
+    daily
+        .to_plist(Path::new("/usr/local/bin/dock-dodger"))
+        .to_file_xml(daily.plist_path())
+        .unwrap();
+    fs::write(
+        agents.join("com.github.fmnisme.dock-dodger.verify.plist"),
+        "",
+    )
+    .unwrap();
+    assert_eq!(agent::profile_schedules(), vec![daily, schedule]);
+    assert!(agent::uninstall_profile_schedule("../../work").is_err());
+    assert!(
+        agents
+            .join("com.github.fmnisme.dock-dodger.verify.plist")
+            .exists()
+    );
+    match home {
+        Some(home) => unsafe { std::env::set_var("HOME", home) },
+        None => unsafe { std::env::remove_var("HOME") },
+    }
+}
+
+#[test]
+fn bundle_id_resolves_to_installed_path() {
+    let env = TestEnv::new();
+    let app = env
+        .bundle("Example")
+        .bundle_id(Some("com.example.App"))
+        .version(Some("2.0"))
+        .build();
+    env.bundle("Old Example")
+        .bundle_id(Some("com.example.App"))
+        .version(Some("1.0"))
+        .build();
+    let excluded = env.applications().join("Excluded");
+    fs::create_dir_all(&excluded).unwrap();
+    common::FakeBundle::new(&excluded, "Other")
+        .bundle_id(Some("org.example.Other"))
+        .build();
+    let scope = scope(&env, &[excluded.to_str().unwrap()]);
+
+    assert_eq!(
+        installed::resolve_bundle_id("com.example.app", &scope).unwrap(),
+        app
+    );
+    assert!(installed::resolve_bundle_id("org.example.Other", &scope).is_err());
+    assert!(installed::resolve_bundle_id(" ", &scope).is_err());
+}
+
+#[test]
+fn managed_apps_match_recorded_bundle_ids() {
+    let env = TestEnv::new();
+    let path = env
+        .bundle("Tool")
+        .bundle_id(Some("org.example.Tool"))
+        .build();
+    let legacy = ManagedApp::new(path.clone());
+    assert_eq!(legacy.bundle_id, None);
+    assert!(legacy.matches_bundle_id("org.example.tool"));
+
+    let app = hide(&path);
+    assert_eq!(app.bundle_id.as_deref(), Some("org.example.Tool"));
+    fs::remove_file(path.join("Contents/Info.plist")).unwrap();
+    assert!(app.matches_bundle_id(" ORG.EXAMPLE.TOOL "));
+    assert!(!app.matches_bundle_id("org.example.Other"));
+}
+
+fn sqlite3(db: &Path, sql: &str) -> String {
+    let output = std::process::Command::new("sqlite3")
+        .arg(db)
+        .arg(sql)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+// 建一个与启动台结构相同、收录了 org.example.Tool 和另一个应用的数据库，
+// 并通过环境变量让 launchpad 模块使用它。
+fn launchpad_db(env: &TestEnv) -> PathBuf {
+    let db = env.root().join("launchpad.db");
+    sqlite3(
+        &db,
+        "CREATE TABLE dbinfo (key VARCHAR, value VARCHAR);
+         INSERT INTO dbinfo VALUES ('ignore_items_update_triggers', '0');
+         CREATE TABLE items (rowid INTEGER PRIMARY KEY ASC, uuid VARCHAR, flags INTEGER,
+             type INTEGER, parent_id INTEGER NOT NULL, ordering INTEGER);
+         CREATE TABLE apps (item_id INTEGER PRIMARY KEY, title VARCHAR, bundleid VARCHAR,
+             storekey VARCHAR, category_id INTEGER, moddate REAL, bookmark BLOB);
+         INSERT INTO items VALUES (10, 'A', 0, 4, 3, 7), (11, 'B', 0, 4, 3, 8);
+         INSERT INTO apps VALUES (10, 'Tool', 'org.example.Tool', NULL, NULL, 1.5, X'0102'),
+             (11, 'Other', 'org.example.Other', NULL, NULL, NULL, NULL);",
+    );
+    unsafe { std::env::set_var(launchpad::LAUNCHPAD_DB_ENV, &db) };
+    db
+}
+
+#[test]
+fn launchpad_entries_are_removed_and_put_back() {
+    let env = TestEnv::new();
+    let db = launchpad_db(&env);
+    let sqlite = |sql: &str| sqlite3(&db, sql);
+
+    let path = env
+        .bundle("Tool")
+        .bundle_id(Some("org.example.Tool"))
+        .build();
+    let mut app = hide(&path);
+    assert_eq!(app.hide_from_launchpad().unwrap(), 1);
+    assert_eq!(app.launchpad[0].parent_id, 3);
+    assert_eq!(app.launchpad[0].bookmark.as_deref(), Some("0102"));
+    assert_eq!(sqlite("SELECT group_concat(item_id) FROM apps"), "11");
+    assert_eq!(sqlite("SELECT count(*) FROM items"), "1");
+    assert_eq!(app.hide_from_launchpad().unwrap(), 0);
+
+    assert_eq!(launchpad::restore(&app.launchpad).unwrap(), 1);
+    assert_eq!(
+        sqlite(
+            "SELECT parent_id || ',' || ordering || ',' || hex(bookmark) FROM items \
+             JOIN apps ON apps.item_id = items.rowid WHERE bundleid = 'org.example.Tool'"
+        ),
+        "3,7,0102"
+    );
+    assert_eq!(launchpad::restore(&app.launchpad).unwrap(), 0);
+    unsafe { std::env::remove_var(launchpad::LAUNCHPAD_DB_ENV) };
+}
+
+fn dock_tile(url: String, bundle_id: &str) -> Value {
+    let mut file_data = plist::Dictionary::new();
+    file_data.insert("_CFURLString".into(), Value::String(url));
+    let mut tile_data = plist::Dictionary::new();
+    tile_data.insert("file-data".into(), Value::Dictionary(file_data));
+    tile_data.insert("bundle-identifier".into(), Value::String(bundle_id.into()));
+    let mut tile = plist::Dictionary::new();
+    tile.insert("tile-data".into(), Value::Dictionary(tile_data));
+    Value::Dictionary(tile)
+}
+
+// 写入固定了 app 与另一个应用的 Dock 偏好，并通过环境变量让 dock 模块读写它。
+fn pin_in_dock(env: &TestEnv, app: &Path) -> PathBuf {
+    let url = format!("file://{}/", app.display()).replace(' ', "%20");
+    let mut prefs = plist::Dictionary::new();
+    prefs.insert(
+        "persistent-apps".into(),
+        Value::Array(vec![
+            dock_tile(url, "org.example.Pinned"),
+            dock_tile(
+                "file:///Applications/Other.app/".into(),
+                "org.example.Other",
+            ),
+        ]),
+    );
+    let prefs_path = env.root().join("com.apple.dock.plist");
+    Value::Dictionary(prefs).to_file_xml(&prefs_path).unwrap();
+    unsafe { std::env::set_var(dock::DOCK_PREFS_ENV, &prefs_path) };
+    prefs_path
+}
+
+fn pinned_count(prefs_path: &Path) -> usize {
+    Value::from_file(prefs_path)
+        .unwrap()
+        .as_dictionary()
+        .unwrap()["persistent-apps"]
+        .as_array()
+        .unwrap()
+        .len()
+}
+
+#[test]
+fn pinned_dock_tiles_are_removed_and_put_back() {
+    let env = TestEnv::new();
+    let path = env.bundle("My Tool").build();
+    let prefs_path = pin_in_dock(&env, &path);
+    let pinned = || pinned_count(&prefs_path);
+
+    let mut app = hide(&path);
+    assert_eq!(app.remove_dock_tiles().unwrap(), 1);
+    assert_eq!(app.dock_tiles[0].position, 0);
+    assert_eq!(pinned(), 1);
+    assert_eq!(app.remove_dock_tiles().unwrap(), 0);
+
+    assert_eq!(
+        dock::restore_tiles(&app.path, None, &app.dock_tiles).unwrap(),
+        1
+    );
+    assert_eq!(pinned(), 2);
+    assert_eq!(
+        Value::from_file(&prefs_path)
+            .unwrap()
+            .as_dictionary()
+            .unwrap()["persistent-apps"]
+            .as_array()
+            .unwrap()[0],
+        app.dock_tiles[0].tile
+    );
+    assert_eq!(
+        dock::restore_tiles(&app.path, Some("org.example.pinned"), &app.dock_tiles).unwrap(),
+        0
+    );
+    unsafe { std::env::remove_var(dock::DOCK_PREFS_ENV) };
+}
+
+#[test]
+fn dock_restarts_are_batched_until_the_outermost_batch_ends() {
+    let env = TestEnv::new();
+    env.write_config(&serde_json::json!({ "auto_restart_dock": false }));
+    let outer = dock::batch();
+    {
+        let _inner = dock::batch();
+        dock::request_restart();
+        dock::request_restart();
+        assert!(dock::restart_pending());
+    }
+    assert!(dock::restart_pending());
+    drop(outer);
+    assert!(!dock::restart_pending());
+
+    dock::request_restart();
+    assert!(!dock::restart_pending());
+}
+
+#[test]
+fn pause_and_resume_round_trip() {
+    let env = TestEnv::new();
+    let mut apps = vec![
+        hide(&env.bundle("One").build()),
+        hide(&env.bundle("Two").ui_element(Value::Boolean(false)).build()),
+    ];
+
+    let results = pause::pause(&mut apps, false).unwrap();
+    assert!(results.iter().all(|result| result.error.is_none()));
+    assert!(pause::is_paused());
+    assert!(
+        apps.iter()
+            .all(|app| !is_dock_icon_hidden(&app.path).unwrap())
+    );
+    assert_eq!(
+        ui_element(&apps[1].path).unwrap(),
+        Some(Value::Boolean(false))
+    );
+
+    let results = pause::resume(&mut apps, false).unwrap();
+    assert!(results.iter().all(|result| result.error.is_none()));
+    assert!(!pause::is_paused());
+    assert!(
+        apps.iter()
+            .all(|app| is_dock_icon_hidden(&app.path).unwrap())
+    );
+}
+
+#[test]
+fn resume_removes_dock_tiles_again() {
+    let env = TestEnv::new();
+    env.write_config(&serde_json::json!({ "auto_restart_dock": false }));
+    let path = env.bundle("Pinned").build();
+    let prefs_path = pin_in_dock(&env, &path);
+    let mut apps = vec![hide(&path)];
+    assert_eq!(apps[0].remove_dock_tiles().unwrap(), 1);
+
+    pause::pause(&mut apps, false).unwrap();
+    assert_eq!(pinned_count(&prefs_path), 2);
+
+    let results = pause::resume(&mut apps, false).unwrap();
+    assert!(results[0].error.is_none());
+    assert_eq!(pinned_count(&prefs_path), 1);
+    assert_eq!(apps[0].dock_tiles.len(), 1);
+    assert_eq!(apps[0].dock_tiles[0].position, 0);
+
+    // 再来一轮，记录不会越积越多
+    pause::pause(&mut apps, false).unwrap();
+    pause::resume(&mut apps, false).unwrap();
+    assert_eq!(pinned_count(&prefs_path), 1);
+    assert_eq!(apps[0].dock_tiles.len(), 1);
+    unsafe { std::env::remove_var(dock::DOCK_PREFS_ENV) };
+}
+
+#[test]
+fn resume_removes_launchpad_entries_again() {
+    let env = TestEnv::new();
+    env.write_config(&serde_json::json!({ "auto_restart_dock": false }));
+    let db = launchpad_db(&env);
+    let listed = || {
+        sqlite3(
+            &db,
+            "SELECT count(*) FROM apps WHERE bundleid = 'org.example.Tool'",
+        )
+    };
+    let path = env
+        .bundle("Tool")
+        .bundle_id(Some("org.example.Tool"))
+        .build();
+    let mut apps = vec![hide(&path)];
+    assert_eq!(apps[0].hide_from_launchpad().unwrap(), 1);
+
+    for _ in 0..2 {
+        pause::pause(&mut apps, false).unwrap();
+        assert_eq!(listed(), "1");
+        let results = pause::resume(&mut apps, false).unwrap();
+        assert!(results[0].error.is_none());
+        assert_eq!(listed(), "0");
+        assert_eq!(apps[0].launchpad.len(), 1);
+        assert_eq!(apps[0].launchpad[0].ordering, 7);
+    }
+    unsafe { std::env::remove_var(launchpad::LAUNCHPAD_DB_ENV) };
+}
+
+#[test]
+fn login_item_helpers_hide_and_restore_with_parent() {
+    let env = TestEnv::new();
+    let app = env
+        .bundle("Sync")
+        .login_item(|helper| helper)
+        .login_item(|helper| helper.hidden())
+        .build();
+    let helper = app.join("Contents/Library/LoginItems/Sync Login Item 1.app");
+    assert_eq!(
+        bundle::visible_login_item_helpers(&app),
+        vec![helper.clone()]
+    );
+
+    let mut managed = hide(&app);
+    assert_eq!(managed.hide_helpers().unwrap(), 1);
+    assert!(is_dock_icon_hidden(&helper).unwrap());
+    assert!(bundle::visible_login_item_helpers(&app).is_empty());
+    state::save_apps(&[managed]).unwrap();
+
+    let mut loaded = state::load_apps().unwrap();
+    assert_eq!(loaded[0].helpers.len(), 1);
+    loaded[0].restore_dock_icon().unwrap();
+    assert!(!is_dock_icon_hidden(&app).unwrap());
+    assert_eq!(ui_element(&helper).unwrap(), None);
+    loaded[0].hide_dock_icon().unwrap();
+    assert!(is_dock_icon_hidden(&helper).unwrap());
+}
+
+#[test]
+fn translocated_paths_resolve_to_the_original_bundle() {
+    let mount = "/dev/disk3s1 on / (apfs, local, journaled)\n\
+        /Users/me/Downloads/Foo.app on /private/var/folders/xy/T/AppTranslocation/1A2B (nullfs, local, nodev, nosuid, read-only, nobrowse, mounted by me)\n\
+        /Users/me/Downloads/Bar on /private/var/folders/xy/T/AppTranslocation/3C4D (nullfs, local, read-only, nobrowse, mounted by me)\n";
+    let translocations = Translocations::parse(mount);
+
+    let foo = std::path::Path::new("/private/var/folders/xy/T/AppTranslocation/1A2B/d/Foo.app");
+    assert!(translocation::is_translocated(foo));
+    assert_eq!(
+        translocations.original(foo),
+        Some("/Users/me/Downloads/Foo.app".into())
+    );
+    let bar = std::path::Path::new("/private/var/folders/xy/T/AppTranslocation/3C4D/d/Bar.app");
+    assert_eq!(
+        translocations.original(bar),
+        Some("/Users/me/Downloads/Bar/Bar.app".into())
+    );
+
+    let env = TestEnv::new();
+    let app = env.bundle("Foo").build();
+    assert!(!translocation::is_translocated(&app));
+    assert_eq!(translocation::resolve(&app).unwrap(), app);
+}
+
+#[test]
+fn input_paths_accept_file_urls_home_and_relative_forms() {
+    let home = config::home_dir();
+    let cwd = std::env::current_dir().unwrap();
+    assert_eq!(
+        config::normalize_input_path("file:///Applications/Foo%20Bar.app/").unwrap(),
+        PathBuf::from("/Applications/Foo Bar.app")
+    );
+    assert_eq!(
+        config::normalize_input_path("file://localhost/Applications/Foo.app").unwrap(),
+        PathBuf::from("/Applications/Foo.app")
+    );
+    assert_eq!(
+        config::normalize_input_path("~/Applications/Foo.app").unwrap(),
+        home.join("Applications/Foo.app")
+    );
+    assert_eq!(
+        config::normalize_input_path("./build/../Foo.app").unwrap(),
+        cwd.join("Foo.app")
+    );
+    assert_eq!(
+        config::normalize_input_path("/Applications/./Foo.app").unwrap(),
+        PathBuf::from("/Applications/Foo.app")
+    );
+    assert_eq!(
+        config::normalize_input_path("file:///Applications/Foo.app?x=1#top").unwrap(),
+        PathBuf::from("/Applications/Foo.app")
+    );
+    assert_eq!(
+        config::normalize_input_path("file:///Applications/Foo%3F.app").unwrap(),
+        PathBuf::from("/Applications/Foo?.app")
+    );
+    assert!(config::normalize_input_path("file://server/Applications/Foo.app").is_err());
+}
+
+#[test]
+fn dropped_text_yields_app_paths() {
+    let env = TestEnv::new();
+    let spaced = env.bundle("Spaced Name").build();
+    let text = format!(
+        "/Applications/Foo\\ Bar.app '/Applications/Quoted One.app' notes.txt\n\
+         # 注释行\n\
+         file:///Applications/Url%20App.app/\n\
+         https://example.com/Web.app\n\
+         /Applications/Inner.app/Contents/MacOS/Inner\n\
+         Relative.app /Applications/Foo\\ Bar.app\n\
+         {}\n",
+        spaced.display()
+    );
+    assert_eq!(
+        config::app_paths_in_text(&text),
+        vec![
+            PathBuf::from("/Applications/Foo Bar.app"),
+            PathBuf::from("/Applications/Quoted One.app"),
+            PathBuf::from("/Applications/Url App.app"),
+            PathBuf::from("/Applications/Inner.app"),
+            spaced,
+        ]
+    );
+    assert!(config::app_paths_in_text("just some words").is_empty());
+}
+
+#[test]
+fn missing_info_plist_is_reported_and_can_be_created() {
+    let env = TestEnv::new();
+    let app = env.bundle("Wrapper").build();
+    fs::remove_file(app.join("Contents/Info.plist")).unwrap();
+
+    let err = hide_dock_icon(&app).unwrap_err();
+    assert_eq!(ErrorKind::classify(&*err), ErrorKind::MissingPlist);
+
+    bundle::create_minimal_info_plist(&app).unwrap();
+    let info = read_info_plist(&app);
+    assert_eq!(
+        info.get("CFBundleExecutable").and_then(Value::as_string),
+        Some("Wrapper")
+    );
+    assert!(is_dock_icon_hidden(&app).unwrap());
+    assert!(bundle::create_minimal_info_plist(&app).is_err());
+}
+
+#[test]
+fn denied_automation_permission_is_classified_separately() {
+    let stderr = "execution error: Not authorized to send Apple events to Foo. (-1743)";
+    assert!(relaunch::is_automation_denied(stderr));
+    assert!(!relaunch::is_automation_denied(
+        "execution error: User canceled. (-128)"
+    ));
+
+    let err: Box<dyn std::error::Error> = format!(
+        "{}，未能请求 /Applications/Foo.app 退出",
+        relaunch::AUTOMATION_DENIED_ERROR
+    )
+    .into();
+    assert_eq!(ErrorKind::classify(&*err), ErrorKind::AutomationDenied);
+    let err: Box<dyn std::error::Error> = "应用拒绝退出".into();
+    assert_eq!(ErrorKind::classify(&*err), ErrorKind::Other);
+}
+
+#[test]
+fn directories_without_an_executable_are_not_modified() {
+    let env = TestEnv::new();
+    let app = env.bundle("Fake").build();
+    fs::remove_file(app.join("Contents/MacOS/Fake")).unwrap();
+    let before = read_info_plist(&app);
+
+    let err = hide_dock_icon(&app).unwrap_err();
+    assert_eq!(ErrorKind::classify(&*err), ErrorKind::InvalidBundle);
+    assert_eq!(read_info_plist(&app), before);
+}
+
+#[test]
+fn relaunch_options_persist_and_skip_apps_not_running() {
+    let env = TestEnv::new();
+    let app = env.bundle("Foo").build();
+    let mut managed = hide(&app);
+    let options = RelaunchOptions {
+        background: true,
+        hidden: false,
+        args: vec!["--minimized".into()],
+    };
+    managed.relaunch = Some(options.clone());
+    state::save_apps(&[managed]).unwrap();
+
+    let loaded = state::load_apps().unwrap();
+    assert_eq!(loaded[0].relaunch, Some(options));
+    assert!(!relaunch::after_hide(&loaded[0]).unwrap());
+}
+
+#[test]
+fn external_change_is_skipped_without_force() {
+    let env = TestEnv::new();
+    let app = env.bundle("Shared").build();
+    let mut apps = vec![hide(&app)];
+
+    let plist_path = app.join("Contents/Info.plist");
+    let mut dict = read_info_plist(&app);
+    dict.insert("NSHumanReadableCopyright".into(), Value::String("x".into()));
+    Value::Dictionary(dict).to_file_xml(&plist_path).unwrap();
+    File::options()
+        .write(true)
+        .open(&plist_path)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    assert!(apps[0].external_change().is_some());
+
+    let results = pause::pause(&mut apps, false).unwrap();
+    assert!(results[0].error.is_some());
+    assert!(is_dock_icon_hidden(&app).unwrap());
+
+    let results = pause::pause(&mut apps, true).unwrap();
+    assert!(results[0].error.is_none());
+    assert!(!is_dock_icon_hidden(&app).unwrap());
+}
+
+#[test]
+fn ending_session_restores_only_session_apps() {
+    let env = TestEnv::new();
+    let mut session_app = hide(&env.bundle("Temporary").build());
+    session_app.session = true;
+    let kept = hide(&env.bundle("Permanent").build());
+    let mut apps = vec![session_app, kept];
+    session::write_journal(&apps).unwrap();
+    assert!(session::journal_path().is_file());
+
+    let results = session::end_session(&mut apps).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(apps.len(), 1);
+    assert!(is_dock_icon_hidden(&apps[0].path).unwrap());
+    assert!(!session::journal_path().exists());
+}
+
+#[test]
+fn failed_session_restores_stay_managed_and_journaled() {
+    let env = TestEnv::new();
+    let mut apps: Vec<ManagedApp> = ["Broken", "Fine"]
+        .into_iter()
+        .map(|name| {
+            let mut app = hide(&env.bundle(name).build());
+            app.session = true;
+            app
+        })
+        .collect();
+    session::write_journal(&apps).unwrap();
+    let broken = apps[0].path.clone();
+    fs::remove_file(broken.join("Contents/Info.plist")).unwrap();
+
+    let results = session::end_session(&mut apps).unwrap();
+    assert!(results[0].error.is_some());
+    assert!(results[1].error.is_none());
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].path, broken);
+    assert!(session::journal_path().is_file());
+
+    // 日志中只剩失败的应用，修好后可以再次恢复
+    bundle::create_minimal_info_plist(&broken).unwrap();
+    let results = session::restore_all_session_apps(&mut Vec::new()).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].path, broken);
+    assert!(results[0].error.is_none());
+    assert!(!session::journal_path().exists());
+}
+
+#[test]
+fn cli_hide_writes_shared_state() {
+    let env = TestEnv::new();
+    let app = env.bundle("Cli").build();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_dock-dodger"))
+        .args(["hide", "--json"])
+        .arg(&app)
+        .env("DOCKDODGER_DATA_DIR", env.data_dir())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["hidden"], 1);
+
+    assert!(is_dock_icon_hidden(&app).unwrap());
+    let loaded = state::load_apps().unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].path, app);
+}
+
+#[test]
+fn cli_verify_notifies_about_each_drift_once() {
+    let env = TestEnv::new();
+    let app = env.bundle("Drifting").build();
+    let verify = || {
+        std::process::Command::new(env!("CARGO_BIN_EXE_dock-dodger"))
+            .args(["verify", "--notify", "--json"])
+            .env("DOCKDODGER_DATA_DIR", env.data_dir())
+            .output()
+            .unwrap()
+    };
+    state::save_apps(&[hide(&app)]).unwrap();
+    bundle::restore_dock_icon(&app).unwrap();
+
+    let first = verify();
+    assert_eq!(first.status.code(), Some(3));
+    let stderr = String::from_utf8_lossy(&first.stderr);
+    assert!(stderr.contains("[Notify]"), "{}", stderr);
+    assert!(
+        stderr.contains("Drifting 的 Dock 图标被更新恢复"),
+        "{}",
+        stderr
+    );
+
+    let second = verify();
+    assert_eq!(second.status.code(), Some(3));
+    assert!(!String::from_utf8_lossy(&second.stderr).contains("[Notify]"));
+}
+
+#[test]
+fn cli_verify_exit_codes_separate_drift_from_errors() {
+    let env = TestEnv::new();
+    let app = env.bundle("Scripted").build();
+    let verify = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_dock-dodger"))
+            .arg("verify")
+            .args(args)
+            .env("DOCKDODGER_DATA_DIR", env.data_dir())
+            .output()
+            .unwrap()
+    };
+    state::save_apps(&[hide(&app)]).unwrap();
+    assert_eq!(verify(&[]).status.code(), Some(0));
+
+    bundle::restore_dock_icon(&app).unwrap();
+    assert_eq!(verify(&["--json"]).status.code(), Some(3));
+    let fixed = verify(&["--fix", "--json"]);
+    assert_eq!(fixed.status.code(), Some(0));
+    let results: serde_json::Value = serde_json::from_slice(&fixed.stdout).unwrap();
+    assert_eq!(results[0]["status"], "reapplied");
+    assert!(is_dock_icon_hidden(&app).unwrap());
+
+    fs::remove_file(app.join("Contents/Info.plist")).unwrap();
+    assert_eq!(verify(&[]).status.code(), Some(1));
+    assert_eq!(verify(&["--bogus"]).status.code(), Some(2));
+}
+
+#[test]
+fn cli_rpc_health_reports_uptime_watcher_and_last_error() {
+    use std::io::Write;
+
+    let env = TestEnv::new();
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_dock-dodger"))
+        .arg("rpc")
+        .env("DOCKDODGER_DATA_DIR", env.data_dir())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    for request in [
+        r#"{"jsonrpc":"2.0","id":1,"method":"health"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"subscribe"}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"health"}"#,
+    ] {
+        writeln!(stdin, "{}", request).unwrap();
+    }
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    let responses: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 3);
+
+    let idle = &responses[0]["result"];
+    assert_eq!(idle["status"], "ok");
+    assert_eq!(idle["watcher"]["subscribed"], false);
+    assert_eq!(idle["pending_operations"], 0);
+    assert!(idle["uptime_secs"].is_u64());
+    assert!(idle["last_error"].is_null());
+
+    let watching = &responses[2]["result"];
+    assert_eq!(watching["status"], "ok");
+    assert_eq!(watching["watcher"]["subscribed"], true);
+    assert_eq!(watching["watcher"]["running"], true);
+}
+
+#[test]
+fn recent_history_lists_newest_operations_first() {
+    let env = TestEnv::new();
+    let first = env.bundle("First").build();
+    let second = env.bundle("Second").build();
+    hide_dock_icon(&first).unwrap();
+    hide_dock_icon(&second).unwrap();
+    bundle::restore_dock_icon(&first).unwrap();
+    history::record_drift(&second);
+
+    let recent = history::recent(2).unwrap();
+    assert_eq!(recent.len(), 2);
+    assert_eq!(recent[0].action, HistoryAction::Restore);
+    assert_eq!(recent[0].path, first);
+    assert_eq!(recent[1].action, HistoryAction::Hide);
+    assert_eq!(recent[1].path, second);
+}
+
+#[test]
+fn history_queries_filter_by_time_range_and_app() {
+    let env = TestEnv::new();
+    let foo = env.bundle("Foo").build();
+    let bar = env.bundle("Bar").build();
+    hide_dock_icon(&foo).unwrap();
+    hide_dock_icon(&bar).unwrap();
+    let revision = history::revision();
+    bundle::restore_dock_icon(&foo).unwrap();
+    assert_ne!(history::revision(), revision);
+
+    let filter = HistoryFilter {
+        apps: vec!["foo".to_string()],
+        ..HistoryFilter::default()
+    };
+    let entries = history::query(&filter).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|entry| entry.path == foo));
+    assert_eq!(entries[1].action, HistoryAction::Restore);
+
+    let until = entries[0].at;
+    let filter = HistoryFilter {
+        until: Some(until),
+        ..HistoryFilter::default()
+    };
+    assert!(history::query(&filter).unwrap().is_empty());
+}
+
+#[test]
+fn history_search_matches_app_names_paths_and_errors() {
+    let env = TestEnv::new();
+    let slack = env.bundle("Slack").build();
+    let zoom = env.bundle("Zoom").build();
+    hide_dock_icon(&slack).unwrap();
+    history::record(
+        HistoryAction::Hide,
+        &zoom,
+        Some("Operation not permitted".to_string()),
+    );
+
+    let search = |text: &str| {
+        let filter = HistoryFilter {
+            text: Some(text.to_string()),
+            ..HistoryFilter::default()
+        };
+        history::query(&filter).unwrap()
+    };
+    let found = search("slack");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, slack);
+    let found = search("NOT PERMITTED");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, zoom);
+    assert_eq!(search("Applications").len(), 2);
+    assert!(search("Teams").is_empty());
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[test]
+fn sqlite_storage_without_the_feature_is_reported() {
+    let env = TestEnv::new();
+    env.write_config(&serde_json::json!({ "storage": "sqlite" }));
+    let err = state::load_apps().unwrap_err();
+    assert_eq!(err.to_string(), state_store::SQLITE_UNAVAILABLE_ERROR);
+}
+
+#[test]
+fn history_records_equivalent_plutil_commands() {
+    let env = TestEnv::new();
+    let app = env.bundle("My Tool").build();
+    let plist = format!("'{}'", app.join("Contents/Info.plist").display());
+
+    let managed = hide(&app);
+    hide_dock_icon(&app).unwrap();
+    managed.restore_dock_icon().unwrap();
+
+    let entries = history::load_history().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(
+        entries[0].commands,
+        vec![format!("plutil -replace LSUIElement -string 1 {}", plist)]
+    );
+    // 没有改动时不记录命令
+    assert!(entries[1].commands.is_empty());
+    assert_eq!(
+        entries[2].commands,
+        vec![format!("plutil -remove LSUIElement {}", plist)]
+    );
+
+    assert_eq!(
+        explain::ui_element_commands(&app, Some(&Value::Boolean(false))),
+        vec![format!("plutil -replace LSUIElement -bool NO {}", plist)]
+    );
+    assert_eq!(explain::shell_quote("it's"), r"'it'\''s'");
+    assert_eq!(
+        explain::shell_quote("/Applications/A.app"),
+        "/Applications/A.app"
+    );
+}
+
+#[test]
+fn launchpad_and_dock_changes_record_equivalent_commands() {
+    let env = TestEnv::new();
+    env.write_config(&serde_json::json!({
+        "hide_from_launchpad": true,
+        "remove_dock_tiles": true,
+        "auto_restart_dock": false,
+    }));
+    let db = launchpad_db(&env);
+    let path = env
+        .bundle("Tool")
+        .bundle_id(Some("org.example.Tool"))
+        .build();
+    let prefs_path = pin_in_dock(&env, &path);
+
+    let mut app = hide(&path);
+    app.apply_hide_options();
+    let entries = history::load_history().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].action, HistoryAction::Dock);
+    let commands = &entries[1].commands;
+    assert_eq!(commands.len(), 2);
+    let quoted = |path: &Path| explain::shell_quote(&path.display().to_string());
+    assert!(commands[0].starts_with(&format!("sqlite3 -bail {} ", quoted(&db))));
+    assert!(commands[0].contains("DELETE FROM apps WHERE item_id IN (10)"));
+    assert_eq!(
+        commands[1],
+        format!("plutil -remove persistent-apps.0 {}", quoted(&prefs_path))
+    );
+    // 单独记录的改动不算作一次隐藏
+    let stats = history::stats(&entries, 1);
+    assert_eq!((stats.hides, stats.total_operations), (1, 1));
+
+    app.restore_dock_icon().unwrap();
+    let entries = history::load_history().unwrap();
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries[3].action, HistoryAction::Dock);
+    assert!(entries[3].commands[0].contains("INSERT OR IGNORE INTO items"));
+    assert!(entries[3].commands[1].starts_with("plutil -insert persistent-apps.0 -xml "));
+    assert_eq!(
+        history::recent(5).unwrap()[0].action,
+        HistoryAction::Restore
+    );
+
+    // 记下的命令可以原样重复同样的修改
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(&commands[0])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(
+        sqlite3(
+            &db,
+            "SELECT count(*) FROM apps WHERE bundleid = 'org.example.Tool'"
+        ),
+        "0"
+    );
+    unsafe { std::env::remove_var(launchpad::LAUNCHPAD_DB_ENV) };
+    unsafe { std::env::remove_var(dock::DOCK_PREFS_ENV) };
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn failed_sqlite_import_is_retried() {
+    let env = TestEnv::new();
+    let app = env.bundle("Later").build();
+    fs::create_dir_all(env.data_dir()).unwrap();
+    fs::write(
+        state::state_file_path(),
+        r#"{"schema_version": 99, "apps": []}"#,
+    )
+    .unwrap();
+    env.write_config(&serde_json::json!({ "storage": "sqlite" }));
+    assert!(state::load_apps().is_err());
+    assert!(env.data_dir().join(state_store::SQLITE_FILE).exists());
+
+    fs::remove_file(state::state_file_path()).unwrap();
+    env.write_config(&serde_json::json!({}));
+    state::save_apps(&[ManagedApp::new(app.clone())]).unwrap();
+    env.write_config(&serde_json::json!({ "storage": "sqlite" }));
+    assert_eq!(state::load_apps().unwrap()[0].path, app);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_storage_imports_json_data_and_round_trips() {
+    let env = TestEnv::new();
+    let foo = env.bundle("Foo").build();
+    let bar = env.bundle("Bar").build();
+    state::save_apps(&[hide(&foo)]).unwrap();
+    hide_dock_icon(&bar).unwrap();
+    assert!(history::record_drift(&foo));
+
+    env.write_config(&serde_json::json!({ "storage": "sqlite" }));
+    let apps = state::load_apps().unwrap();
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].path, foo);
+    assert!(env.data_dir().join(state_store::SQLITE_FILE).exists());
+    assert_eq!(history::load_history().unwrap().len(), 3);
+    assert!(!history::record_drift(&foo));
+
+    state::save_apps(&[apps[0].clone(), ManagedApp::new(bar.clone())]).unwrap();
+    let paths: Vec<_> = state::load_apps()
+        .unwrap()
+        .into_iter()
+        .map(|app| app.path)
+        .collect();
+    assert_eq!(paths, vec![foo.clone(), bar.clone()]);
+
+    bundle::restore_dock_icon(&foo).unwrap();
+    let filter = HistoryFilter {
+        text: Some("foo.app".to_string()),
+        ..HistoryFilter::default()
+    };
+    assert_eq!(history::query(&filter).unwrap().len(), 3);
+    let recent = history::recent(2).unwrap();
+    assert_eq!(recent[0].action, HistoryAction::Restore);
+    assert_eq!(recent[0].commands.len(), 1);
+    assert_eq!(recent[1].path, bar);
+}
+
+#[test]
+fn rollback_plan_inverts_changes_made_after_the_target_time() {
+    let entry = |at: u64, action: HistoryAction, path: &str, error: Option<&str>| HistoryEntry {
+        at,
+        action,
+        path: PathBuf::from(path),
+        error: error.map(str::to_string),
+        commands: Vec::new(),
+    };
+    let entries = vec![
+        entry(100, HistoryAction::Hide, "/Applications/Kept.app", None),
+        entry(100, HistoryAction::Hide, "/Applications/Restored.app", None),
+        entry(
+            200,
+            HistoryAction::Restore,
+            "/Applications/Restored.app",
+            None,
+        ),
+        entry(250, HistoryAction::Drift, "/Applications/Kept.app", None),
+        entry(300, HistoryAction::Hide, "/Applications/Added.app", None),
+        entry(
+            300,
+            HistoryAction::Hide,
+            "/Applications/Failed.app",
+            Some("denied"),
+        ),
+    ];
+
+    let steps = rollback::plan(&entries, 150, |_| None);
+    let summary: Vec<_> = steps
+        .iter()
+        .map(|step| (step.path.to_str().unwrap(), step.action, step.changed_at))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("/Applications/Added.app", RollbackAction::Restore, 300),
+            ("/Applications/Restored.app", RollbackAction::Hide, 200),
+        ]
+    );
+
+    // 当前状态以 Info.plist 为准：图标已经失效显示出来的应用也会重新隐藏。
+    let steps = rollback::plan(&entries, 150, |path| {
+        Some(
+            path != Path::new("/Applications/Kept.app")
+                && path != Path::new("/Applications/Restored.app"),
+        )
+    });
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[1].path, PathBuf::from("/Applications/Kept.app"));
+    assert_eq!(steps[1].action, RollbackAction::Hide);
+}
+
+#[test]
+fn rollback_runs_as_a_batch_and_undoes_everything_on_failure() {
+    let env = TestEnv::new();
+    let hidden = env.bundle("Hidden").build();
+    let visible = env.bundle("Visible").build();
+    let mut apps = vec![hide(&hidden)];
+    let step = |path: &std::path::Path, action| RollbackStep {
+        path: path.to_path_buf(),
+        action,
+        changed_at: 0,
+    };
+
+    let missing = env.applications().join("Missing.app");
+    let steps = [
+        step(&hidden, RollbackAction::Restore),
+        step(&visible, RollbackAction::Hide),
+        step(&missing, RollbackAction::Hide),
+    ];
+    let failure = rollback::execute(&mut apps, &steps, false).unwrap_err();
+    assert_eq!(failure.step.path, missing);
+    assert!(failure.undo_errors.is_empty());
+    assert!(is_dock_icon_hidden(&hidden).unwrap());
+    assert!(!is_dock_icon_hidden(&visible).unwrap());
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].path, hidden);
+
+    rollback::execute(&mut apps, &steps[..2], false).unwrap();
+    assert!(!is_dock_icon_hidden(&hidden).unwrap());
+    assert!(is_dock_icon_hidden(&visible).unwrap());
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].path, visible);
+}
+
+#[test]
+fn snapshots_restore_the_managed_set_with_its_original_records() {
+    let env = TestEnv::new();
+    let kept = env.bundle("Kept").build();
+    let dropped = env.bundle("Dropped").build();
+    let added = env.bundle("Added").build();
+    let mut tagged = hide(&kept);
+    tagged.tags = vec!["work".to_string()];
+    let apps = vec![tagged, hide(&dropped)];
+    assert!(snapshot::take("../escape", &apps).is_err());
+    snapshot::take("work", &apps).unwrap();
+
+    let mut apps = apps;
+    apps[1].restore_dock_icon().unwrap();
+    apps.remove(1);
+    apps[0].tags.clear();
+    apps.push(hide(&added));
+
+    let saved = snapshot::load("work").unwrap();
+    assert_eq!(saved.apps.len(), 2);
+    let plan = snapshot::plan(&saved, &apps, |path| is_dock_icon_hidden(path).ok());
+    let summary: Vec<_> = plan
+        .steps
+        .iter()
+        .map(|step| (step.path.clone(), step.action))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (dropped.clone(), RollbackAction::Hide),
+            (added.clone(), RollbackAction::Restore),
+        ]
+    );
+    assert!(plan.missing.is_empty());
+
+    rollback::execute(&mut apps, &plan.steps, false).unwrap();
+    snapshot::apply_entries(&mut apps, &saved, &plan.missing);
+    let paths: Vec<_> = apps.iter().map(|app| app.path.clone()).collect();
+    assert_eq!(paths, vec![kept.clone(), dropped.clone()]);
+    assert_eq!(apps[0].tags, vec!["work".to_string()]);
+    assert!(is_dock_icon_hidden(&dropped).unwrap());
+    assert!(!is_dock_icon_hidden(&added).unwrap());
+
+    let infos = snapshot::list().unwrap();
+    assert_eq!(infos.len(), 1);
+    assert_eq!((infos[0].name.as_str(), infos[0].apps), ("work", 2));
+    snapshot::delete("work").unwrap();
+    assert!(snapshot::load("work").is_err());
+}
+
+#[test]
+fn update_waves_sort_managed_apps_into_reverted_survived_and_gone() {
+    let env = TestEnv::new();
+    let reverted = env.bundle("Reverted").build();
+    let survived = env.bundle("Survived").build();
+    let gone = env.bundle("Gone").build();
+    let untouched = env.bundle("Untouched").build();
+    let apps: Vec<ManagedApp> = [&reverted, &survived, &gone, &untouched]
+        .into_iter()
+        .map(|app| hide(app))
+        .collect();
+
+    bundle::restore_dock_icon(&reverted).unwrap();
+    let later = SystemTime::now() + Duration::from_secs(120);
+    for app in [&reverted, &survived] {
+        File::options()
+            .write(true)
+            .open(app.join("Contents/Info.plist"))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+    }
+    fs::remove_dir_all(&gone).unwrap();
+
+    let changes = update_wave::changes(&apps);
+    assert_eq!(changes.reverted, vec![reverted.clone()]);
+    assert_eq!(changes.survived, vec![survived.clone()]);
+    assert_eq!(changes.disappeared, vec![gone.clone()]);
+
+    let report = update_wave::detect(changes.clone(), Some("14.4"), &UpdateCheck::default());
+    assert_eq!(report.unwrap().previous_os, None);
+
+    // 同一批变化只报告一次；系统版本变化时即使变化不多也报告。
+    let seen = UpdateCheck {
+        os_version: Some("14.4".to_string()),
+        reported: vec![reverted.clone(), survived.clone(), gone.clone()],
+    };
+    assert!(update_wave::detect(changes.clone(), Some("14.4"), &seen).is_none());
+    let mut single = changes.clone();
+    single.survived.clear();
+    single.disappeared.clear();
+    let report = update_wave::detect(single, Some("15.0"), &seen).unwrap();
+    assert_eq!(report.previous_os.as_deref(), Some("14.4"));
+    assert_eq!(report.reverted, vec![reverted]);
+}
+
+#[test]
+fn local_times_parse_with_optional_hours_and_minutes() {
+    let day = history::parse_date("2024-03-05").unwrap();
+    let midnight = history::parse_local_time("2024-03-05").unwrap();
+    assert_eq!(
+        history::parse_local_time("2024-03-05 09:30"),
+        Some(midnight + 9 * 3600 + 30 * 60)
+    );
+    assert_eq!(
+        history::parse_local_time("2024-03-05T09:30"),
+        history::parse_local_time("2024-03-05 09:30")
+    );
+    assert!(midnight.abs_diff(day) <= 14 * 3600);
+    assert!(history::parse_local_time("2024-03-05 25:00").is_none());
+    assert!(history::parse_local_time("last tuesday").is_none());
+}
+
+#[test]
+fn exported_profile_imports_with_names_bundle_ids_and_tags() {
+    let env = TestEnv::new();
+    let app = env.bundle("Shared").build();
+    let mut managed = hide(&app);
+    managed.tags = vec!["work".to_string()];
+
+    let profile = import::export_profile(&[&managed]);
+    let entries = import::parse_import(&profile).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].path.as_deref(), Some(app.as_path()));
+    assert_eq!(entries[0].bundle_id, bundle::bundle_identifier(&app));
+    assert!(entries[0].name.is_some());
+    assert_eq!(entries[0].tags, vec!["work".to_string()]);
+    assert!(import::is_import_list(std::path::Path::new(
+        "shared.dockdodger"
+    )));
+    // 只有导出的配置文件需要 confirm_destructive 验证，普通列表不算切换配置。
+    assert!(import::is_profile(&profile));
+    assert!(!import::is_profile(&app.display().to_string()));
+    assert!(!import::is_profile(r#"{"apps": []}"#));
+}
+
+#[test]
+fn write_queue_spaces_writes() {
+    use std::time::Instant;
+
+    let limits = WriteLimits {
+        per_second: Some(20.0),
+    };
+    let started = Instant::now();
+    for index in 0..3 {
+        assert_eq!(write_queue::run_with(&limits, || index), index);
+    }
+    assert!(started.elapsed() >= Duration::from_millis(100));
+    assert_eq!(write_queue::pending(), 0);
+
+    let unlimited = WriteLimits { per_second: None };
+    assert_eq!(write_queue::run_with(&unlimited, || 7), 7);
+}
+
+#[test]
+fn plist_cache_follows_writes_and_external_changes() {
+    let env = TestEnv::new();
+    let app = env.bundle("Cached").build();
+    let plist = app.join("Contents/Info.plist");
+    let original = fs::read(&plist).unwrap();
+
+    assert!(!is_dock_icon_hidden(&app).unwrap());
+    hide_dock_icon(&app).unwrap();
+    assert!(is_dock_icon_hidden(&app).unwrap());
+
+    // 其他工具改写文件：大小或修改时间变化后重新解析
+    fs::write(&plist, &original).unwrap();
+    assert!(!is_dock_icon_hidden(&app).unwrap());
+    assert!(bundle::bundle_identifier(&app).is_some());
+
+    fs::remove_file(&plist).unwrap();
+    assert!(is_dock_icon_hidden(&app).is_err());
+    assert_eq!(bundle::bundle_identifier(&app), None);
+}
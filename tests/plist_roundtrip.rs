@@ -0,0 +1,142 @@
+mod common;
+
+use osx_dock_dodger_rs::bundle::{
+    hide_dock_icon, is_dock_icon_hidden, replace_ui_element, restore_original_ui_element,
+    ui_element,
+};
+use plist::{Dictionary, Value};
+use proptest::prelude::*;
+
+use common::{TestEnv, is_binary_plist, read_info_plist};
+
+fn leaf() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        "[a-zA-Z0-9 ._-]{0,16}".prop_map(Value::String),
+        any::<bool>().prop_map(Value::Boolean),
+        any::<i64>().prop_map(|value| Value::Integer(value.into())),
+        (-1e9f64..1e9).prop_map(Value::Real),
+        prop::collection::vec(any::<u8>(), 0..16).prop_map(Value::Data),
+    ]
+}
+
+fn value() -> impl Strategy<Value = Value> {
+    leaf().prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+            prop::collection::btree_map("[A-Za-z][A-Za-z0-9_.]{0,12}", inner, 0..4)
+                .prop_map(|map| Value::Dictionary(map.into_iter().collect())),
+        ]
+    })
+}
+
+fn visible_ui_element() -> impl Strategy<Value = Option<Value>> {
+    prop_oneof![
+        Just(None),
+        Just(Some(Value::String("0".into()))),
+        Just(Some(Value::String("NO".into()))),
+        Just(Some(Value::Boolean(false))),
+        Just(Some(Value::Integer(0.into()))),
+    ]
+}
+
+fn hiding_ui_element() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::String("1".into())),
+        Just(Value::String("true".into())),
+        Just(Value::Boolean(true)),
+        Just(Value::Integer(1.into())),
+    ]
+}
+
+// 生成的 Info.plist 可能缺少 CFBundleIdentifier 等常见键，键值类型也各不相同。
+fn info_plist(ui_element: Option<Value>) -> impl Strategy<Value = Dictionary> {
+    (
+        prop::collection::btree_map("[A-Z][A-Za-z0-9]{0,12}", value(), 0..8),
+        prop::option::of("[a-z]{1,8}(\\.[a-z]{1,8}){1,3}"),
+        prop::option::of("[0-9]{1,2}\\.[0-9]{1,2}"),
+    )
+        .prop_map(move |(extra, bundle_id, version)| {
+            let mut dict: Dictionary = extra.into_iter().collect();
+            dict.remove("LSUIElement");
+            if let Some(bundle_id) = bundle_id {
+                dict.insert("CFBundleIdentifier".into(), Value::String(bundle_id));
+            }
+            if let Some(version) = version {
+                dict.insert("CFBundleShortVersionString".into(), Value::String(version));
+            }
+            if let Some(value) = ui_element.clone() {
+                dict.insert("LSUIElement".into(), value);
+            }
+            dict
+        })
+}
+
+proptest! {
+    #[test]
+    fn hide_then_restore_is_lossless(
+        dict in visible_ui_element().prop_flat_map(info_plist),
+        binary in any::<bool>(),
+    ) {
+        let env = TestEnv::new();
+        let mut bundle = env.bundle("Sample").info(dict.clone());
+        if binary {
+            bundle = bundle.binary();
+        }
+        let app = bundle.build();
+
+        let original = ui_element(&app).unwrap();
+        hide_dock_icon(&app).unwrap();
+        prop_assert!(is_dock_icon_hidden(&app).unwrap());
+        prop_assert_eq!(is_binary_plist(&app), binary);
+
+        restore_original_ui_element(&app, original.as_ref()).unwrap();
+        prop_assert!(!is_dock_icon_hidden(&app).unwrap());
+        prop_assert_eq!(is_binary_plist(&app), binary);
+        prop_assert_eq!(read_info_plist(&app), dict);
+    }
+
+    #[test]
+    fn restore_shows_icon_when_originally_hidden(
+        dict in hiding_ui_element().prop_flat_map(|value| info_plist(Some(value))),
+        binary in any::<bool>(),
+    ) {
+        let env = TestEnv::new();
+        let mut bundle = env.bundle("Sample").info(dict.clone());
+        if binary {
+            bundle = bundle.binary();
+        }
+        let app = bundle.build();
+
+        let original = ui_element(&app).unwrap();
+        hide_dock_icon(&app).unwrap();
+        restore_original_ui_element(&app, original.as_ref()).unwrap();
+        prop_assert!(!is_dock_icon_hidden(&app).unwrap());
+
+        let mut expected = dict;
+        expected.insert("LSUIElement".into(), Value::String("0".into()));
+        prop_assert_eq!(read_info_plist(&app), expected);
+    }
+
+    #[test]
+    fn replace_keeps_other_keys_and_encoding(
+        dict in prop::option::of(leaf()).prop_flat_map(info_plist),
+        binary in any::<bool>(),
+    ) {
+        let mut contents = Vec::new();
+        let plist = Value::Dictionary(dict.clone());
+        if binary {
+            plist::to_writer_binary(&mut contents, &plist).unwrap();
+        } else {
+            plist::to_writer_xml(&mut contents, &plist).unwrap();
+        }
+
+        let (hidden, previous) =
+            replace_ui_element(&contents, Some(Value::String("1".into()))).unwrap();
+        prop_assert_eq!(previous.as_ref(), dict.get("LSUIElement"));
+        prop_assert_eq!(hidden.starts_with(b"bplist"), binary);
+
+        let (restored, _) = replace_ui_element(&hidden, previous).unwrap();
+        let restored = Value::from_reader(std::io::Cursor::new(restored)).unwrap();
+        prop_assert_eq!(restored.into_dictionary().unwrap(), dict);
+    }
+}
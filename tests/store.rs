@@ -0,0 +1,126 @@
+use std::io;
+use std::path::Path;
+
+use osx_dock_dodger_rs::bundle::{
+    hide_dock_icon_in, is_dock_icon_hidden_in, restore_dock_icon_in,
+    restore_original_ui_element_in, ui_element_in,
+};
+use osx_dock_dodger_rs::scan::ScanScope;
+use osx_dock_dodger_rs::store::MemoryStore;
+use plist::{Dictionary, Value};
+
+fn info_plist(ui_element: Option<&str>) -> Vec<u8> {
+    let mut dict = Dictionary::new();
+    dict.insert("CFBundleIdentifier".into(), "com.example.app".into());
+    if let Some(value) = ui_element {
+        dict.insert("LSUIElement".into(), value.into());
+    }
+    let mut contents = Vec::new();
+    plist::to_writer_xml(&mut contents, &Value::Dictionary(dict)).unwrap();
+    contents
+}
+
+fn io_kind(err: &(dyn std::error::Error + 'static)) -> Option<io::ErrorKind> {
+    err.downcast_ref::<io::Error>().map(io::Error::kind)
+}
+
+#[test]
+fn hide_and_restore_in_memory() {
+    let store = MemoryStore::new();
+    let app = Path::new("/Applications/Foo.app");
+    store.insert_info_plist(app, info_plist(None));
+
+    hide_dock_icon_in(&store, app).unwrap();
+    assert!(is_dock_icon_hidden_in(&store, app).unwrap());
+    restore_original_ui_element_in(&store, app, None).unwrap();
+    assert_eq!(ui_element_in(&store, app).unwrap(), None);
+    assert_eq!(store.info_plist(app), Some(info_plist(None)));
+    assert_eq!(store.write_count(), 2);
+}
+
+#[test]
+fn unchanged_plist_is_not_rewritten() {
+    let store = MemoryStore::new();
+    let app = Path::new("/Applications/Foo.app");
+    store.insert_info_plist(app, info_plist(Some("1")));
+    hide_dock_icon_in(&store, app).unwrap();
+    assert_eq!(store.write_count(), 0);
+}
+
+#[test]
+fn missing_plist_reports_not_found() {
+    let store = MemoryStore::new();
+    let err = hide_dock_icon_in(&store, Path::new("/Applications/Gone.app")).unwrap_err();
+    assert_eq!(io_kind(err.as_ref()), Some(io::ErrorKind::NotFound));
+    assert_eq!(store.write_count(), 0);
+}
+
+#[test]
+fn permission_denied_leaves_plist_untouched() {
+    let store = MemoryStore::new();
+    let app = Path::new("/Applications/Admin.app");
+    store.insert_info_plist(app, info_plist(None));
+    store.deny_writes(app);
+
+    let err = hide_dock_icon_in(&store, app).unwrap_err();
+    assert_eq!(io_kind(err.as_ref()), Some(io::ErrorKind::PermissionDenied));
+    assert_eq!(store.info_plist(app), Some(info_plist(None)));
+}
+
+#[test]
+fn read_only_volume_rejects_writes() {
+    let store = MemoryStore::new();
+    let app = Path::new("/Volumes/Installer/Foo.app");
+    store.insert_info_plist(app, info_plist(Some("1")));
+    store.set_read_only(true);
+
+    let err = restore_dock_icon_in(&store, app).unwrap_err();
+    assert_eq!(
+        io_kind(err.as_ref()),
+        Some(io::ErrorKind::ReadOnlyFilesystem)
+    );
+    assert!(is_dock_icon_hidden_in(&store, app).unwrap());
+}
+
+#[test]
+fn corrupt_or_unexpected_plists() {
+    let store = MemoryStore::new();
+    let corrupt = Path::new("/Applications/Corrupt.app");
+    store.insert_info_plist(corrupt, b"<plist><dict><key>".to_vec());
+    assert!(hide_dock_icon_in(&store, corrupt).is_err());
+
+    let array = Path::new("/Applications/Array.app");
+    let mut contents = Vec::new();
+    plist::to_writer_xml(&mut contents, &Value::Array(Vec::new())).unwrap();
+    store.insert_info_plist(array, contents.clone());
+    hide_dock_icon_in(&store, array).unwrap();
+    assert_eq!(store.info_plist(array), Some(contents));
+    assert_eq!(store.write_count(), 0);
+}
+
+#[test]
+fn scan_in_memory_respects_depth_and_excludes() {
+    let store = MemoryStore::new();
+    for app in [
+        "/Applications/Top.app",
+        "/Applications/Top.app/Contents/Frameworks/Top Helper.app",
+        "/Applications/Utilities/Tool.app",
+        "/Applications/a/b/Deep.app",
+        "/Applications/Downloads/Installer.app",
+    ] {
+        store.insert_info_plist(Path::new(app), info_plist(Some("1")));
+    }
+    store.insert_info_plist(Path::new("/Applications/Visible.app"), info_plist(None));
+
+    let scope = ScanScope {
+        roots: vec![("/Applications".into(), 2)],
+        exclude: vec!["/Applications/Downloads".into()],
+    };
+    assert_eq!(
+        scope.find_hidden_apps_in(&store),
+        vec![
+            Path::new("/Applications/Top.app").to_path_buf(),
+            Path::new("/Applications/Utilities/Tool.app").to_path_buf(),
+        ]
+    );
+}